@@ -0,0 +1,56 @@
+/* locale-match is a small library for matching user's preferred locales to available locales. */
+
+#ifndef LOCALE_MATCH_H
+#define LOCALE_MATCH_H
+
+/* Generated with cbindgen:0.29.4 */
+
+/* This file is generated by cbindgen. Do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Finds the best matching locale from an array of available BCP 47 locales based on an array of
+// user BCP 47 locales.
+//
+// See [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) for the details of the
+// matching.  
+// Returns the index of the best matching available locale, or `-1` if none of them matches.
+//
+// # Safety
+//
+// `available` must point to `n` pointers and `user` must point to `m` pointers, each of which is
+// either null or points to a NUL-terminated string. The arrays may be null if their lengths are `0`.
+ptrdiff_t locale_match_best_bcp47(const char *const *available,
+                                  size_t n,
+                                  const char *const *user,
+                                  size_t m);
+
+// Finds the best matching locale from an array of available POSIX locales based on an array of
+// user POSIX locales.
+//
+// See [`posix::best_matching_locale`](crate::posix::best_matching_locale) for the details of the
+// matching.  
+// Returns the index of the best matching available locale, or `-1` if none of them matches.
+//
+// # Safety
+//
+// `available` must point to `n` pointers and `user` must point to `m` pointers, each of which is
+// either null or points to a NUL-terminated string. The arrays may be null if their lengths are `0`.
+ptrdiff_t locale_match_best_posix(const char *const *available,
+                                  size_t n,
+                                  const char *const *user,
+                                  size_t m);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LOCALE_MATCH_H */
@@ -0,0 +1,164 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Procedural macros for [`locale-match`](https://docs.rs/locale-match).
+//!
+//! The macros are re-exported by `locale-match` with the `macros` feature and should be used from there.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// The placeholder for the locale in a file name pattern.
+const PLACEHOLDER: &str = "{locale}";
+
+/// Validates a BCP 47 locale literal at compile time and expands to the literal as a `&'static str`.
+///
+/// A malformed locale is a compile error, so typos in lists of available locales are caught
+/// during the build instead of being silently ignored by the matcher at runtime.  
+/// The locale is validated the same way as by [`locale_match::bcp47`](https://docs.rs/locale-match/latest/locale_match/bcp47/index.html).
+///
+/// # Examples
+///
+/// ```
+/// use locale_match_macros::locale;
+///
+///
+/// const AVAILABLE_LOCALES: [&str; 3] = [locale!("en-US"), locale!("zh-Hans-CN"), locale!("x-private")];
+///
+/// assert_eq!(AVAILABLE_LOCALES, ["en-US", "zh-Hans-CN", "x-private"]);
+/// ```
+///
+/// Malformed locales do not compile:
+///
+/// ```compile_fail
+/// use locale_match_macros::locale;
+///
+///
+/// let locale = locale!("en_US");
+/// ```
+#[proc_macro]
+pub fn locale(input: TokenStream) -> TokenStream {
+	let literal = parse_macro_input!(input as LitStr);
+	match language_tags::LanguageTag::parse(&literal.value()) {
+		Ok(_) => quote!(#literal).into(),
+		Err(err) => syn::Error::new(literal.span(), format!("malformed BCP 47 locale `{}`: {err}", literal.value()))
+			.to_compile_error()
+			.into(),
+	}
+}
+
+/// Scans the translation files at compile time and expands to the `&'static [&'static str]` of the
+/// locales, for which a file matching the pattern exists.
+///
+/// The pattern is interpreted like by [`locale_match::files::available_locales_from_pattern`](https://docs.rs/locale-match/latest/locale_match/files/fn.available_locales_from_pattern.html),
+/// relative to the directory of the `Cargo.toml` of the crate being compiled, so the list of
+/// available locales cannot drift apart from the catalog of translations.  
+/// A pattern without the `{locale}` placeholder or a directory that cannot be read is a compile error.
+///
+/// The directory is scanned only when the crate is compiled. To pick up added and removed files,
+/// make cargo rebuild the crate when the directory changes, e.g. by printing
+/// `cargo::rerun-if-changed=i18n` from the build script.
+///
+/// # Examples
+///
+/// ```ignore
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::include_available_locales;
+///
+///
+/// const AVAILABLE_LOCALES: &[&str] = include_available_locales!("i18n/{locale}.ftl");
+///
+/// let best_match = best_matching_locale(AVAILABLE_LOCALES, ["de-AT", "en"]);
+/// ```
+#[proc_macro]
+pub fn include_available_locales(input: TokenStream) -> TokenStream {
+	let literal = parse_macro_input!(input as LitStr);
+	let root = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+	match available_locales(&root, &literal.value()) {
+		Ok(locales) => quote!(&[#(#locales),*]).into(),
+		Err(message) => syn::Error::new(literal.span(), message).to_compile_error().into(),
+	}
+}
+
+/// Returns the sorted locales matching the pattern relative to the root, like
+/// `locale_match::files::available_locales_from_pattern`.
+fn available_locales(root: &Path, pattern: &str) -> Result<Vec<String>, String> {
+	let Some(start) = pattern.find(PLACEHOLDER) else {
+		return Err(format!("the pattern `{pattern}` has no `{PLACEHOLDER}` placeholder"));
+	};
+	let (head, tail) = (&pattern[..start], &pattern[start + PLACEHOLDER.len()..]);
+	let (dir, prefix) = match head.rfind(is_separator) {
+		Some(i) => (&head[..=i], &head[i + 1..]),
+		None => ("", head),
+	};
+	let (suffix, rest) = match tail.find(is_separator) {
+		Some(i) => (&tail[..i], &tail[i..]),
+		None => (tail, ""),
+	};
+
+	let dir = root.join(dir);
+	let entries = fs::read_dir(&dir).map_err(|err| format!("cannot read the directory `{}`: {err}", dir.display()))?;
+	let mut locales = entries
+		.filter_map(Result::ok)
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.filter_map(|name| {
+			let locale = name.strip_prefix(prefix)?.strip_suffix(suffix).filter(|locale| !locale.is_empty())?;
+			let mut path = dir.join(&name);
+			path.extend(rest.split(is_separator).filter(|component| !component.is_empty()));
+			path.exists().then(|| locale.to_string())
+		})
+		.collect::<Vec<String>>();
+	locales.sort();
+	Ok(locales)
+}
+
+fn is_separator(c: char) -> bool {
+	c == '/' || std::path::is_separator(c)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_available_locales() {
+		let dir = env::temp_dir().join(format!("locale-match-macros-{}", std::process::id()));
+		fs::create_dir_all(dir.join("i18n")).unwrap();
+		for name in ["en-US.ftl", "ru.ftl", "de.json", ".ftl"] {
+			fs::write(dir.join("i18n").join(name), []).unwrap();
+		}
+		for locale in ["pt_BR", "uk"] {
+			fs::create_dir_all(dir.join("po").join(locale)).unwrap();
+		}
+		fs::write(dir.join("po").join("pt_BR").join("messages.po"), []).unwrap();
+
+		let ftl = available_locales(&dir, "i18n/{locale}.ftl");
+		let po = available_locales(&dir, "po/{locale}/messages.po");
+		let no_placeholder = available_locales(&dir, "i18n/en-US.ftl");
+		let missing = available_locales(&dir, "missing/{locale}.ftl");
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert_eq!(ftl, Ok(vec!["en-US".to_string(), "ru".to_string()]));
+		assert_eq!(po, Ok(vec!["pt_BR".to_string()]));
+		assert!(no_placeholder.is_err());
+		assert!(missing.is_err());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for detecting user locales on Android.
+
+use jni::objects::JString;
+use jni::{JNIEnv, JavaVM};
+
+/// Returns the preferred locales of the user, as returned by
+/// [`LocaleList.getDefault()`](https://developer.android.com/reference/android/os/LocaleList#getDefault()).
+///
+/// The list reflects the per-app locales if the app has set them, and the system locales otherwise.  
+/// The Java VM is obtained from [`ndk_context`], which is initialized by the Android glue crates
+/// (e.g. `android-activity`). If it is not initialized, an empty list is returned, unless panics
+/// abort the process.  
+/// The locales are in the BCP 47 format and ordered by priority, so the result is ready to be passed
+/// to [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) as the user locales.  
+/// Returns an empty list if the locales cannot be retrieved.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::android::user_locales;
+///
+///
+/// let available_locales = ["en-US", "ru-RU"];
+///
+/// let best_match = best_matching_locale(available_locales, user_locales())
+///     .unwrap_or("en-US");
+/// ```
+pub fn user_locales() -> Vec<String> {
+	// `android_context` panics if the context is not initialized
+	let Ok(context) = std::panic::catch_unwind(ndk_context::android_context) else {
+		return Vec::new();
+	};
+	// SAFETY: The pointer is a valid `JavaVM` provided by the Android glue.
+	let Ok(vm) = (unsafe { JavaVM::from_raw(context.vm().cast()) }) else {
+		return Vec::new();
+	};
+	let Ok(mut env) = vm.attach_current_thread() else {
+		return Vec::new();
+	};
+	user_locales_with_env(&mut env)
+}
+
+/// Returns the preferred locales of the user using the given JNI environment.
+///
+/// Behaves like [`user_locales`], but does not rely on [`ndk_context`],
+/// which is useful when the locales are detected from a native method called from Java or Kotlin.
+pub fn user_locales_with_env(env: &mut JNIEnv) -> Vec<String> {
+	let tags = language_tags(env);
+	if env.exception_check().unwrap_or(false) {
+		let _ = env.exception_clear();
+	}
+	tags.map(|tags| parse_language_tags(&tags)).unwrap_or_default()
+}
+
+/// Calls `LocaleList.getDefault().toLanguageTags()` in a local frame, so the local references are
+/// freed on every path.
+fn language_tags(env: &mut JNIEnv) -> jni::errors::Result<String> {
+	env.with_local_frame(2, |env| {
+		let locale_list = env.call_static_method("android/os/LocaleList", "getDefault", "()Landroid/os/LocaleList;", &[])?.l()?;
+		let tags = JString::from(env.call_method(&locale_list, "toLanguageTags", "()Ljava/lang/String;", &[])?.l()?);
+		let tags = env.get_string(&tags)?.into();
+		Ok(tags)
+	})
+}
+
+/// Splits the comma-separated output of `LocaleList.toLanguageTags()`.
+fn parse_language_tags(tags: &str) -> Vec<String> {
+	let mut locales = Vec::new();
+	for tag in tags.split(',').map(str::trim).filter(|tag| !tag.is_empty() && *tag != "und") {
+		if !locales.iter().any(|locale| locale == tag) {
+			locales.push(tag.to_string());
+		}
+	}
+	locales
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_language_tags() {
+		assert_eq!(parse_language_tags("en-US,ru-RU"), vec!["en-US", "ru-RU"]);
+		assert_eq!(parse_language_tags("en-US,,ru-RU,en-US"), vec!["en-US", "ru-RU"]);
+		assert_eq!(parse_language_tags("und"), Vec::<String>::new());
+		assert_eq!(parse_language_tags(""), Vec::<String>::new());
+	}
+}
@@ -0,0 +1,37 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Shared detection of user locales on Apple platforms.
+
+use core_foundation::array::CFArray;
+use core_foundation::base::TCFType;
+use core_foundation::string::CFString;
+use core_foundation_sys::locale::CFLocaleCopyPreferredLanguages;
+
+/// Returns the languages from `CFLocaleCopyPreferredLanguages`, which backs `NSLocale.preferredLanguages`.
+pub(crate) fn preferred_languages() -> Vec<String> {
+	// SAFETY: The function has no preconditions.
+	let languages = unsafe { CFLocaleCopyPreferredLanguages() };
+	if languages.is_null() {
+		return Vec::new();
+	}
+	// SAFETY: The returned array is owned by the caller and contains strings.
+	let languages = unsafe { CFArray::<CFString>::wrap_under_create_rule(languages) };
+	languages.iter()
+		.map(|language| language.to_string())
+		.filter(|language| !language.is_empty())
+		.collect()
+}
\ No newline at end of file
@@ -0,0 +1,253 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching locales given in a mix of the BCP 47 and POSIX formats.
+//!
+//! The format of each locale is detected separately: locales with a `_`, `.` or `@`, e.g.
+//! `en_US.UTF-8`, and the `C` and `POSIX` locales are POSIX locales, other locales, e.g. `en-US`
+//! or `en`, are BCP 47 locales, see [`is_posix`].  
+//! Both the available and the user locales can be in either format, so, for example, BCP 47 user
+//! locales from a browser can be matched against a POSIX catalog of `.mo` files without converting
+//! them first.  
+//! POSIX locales are converted to BCP 47 with [`posix::to_bcp47`] before
+//! matching, so the modifiers that name neither a script nor a variant are ignored, and the `C` and
+//! `POSIX` locales never match. Their codesets only break ties between POSIX locales.
+//!
+//! # Examples
+//!
+//! ```
+//! use locale_match::auto::best_matching_locale;
+//!
+//!
+//! let available_locales = ["en_US.UTF-8", "sr-Latn-RS", "de"];
+//! let user_locales = ["sr_RS.UTF-8@latin", "en-US"];
+//!
+//! let best_match = best_matching_locale(available_locales, user_locales);
+//!
+//! assert_eq!(best_match, Some("sr-Latn-RS"));
+//! ```
+
+use crate::bcp47;
+use crate::matching::{eq_ignore_ascii_case, LocaleMatch};
+use crate::posix::{self, PosixLocale};
+
+/// Finds the best matching locale from a list of available locales based on a list of user locales,
+/// each of which may be in either the BCP 47 or the POSIX format.
+///
+/// The available locales are returned as they were passed in, e.g. `en_US.UTF-8` stays in the
+/// POSIX format even if it matched the BCP 47 user locale `en-US`.  
+/// Otherwise, the function works like [`bcp47::best_matching_locale`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::auto::best_matching_locale;
+///
+///
+/// let available_locales = ["en-US", "ru_RU.UTF-8", "de"];
+///
+/// assert_eq!(best_matching_locale(available_locales, ["ru-BY"]), Some("ru_RU.UTF-8"));
+/// assert_eq!(best_matching_locale(available_locales, ["en_GB.UTF-8"]), Some("en-US"));
+/// assert_eq!(best_matching_locale(available_locales, ["C.UTF-8", "de_AT"]), Some("de"));
+/// assert_eq!(best_matching_locale(available_locales, ["fr-FR"]), None);
+/// ```
+pub fn best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	best_matching_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+}
+
+/// Finds the best matching locale like [`best_matching_locale`] and reports the quality of the match.
+///
+/// The indices in the returned [`LocaleMatch`] are the indices in the original lists, including
+/// the locales without a BCP 47 equivalent, and the confidence is calculated for the locales
+/// converted to BCP 47.  
+/// A POSIX user locale prefers the available POSIX locale with its codeset among the available
+/// locales that differ only in the codeset. Codesets are compared ignoring case and punctuation,
+/// like in [`posix::Matcher`], so `utf8` is the same codeset as `UTF-8`.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::auto::best_matching_locale_with_confidence;
+///
+///
+/// // POSIX catalog, BCP 47 user locales, e.g. from a browser
+/// let best_match = best_matching_locale_with_confidence(["de_DE.UTF-8", "pt_BR.UTF-8"], ["pt-BR", "de"]).unwrap();
+///
+/// assert_eq!(best_match.locale, "pt_BR.UTF-8");
+/// assert_eq!(best_match.user_index, 0);
+/// assert_eq!(best_match.confidence, 1.0);
+///
+/// // BCP 47 catalog, POSIX user locales, e.g. from the environment
+/// let best_match = best_matching_locale_with_confidence(["en", "sr-Latn"], ["C", "sr_RS.UTF-8@latin"]).unwrap();
+///
+/// assert_eq!(best_match.locale, "sr-Latn");
+/// assert_eq!(best_match.user_index, 1);
+///
+/// // The codeset breaks ties between POSIX locales
+/// let best_match = best_matching_locale_with_confidence(["ru_RU.KOI8-R", "ru_RU.UTF-8"], ["ru_RU.UTF-8"]).unwrap();
+///
+/// assert_eq!(best_match.locale, "ru_RU.UTF-8");
+/// ```
+pub fn best_matching_locale_with_confidence<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let mut available_locales = available_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| Normalized::new(locale.as_ref()).map(|normalized| (i, locale, normalized)))
+		.collect::<Vec<(usize, T1, Normalized)>>();
+	let user_locales = user_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| Normalized::new(locale.as_ref()).map(|normalized| (i, normalized)))
+		.collect::<Vec<(usize, Normalized)>>();
+	let locale_match = bcp47::best_matching_locale_with_confidence(
+		available_locales.iter().map(|(_, _, normalized)| normalized),
+		user_locales.iter().map(|(_, normalized)| normalized),
+	)?;
+	let (user_index, user_locale) = &user_locales[locale_match.user_index];
+	let index = user_locale.codeset.as_deref()
+		.and_then(|codeset| available_locales.iter().position(|(_, _, normalized)| {
+			eq_ignore_ascii_case(&normalized.tag, &locale_match.locale.tag)
+				&& normalized.codeset.as_deref() == Some(codeset)
+		}))
+		.unwrap_or(locale_match.available_index);
+	let (user_index, confidence) = (*user_index, locale_match.confidence);
+	let (available_index, locale, _) = available_locales.swap_remove(index);
+	Some(LocaleMatch { locale, available_index, user_index, confidence })
+}
+
+/// A locale in either format in the shared representation used for matching across the formats:
+/// the locale converted to BCP 47 and the codeset of a POSIX locale, which BCP 47 lacks, normalized
+/// like in the POSIX matcher, e.g. `utf8` for `UTF-8`.
+struct Normalized {
+	tag: String,
+	codeset: Option<String>,
+}
+
+impl Normalized {
+	/// Normalizes a locale in either format.  
+	/// Returns [`None`] if the locale has no BCP 47 equivalent, e.g. `C`.
+	fn new(locale: &str) -> Option<Self> {
+		match is_posix(locale) {
+			true => posix::to_bcp47(locale).map(|tag| Self {
+				tag,
+				codeset: PosixLocale::parse(locale).codeset().map(posix::normalize_codeset),
+			}),
+			false => Some(Self { tag: locale.to_string(), codeset: None }),
+		}
+	}
+}
+
+impl AsRef<str> for Normalized {
+	fn as_ref(&self) -> &str {
+		&self.tag
+	}
+}
+
+/// Checks whether a locale is in the POSIX format, as opposed to the BCP 47 format, as detected by
+/// the functions of this module.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::auto::is_posix;
+///
+///
+/// assert!(is_posix("en_US.UTF-8"));
+/// assert!(is_posix("C"));
+/// assert!(!is_posix("en-US"));
+/// assert!(!is_posix("en"));
+/// ```
+pub fn is_posix(locale: &str) -> bool {
+	locale.contains(['_', '.', '@']) || locale == "C" || locale == "POSIX"
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(best_matching_locale(available_locales, user_locales).copied(), expected, "{available_locales:?}, {user_locales:?}");
+		}
+
+		// Uniform formats
+		case(&["en-US", "ru-RU"], &["ru-BY"], Some("ru-RU"));
+		case(&["en_US.UTF-8", "ru_RU.UTF-8"], &["ru_BY.UTF-8"], Some("ru_RU.UTF-8"));
+
+		// Mixed available locales
+		case(&["en-US", "sr_RS@latin", "sr-Cyrl-RS"], &["sr-Latn"], Some("sr_RS@latin"));
+		case(&["en_US", "de", "fr-FR"], &["fr-CA"], Some("fr-FR"));
+
+		// Mixed user locales
+		case(&["en-US", "ru-RU"], &["fr_FR.UTF-8", "ru_UA.UTF-8"], Some("ru-RU"));
+		case(&["ca-ES-valencia", "ca-ES"], &["ca_ES.UTF-8@valencia", "en"], Some("ca-ES-valencia"));
+
+		// Locales without a BCP 47 equivalent
+		case(&["C", "POSIX.UTF-8", "en"], &["C.UTF-8", "en_US"], Some("en"));
+		case(&["C", "en"], &["C"], None);
+
+		// No match
+		case(&["en-US", "ru_RU"], &["de_DE", "fr"], None);
+		case(&[], &["en"], None);
+		case(&["en"], &[], None);
+	}
+
+	#[test]
+	fn test_best_matching_locale_with_confidence() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Option<(&str, usize, usize)>) {
+			let locale_match = best_matching_locale_with_confidence(available_locales, user_locales);
+			assert_eq!(locale_match.map(|m| (*m.locale, m.available_index, m.user_index)), expected, "{available_locales:?}, {user_locales:?}");
+		}
+
+		// Indices in the original lists
+		case(&["C", "en_US", "ru-RU"], &["POSIX", "fr", "ru_UA"], Some(("ru-RU", 2, 2)));
+		case(&["C", "POSIX", "de_DE.UTF-8"], &["de-AT"], Some(("de_DE.UTF-8", 2, 0)));
+
+		// Codesets
+		case(&["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU.utf-8"], Some(("ru_RU.UTF-8", 1, 0)));
+		case(&["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU.utf8"], Some(("ru_RU.UTF-8", 1, 0)));
+		case(&["ru_RU.KOI8-R", "ru_RU.utf8"], &["ru_RU.UTF-8"], Some(("ru_RU.utf8", 1, 0)));
+		case(&["ru_RU.UTF-8", "ru_RU.koi8r"], &["ru_RU.KOI8-R"], Some(("ru_RU.koi8r", 1, 0)));
+		case(&["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru-RU"], Some(("ru_RU.KOI8-R", 0, 0)));
+		case(&["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU.CP1251"], Some(("ru_RU.KOI8-R", 0, 0)));
+		case(&["ru-RU", "ru_RU.UTF-8"], &["ru_RU.UTF-8"], Some(("ru_RU.UTF-8", 1, 0)));
+		case(&["ru_RU.KOI8-R", "ru_UA.UTF-8"], &["ru_RU.UTF-8"], Some(("ru_RU.KOI8-R", 0, 0)));
+
+		// Confidence
+		let locale_match = best_matching_locale_with_confidence(["en_US.UTF-8"], ["en-US"]).unwrap();
+		assert_eq!(locale_match.confidence, 1.0);
+		let locale_match = best_matching_locale_with_confidence(["en_US.UTF-8"], ["en"]).unwrap();
+		assert!(locale_match.confidence < 1.0);
+	}
+
+	#[test]
+	fn test_is_posix() {
+		assert!(is_posix("en_US"));
+		assert!(is_posix("en.UTF-8"));
+		assert!(is_posix("de@euro"));
+		assert!(is_posix("C"));
+		assert!(is_posix("POSIX"));
+		assert!(!is_posix("en-US"));
+		assert!(!is_posix("en"));
+	}
+}
\ No newline at end of file
@@ -16,7 +16,69 @@
 
 //! A module for matching locales in the [BCP 47](https://www.ietf.org/rfc/bcp/bcp47.html) format.
 
-use language_tags::LanguageTag;
+mod accept_language;
+#[cfg(feature = "bumpalo")]
+mod bump;
+mod bytes;
+mod cache;
+mod case;
+mod diff;
+mod dotnet;
+mod explain;
+mod fallback;
+mod fixed;
+mod keys;
+mod lcid;
+mod matrix;
+mod matchable;
+mod names;
+mod negotiate;
+mod parts;
+mod picker;
+#[cfg(feature = "iana")]
+mod registry;
+mod resolve;
+mod strict;
+mod subsumes;
+mod suggest;
+mod tags;
+mod tenants;
+mod ties;
+mod warnings;
+
+use std::borrow::Borrow;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use language_tags::{LanguageTag, ParseError};
+
+pub use accept_language::{accept_language_header, best_matching_locale_bytes, content_language_header, AcceptLanguage};
+pub use bytes::best_matching_locale_from_bytes;
+pub use case::canonical_case;
+pub use diff::diff;
+pub use dotnet::culture_name_to_locale;
+pub use fallback::matches_iter;
+pub use fixed::best_matching_locale_fixed;
+pub use keys::best_matching_key;
+pub use lcid::{lcid_to_locale, locale_to_lcid};
+pub use matchable::{best_matching_matchable_locale, MatchableLocale};
+pub use names::language_name_to_locale;
+pub use matrix::score_matrix;
+pub use negotiate::{negotiate_languages, NegotiationStrategy};
+pub use parts::TagParts;
+pub use picker::sort_by_relevance;
+#[cfg(feature = "iana")]
+pub use registry::Deprecation;
+pub use resolve::resolve;
+pub use strict::try_best_matching_locale;
+pub use suggest::suggest_locale;
+pub use tags::best_matching_tag;
+pub use tenants::MatcherRegistry;
+pub use ties::best_matching_locales_all_ties;
+pub use warnings::best_matching_locale_with_warnings;
+
+use crate::engine::{self, Engine, Format, Locale};
+use crate::matching::{event, score, Comparison, LocaleKind, LocaleMatch, MissingSubtag, Scoring, TieBreak, Warning};
 
 /// Finds the best matching locale from a list of available locales based on a list of user locales.  
 /// The function ignores any locales that are not valid BCP 47 locales according to
@@ -29,7 +91,9 @@ use language_tags::LanguageTag;
 /// The score calculation gives higher priority to matching more significant parts of the locale
 /// (i.e., earlier segments in the locale string).  
 /// If a subtag is empty, it is considered to match equally well with any subtag from the same
-/// category.
+/// category.  
+/// Variant subtags are compared as sets, so a locale sharing some of the variants of the user
+/// locale scores higher than a locale sharing none of them, regardless of the order of variants.
 ///
 /// If multiple available locales have the same score, the function selects the one that appears
 /// earlier in the list of available locales.  
@@ -96,38 +160,989 @@ where
 	T1: AsRef<str>,
 	T2: AsRef<str>
 {
-	let available_tags = available_locales.into_iter()
-		.filter_map(|l| LanguageTag::parse(l.as_ref()).ok().map(|tag| (l, tag)))
-		.collect::<Vec<(T1, LanguageTag)>>();
-
-	user_locales.into_iter()
-		.filter_map(|locale| LanguageTag::parse(locale.as_ref()).ok())
-		.find_map(|user_tag|
-			available_tags.iter()
+	best_matching_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+}
+
+/// Finds the best matching locale exactly like [`best_matching_locale`], but also returns
+/// information about the quality of the match.
+///
+/// See [`LocaleMatch`] for the description of the returned information.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale_with_confidence;
+///
+///
+/// let available_locales = ["en-US", "pt-BR", "pt-PT"];
+/// let user_locales = ["pt-PT", "en"];
+///
+/// let best_match = best_matching_locale_with_confidence(available_locales, user_locales).unwrap();
+///
+/// // "pt-PT" matches the most preferred user locale exactly
+/// assert_eq!(best_match.locale, "pt-PT");
+/// assert_eq!(best_match.user_index, 0);
+/// assert_eq!(best_match.confidence, 1.0);
+///
+///
+/// let available_locales = ["en-US", "pt-BR"];
+/// let user_locales = ["fr-FR", "pt-PT", "en"];
+///
+/// let best_match = best_matching_locale_with_confidence(available_locales, user_locales).unwrap();
+///
+/// // Only the language of the second user locale is matched
+/// assert_eq!(best_match.locale, "pt-BR");
+/// assert_eq!(best_match.user_index, 1);
+/// assert!(best_match.confidence < 1.0);
+/// ```
+pub fn best_matching_locale_with_confidence<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locale_with_confidence(available_locales, user_locales)
+}
+
+/// Checks whether a locale is a pseudo-locale used for localization testing.
+///
+/// The following locales are recognized as pseudo-locales:
+/// * locales with the `XA`, `XB` or `XC` region, e.g. `en-XA` (accented English) or `ar-XB`
+///   (right-to-left English), as used by Android and Chromium;
+/// * locales with the `qps` language, e.g. `qps-ploc`, `qps-ploca` or `qps-plocm`, as used by
+///   Windows.
+///
+/// Malformed locales are not pseudo-locales.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::is_pseudo_locale;
+///
+///
+/// assert!(is_pseudo_locale("en-XA"));
+/// assert!(is_pseudo_locale("ar-XB"));
+/// assert!(is_pseudo_locale("qps-ploc"));
+///
+/// assert!(!is_pseudo_locale("en-US"));
+/// assert!(!is_pseudo_locale("ar"));
+/// ```
+pub fn is_pseudo_locale(locale: impl AsRef<str>) -> bool {
+	LanguageTag::parse(locale.as_ref()).is_ok_and(|tag| is_pseudo_tag(&tag))
+}
+
+/// Replaces the primary language and the extended language of a language tag by the extended
+/// language, which is the canonical form of every extended language in the IANA Language Subtag
+/// Registry, e.g. `zh-cmn-Hans` by `cmn-Hans`.  
+/// Tags with several extended languages have no canonical form and are kept. With the `iana`
+/// feature, so are tags whose extended language is not in the registry or does not have the primary
+/// language as its Prefix, e.g. `zh-afb`. Without it, every extended language is assumed to be
+/// registered with the primary language as its Prefix.
+fn canonicalize_extlang(tag: LanguageTag) -> LanguageTag {
+	match tag.extended_language() {
+		#[cfg(feature = "iana")]
+		Some(extlang) if !registry::is_extlang_prefix(extlang, tag.primary_language()) => tag,
+		Some(extlang) if !extlang.contains('-') => {
+			let rest = &tag.as_str()[tag.primary_language().len() + 1 + extlang.len()..];
+			LanguageTag::parse(&format!("{extlang}{rest}")).unwrap_or(tag)
+		}
+		_ => tag,
+	}
+}
+
+fn is_pseudo_tag(tag: &(impl MatchableLocale + ?Sized)) -> bool {
+	tag.language().eq_ignore_ascii_case("qps")
+		|| tag.region().is_some_and(|region| ["XA", "XB", "XC"].iter().any(|pseudo| region.eq_ignore_ascii_case(pseudo)))
+}
+
+/// A valid BCP 47 locale.
+///
+/// The locale is validated on creation, but keeps its original string, so it can be passed to the
+/// matching functions and returned from them as is.  
+/// With the `serde` feature, the locale is serialized as a string and validated on deserialization.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{best_matching_locale, Bcp47Locale};
+///
+///
+/// let available_locales = ["en-US".parse::<Bcp47Locale>().unwrap(), "ru-BY".parse().unwrap()];
+///
+/// let best_match = best_matching_locale(&available_locales, ["ru-RU"]);
+///
+/// assert_eq!(best_match.map(Bcp47Locale::as_str), Some("ru-BY"));
+/// assert!("en_US".parse::<Bcp47Locale>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bcp47Locale(String);
+
+impl Bcp47Locale {
+	/// Validates a BCP 47 locale.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the locale is not a valid BCP 47 locale.
+	pub fn parse(locale: impl Into<String>) -> Result<Self, language_tags::ParseError> {
+		let locale = locale.into();
+		LanguageTag::parse(&locale)?;
+		Ok(Self(locale))
+	}
+
+	/// Returns the locale as a string slice.
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Returns the locale as a string.
+	pub fn into_string(self) -> String {
+		self.0
+	}
+}
+
+impl std::str::FromStr for Bcp47Locale {
+	type Err = language_tags::ParseError;
+
+	fn from_str(locale: &str) -> Result<Self, Self::Err> {
+		Self::parse(locale)
+	}
+}
+
+impl AsRef<str> for Bcp47Locale {
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+impl std::fmt::Display for Bcp47Locale {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Bcp47Locale {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.0)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Bcp47Locale {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Self::parse(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+	}
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Bcp47Locale {
+	fn inline_schema() -> bool {
+		true
+	}
+
+	fn schema_name() -> std::borrow::Cow<'static, str> {
+		"Bcp47Locale".into()
+	}
+
+	fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+		schemars::json_schema!({
+			"type": "string",
+			"minLength": 1,
+			"description": "A BCP 47 locale, e.g. `en-US`.",
+		})
+	}
+}
+
+/// A configurable matcher of BCP 47 locales.
+///
+/// A default matcher behaves exactly like the [`best_matching_locale`] function.
+/// The behavior can be adjusted with the `with_*` methods.
+///
+/// The functions comparing locales, set with [`Matcher::with_private_use_matcher`],
+/// [`Matcher::with_scorer`] and [`Matcher::with_observer`], always receive the user locale (or its
+/// subtags) first and the available locale second.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::Matcher;
+///
+///
+/// // Private use subtags of the form "x-tenant-<name>" are compatible only with the same tenant
+/// let matcher = Matcher::new().with_private_use_matcher(|user, aval| match (user, aval) {
+///     (["tenant", u], ["tenant", a]) if u != a => None,
+///     _ => Some(if user == aval { 1.0 } else { 0.0 }),
+/// });
+///
+/// let available_locales = ["de-x-tenant-acme", "de-x-tenant-globex", "en"];
+/// let user_locales = ["de-x-tenant-initech", "en"];
+///
+/// let best_match = matcher.best_matching_locale(available_locales, user_locales);
+///
+/// // Neither of the German locales is compatible with the tenant of the user
+/// assert_eq!(best_match, Some("en"));
+/// ```
+#[derive(Clone, Default)]
+pub struct Matcher {
+	scoring: Scoring,
+	private_use_matcher: Option<Arc<PrivateUseMatcher>>,
+	scorer: Option<Arc<Scorer>>,
+	observer: Option<Arc<Observer>>,
+	random_tie_break: Option<RandomTieBreak>,
+	isolate_pseudo_locales: bool,
+	lcid_inputs: bool,
+	dotnet_culture_names: bool,
+	language_names: bool,
+	extlang_canonicalization: bool,
+	user_locale_cache: bool,
+	#[cfg(feature = "iana")]
+	registry_validation: bool,
+	#[cfg(feature = "iana")]
+	implicit_scripts: bool,
+	#[cfg(feature = "iana")]
+	canonicalization: bool,
+}
+
+/// A category of subtags of a BCP 47 locale, except for the primary language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Subtag {
+	/// The extended language subtag, e.g. `cmn` in `zh-cmn-Hans-CN`.
+	ExtendedLanguage,
+	/// The script subtag, e.g. `Hans` in `zh-cmn-Hans-CN`.
+	Script,
+	/// The region subtag, e.g. `CN` in `zh-cmn-Hans-CN`.
+	Region,
+	/// The variant subtags, e.g. `rozaj-biske` in `sl-rozaj-biske`.
+	Variant,
+	/// The extension subtags, e.g. `u-nu-latn` in `ar-u-nu-latn`.
+	Extension,
+	/// The private use subtags, e.g. `x-foo` in `de-x-foo`.
+	PrivateUse,
+}
+
+/// The subtag categories in the order of the comparisons of the matcher.
+const SUBTAGS: [Subtag; 6] = [Subtag::ExtendedLanguage, Subtag::Script, Subtag::Region, Subtag::Variant, Subtag::Extension, Subtag::PrivateUse];
+
+/// A function deciding how private use subtags match, see [`Matcher::with_private_use_matcher`].
+type PrivateUseMatcher = dyn Fn(&[&str], &[&str]) -> Option<f64> + Send + Sync;
+
+/// A function adjusting the score of a match, see [`Matcher::with_scorer`].
+type Scorer = dyn Fn(&LanguageTag, &LanguageTag) -> Option<f64> + Send + Sync;
+
+/// A function observing the scored candidates, see [`Matcher::with_observer`].
+type Observer = dyn Fn(&LanguageTag, &LanguageTag, f64) -> ControlFlow<()> + Send + Sync;
+
+/// A source of random numbers and the weights of the available locales, see
+/// [`Matcher::with_weighted_random_tie_break`].
+#[derive(Clone)]
+struct RandomTieBreak {
+	random: Arc<dyn Fn() -> f64 + Send + Sync>,
+	weight: Arc<dyn Fn(&LanguageTag) -> f64 + Send + Sync>,
+}
+
+impl Matcher {
+	/// Creates a matcher with the default configuration.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the penalty for contradicting subtags.
+	///
+	/// By default, a subtag present in both locales with different values earns nothing, just like
+	/// a subtag absent in one of the locales. With a positive penalty, such a subtag reduces the
+	/// score by `penalty` times the weight of the subtag, so neutral locales win over locales with
+	/// wrong subtags. For example, with the penalty of `1.0` a wrong region costs exactly as much
+	/// as a matching region earns.  
+	/// Negative values are treated as `0.0`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let available_locales = ["fr-FR", "fr"];
+	/// let user_locales = ["fr-CA"];
+	///
+	/// // By default, "fr-FR" and "fr" match "fr-CA" equally well, so the first one is chosen
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, user_locales), Some("fr-FR"));
+	///
+	/// // With the penalty, the neutral "fr" wins over the wrong region
+	/// let matcher = Matcher::new().with_mismatch_penalty(1.0);
+	/// assert_eq!(matcher.best_matching_locale(available_locales, user_locales), Some("fr"));
+	/// ```
+	pub fn with_mismatch_penalty(mut self, penalty: f64) -> Self {
+		self.scoring.mismatch_penalty = penalty.max(0.0);
+		self
+	}
+
+	/// Sets how a subtag of the given category is scored if it is present in only one of the
+	/// locales.
+	///
+	/// By default, all categories use [`MissingSubtag::Neutral`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::{Matcher, Subtag};
+	/// use locale_match::matching::MissingSubtag;
+	///
+	///
+	/// let available_locales = ["en-US", "en"];
+	/// let user_locales = ["en"];
+	///
+	/// // By default, "en-US" and "en" match "en" equally well, so the first one is chosen
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, user_locales), Some("en-US"));
+	///
+	/// // Prefer locales without a region if the user did not specify one
+	/// let matcher = Matcher::new().with_missing_subtag(Subtag::Region, MissingSubtag::RequireAbsence);
+	/// assert_eq!(matcher.best_matching_locale(available_locales, user_locales), Some("en"));
+	/// ```
+	pub fn with_missing_subtag(mut self, subtag: Subtag, missing: MissingSubtag) -> Self {
+		self.scoring.set_missing_subtag(subtag as usize, missing);
+		self
+	}
+
+	/// Sets a function deciding how private use subtags of a user locale and an available locale
+	/// match.
+	///
+	/// The function receives the private use subtags (without the leading `x`) of the user locale
+	/// and of the available locale, in the order described in [`Matcher`]. It is called only if at
+	/// least one of the locales has private use subtags.  
+	/// If the subtags are compatible, the function returns their similarity in the range
+	/// `0.0..=1.0`, which is used instead of the default exact comparison. Values outside of this
+	/// range are clamped, and NaN is treated as `0.0`.  
+	/// If the function returns [`None`], the available locale is not considered a match for the
+	/// user locale at all.
+	pub fn with_private_use_matcher(mut self, private_use_matcher: impl Fn(&[&str], &[&str]) -> Option<f64> + Send + Sync + 'static) -> Self {
+		self.private_use_matcher = Some(Arc::new(private_use_matcher));
+		self
+	}
+
+	/// Sets a function adjusting the score of every available locale that has the same primary
+	/// language as the user locale.
+	///
+	/// The function receives the user locale and the available locale, in the order described in
+	/// [`Matcher`], and returns a value that is added to the score calculated by the matcher.  
+	/// The scorer is directional: the score for a user locale `A` and an available locale `B` does
+	/// not have to be equal to the score for a user locale `B` and an available locale `A`. This
+	/// allows to express that, for example, a British English speaker is more comfortable with
+	/// American English than an American English speaker is with Indian English.  
+	/// If the function returns [`None`], the available locale is not considered a match for the
+	/// user locale at all.
+	///
+	/// For reference, the matcher adds `32` for matching extended languages, `16` for matching
+	/// scripts, `8` for matching regions, up to `4` for matching variants, `2` for matching
+	/// extensions and up to `1` for matching private use subtags.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_scorer(|user, aval| {
+	///     match (user.region(), aval.region()) {
+	///         (Some("GB"), Some("US")) => Some(2.0),
+	///         (Some("US"), Some("IN")) => Some(1.0),
+	///         _ => Some(0.0),
+	///     }
+	/// });
+	///
+	/// // A British user prefers American English to Indian English...
+	/// assert_eq!(matcher.best_matching_locale(["en-IN", "en-US"], ["en-GB"]), Some("en-US"));
+	///
+	/// // ...but it does not mean that an American user prefers British English
+	/// assert_eq!(matcher.best_matching_locale(["en-GB", "en-IN"], ["en-US"]), Some("en-IN"));
+	/// ```
+	pub fn with_scorer(mut self, scorer: impl Fn(&LanguageTag, &LanguageTag) -> Option<f64> + Send + Sync + 'static) -> Self {
+		self.scorer = Some(Arc::new(scorer));
+		self
+	}
+
+	/// Sets a function called with a user locale, an available locale and its score for each
+	/// candidate evaluated during matching, e.g. for custom logging or metrics.
+	///
+	/// The locales are passed in the order described in [`Matcher`], and the candidates for a user
+	/// locale are evaluated in the order of the available locales.
+	/// If the function returns [`ControlFlow::Break`], the remaining candidates for the user locale
+	/// are skipped, and the best of the evaluated ones is chosen, which allows early termination
+	/// policies, e.g. accepting the first good enough match.  
+	/// The function is not called by [`Matcher::explain`] and [`Matcher::score_matrix`], which
+	/// evaluate the candidates without matching.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use std::ops::ControlFlow;
+	/// use std::sync::atomic::{AtomicUsize, Ordering};
+	/// use std::sync::Arc;
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let evaluations = Arc::new(AtomicUsize::new(0));
+	/// let counter = evaluations.clone();
+	/// let matcher = Matcher::new().with_observer(move |_user, _aval, score| {
+	///     counter.fetch_add(1, Ordering::Relaxed);
+	///     // Accept the first locale with a matching region
+	///     if score >= 8.0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+	/// });
+	///
+	/// let best_match = matcher.best_matching_locale(["en", "en-US", "en-US-x-foo", "en-GB"], ["en-US-x-foo"]);
+	///
+	/// assert_eq!(best_match, Some("en-US"));
+	/// assert_eq!(evaluations.load(Ordering::Relaxed), 2);
+	/// ```
+	pub fn with_observer(mut self, observer: impl Fn(&LanguageTag, &LanguageTag, f64) -> ControlFlow<()> + Send + Sync + 'static) -> Self {
+		self.observer = Some(Arc::new(observer));
+		self
+	}
+
+	/// Sets how to choose among available locales that match a user locale equally well.
+	///
+	/// By default, the locale that appears earlier in the list of available locales is chosen.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	/// use locale_match::matching::TieBreak;
+	///
+	///
+	/// let matcher = Matcher::new().with_tie_break(TieBreak::MostSpecific);
+	///
+	/// let best_match = matcher.best_matching_locale(["en", "en-US"], ["en-GB"]);
+	///
+	/// assert_eq!(best_match, Some("en-US"));
+	/// ```
+	pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+		self.scoring.tie_break = tie_break;
+		self
+	}
+
+	/// Sets a random tie break, which picks one of the available locales that match a user locale
+	/// equally well uniformly at random, e.g. for A/B testing of regional variants.
+	///
+	/// `random` is the source of random numbers in the range `0.0..1.0`, e.g. `rand::random` or a
+	/// seeded generator for reproducible experiments. It is called only when there is a tie.  
+	/// The random tie break replaces the one set with [`with_tie_break`](Self::with_tie_break), and
+	/// is not applied by the functions that do not select a single locale, e.g.
+	/// [`best_matching_locales_all_ties`](Self::best_matching_locales_all_ties).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_random_tie_break(|| 0.75);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "en-GB"], ["en-CA"]), Some("en-GB"));
+	/// ```
+	pub fn with_random_tie_break(self, random: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+		self.with_weighted_random_tie_break(random, |_| 1.0)
+	}
+
+	/// Sets a random tie break like [`with_random_tie_break`](Self::with_random_tie_break), but
+	/// picks the tied available locales with probabilities proportional to their weights.
+	///
+	/// Negative weights count as zero, and the first of the tied locales is picked if all of their
+	/// weights are zero.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// // Serve the first locale to 90% of the users
+	/// let matcher = Matcher::new().with_weighted_random_tie_break(|| 0.95, |tag| if tag.region() == Some("US") { 9.0 } else { 1.0 });
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "en-GB"], ["en-CA"]), Some("en-GB"));
+	/// ```
+	pub fn with_weighted_random_tie_break(mut self, random: impl Fn() -> f64 + Send + Sync + 'static, weight: impl Fn(&LanguageTag) -> f64 + Send + Sync + 'static) -> Self {
+		self.random_tie_break = Some(RandomTieBreak { random: Arc::new(random), weight: Arc::new(weight) });
+		self
+	}
+
+	/// Sets whether available pseudo-locales (see [`is_pseudo_locale`]) can match only user
+	/// pseudo-locales.
+	///
+	/// By default, pseudo-locales are matched like any other locales, so, for example, `en-XA` can
+	/// be chosen for a user who prefers `en`. With isolation enabled, an available pseudo-locale is
+	/// chosen only if the user asks for a pseudo-locale explicitly.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_isolated_pseudo_locales(true);
+	///
+	/// let available_locales = ["en-XA", "ar-XB", "fr"];
+	///
+	/// // Real preferences never get pseudo-locales
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["en-US", "fr"]), Some("fr"));
+	///
+	/// // Pseudo-locales are still available on explicit request
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["ar-XB", "fr"]), Some("ar-XB"));
+	/// ```
+	pub fn with_isolated_pseudo_locales(mut self, isolate: bool) -> Self {
+		self.isolate_pseudo_locales = isolate;
+		self
+	}
+
+	/// Sets whether Windows locale identifiers (LCIDs) are accepted as locales.
+	///
+	/// By default, LCIDs are malformed locales and are ignored. With this option enabled, a locale
+	/// that is a hexadecimal LCID with the `0x` prefix (e.g. `0x0409`) or a decimal LCID (e.g.
+	/// `1033`) is converted with [`lcid_to_locale`] and matched as the resulting locale.  
+	/// The returned locale is still the original input, e.g. `0x0409`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_lcid_inputs(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "ru-RU"], ["0x0419"]), Some("ru-RU"));
+	/// assert_eq!(matcher.best_matching_locale(["0x0409", "0x0419"], ["ru"]), Some("0x0419"));
+	/// ```
+	pub fn with_lcid_inputs(mut self, lcid_inputs: bool) -> Self {
+		self.lcid_inputs = lcid_inputs;
+		self
+	}
+
+	/// Sets whether locales are treated as .NET culture names.
+	///
+	/// By default, the legacy culture names are matched as BCP 47 locales, so `zh-CHS` has the
+	/// extended language `chs`, and `uz-UZ-Cyrl` and the Windows names with sort orders, e.g.
+	/// `de-DE_phoneb`, are malformed. With this option enabled, the locales are converted with
+	/// [`culture_name_to_locale`] before matching, e.g. for locales received from Windows and .NET
+	/// clients.  
+	/// The returned locale is still the original input, e.g. `zh-CHS`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_dotnet_culture_names(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["zh-Hant-TW", "zh-Hans-CN"], ["zh-CHS"]), Some("zh-Hans-CN"));
+	/// assert_eq!(matcher.best_matching_locale(["uz-Latn-UZ", "uz-Cyrl-UZ"], ["uz-UZ-Cyrl"]), Some("uz-Cyrl-UZ"));
+	/// assert_eq!(matcher.best_matching_locale(["de-AT", "de-DE"], ["de-DE_phoneb"]), Some("de-DE"));
+	/// ```
+	pub fn with_dotnet_culture_names(mut self, dotnet_culture_names: bool) -> Self {
+		self.dotnet_culture_names = dotnet_culture_names;
+		self
+	}
+
+	/// Sets whether human-readable language names are accepted as locales, e.g. for language
+	/// preferences typed by users in free text.
+	///
+	/// By default, names like `german` are matched as BCP 47 locales, so they match nothing, and
+	/// names like `Chinese (Simplified)` are malformed. With this option enabled, the known names
+	/// are converted with [`language_name_to_locale`] before matching, e.g. `Deutsch` to `de`.  
+	/// The returned locale is still the original input, e.g. `Deutsch`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_language_names(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "de-DE"], ["Deutsch"]), Some("de-DE"));
+	/// assert_eq!(matcher.best_matching_locale(["zh-Hant-TW", "zh-Hans-CN"], ["chinese (simplified)"]), Some("zh-Hans-CN"));
+	/// ```
+	pub fn with_language_names(mut self, language_names: bool) -> Self {
+		self.language_names = language_names;
+		self
+	}
+
+	/// Sets whether the extended language forms of locales are replaced by their canonical forms
+	/// before matching, e.g. `zh-cmn-Hans` by `cmn-Hans` and `ar-afb` by `afb`.
+	///
+	/// By default, the extended language is compared as a separate subtag, so `zh-cmn-Hans`
+	/// matches `zh-Hans`, but not `cmn-Hans`. With this option enabled, the canonical and the
+	/// extended language forms, which both appear in real data, are equivalent, while `zh` and
+	/// `cmn` are different languages.  
+	/// With the `iana` feature, only the extended languages registered with the primary language as
+	/// their Prefix are replaced, so `zh-afb` is kept.  
+	/// The returned locale is still the original input, e.g. `zh-cmn-Hans`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_extlang_canonicalization(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "cmn-Hans"], ["zh-cmn-Hans-CN"]), Some("cmn-Hans"));
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "ar-afb"], ["afb"]), Some("ar-afb"));
+	/// ```
+	pub fn with_extlang_canonicalization(mut self, extlang_canonicalization: bool) -> Self {
+		self.extlang_canonicalization = extlang_canonicalization;
+		self
+	}
+
+	/// Sets whether the parsed user locales are cached.
+	///
+	/// Parsing the user locales takes a noticeable part of matching, and servers tend to see the same
+	/// few lists of user locales, e.g. from the `Accept-Language` header, over and over. With this
+	/// option enabled, each thread keeps the parsed tags of the 64 most recently matched lists
+	/// of user locales, and the `best_matching_locale` methods reuse them for an equal list.  
+	/// Only the parsed user locales are cached, not the results of matching, so the cache is valid for
+	/// any available locales. The cache is shared by all matchers on the thread.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_user_locale_cache(true);
+	///
+	/// // The user locales are parsed once and reused for the second call
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "ru-BY"], ["ru-RU", "en"]), Some("ru-BY"));
+	/// assert_eq!(matcher.best_matching_locale(["en-GB", "fr"], ["ru-RU", "en"]), Some("en-GB"));
+	/// ```
+	pub fn with_user_locale_cache(mut self, cache: bool) -> Self {
+		self.user_locale_cache = cache;
+		self
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale`], using the configuration of
+	/// the matcher.
+	pub fn best_matching_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		self.best_matching_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale_with_confidence`], using the
+	/// configuration of the matcher.
+	pub fn best_matching_locale_with_confidence<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locale_with_confidence(self, available_locales, user_locales)
+	}
+
+	/// Finds the best match among the available tags, which `tag` returns with their original
+	/// indices by their indices in `available`.  
+	/// Returns the index of the matched tag in `available`, the index of the user locale and the
+	/// confidence of the match.
+	fn best_match<X, T>(&self, available: &[X], tag: impl Fn(usize, &X) -> (usize, &LanguageTag), user_locales: impl IntoIterator<Item = T>) -> Option<(usize, usize, f64)>
+	where
+		T: AsRef<str>
+	{
+		if self.user_locale_cache {
+			let user_tags = cache::parse_user_locales(self, user_locales);
+			self.best_match_for_tags(available, tag, user_tags.iter().map(|(user_index, user_tag)| (*user_index, user_tag)))
+		} else {
+			let user_tags = user_locales.into_iter()
 				.enumerate()
-				.rev() // For max_by_key to return the first tag with max score
-				.filter(|(_, (_, aval_tag))| aval_tag.primary_language() == user_tag.primary_language())
-				.max_by_key(|(_, (_, aval_tag))| {
-					let mut score = 0;
-					for (aval, user, weight) in [
-						(aval_tag.extended_language(), user_tag.extended_language(), 32),
-						(aval_tag.script(),            user_tag.script(),            16),
-						(aval_tag.region(),            user_tag.region(),             8),
-						(aval_tag.variant(),           user_tag.variant(),            4),
-						// TODO: Implement separate comparison for each extension
-						(aval_tag.extension(),         user_tag.extension(),          2),
-						(aval_tag.private_use(),       user_tag.private_use(),        1),
-					] {
-						match (aval, user) {
-							(Some(a), Some(u)) if a == u => score += weight,
-							_ => {} // Ignore if both are None
-						}
-					}
-					score
-				})
-				.map(|(i, _)| i)
-		)
-		.map(|i| available_tags.into_iter().nth(i).unwrap().0)
+				.filter_map(|(user_index, locale)| self.parse(locale.as_ref()).map(|tag| (user_index, tag)));
+			self.best_match_for_tags(available, tag, user_tags)
+		}
+	}
+
+	/// Finds the best match among the available tags for the parsed user tags with their indices,
+	/// like [`best_match`](Self::best_match).
+	fn best_match_for_tags<X, U>(&self, available: &[X], tag: impl Fn(usize, &X) -> (usize, &LanguageTag), user_tags: impl IntoIterator<Item = (usize, U)>) -> Option<(usize, usize, f64)>
+	where
+		U: Borrow<LanguageTag>
+	{
+		self.engine().best_match(available, tag, user_tags, |_, aval_tag, user_tag| self.candidate(aval_tag, user_tag))
+	}
+
+	/// The matching engine with the options of the matcher.
+	fn engine(&self) -> Engine<'_, LanguageTag, LanguageTag> {
+		Engine {
+			format: "bcp47",
+			language_weight: <Self as Format>::LANGUAGE_WEIGHT,
+			tie_break: self.scoring.tie_break,
+			rank: None,
+			observer: self.observer.as_deref().map(|observer| observer as &dyn Fn(&LanguageTag, &LanguageTag, f64) -> ControlFlow<()>),
+			random_tie_break: self.random_tie_break.as_ref().map(|r| (&*r.random as &dyn Fn() -> f64, &*r.weight as &dyn Fn(&LanguageTag) -> f64)),
+		}
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale`](Self::best_matching_locale) for
+	/// locale types that are serialized with [`Display`](std::fmt::Display), e.g. those of other
+	/// locale crates.
+	#[cfg(any(feature = "icu", feature = "unic"))]
+	pub(crate) fn best_matching_displayed_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: std::fmt::Display,
+		T2: std::fmt::Display,
+	{
+		let available_locales = available_locales.into_iter().map(Serialized::new);
+		let user_locales = user_locales.into_iter().map(|locale| locale.to_string());
+		self.best_matching_locale(available_locales, user_locales).map(|Serialized(_, locale)| locale)
+	}
+
+	/// Score of an available tag that can match a user tag with the same language, along with the
+	/// comparisons of their subtags.  
+	/// Returns [`None`] if the available tag cannot match the user tag.
+	fn candidate(&self, aval_tag: &LanguageTag, user_tag: &LanguageTag) -> Option<(f64, [(Comparison, f64); 6])> {
+		if self.isolate_pseudo_locales && is_pseudo_tag(aval_tag) && !is_pseudo_tag(user_tag) {
+			return None;
+		}
+		self.score(aval_tag, user_tag)
+	}
+
+	/// Parses a locale according to the configuration of the matcher.  
+	/// Returns [`None`] if the locale is malformed.
+	fn parse(&self, locale: &str) -> Option<LanguageTag> {
+		let culture_locale = match self.dotnet_culture_names {
+			true => dotnet::culture_name_to_locale(locale),
+			false => None,
+		};
+		let named_locale = match self.language_names {
+			true => names::language_name_to_locale(locale),
+			false => None,
+		};
+		let tag = LanguageTag::parse(named_locale.or(culture_locale.as_deref()).unwrap_or(locale)).ok().or_else(|| match self.lcid_inputs {
+			true => lcid::parse_lcid(locale).and_then(|locale| LanguageTag::parse(locale).ok()),
+			false => None,
+		});
+		let tag = tag.map(|tag| match self.extlang_canonicalization {
+			true => canonicalize_extlang(tag),
+			false => tag,
+		});
+		#[cfg(feature = "iana")]
+		let tag = tag.filter(|tag| !self.registry_validation || tag.is_valid())
+			.map(|tag| match self.canonicalization {
+				true => registry::canonicalize(tag),
+				false => tag,
+			});
+		if tag.is_none() {
+			event!(debug, locale, "ignored malformed locale");
+		}
+		tag
+	}
+
+	/// Score of an available language tag for a user language tag, along with the comparisons of
+	/// their subtags.  
+	/// Returns [`None`] if the tags are incompatible.
+	fn score(&self, aval_tag: &LanguageTag, user_tag: &LanguageTag) -> Option<(f64, [(Comparison, f64); 6])> {
+		let Some(comparisons) = self.comparisons(aval_tag, user_tag) else {
+			event!(trace, available = %aval_tag, user = %user_tag, "rejected incompatible candidate");
+			return None;
+		};
+		let adjustment = match &self.scorer {
+			Some(scorer) => match scorer(user_tag, aval_tag) {
+				Some(adjustment) => adjustment,
+				None => {
+					event!(trace, available = %aval_tag, user = %user_tag, "candidate rejected by the scorer");
+					return None;
+				}
+			},
+			None => 0.0,
+		};
+		let score = score(comparisons, &self.scoring) + adjustment;
+		event!(trace, available = %aval_tag, user = %user_tag, score, "scored candidate");
+		Some((score, comparisons))
+	}
+
+	/// Comparisons of corresponding subtags of two locales (except for the primary language) with
+	/// their weights, in the order of [`Subtag`].  
+	/// Returns [`None`] if the locales are incompatible.
+	///
+	/// Variants are compared as sets, so that locales sharing some of their variants get partial credit.
+	fn comparisons(&self, aval_tag: &(impl MatchableLocale + ?Sized), user_tag: &(impl MatchableLocale + ?Sized)) -> Option<[(Comparison, f64); 6]> {
+		fn subtags(subtags: Option<&str>) -> Vec<&str> {
+			subtags.map_or(Vec::new(), |subtags| subtags.split('-').collect())
+		}
+		fn variants(tag: &(impl MatchableLocale + ?Sized)) -> impl Iterator<Item = &str> + Clone {
+			tag.variant().into_iter().flat_map(|variants| variants.split('-'))
+		}
+
+		let eq = str::eq_ignore_ascii_case;
+		let (aval_script, user_script) = (aval_tag.script(), user_tag.script());
+		#[cfg(feature = "iana")]
+		let (aval_script, user_script) = match self.implicit_scripts {
+			true => registry::implicit_scripts(aval_tag, user_tag),
+			false => (aval_script, user_script),
+		};
+		let private_use = match &self.private_use_matcher {
+			Some(private_use_matcher) if aval_tag.private_use().is_some() || user_tag.private_use().is_some() => {
+				// Without the "x" singleton
+				let aval_private_use = subtags(aval_tag.private_use().and_then(|subtags| subtags.get(2..)));
+				let user_private_use = subtags(user_tag.private_use().and_then(|subtags| subtags.get(2..)));
+				let similarity = private_use_matcher(&user_private_use, &aval_private_use)?;
+				Comparison::Present(if similarity.is_nan() { 0.0 } else { similarity.clamp(0.0, 1.0) })
+			}
+			_ => Comparison::of(aval_tag.private_use(), user_tag.private_use(), eq),
+		};
+		Some([
+			(Comparison::of(aval_tag.extended_language(), user_tag.extended_language(), eq), 32.0),
+			(Comparison::of(aval_script,                  user_script,                  eq), 16.0),
+			(Comparison::of(aval_tag.region(),            user_tag.region(),            eq),  8.0),
+			(Comparison::of_sets(variants(aval_tag),      variants(user_tag),           eq),  4.0),
+			// TODO: Implement separate comparison for each extension
+			(Comparison::of(aval_tag.extension(),         user_tag.extension(),         eq),  2.0),
+			(private_use,                                                                     1.0),
+		])
+	}
+}
+
+impl Locale for LanguageTag {
+	fn as_str(&self) -> &str {
+		self.as_str()
+	}
+
+	fn language(&self) -> &str {
+		self.primary_language()
+	}
+
+	fn script_or_modifier(&self) -> Option<&str> {
+		self.script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.region()
+	}
+}
+
+/// A locale parsed into a language tag by a [`Matcher`], along with the locale as it was passed in.
+pub(crate) struct Tagged<T> {
+	pub(crate) locale: T,
+	pub(crate) tag: LanguageTag,
+}
+
+impl<T: AsRef<str>> Locale for Tagged<T> {
+	fn as_str(&self) -> &str {
+		self.locale.as_ref()
+	}
+
+	fn language(&self) -> &str {
+		self.tag.primary_language()
+	}
+
+	fn script_or_modifier(&self) -> Option<&str> {
+		self.tag.script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.tag.region()
+	}
+}
+
+impl Format for Matcher {
+	type Parsed<T: AsRef<str>> = Tagged<T>;
+	type Subtag = Subtag;
+	type Comparisons = [(Comparison, f64); 6];
+	type Error = ParseError;
+
+	const SUBTAGS: &'static [Subtag] = &SUBTAGS;
+	const LANGUAGE_WEIGHT: f64 = 64.0;
+
+	fn parse_locale<T: AsRef<str>>(&self, locale: T) -> Result<Tagged<T>, T> {
+		match self.parse(locale.as_ref()) {
+			Some(tag) => Ok(Tagged { locale, tag }),
+			None => Err(locale),
+		}
+	}
+
+	fn into_inner<T: AsRef<str>>(locale: Tagged<T>) -> T {
+		locale.locale
+	}
+
+	fn check<T: AsRef<str>>(&self, locale: Result<&Tagged<T>, &str>) -> Result<(), ParseError> {
+		match locale {
+			Ok(_) => Ok(()),
+			Err(locale) => LanguageTag::parse(locale).map(drop),
+		}
+	}
+
+	fn warning<T: AsRef<str>>(&self, kind: LocaleKind, index: usize, locale: Result<&Tagged<T>, &str>) -> Option<Warning> {
+		let locale = locale.err()?;
+		let reason = match LanguageTag::parse(locale) {
+			Err(error) => error.to_string(),
+			#[cfg(feature = "iana")]
+			Ok(tag) => registry::validation_error(&tag).unwrap_or_default(),
+			#[cfg(not(feature = "iana"))]
+			Ok(_) => String::new(),
+		};
+		let replacement = suggest_locale(locale).into_iter().next();
+		Some(Warning { kind, index, input: locale.to_string(), reason, replacement })
+	}
+
+	#[cfg(feature = "iana")]
+	fn deprecation_warning(&self, kind: LocaleKind, index: usize, locale: &str) -> Option<Warning> {
+		registry::deprecation_warning(kind, index, locale)
+	}
+
+	fn scoring(&self) -> &Scoring {
+		&self.scoring
+	}
+
+	fn candidate<A: AsRef<str>, U: AsRef<str>>(&self, aval_locale: &Tagged<A>, user_locale: &Tagged<U>) -> Option<(f64, [(Comparison, f64); 6])> {
+		self.candidate(&aval_locale.tag, &user_locale.tag)
+	}
+
+	fn best_match<A: AsRef<str>, U: AsRef<str>>(&self, available: &[(usize, Tagged<A>)], user_locales: impl IntoIterator<Item = (usize, Tagged<U>)>) -> Option<(usize, usize, f64)> {
+		let user_tags = user_locales.into_iter().map(|(i, locale)| (i, locale.tag));
+		self.best_match_for_tags(available, |_, (i, locale)| (*i, &locale.tag), user_tags)
+	}
+
+	fn best_match_for_locales<A: AsRef<str>, T: AsRef<str>>(&self, available: &[(usize, Tagged<A>)], user_locales: impl IntoIterator<Item = T>) -> Option<(usize, usize, f64)> {
+		self.best_match(available, |_, (i, locale)| (*i, &locale.tag), user_locales)
+	}
+}
+
+/// A locale of another locale crate with its serialization, which is used for matching.
+#[cfg(any(feature = "icu", feature = "unic"))]
+pub(crate) struct Serialized<T>(pub(crate) String, pub(crate) T);
+
+#[cfg(any(feature = "icu", feature = "unic"))]
+impl<T: std::fmt::Display> Serialized<T> {
+	pub(crate) fn new(locale: T) -> Self {
+		Self(locale.to_string(), locale)
+	}
+}
+
+#[cfg(any(feature = "icu", feature = "unic"))]
+impl<T> AsRef<str> for Serialized<T> {
+	fn as_ref(&self) -> &str {
+		&self.0
+	}
+}
+
+impl std::fmt::Debug for Matcher {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut debug = f.debug_struct("Matcher");
+		debug.field("scoring", &self.scoring)
+			.field("private_use_matcher", &self.private_use_matcher.as_ref().map(|_| ".."))
+			.field("scorer", &self.scorer.as_ref().map(|_| ".."))
+			.field("observer", &self.observer.as_ref().map(|_| ".."))
+			.field("random_tie_break", &self.random_tie_break.as_ref().map(|_| ".."))
+			.field("isolate_pseudo_locales", &self.isolate_pseudo_locales)
+			.field("lcid_inputs", &self.lcid_inputs)
+			.field("dotnet_culture_names", &self.dotnet_culture_names)
+			.field("language_names", &self.language_names)
+			.field("extlang_canonicalization", &self.extlang_canonicalization)
+			.field("user_locale_cache", &self.user_locale_cache);
+		#[cfg(feature = "iana")]
+		debug.field("registry_validation", &self.registry_validation)
+			.field("implicit_scripts", &self.implicit_scripts)
+			.field("canonicalization", &self.canonicalization);
+		debug.finish()
+	}
 }
 
 #[cfg(test)]
@@ -135,6 +1150,7 @@ mod tests {
 	use super::*;
 
 	#[test]
+	#[allow(clippy::needless_borrows_for_generic_args)]
 	fn test_best_matching_locale() {
 
 		fn case<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>, expected: Option<T1>)
@@ -194,6 +1210,14 @@ mod tests {
 		case(["zh", "zh-cmn", "zh-cmn-Hans", "zh-cmn-Hans-SG"], ["zh-Hans"], Some("zh-cmn-Hans"));
 		case(["zh", "zh-cmn", "zh-cmn-Hans", "zh-cmn-Hans-SG"], ["zh-SG"], Some("zh-cmn-Hans-SG"));
 
+		// Variants
+		case(["sl", "sl-rozaj", "sl-rozaj-biske"], ["sl-rozaj-biske"], Some("sl-rozaj-biske"));
+		case(["sl-nedis", "sl-rozaj-lipaw"], ["sl-rozaj-biske"], Some("sl-rozaj-lipaw"));
+		case(["sl-nedis", "sl-biske-rozaj"], ["sl-rozaj-biske"], Some("sl-biske-rozaj"));
+		case(["sl-rozaj-biske-1994", "sl-rozaj-biske"], ["sl-rozaj-biske"], Some("sl-rozaj-biske"));
+		case(["de-1996", "de-1901-1994"], ["de-1901"], Some("de-1901-1994"));
+		case(["de", "de-1996", "de-1901"], ["de-1901-x-foo"], Some("de-1901"));
+
 		// Extensions
 		case(["zh", "he"], ["he-IL-u-ca-hebrew-tz-jeruslm", "zh"], Some("he"));
 		case(["zh", "he-IL-u-ca-hebrew-tz-jeruslm-nu-latn"], ["he", "zh"], Some("he-IL-u-ca-hebrew-tz-jeruslm-nu-latn"));
@@ -257,4 +1281,498 @@ mod tests {
 		// Box
 		case([Box::from("en-US"), Box::from("ru-RU")], ["ru", "en"], Some(Box::from("ru-RU")));
 	}
+
+	#[test]
+	fn test_best_matching_locale_with_confidence() {
+
+		fn case<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>, expected: Option<(T1, usize, usize, f64)>)
+		where
+			T1: AsRef<str> + PartialEq + std::fmt::Debug,
+			T2: AsRef<str>
+		{
+			let expected = expected.map(|(locale, available_index, user_index, confidence)| LocaleMatch { locale, available_index, user_index, confidence });
+			assert_eq!(best_matching_locale_with_confidence(available_locales, user_locales), expected);
+		}
+
+		// Exact matches
+		case(["en"], ["en"], Some(("en", 0, 0, 1.0)));
+		case(["en-US", "ru-RU"], ["ru-RU", "en-US"], Some(("ru-RU", 1, 0, 1.0)));
+		case(["zh-cmn-Hans-SG"], ["ZH-CMN-HANS-SG"], Some(("zh-cmn-Hans-SG", 0, 0, 1.0)));
+
+		// Partial matches
+		case(["en", "ru"], ["ru-RU"], Some(("ru", 1, 0, 64.0 / 72.0)));
+		case(["ru-UA"], ["ru-RU"], Some(("ru-UA", 0, 0, 64.0 / 72.0)));
+		case(["zh-Hant-TW"], ["zh-Hans-TW"], Some(("zh-Hant-TW", 0, 0, 72.0 / 88.0)));
+		case(["zh-cmn-Hans-SG"], ["zh"], Some(("zh-cmn-Hans-SG", 0, 0, 64.0 / 120.0)));
+		case(["sl-rozaj-lipaw"], ["sl-rozaj-biske"], Some(("sl-rozaj-lipaw", 0, 0, (64.0 + 4.0 / 3.0) / 68.0)));
+
+		// Fallback to less preferred user locales
+		case(["en-US", "pt-BR"], ["fr-FR", "pt-PT", "en"], Some(("pt-BR", 1, 1, 64.0 / 72.0)));
+		case(["en"], ["fr", "de", "en"], Some(("en", 0, 2, 1.0)));
+
+		// Malformed locales keep their positions
+		case(["!!!", "en", "ru--", "ru"], ["", "ru"], Some(("ru", 3, 1, 1.0)));
+
+		// Not found
+		case(["en"], ["ru"], None);
+		case(&[] as &[&str], &[] as &[&str], None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_private_use_matcher() {
+		fn case(matcher: &Matcher, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Tenant dialects: same tenant is an exact match, other tenants are incompatible,
+		// the absence of a tenant is a partial match
+		let matcher = Matcher::new().with_private_use_matcher(|user, aval| {
+			match (user, aval) {
+				(["tenant", u], ["tenant", a]) => (u == a).then_some(1.0),
+				_ => Some(0.5),
+			}
+		});
+		case(&matcher, &["de-x-tenant-acme", "de-x-tenant-globex"], &["de-x-tenant-globex"], Some("de-x-tenant-globex"));
+		case(&matcher, &["de-x-tenant-acme", "de"], &["de-x-tenant-globex"], Some("de"));
+		case(&matcher, &["de-x-tenant-acme", "de-AT"], &["de-DE-x-tenant-acme"], Some("de-x-tenant-acme"));
+		case(&matcher, &["de-x-tenant-acme"], &["de-x-tenant-globex", "de"], Some("de-x-tenant-acme"));
+		case(&matcher, &["de-x-tenant-acme"], &["de-x-tenant-globex"], None);
+
+		// Private use subtags are ignored completely
+		let matcher = Matcher::new().with_private_use_matcher(|_, _| Some(0.0));
+		case(&matcher, &["de-x-foo", "de-x-bar"], &["de-x-bar"], Some("de-x-foo"));
+		case(&matcher, &["de-x-foo", "de-AT-x-bar"], &["de-AT-x-foo"], Some("de-AT-x-bar"));
+
+		// Out of range similarities are clamped
+		let matcher = Matcher::new().with_private_use_matcher(|_, aval| Some(if aval.is_empty() { -100.0 } else { 100.0 }));
+		case(&matcher, &["de", "de-x-foo", "de-CH"], &["de-CH"], Some("de-CH"));
+		case(&matcher, &["de-AT", "de-x-foo"], &["de-AT"], Some("de-AT"));
+
+		// NaN similarities are treated as 0.0
+		let matcher = Matcher::new().with_private_use_matcher(|_, aval| Some(if aval.is_empty() { 0.5 } else { f64::NAN }));
+		case(&matcher, &["de-x-foo", "de"], &["de-x-bar"], Some("de"));
+		case(&matcher, &["de-x-foo"], &["de-x-bar"], Some("de-x-foo"));
+
+		// The user subtags come first
+		let matcher = Matcher::new().with_private_use_matcher(|user, aval| Some(if user == ["user"] && aval == ["aval"] { 1.0 } else { 0.0 }));
+		case(&matcher, &["de-x-user", "de-x-aval"], &["de-x-user"], Some("de-x-aval"));
+
+		// The function is not called if there are no private use subtags
+		let matcher = Matcher::new().with_private_use_matcher(|_, _| unreachable!());
+		case(&matcher, &["de", "de-AT"], &["de-AT"], Some("de-AT"));
+
+		// Cloned matchers share the function
+		let matcher = Matcher::new().with_private_use_matcher(|user, aval| Some(if user == ["user"] && aval == ["aval"] { 1.0 } else { 0.0 }));
+		case(&matcher.clone(), &["de-x-user", "de-x-aval"], &["de-x-user"], Some("de-x-aval"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_scorer() {
+
+		fn case(matcher: &Matcher, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Directional distances between regions
+		let matcher = Matcher::new().with_scorer(|user, aval| {
+			match (user.region(), aval.region()) {
+				(Some("GB"), Some("US")) => Some(2.0),
+				(Some("GB"), Some("IN")) => Some(1.0),
+				(Some("US"), Some("GB")) => Some(1.0),
+				(Some("US"), Some("IN")) => Some(-1.0),
+				_ => Some(0.0),
+			}
+		});
+		case(&matcher, &["en-IN", "en-US"], &["en-GB"], Some("en-US"));
+		case(&matcher, &["en-IN", "en-GB"], &["en-US"], Some("en-GB"));
+		case(&matcher, &["en-IN", "en"], &["en-GB"], Some("en-IN"));
+		case(&matcher, &["en-IN", "en"], &["en-US"], Some("en"));
+		case(&matcher, &["en-IN", "en-US", "en-GB"], &["en-GB"], Some("en-GB"));
+
+		// Incompatible locales
+		let matcher = Matcher::new().with_scorer(|user, aval| (user.script() == aval.script()).then_some(0.0));
+		case(&matcher, &["sr-Latn", "sr-Cyrl-RS"], &["sr-RS"], None);
+		case(&matcher, &["sr-Latn", "sr-Cyrl-RS", "sr-ME"], &["sr-RS"], Some("sr-ME"));
+		case(&matcher, &["sr-Latn", "sr-Cyrl-RS"], &["sr-Cyrl", "sr-Latn"], Some("sr-Cyrl-RS"));
+
+		// The scorer is not called for different languages
+		let matcher = Matcher::new().with_scorer(|user, aval| {
+			assert_eq!(user.primary_language(), aval.primary_language());
+			Some(0.0)
+		});
+		case(&matcher, &["en", "fr", "de"], &["fr-CA"], Some("fr"));
+
+		// Cloned matchers share the scorer
+		let matcher = Matcher::new().with_scorer(|user, aval| (user.script() == aval.script()).then_some(0.0));
+		case(&matcher.clone(), &["sr-Latn", "sr-Cyrl-RS", "sr-ME"], &["sr-RS"], Some("sr-ME"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_mismatch_penalty() {
+
+		fn case(penalty: f64, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_mismatch_penalty(penalty);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// No penalty
+		case(0.0, &["fr-FR", "fr"], &["fr-CA"], Some("fr-FR"));
+		case(-1.0, &["fr-FR", "fr"], &["fr-CA"], Some("fr-FR"));
+
+		// Neutral locales win over contradicting ones
+		case(1.0, &["fr-FR", "fr"], &["fr-CA"], Some("fr"));
+		case(0.1, &["fr-FR", "fr"], &["fr-CA"], Some("fr"));
+		case(1.0, &["zh-Hant-TW", "zh-TW"], &["zh-Hans-TW"], Some("zh-TW"));
+		case(1.0, &["sl-rozaj-lipaw", "sl"], &["sl-rozaj-biske"], Some("sl"));
+
+		// Matching subtags still win
+		case(1.0, &["fr", "fr-CA"], &["fr-CA"], Some("fr-CA"));
+		case(0.5, &["zh-TW", "zh-Hans-HK"], &["zh-Hans-TW"], Some("zh-Hans-HK"));
+		case(2.0, &["zh-TW", "zh-Hans-HK"], &["zh-Hans-TW"], Some("zh-TW"));
+
+		// Contradicting locales are still better than nothing
+		case(1.0, &["en", "fr-FR"], &["fr-CA"], Some("fr-FR"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_missing_subtag() {
+
+		fn case(matcher: &Matcher, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Neutral
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Region, MissingSubtag::Neutral);
+		case(&matcher, &["en-US", "en"], &["en"], Some("en-US"));
+		case(&matcher, &["en", "en-US"], &["en-US"], Some("en-US"));
+		case(&matcher, &["en", "en-GB"], &["en-US"], Some("en"));
+
+		// Wildcard
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Region, MissingSubtag::Wildcard);
+		case(&matcher, &["en", "en-US"], &["en-US"], Some("en"));
+		case(&matcher, &["en-GB", "en"], &["en-US"], Some("en"));
+		case(&matcher, &["en-US", "en"], &["en"], Some("en-US"));
+		case(&matcher, &["zh-TW", "zh-Hant"], &["zh-Hant-TW"], Some("zh-Hant"));
+
+		// Partial
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Region, MissingSubtag::Partial(0.5));
+		case(&matcher, &["en", "en-US"], &["en-US"], Some("en-US"));
+		case(&matcher, &["en-GB", "en"], &["en-US"], Some("en"));
+		case(&matcher, &["en-US", "en"], &["en"], Some("en"));
+
+		// Require absence
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Region, MissingSubtag::RequireAbsence);
+		case(&matcher, &["en-US", "en"], &["en"], Some("en"));
+		case(&matcher, &["en", "en-US"], &["en-US"], Some("en-US"));
+		case(&matcher, &["en", "en-GB"], &["en-US"], Some("en"));
+		case(&matcher, &["en-US"], &["en"], Some("en-US"));
+
+		// Several categories
+		let matcher = Matcher::new()
+			.with_missing_subtag(Subtag::Script, MissingSubtag::Wildcard)
+			.with_missing_subtag(Subtag::Variant, MissingSubtag::RequireAbsence);
+		case(&matcher, &["sr-Cyrl", "sr"], &["sr-Latn"], Some("sr"));
+		case(&matcher, &["sl-rozaj", "sl"], &["sl"], Some("sl"));
+		case(&matcher, &["sl-rozaj", "sl-nedis"], &["sl-rozaj-biske"], Some("sl-rozaj"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_tie_break() {
+
+		fn case(tie_break: TieBreak, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_tie_break(tie_break);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// List order
+		case(TieBreak::ListOrder, &["en-US", "en", "en-Latn-US"], &["en-GB"], Some("en-US"));
+		case(TieBreak::ListOrder, &["en", "en-US", "en-Latn-US"], &["en-GB"], Some("en"));
+
+		// Most specific
+		case(TieBreak::MostSpecific, &["en", "en-US", "en-Latn-US"], &["en-GB"], Some("en-Latn-US"));
+		case(TieBreak::MostSpecific, &["en", "en-US", "en-CA"], &["en-GB"], Some("en-US"));
+		case(TieBreak::MostSpecific, &["en", "en-US", "en-GB"], &["en-GB"], Some("en-GB"));
+		case(TieBreak::MostSpecific, &["sl-rozaj-biske", "sl-IT-rozaj"], &["sl"], Some("sl-IT-rozaj"));
+		case(TieBreak::MostSpecific, &["sl-rozaj", "sl-rozaj-biske"], &["sl"], Some("sl-rozaj"));
+
+		// Least specific
+		case(TieBreak::LeastSpecific, &["en-Latn-US", "en-US", "en"], &["en-GB"], Some("en"));
+		case(TieBreak::LeastSpecific, &["en-Latn-US", "en-US", "en-CA"], &["en-GB"], Some("en-US"));
+		case(TieBreak::LeastSpecific, &["en", "en-US", "en-GB"], &["en-GB"], Some("en-GB"));
+	}
+
+	#[test]
+	fn test_is_pseudo_locale() {
+		// Pseudo-locales
+		assert!(is_pseudo_locale("en-XA"));
+		assert!(is_pseudo_locale("ar-XB"));
+		assert!(is_pseudo_locale("en-XC"));
+		assert!(is_pseudo_locale("en-xa"));
+		assert!(is_pseudo_locale("qps-ploc"));
+		assert!(is_pseudo_locale("qps-ploca"));
+		assert!(is_pseudo_locale("qps-plocm"));
+		assert!(is_pseudo_locale("QPS-PLOC"));
+
+		// Real locales
+		assert!(!is_pseudo_locale("en"));
+		assert!(!is_pseudo_locale("en-US"));
+		assert!(!is_pseudo_locale("ar-EG"));
+		assert!(!is_pseudo_locale("en-XD"));
+		assert!(!is_pseudo_locale("en-x-xa"));
+
+		// Malformed
+		assert!(!is_pseudo_locale(""));
+		assert!(!is_pseudo_locale("en_XA"));
+		assert!(!is_pseudo_locale("qps--ploc"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_isolated_pseudo_locales() {
+
+		fn case(isolate: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_isolated_pseudo_locales(isolate);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Not isolated
+		case(false, &["en-XA", "en-US"], &["en"], Some("en-XA"));
+		case(false, &["ar-XB", "fr"], &["ar-EG", "fr"], Some("ar-XB"));
+		case(false, &["en-XA", "en-US"], &["en-XA"], Some("en-XA"));
+
+		// Isolated
+		case(true, &["en-XA", "en-US"], &["en"], Some("en-US"));
+		case(true, &["ar-XB", "fr"], &["ar-EG", "fr"], Some("fr"));
+		case(true, &["en-XA", "ar-XB"], &["en-US", "ar"], None);
+		case(true, &["qps-ploc", "en-XA"], &["en"], None);
+
+		// Explicit requests
+		case(true, &["en-US", "en-XA"], &["en-XA"], Some("en-XA"));
+		case(true, &["ar", "ar-XB", "en-XA"], &["ar-XB"], Some("ar-XB"));
+		case(true, &["en-XA", "qps-ploc"], &["qps-ploc"], Some("qps-ploc"));
+		case(true, &["en-US", "en"], &["en-XA"], Some("en-US"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_lcid_inputs() {
+
+		fn case(lcid_inputs: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_lcid_inputs(lcid_inputs);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// LCIDs are malformed by default
+		case(false, &["en-US", "ru-RU"], &["0x0419", "en"], Some("en-US"));
+		case(false, &["0x0409", "0x0419"], &["ru"], None);
+
+		// Lenient
+		case(true, &["en-US", "ru-RU"], &["0x0419", "en"], Some("ru-RU"));
+		case(true, &["en-US", "ru-RU"], &["1049", "en"], Some("ru-RU"));
+		case(true, &["0x0409", "0x0419"], &["ru"], Some("0x0419"));
+		case(true, &["1033", "0x0809"], &["en-GB"], Some("0x0809"));
+		case(true, &["en", "zh-Hans", "zh-Hant"], &["0x7C04"], Some("zh-Hant"));
+
+		// Unknown LCIDs are still malformed
+		case(true, &["en-US", "ru-RU"], &["0xFFFF", "en"], Some("en-US"));
+		case(true, &["0x", "12345678901234567890"], &["en"], None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_dotnet_culture_names() {
+		fn case(dotnet_culture_names: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_dotnet_culture_names(dotnet_culture_names);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Legacy names are BCP 47 locales by default
+		case(false, &["zh-Hant", "zh-Hans"], &["zh-CHS"], Some("zh-Hant"));
+		case(false, &["uz-Cyrl-UZ"], &["uz-UZ-Cyrl"], None);
+		case(false, &["dv-MV"], &["div-MV"], None);
+
+		// Culture names
+		case(true, &["zh-Hant", "zh-Hans"], &["zh-CHS"], Some("zh-Hans"));
+		case(true, &["zh-CHT", "zh-CHS"], &["zh-Hans-CN"], Some("zh-CHS"));
+		case(true, &["uz-Latn-UZ", "uz-Cyrl-UZ"], &["uz-UZ-Cyrl"], Some("uz-Cyrl-UZ"));
+		case(true, &["dv-MV"], &["div-MV"], Some("dv-MV"));
+		case(true, &["en-US", "en-029"], &["en-029"], Some("en-029"));
+
+		// Sort orders
+		case(false, &["de-AT", "de-DE"], &["de-DE_phoneb"], None);
+		case(true, &["de-AT", "de-DE"], &["de-DE_phoneb"], Some("de-DE"));
+		case(true, &["de-DE", "de-DE-u-co-phonebk"], &["de-DE_phoneb"], Some("de-DE-u-co-phonebk"));
+		case(true, &["zh-TW_pronun", "zh-CN_stroke"], &["zh-TW"], Some("zh-TW_pronun"));
+		case(true, &["hu-HU_technl"], &["hu"], Some("hu-HU_technl"));
+
+		// Other inputs are unaffected
+		case(true, &["en-US", "ru-RU"], &["ru-BY"], Some("ru-RU"));
+		case(true, &["en-US", "ru-RU"], &["en_US"], None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_language_names() {
+		fn case(language_names: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_language_names(language_names);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Names are BCP 47 locales by default
+		case(false, &["en-US", "de-DE"], &["german"], None);
+		case(false, &["en-US", "de-DE"], &["Chinese (Simplified)"], None);
+
+		// Language names
+		case(true, &["en-US", "de-DE"], &["german"], Some("de-DE"));
+		case(true, &["en-US", "de-DE"], &["Deutsch"], Some("de-DE"));
+		case(true, &["zh-Hant-TW", "zh-Hans-CN"], &["chinese (simplified)"], Some("zh-Hans-CN"));
+		case(true, &["pt-PT", "pt-BR"], &["Portuguese (Brazil)"], Some("pt-BR"));
+		case(true, &["en-US", "fr-FR"], &["Klingon", "français"], Some("fr-FR"));
+		case(true, &["English", "Français"], &["fr-CA"], Some("Français"));
+
+		// Codes are unaffected
+		case(true, &["en-US", "de-DE"], &["de-AT"], Some("de-DE"));
+		case(true, &["en-US", "de-DE"], &["Klingon"], None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_extlang_canonicalization() {
+		fn case(extlang_canonicalization: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_extlang_canonicalization(extlang_canonicalization);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Extended languages are separate subtags by default
+		case(false, &["cmn-Hans", "zh-Hans"], &["zh-cmn-Hans"], Some("zh-Hans"));
+		case(false, &["ar-afb"], &["afb"], None);
+
+		// Canonical forms
+		case(true, &["cmn-Hans", "zh-Hans"], &["zh-cmn-Hans"], Some("cmn-Hans"));
+		case(true, &["zh-Hans", "zh-cmn-Hans-CN"], &["cmn-CN"], Some("zh-cmn-Hans-CN"));
+		case(true, &["ar-afb"], &["afb"], Some("ar-afb"));
+		case(true, &["ZH-YUE-hk"], &["yue-HK"], Some("ZH-YUE-hk"));
+		case(true, &["zh-cmn-Hans-u-co-pinyin-x-foo"], &["cmn-Hans-u-co-pinyin-x-foo"], Some("zh-cmn-Hans-u-co-pinyin-x-foo"));
+
+		// Different languages
+		case(true, &["zh-cmn-Hans"], &["zh-Hans"], None);
+
+		// Several extended languages are kept
+		case(true, &["zh-cmn-yue"], &["zh"], Some("zh-cmn-yue"));
+
+		// Unregistered extended languages and extended languages with another Prefix are kept
+		#[cfg(feature = "iana")]
+		{
+			case(true, &["zh-afb"], &["afb"], None);
+			case(true, &["zh-afb", "zh"], &["zh"], Some("zh-afb"));
+			case(true, &["ar-qqq"], &["qqq"], None);
+		}
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_observer() {
+		use std::sync::{Arc, Mutex};
+
+		fn case(break_at: f64, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>, expected_calls: &[(&str, &str, f64)]) {
+			let calls = Arc::new(Mutex::new(Vec::new()));
+			let observed = calls.clone();
+			let matcher = Matcher::new().with_observer(move |user, aval, score| {
+				observed.lock().unwrap().push((user.to_string(), aval.to_string(), score));
+				if score >= break_at { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+			});
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+			let calls = calls.lock().unwrap();
+			assert_eq!(calls.iter().map(|(u, a, s)| (u.as_str(), a.as_str(), *s)).collect::<Vec<_>>(), expected_calls);
+		}
+
+		// All candidates are observed in order
+		case(f64::INFINITY, &["en-US", "ru-RU", "en-GB"], &["en-GB"], Some("en-GB"), &[("en-GB", "en-US", 0.0), ("en-GB", "en-GB", 8.0)]);
+		case(f64::INFINITY, &["en-US", "ru-RU"], &["fr", "ru"], Some("ru-RU"), &[("ru", "ru-RU", 0.0)]);
+		case(f64::INFINITY, &["en-US", "ru-RU"], &["fr"], None, &[]);
+
+		// Early termination
+		case(0.0, &["en", "en-GB"], &["en-GB"], Some("en"), &[("en-GB", "en", 0.0)]);
+		case(8.0, &["en", "en-GB", "en-Latn-GB"], &["en-Latn-GB"], Some("en-GB"), &[("en-Latn-GB", "en", 0.0), ("en-Latn-GB", "en-GB", 8.0)]);
+		case(8.0, &["en-GB", "en"], &["en-US"], Some("en-GB"), &[("en-US", "en-GB", 0.0), ("en-US", "en", 0.0)]);
+
+		// Cloned matchers share the observer
+		let calls = Arc::new(Mutex::new(0));
+		let observed = calls.clone();
+		let matcher = Matcher::new().with_observer(move |_, _, _| {
+			*observed.lock().unwrap() += 1;
+			ControlFlow::Continue(())
+		});
+		matcher.clone().best_matching_locale(["en-US", "en-GB"], ["en"]);
+		assert_eq!(*calls.lock().unwrap(), 2);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_random_tie_break() {
+		fn case(random: f64, weights: &[(&'static str, f64)], available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let weights = weights.to_vec();
+			let matcher = Matcher::new().with_weighted_random_tie_break(move || random, move |tag| {
+				weights.iter().find(|(locale, _)| *locale == tag.as_str()).map_or(1.0, |(_, weight)| *weight)
+			});
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Uniform
+		case(0.0, &[], &["en-US", "en-GB", "en-AU"], &["en-CA"], Some("en-US"));
+		case(0.5, &[], &["en-US", "en-GB", "en-AU"], &["en-CA"], Some("en-GB"));
+		case(0.99, &[], &["en-US", "en-GB", "en-AU"], &["en-CA"], Some("en-AU"));
+
+		// Weighted
+		case(0.5, &[("en-US", 9.0)], &["en-US", "en-GB"], &["en-CA"], Some("en-US"));
+		case(0.95, &[("en-US", 9.0)], &["en-US", "en-GB"], &["en-CA"], Some("en-GB"));
+		case(0.0, &[("en-US", 0.0)], &["en-US", "en-GB"], &["en-CA"], Some("en-GB"));
+
+		// No ties
+		case(0.99, &[], &["en-US", "en-GB", "en-AU"], &["en-GB"], Some("en-GB"));
+		case(0.99, &[], &["en-US", "fr-FR"], &["fr-CA", "en"], Some("fr-FR"));
+		case(0.99, &[], &["en-US", "en-GB"], &["fr"], None);
+
+		// Uniform random tie break
+		let matcher = Matcher::new().with_random_tie_break(|| 0.5);
+		assert_eq!(matcher.best_matching_locale(["en-US", "en-GB"], ["en"]), Some("en-GB"));
+
+		// Cloned matchers share the random tie break
+		assert_eq!(matcher.clone().best_matching_locale(["en-US", "en-GB"], ["en"]), Some("en-GB"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale() {
+		assert_eq!("en-US".parse::<Bcp47Locale>().map(Bcp47Locale::into_string), Ok("en-US".to_string()));
+		assert_eq!(Bcp47Locale::parse("zh-hans-cn").unwrap().as_str(), "zh-hans-cn");
+		assert_eq!(Bcp47Locale::parse("de").unwrap().to_string(), "de");
+		assert!(Bcp47Locale::parse("en_US").is_err());
+		assert!(Bcp47Locale::parse("").is_err());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_serde() {
+		let locale = Bcp47Locale::parse("en-US").unwrap();
+		assert_eq!(serde_json::to_string(&locale).unwrap(), r#""en-US""#);
+		assert_eq!(serde_json::from_str::<Bcp47Locale>(r#""en-US""#).unwrap(), locale);
+		assert!(serde_json::from_str::<Bcp47Locale>(r#""en_US""#).is_err());
+		assert!(serde_json::from_str::<Bcp47Locale>("42").is_err());
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_schema() {
+		let schema = schemars::schema_for!(Bcp47Locale);
+		assert_eq!(schema.get("type"), Some(&serde_json::json!("string")));
+		assert_eq!(serde_json::to_value(schemars::schema_for!(Subtag)).unwrap()["oneOf"][0]["const"], "extended_language");
+	}
 }
\ No newline at end of file
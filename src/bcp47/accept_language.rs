@@ -0,0 +1,462 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing and building of the HTTP `Accept-Language` and `Content-Language` headers.
+
+use language_tags::LanguageTag;
+
+use super::Matcher;
+
+/// A parsed HTTP `Accept-Language` header, as defined in
+/// [RFC 9110, Section 12.5.4](https://www.rfc-editor.org/rfc/rfc9110#section-12.5.4).
+///
+/// The header is a comma-separated list of language ranges with optional quality values, e.g.
+/// `fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5`.  
+/// Ranges with the quality value `0` are rejections: the client does not accept the language at all,
+/// and a locale matched by such a range is never returned by
+/// [`best_matching_locale`](AcceptLanguage::best_matching_locale), even if it is the only locale with
+/// a matching language.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::AcceptLanguage;
+///
+///
+/// let accept_language = AcceptLanguage::parse("en-US, en;q=0.9, de;q=0");
+///
+/// assert_eq!(accept_language.preferred(), ["en-US", "en"]);
+/// assert_eq!(accept_language.rejected(), ["de"]);
+///
+/// // "de-AT" is rejected, so only "en-GB" is left
+/// assert_eq!(accept_language.best_matching_locale(["de-AT", "en-GB"]), Some("en-GB"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptLanguage<'a> {
+	ranges: Vec<(&'a str, f64)>,
+}
+
+impl<'a> AcceptLanguage<'a> {
+	/// The wildcard language range, which matches any language.
+	const WILDCARD: &'static str = "*";
+
+	/// Parses the value of an `Accept-Language` header.
+	///
+	/// Parsing is lenient: whitespace around ranges and parameters is ignored, and entries that
+	/// are empty, have an invalid quality value or have a quality value out of the range
+	/// `0.0..=1.0` are skipped. Parameters other than `q` are ignored.  
+	/// A range listed more than once (compared case-insensitively) keeps the quality value of its
+	/// first occurrence, so `de;q=0, de` rejects `de` and `de, de;q=0` accepts it.
+	pub fn parse(header: &'a str) -> Self {
+		Self::from_ranges(header.split(',').filter_map(parse_entry))
+	}
+
+	/// Parses the raw bytes of an `Accept-Language` header, e.g. as received by a proxy.
+	///
+	/// Behaves like [`parse`](Self::parse), but entries that are not ASCII are skipped, so the header
+	/// does not have to be validated as UTF-8 or copied beforehand.
+	pub fn parse_bytes(header: &'a [u8]) -> Self {
+		let ranges = header.split(|&b| b == b',')
+			.filter(|entry| entry.is_ascii())
+			.filter_map(|entry| std::str::from_utf8(entry).ok())
+			.filter_map(parse_entry);
+		Self::from_ranges(ranges)
+	}
+
+	/// Collects the parsed ranges, skipping the later occurrences of a range.
+	fn from_ranges(parsed: impl Iterator<Item = (&'a str, f64)>) -> Self {
+		let mut ranges = Vec::<(&str, f64)>::new();
+		for (range, quality) in parsed {
+			if !ranges.iter().any(|(added, _)| added.eq_ignore_ascii_case(range)) {
+				ranges.push((range, quality));
+			}
+		}
+		Self { ranges }
+	}
+
+	/// Returns the accepted language ranges ordered by descending quality value, ranges with equal
+	/// quality values keep the order of the header.  
+	/// The wildcard `*` is not included, since it does not name a language to match.
+	pub fn preferred(&self) -> Vec<&'a str> {
+		let mut ranges = self.ranges.iter()
+			.filter(|&&(range, quality)| quality > 0.0 && range != Self::WILDCARD)
+			.collect::<Vec<_>>();
+		ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+		ranges.into_iter().map(|&(range, _)| range).collect()
+	}
+
+	/// Returns the rejected language ranges, i.e. those with the quality value `0`, in the order of
+	/// the header.
+	pub fn rejected(&self) -> Vec<&'a str> {
+		self.ranges.iter()
+			.filter(|&&(_, quality)| quality == 0.0)
+			.map(|&(range, _)| range)
+			.collect()
+	}
+
+	/// Returns whether the locale is rejected by the header.
+	///
+	/// A locale is rejected if the most specific range matching it has the quality value `0`.
+	/// Ranges match as in the basic filtering of
+	/// [RFC 4647, Section 3.3.1](https://www.rfc-editor.org/rfc/rfc4647#section-3.3.1): `de`
+	/// matches `de` and `de-AT`, but not `den`. Thus, `de;q=0, de-AT` rejects `de-DE`, but not
+	/// `de-AT`.  
+	/// A range listed more than once counts with its first occurrence, see [`parse`](Self::parse).  
+	/// A rejected wildcard `*;q=0` does not reject any locale, since only the languages listed in the
+	/// header are ever matched.
+	pub fn is_rejected(&self, locale: &str) -> bool {
+		self.ranges.iter()
+			.filter(|&&(range, _)| range != Self::WILDCARD && matches_range(range, locale))
+			.max_by_key(|&&(range, _)| range.len())
+			.is_some_and(|&(_, quality)| quality == 0.0)
+	}
+
+	/// Finds the best matching locale from a list of available locales based on the header.
+	///
+	/// The rejected locales are removed from the available locales (see [`is_rejected`](Self::is_rejected)),
+	/// and the rest are matched with [`bcp47::best_matching_locale`](super::best_matching_locale)
+	/// against the [`preferred`](Self::preferred) ranges.
+	pub fn best_matching_locale<T: AsRef<str>>(&self, available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+		self.best_matching_locale_with(&Matcher::new(), available_locales)
+	}
+
+	/// Finds the best matching locale exactly like [`best_matching_locale`](Self::best_matching_locale),
+	/// but with the given [`Matcher`] instead of the default one.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::{AcceptLanguage, Matcher};
+	///
+	///
+	/// let matcher = Matcher::new().with_mismatch_penalty(1.0);
+	/// let accept_language = AcceptLanguage::parse("fr-CA");
+	///
+	/// assert_eq!(accept_language.best_matching_locale_with(&matcher, ["fr-FR", "fr"]), Some("fr"));
+	/// ```
+	pub fn best_matching_locale_with<T: AsRef<str>>(&self, matcher: &Matcher, available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+		let available_locales = available_locales.into_iter().filter(|locale| !self.is_rejected(locale.as_ref()));
+		matcher.best_matching_locale(available_locales, self.preferred())
+	}
+}
+
+/// Parses an entry of an `Accept-Language` header into a language range and its quality value.
+fn parse_entry(entry: &str) -> Option<(&str, f64)> {
+	let mut parts = entry.split(';').map(str::trim);
+	let range = parts.next().filter(|range| !range.is_empty())?;
+	let mut quality = 1.0;
+	for param in parts {
+		let Some((name, value)) = param.split_once('=') else {
+			continue;
+		};
+		if name.trim().eq_ignore_ascii_case("q") {
+			quality = value.trim().parse::<f64>().ok().filter(|q| (0.0..=1.0).contains(q))?;
+		}
+	}
+	Some((range, quality))
+}
+
+/// Finds the best matching locale from a list of available locales based on the raw bytes of an
+/// `Accept-Language` header and returns its index in the list.
+///
+/// The header is parsed with [`AcceptLanguage::parse_bytes`] and matched as described in
+/// [`AcceptLanguage`]. This is a fast path for reverse proxies and CDNs that negotiate a locale for
+/// every request and only need to know which variant to serve.  
+/// Returns [`None`] if none of the languages matches.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale_bytes;
+///
+///
+/// let available_locales = ["en-US", "de-DE", "ru-RU"];
+///
+/// assert_eq!(best_matching_locale_bytes(available_locales, b"de-AT, en;q=0.5"), Some(1));
+/// assert_eq!(best_matching_locale_bytes(available_locales, b"de;q=0, en;q=0.5"), Some(0));
+/// assert_eq!(best_matching_locale_bytes(available_locales, b"fr"), None);
+/// ```
+pub fn best_matching_locale_bytes<T: AsRef<str>>(available_locales: impl IntoIterator<Item = T>, header: &[u8]) -> Option<usize> {
+	let available_locales = available_locales.into_iter().enumerate().map(|(i, locale)| Indexed(i, locale));
+	AcceptLanguage::parse_bytes(header).best_matching_locale(available_locales).map(|Indexed(i, _)| i)
+}
+
+/// A locale with its index in the list of available locales.
+struct Indexed<T>(usize, T);
+
+impl<T: AsRef<str>> AsRef<str> for Indexed<T> {
+	fn as_ref(&self) -> &str {
+		self.1.as_ref()
+	}
+}
+
+/// Builds the value of an `Accept-Language` header from a list of user locales ordered by priority.
+///
+/// The first locale gets no quality value, which means `1`, and the following locales get
+/// descending quality values: in steps of `0.1` for up to 10 locales, `0.01` for up to 100 locales
+/// and `0.001` otherwise. Quality values never drop to `0`, which would reject a language, so
+/// locales after the 1000th all get `0.001`.  
+/// Locales that are not language ranges of
+/// [RFC 4647, Section 2.1](https://www.rfc-editor.org/rfc/rfc4647#section-2.1), e.g. empty locales
+/// or locales with whitespace, `,` or `;`, and duplicates (compared case-insensitively) are skipped,
+/// so the header cannot be split or extended by a locale.  
+/// The result is the inverse of [`AcceptLanguage::parse`]: its [`preferred`](AcceptLanguage::preferred)
+/// ranges are the given locales in the same order, except for the wildcard `*`.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::accept_language_header;
+///
+///
+/// assert_eq!(accept_language_header(["de-AT", "de", "en"]), "de-AT, de;q=0.9, en;q=0.8");
+/// ```
+pub fn accept_language_header<T: AsRef<str>>(user_locales: impl IntoIterator<Item = T>) -> String {
+	let mut locales = Vec::<T>::new();
+	for locale in user_locales {
+		let l = locale.as_ref();
+		if is_language_range(l) && !locales.iter().any(|added| added.as_ref().eq_ignore_ascii_case(l)) {
+			locales.push(locale);
+		}
+	}
+	let step = match locales.len() {
+		0..=10 => 100,
+		11..=100 => 10,
+		_ => 1,
+	};
+	locales.iter()
+		.enumerate()
+		.map(|(i, locale)| match i {
+			0 => locale.as_ref().to_string(),
+			_ => {
+				let quality = 1000usize.saturating_sub(i * step).max(1);
+				let quality = format!("{:03}", quality);
+				format!("{};q=0.{}", locale.as_ref(), quality.trim_end_matches('0'))
+			}
+		})
+		.collect::<Vec<String>>()
+		.join(", ")
+}
+
+/// Builds the value of a `Content-Language` header, as defined in
+/// [RFC 9110, Section 8.5](https://www.rfc-editor.org/rfc/rfc9110#section-8.5), from the locales of
+/// the content, e.g. the locale matched for the request.
+///
+/// The locales are written in the canonical BCP 47 casing (e.g. `zh-hant-tw` becomes `zh-Hant-TW`)
+/// and separated with commas.  
+/// Malformed locales and duplicates are skipped. The result is empty if no locale is left, in which
+/// case the header should not be sent.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::content_language_header;
+///
+///
+/// assert_eq!(content_language_header(["en-us"]), "en-US");
+/// assert_eq!(content_language_header(["mi", "EN"]), "mi, en");
+/// ```
+pub fn content_language_header<T: AsRef<str>>(locales: impl IntoIterator<Item = T>) -> String {
+	let mut tags = Vec::<String>::new();
+	for tag in locales.into_iter().filter_map(|locale| LanguageTag::parse(locale.as_ref()).ok()) {
+		let tag = tag.into_string();
+		if !tags.contains(&tag) {
+			tags.push(tag);
+		}
+	}
+	tags.join(", ")
+}
+
+/// Whether the string is a language range, i.e. the wildcard `*` or subtags of 1 to 8 letters and
+/// digits joined by `-`, the first of them only of letters.
+fn is_language_range(range: &str) -> bool {
+	range == AcceptLanguage::WILDCARD || range.split('-').enumerate().all(|(i, subtag)| {
+		(1..=8).contains(&subtag.len()) && match i {
+			0 => subtag.bytes().all(|b| b.is_ascii_alphabetic()),
+			_ => subtag.bytes().all(|b| b.is_ascii_alphanumeric()),
+		}
+	})
+}
+
+/// Whether the language range matches the locale in the basic filtering scheme.
+fn matches_range(range: &str, locale: &str) -> bool {
+	locale.get(..range.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(range))
+		&& matches!(locale.as_bytes().get(range.len()), None | Some(b'-'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		fn case(header: &str, preferred: &[&str], rejected: &[&str]) {
+			let accept_language = AcceptLanguage::parse(header);
+			assert_eq!(accept_language.preferred(), preferred);
+			assert_eq!(accept_language.rejected(), rejected);
+		}
+
+		case("", &[], &[]);
+		case("en", &["en"], &[]);
+		case("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5", &["fr-CH", "fr", "en", "de"], &[]);
+
+		// Ordering by quality
+		case("en;q=0.5, de, fr;q=0.8", &["de", "fr", "en"], &[]);
+		case("en;q=0.5, de;q=0.5, fr;q=0.5", &["en", "de", "fr"], &[]);
+
+		// Rejections
+		case("en, de;q=0, fr;q=0.000", &["en"], &["de", "fr"]);
+		case("*;q=0, en", &["en"], &["*"]);
+
+		// Whitespace, case and other parameters
+		case(" en-US ; Q = 0.5 ,de", &["de", "en-US"], &[]);
+		case("en;level=1;q=0.5", &["en"], &[]);
+
+		// Malformed entries
+		case("en;q=2, de;q=-1, fr;q=abc, ,, it;q=", &[], &[]);
+
+		// Repeated ranges
+		case("de;q=0, de", &[], &["de"]);
+		case("de, de;q=0", &["de"], &[]);
+		case("en;q=0.5, de, EN", &["de", "en"], &[]);
+		case("en;q=2, en;q=0.5", &["en"], &[]);
+	}
+
+	#[test]
+	fn test_parse_bytes() {
+		fn case(header: &[u8], preferred: &[&str], rejected: &[&str]) {
+			let accept_language = AcceptLanguage::parse_bytes(header);
+			assert_eq!(accept_language.preferred(), preferred);
+			assert_eq!(accept_language.rejected(), rejected);
+		}
+
+		case(b"", &[], &[]);
+		case(b"fr-CH, fr;q=0.9, de;q=0", &["fr-CH", "fr"], &["de"]);
+
+		// Non-ASCII and invalid UTF-8 entries
+		case("en, fr-\u{00E9};q=0.9, de;q=0.8".as_bytes(), &["en", "de"], &[]);
+		case(b"en, \xFF\xFE, de;q=0.8", &["en", "de"], &[]);
+	}
+
+	#[test]
+	fn test_is_rejected() {
+		fn case(header: &str, locale: &str, expected: bool) {
+			assert_eq!(AcceptLanguage::parse(header).is_rejected(locale), expected);
+		}
+
+		case("en, de;q=0", "de", true);
+		case("en, de;q=0", "de-AT", true);
+		case("en, DE;q=0", "de-at", true);
+		case("en, de;q=0", "den", false);
+		case("en, de;q=0", "en", false);
+
+		// More specific ranges
+		case("de;q=0, de-AT", "de-AT", false);
+		case("de;q=0, de-AT", "de-DE", true);
+		case("de, de-AT;q=0", "de-AT", true);
+		case("de, de-AT;q=0", "de-AT-1996", true);
+		case("de, de-AT;q=0", "de-CH", false);
+
+		// Wildcard
+		case("*;q=0", "de", false);
+
+		// Repeated ranges count with their first occurrence
+		case("de;q=0, de", "de-DE", true);
+		case("de, de;q=0", "de-DE", false);
+		case("de-AT;q=0, DE-at", "de-AT", true);
+		case("de-at, de-AT;q=0", "de-AT", false);
+	}
+
+	#[test]
+	fn test_best_matching_locale() {
+		fn case(header: &str, available_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(AcceptLanguage::parse(header).best_matching_locale(available_locales.iter().copied()), expected);
+		}
+
+		case("de-DE, en;q=0.5", &["en-US", "de-AT"], Some("de-AT"));
+		case("en;q=0.5, de-DE", &["en-US", "de-AT"], Some("de-AT"));
+		case("fr", &["en-US", "de-AT"], None);
+		case("", &["en-US", "de-AT"], None);
+
+		// Rejected languages are never returned
+		case("en-US, en-GB;q=0", &["en-GB"], None);
+		case("en-US, en-GB;q=0", &["en-GB", "en"], Some("en"));
+		case("de-DE, de-AT;q=0, en;q=0.1", &["de-AT", "en"], Some("en"));
+		case("de;q=0, de-AT", &["de-DE", "de-AT"], Some("de-AT"));
+	}
+
+	#[test]
+	fn test_best_matching_locale_bytes() {
+		fn case(header: &[u8], expected: Option<usize>) {
+			assert_eq!(best_matching_locale_bytes(["en-US", "de-AT", "de-DE"], header), expected);
+		}
+
+		case(b"de-DE, en;q=0.5", Some(2));
+		case(b"de, en;q=0.5", Some(1));
+		case(b"de-AT;q=0, de, en;q=0.5", Some(2));
+		case(b"de;q=0, en;q=0.5", Some(0));
+		case(b"fr", None);
+		case(b"\xFF", None);
+	}
+
+	#[test]
+	fn test_accept_language_header() {
+		fn case(user_locales: &[&str], expected: &str) {
+			assert_eq!(accept_language_header(user_locales), expected);
+		}
+
+		case(&[], "");
+		case(&["en"], "en");
+		case(&["de-AT", "de", "en"], "de-AT, de;q=0.9, en;q=0.8");
+		case(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"], "a, b;q=0.9, c;q=0.8, d;q=0.7, e;q=0.6, f;q=0.5, g;q=0.4, h;q=0.3, i;q=0.2, j;q=0.1");
+
+		// Empty locales and duplicates
+		case(&["en", "", "EN", "de"], "en, de;q=0.9");
+
+		// Locales that are not language ranges
+		case(&["en\r\nX-Evil: 1", "de"], "de");
+		case(&["en\n", "de\r", "fr"], "fr");
+		case(&["de,fr", "en"], "en");
+		case(&["de;q=0", "en"], "en");
+		case(&["en US", " en", "en-", "-en", "en--US", "123", "en-abcdefghi", "en"], "en");
+		case(&["*", "en-US", "zh-Hant-TW", "de-1996"], "*, en-US;q=0.9, zh-Hant-TW;q=0.8, de-1996;q=0.7");
+
+		// Many locales
+		let locales = (0..11).map(|i| format!("x-{i}")).collect::<Vec<String>>();
+		assert!(accept_language_header(&locales).ends_with("x-9;q=0.91, x-10;q=0.9"));
+		let locales = (0..1500).map(|i| format!("x-{i}")).collect::<Vec<String>>();
+		let header = accept_language_header(&locales);
+		assert!(header.contains("x-998;q=0.002, x-999;q=0.001, x-1000;q=0.001"));
+		assert_eq!(AcceptLanguage::parse(&header).preferred(), locales);
+	}
+
+	#[test]
+	fn test_content_language_header() {
+		fn case(locales: &[&str], expected: &str) {
+			assert_eq!(content_language_header(locales), expected);
+		}
+
+		case(&[], "");
+		case(&["en-US"], "en-US");
+		case(&["EN-us"], "en-US");
+		case(&["zh-hant-tw", "SR-latn"], "zh-Hant-TW, sr-Latn");
+		case(&["de-CH-1996", "X-Private"], "de-CH-1996, x-private");
+
+		// Malformed locales and duplicates
+		case(&["en", "en_US", "", "EN"], "en");
+		case(&["12"], "");
+	}
+}
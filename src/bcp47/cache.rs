@@ -0,0 +1,188 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The thread-local cache of parsed user locales, see [`Matcher::with_user_locale_cache`].
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use language_tags::LanguageTag;
+
+use super::Matcher;
+
+/// The number of lists of user locales cached by each thread.
+const CAPACITY: usize = 64;
+
+/// The options of a matcher that affect how it parses locales.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ParseOptions {
+	/// See [`Matcher::with_lcid_inputs`].
+	lcid_inputs: bool,
+	/// See [`Matcher::with_dotnet_culture_names`].
+	dotnet_culture_names: bool,
+	/// See [`Matcher::with_language_names`].
+	language_names: bool,
+	/// See [`Matcher::with_extlang_canonicalization`].
+	extlang_canonicalization: bool,
+	/// See [`Matcher::with_registry_validation`].
+	#[cfg(feature = "iana")]
+	registry_validation: bool,
+	/// See [`Matcher::with_canonicalization`].
+	#[cfg(feature = "iana")]
+	canonicalization: bool,
+}
+
+impl ParseOptions {
+	fn new(matcher: &Matcher) -> Self {
+		Self {
+			lcid_inputs: matcher.lcid_inputs,
+			dotnet_culture_names: matcher.dotnet_culture_names,
+			language_names: matcher.language_names,
+			extlang_canonicalization: matcher.extlang_canonicalization,
+			#[cfg(feature = "iana")]
+			registry_validation: matcher.registry_validation,
+			#[cfg(feature = "iana")]
+			canonicalization: matcher.canonicalization,
+		}
+	}
+}
+
+/// A cached list of user locales.
+struct Entry {
+	/// The options the list was parsed with.
+	options: ParseOptions,
+	/// The user locales as they were passed in.
+	locales: Vec<String>,
+	/// The well-formed user locales parsed into tags, with their indices in the list.
+	tags: Rc<[(usize, LanguageTag)]>,
+}
+
+thread_local! {
+	/// The cached lists of user locales, from the most recently used one.
+	static CACHE: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Parses the well-formed user locales like the matcher, along with their indices in the list.  
+/// The result is reused if the same list was parsed on the thread recently.
+pub(super) fn parse_user_locales<T: AsRef<str>>(matcher: &Matcher, user_locales: impl IntoIterator<Item = T>) -> Rc<[(usize, LanguageTag)]> {
+	let user_locales = user_locales.into_iter().collect::<Vec<T>>();
+	let options = ParseOptions::new(matcher);
+	CACHE.with_borrow_mut(|cache| {
+		let cached = cache.iter().position(|entry| entry.options == options
+			&& entry.locales.len() == user_locales.len()
+			&& entry.locales.iter().zip(&user_locales).all(|(cached, locale)| cached == locale.as_ref())
+		);
+		let entry = match cached {
+			Some(i) => cache.remove(i),
+			None => Entry {
+				options,
+				locales: user_locales.iter().map(|locale| locale.as_ref().to_string()).collect(),
+				tags: user_locales.iter()
+					.enumerate()
+					.filter_map(|(i, locale)| matcher.parse(locale.as_ref()).map(|tag| (i, tag)))
+					.collect(),
+			},
+		};
+		let tags = Rc::clone(&entry.tags);
+		cache.insert(0, entry);
+		cache.truncate(CAPACITY);
+		tags
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_user_locales() {
+		fn tags(matcher: &Matcher, user_locales: &[&str]) -> Vec<(usize, String)> {
+			parse_user_locales(matcher, user_locales).iter().map(|(i, tag)| (*i, tag.to_string())).collect()
+		}
+		let cached = |user_locales: &[&str]| CACHE.with_borrow(|cache|
+			cache.iter().position(|entry| entry.options == ParseOptions::new(&Matcher::new()) && entry.locales == user_locales)
+		);
+
+		let matcher = Matcher::new();
+		let lcid_matcher = Matcher::new().with_lcid_inputs(true);
+
+		// Parsing
+		assert_eq!(tags(&matcher, &["en-us", "en_GB", "0x0419", "ru"]), [(0, "en-US".to_string()), (3, "ru".to_string())]);
+		assert_eq!(tags(&lcid_matcher, &["en-us", "en_GB", "0x0419", "ru"]), [(0, "en-US".to_string()), (2, "ru-RU".to_string()), (3, "ru".to_string())]);
+		assert_eq!(tags(&matcher, &[]), []);
+
+		// Reuse of the most recently used lists
+		assert_eq!(cached(&["en-us", "en_GB", "0x0419", "ru"]), Some(2));
+		assert_eq!(tags(&matcher, &["en-us", "en_GB", "0x0419", "ru"]), [(0, "en-US".to_string()), (3, "ru".to_string())]);
+		assert_eq!(cached(&["en-us", "en_GB", "0x0419", "ru"]), Some(0));
+		for i in 0..CAPACITY {
+			tags(&matcher, &[&format!("de-{i:03}")]);
+		}
+		assert_eq!(cached(&["en-us", "en_GB", "0x0419", "ru"]), None);
+		assert_eq!(CACHE.with_borrow(Vec::len), CAPACITY);
+	}
+
+	#[test]
+	fn test_parse_user_locales_options() {
+		fn case(matchers: &[Matcher], user_locales: &[&str]) {
+			// Alternates the matchers so that each of them could reuse a list parsed by another one.
+			for _ in 0..2 {
+				for matcher in matchers {
+					let expected = user_locales.iter()
+						.enumerate()
+						.filter_map(|(i, locale)| matcher.parse(locale).map(|tag| (i, tag)))
+						.collect::<Vec<_>>();
+					assert_eq!(*parse_user_locales(matcher, user_locales), *expected);
+				}
+			}
+		}
+
+		// LCID inputs
+		case(&[Matcher::new(), Matcher::new().with_lcid_inputs(true)], &["0x0419", "en"]);
+
+		// Registry validation
+		#[cfg(feature = "iana")]
+		case(&[Matcher::new(), Matcher::new().with_registry_validation(true)], &["en-UK", "en"]);
+
+		// Canonicalization
+		#[cfg(feature = "iana")]
+		case(&[Matcher::new(), Matcher::new().with_canonicalization(true)], &["iw", "en-Latn-US"]);
+
+		// Extended language canonicalization
+		case(&[Matcher::new(), Matcher::new().with_extlang_canonicalization(true)], &["zh-yue-HK", "en"]);
+
+		// .NET culture names
+		case(&[Matcher::new(), Matcher::new().with_dotnet_culture_names(true)], &["zh-CHT", "en"]);
+
+		// Language names
+		case(&[Matcher::new(), Matcher::new().with_language_names(true)], &["German", "en"]);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_user_locale_cache() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_user_locale_cache(true);
+			assert_eq!(matcher.best_matching_locale(available_locales.iter().copied(), user_locales), expected);
+			assert_eq!(matcher.best_matching_locale(available_locales.iter().copied(), user_locales), expected);
+		}
+
+		case(&["en-US", "ru-BY"], &["ru-RU", "en"], Some("ru-BY"));
+		case(&["en-GB", "fr"], &["ru-RU", "en"], Some("en-GB"));
+		case(&["en-GB", "fr"], &["ru_RU", "FR-ca"], Some("fr"));
+		case(&["en-GB", "fr"], &["de"], None);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Formatting of BCP 47 locales with the case conventions of the standard.
+
+use language_tags::LanguageTag;
+
+use super::Bcp47Locale;
+
+/// Reformats a BCP 47 locale with the case conventions of
+/// [RFC 5646 2.1.1](https://www.rfc-editor.org/rfc/rfc5646#section-2.1.1), e.g. for display and
+/// storage: scripts in title case, regions in uppercase, and all other subtags in lowercase.  
+/// Returns [`None`] if the locale is malformed.
+///
+/// The matching functions return the matched locale exactly as it was passed in, so this function
+/// can be applied to their result when a canonical spelling is needed.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{best_matching_locale, canonical_case};
+///
+///
+/// let best_match = best_matching_locale(["en-us", "zh-cmn-hans-sg"], ["zh-SG"]);
+///
+/// assert_eq!(best_match, Some("zh-cmn-hans-sg"));
+/// assert_eq!(best_match.and_then(canonical_case).as_deref(), Some("zh-cmn-Hans-SG"));
+/// assert_eq!(canonical_case("EN_us"), None);
+/// ```
+pub fn canonical_case(locale: &str) -> Option<String> {
+	LanguageTag::parse(locale).ok()?;
+	let mut after_singleton = false;
+	let subtags = locale.split('-')
+		.enumerate()
+		.map(|(i, subtag)| {
+			// The extensions and the private use subtags, as well as the subtags of the irregular
+			// grandfathered tags, e.g. "i-klingon", are in lowercase
+			after_singleton |= subtag.len() == 1;
+			match subtag.len() {
+				2 if i > 0 && !after_singleton => subtag.to_ascii_uppercase(),
+				4 if i > 0 && !after_singleton && subtag.bytes().all(|c| c.is_ascii_alphabetic()) => {
+					subtag[..1].to_ascii_uppercase() + &subtag[1..].to_ascii_lowercase()
+				}
+				_ => subtag.to_ascii_lowercase(),
+			}
+		})
+		.collect::<Vec<_>>();
+	Some(subtags.join("-"))
+}
+
+impl Bcp47Locale {
+	/// Returns the locale reformatted with the case conventions of the standard, see
+	/// [`canonical_case`].
+	pub fn to_canonical_case(&self) -> Bcp47Locale {
+		// The locale is well-formed, and the case does not change that
+		Bcp47Locale(canonical_case(self.as_str()).unwrap_or_else(|| self.as_str().to_string()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_canonical_case() {
+		fn case(locale: &str, expected: Option<&str>) {
+			assert_eq!(canonical_case(locale).as_deref(), expected, "{locale:?}");
+		}
+
+		// Languages, scripts and regions
+		case("en", Some("en"));
+		case("EN-us", Some("en-US"));
+		case("zh-cmn-hans-sg", Some("zh-cmn-Hans-SG"));
+		case("ZH-HANT-tw", Some("zh-Hant-TW"));
+		case("es-419", Some("es-419"));
+		case("sr-latn-rs", Some("sr-Latn-RS"));
+
+		// Variants
+		case("SL-ROZAJ-BISKE-1994", Some("sl-rozaj-biske-1994"));
+		case("de-CH-1901", Some("de-CH-1901"));
+
+		// Extensions and private use
+		case("DE-de-U-CO-PHONEBK", Some("de-DE-u-co-phonebk"));
+		case("en-US-x-TWAIN-Abcd", Some("en-US-x-twain-abcd"));
+		case("X-Whatever-AB", Some("x-whatever-ab"));
+
+		// Grandfathered tags
+		case("I-KLINGON", Some("i-klingon"));
+		case("EN-gb-OED", Some("en-GB-oed"));
+
+		// Malformed locales
+		case("en_US", None);
+		case("", None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_to_canonical_case() {
+		let locale = Bcp47Locale::parse("zh-hant-tw").unwrap();
+		assert_eq!(locale.to_canonical_case().as_str(), "zh-Hant-TW");
+		assert_eq!(locale.as_str(), "zh-hant-tw");
+	}
+}
\ No newline at end of file
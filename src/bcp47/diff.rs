@@ -0,0 +1,111 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Comparison of the subtags of two BCP 47 locales, e.g. for explaining how a served locale
+//! differs from the requested one.
+
+use crate::engine;
+use crate::matching::LocaleDiff;
+
+use super::{Subtag, TagParts, SUBTAGS};
+
+/// Compares the subtags of two BCP 47 locales, and returns which of them are equal, differ, or are
+/// present in only one of the locales.
+///
+/// The subtags are compared ignoring ASCII case, and the subtags absent in both locales are
+/// omitted.  
+/// Returns [`None`] if any of the locales is malformed or is an irregular grandfathered tag, e.g.
+/// `i-klingon`, which has no subtags to compare.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{diff, Subtag};
+/// use locale_match::matching::Difference;
+///
+///
+/// let diff = diff("zh-Hant-TW", "zh-HK").unwrap();
+///
+/// assert_eq!(diff.language, Difference::Equal("zh"));
+/// assert_eq!(diff.subtags, [
+///     (Subtag::Script, Difference::OnlyFirst("Hant")),
+///     (Subtag::Region, Difference::Different("TW", "HK")),
+/// ]);
+/// assert!(!diff.is_equal());
+/// ```
+pub fn diff<'a>(a: &'a str, b: &'a str) -> Option<LocaleDiff<'a, Subtag>> {
+	let (a, b) = (TagParts::parse(a)?, TagParts::parse(b)?);
+	let part = |locale: &TagParts<'a>, subtag: Subtag| match subtag {
+		Subtag::ExtendedLanguage => locale.extended_language(),
+		Subtag::Script => locale.script(),
+		Subtag::Region => locale.region(),
+		Subtag::Variant => locale.variant(),
+		Subtag::Extension => locale.extension(),
+		Subtag::PrivateUse => locale.private_use(),
+	};
+	Some(engine::diff(&a, &b, TagParts::language, &SUBTAGS, part, |_, a, b| a.eq_ignore_ascii_case(b)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::Difference;
+
+	#[test]
+	fn test_diff() {
+		fn case(a: &str, b: &str, language: Difference, subtags: &[(Subtag, Difference)]) {
+			let diff = diff(a, b).unwrap();
+			assert_eq!((diff.language, diff.subtags.as_slice()), (language, subtags), "{a} and {b}");
+		}
+
+		// Equal locales
+		case("en", "en", Difference::Equal("en"), &[]);
+		case("en-US", "EN-us", Difference::Equal("en"), &[(Subtag::Region, Difference::Equal("US"))]);
+
+		// Different subtags
+		case("en-US", "en-GB", Difference::Equal("en"), &[(Subtag::Region, Difference::Different("US", "GB"))]);
+		case("en", "ru", Difference::Different("en", "ru"), &[]);
+		case("sl-rozaj", "sl-nedis", Difference::Equal("sl"), &[(Subtag::Variant, Difference::Different("rozaj", "nedis"))]);
+
+		// Subtags present in one of the locales
+		case("en-US", "en", Difference::Equal("en"), &[(Subtag::Region, Difference::OnlyFirst("US"))]);
+		case("zh-TW", "zh-cmn-Hant-TW", Difference::Equal("zh"), &[
+			(Subtag::ExtendedLanguage, Difference::OnlySecond("cmn")),
+			(Subtag::Script, Difference::OnlySecond("Hant")),
+			(Subtag::Region, Difference::Equal("TW")),
+		]);
+		case("ar-u-nu-latn", "ar-x-foo", Difference::Equal("ar"), &[
+			(Subtag::Extension, Difference::OnlyFirst("u-nu-latn")),
+			(Subtag::PrivateUse, Difference::OnlySecond("x-foo")),
+		]);
+
+		// Variants starting with a digit
+		case("de-1606nict", "de", Difference::Equal("de"), &[(Subtag::Variant, Difference::OnlyFirst("1606nict"))]);
+		case("de-CH-1901", "de-CH-1996", Difference::Equal("de"), &[
+			(Subtag::Region, Difference::Equal("CH")),
+			(Subtag::Variant, Difference::Different("1901", "1996")),
+		]);
+
+		// Regular grandfathered tags are compared like other locales
+		case("zh-min-nan", "zh", Difference::Equal("zh"), &[(Subtag::ExtendedLanguage, Difference::OnlyFirst("min-nan"))]);
+
+		// Malformed locales and irregular grandfathered tags
+		assert_eq!(diff("en_US", "en-US"), None);
+		assert_eq!(diff("en-US", ""), None);
+		assert_eq!(diff("i-klingon", "tlh"), None);
+		assert_eq!(diff("en-GB", "en-GB-oed"), None);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,166 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversion of .NET culture names and Windows locale names to BCP 47 locales.
+
+use language_tags::LanguageTag;
+
+/// Legacy .NET culture names that are not, or not correctly, BCP 47 locales, and the
+/// corresponding BCP 47 locales.  
+/// See [CultureInfo](https://learn.microsoft.com/en-us/dotnet/api/system.globalization.cultureinfo)
+/// and [National Language Support (NLS) API Reference](https://learn.microsoft.com/en-us/previous-versions/windows/embedded/ms912389(v=winembedded.11)).
+const LEGACY_CULTURE_NAMES: &[(&str, &str)] = &[
+	("zh-CHS", "zh-Hans"),
+	("zh-CHT", "zh-Hant"),
+	("div", "dv"),
+	("div-MV", "dv-MV"),
+	("ky-KZ", "ky-KG"),
+	("sr-SP-Latn", "sr-Latn-RS"),
+	("sr-SP-Cyrl", "sr-Cyrl-RS"),
+];
+
+/// Windows locale names with alternate sort orders and the corresponding BCP 47 locales with the
+/// collations, if CLDR has them.  
+/// See [Sort Order Identifiers](https://learn.microsoft.com/en-us/windows/win32/intl/sort-order-identifiers).
+const SORT_ORDER_NAMES: &[(&str, &str)] = &[
+	("de-DE_phoneb", "de-DE-u-co-phonebk"),
+	("es-ES_tradnl", "es-ES-u-co-trad"),
+	("hu-HU_technl", "hu-HU"),
+	("ja-JP_radstr", "ja-JP-u-co-unihan"),
+	("ka-GE_modern", "ka-GE"),
+	("zh-CN_phoneb", "zh-CN-u-co-pinyin"),
+	("zh-CN_stroke", "zh-CN-u-co-stroke"),
+	("zh-HK_radstr", "zh-HK-u-co-unihan"),
+	("zh-MO_radstr", "zh-MO-u-co-unihan"),
+	("zh-MO_stroke", "zh-MO-u-co-stroke"),
+	("zh-SG_phoneb", "zh-SG-u-co-pinyin"),
+	("zh-SG_stroke", "zh-SG-u-co-stroke"),
+	("zh-TW_pronun", "zh-TW-u-co-zhuyin"),
+	("zh-TW_radstr", "zh-TW-u-co-unihan"),
+];
+
+/// Converts a .NET culture name to a BCP 47 locale.
+///
+/// Most culture names, including the neutral cultures (e.g. `uz-Cyrl`) and the names with scripts
+/// or UN M.49 regions (e.g. `zh-Hans-CN` or `en-029`), are BCP 47 locales already and are returned
+/// unchanged. The legacy names are converted:
+/// * `zh-CHS` and `zh-CHT` to `zh-Hans` and `zh-Hant`.
+/// * Names with the script after the region, e.g. `uz-UZ-Cyrl`, to `uz-Cyrl-UZ`.
+/// * Names with outdated codes, e.g. `sr-SP-Latn`, `div-MV` and `ky-KZ`, to `sr-Latn-RS`, `dv-MV`
+///   and `ky-KG`.
+/// * Windows names with alternate sort orders after `_`, e.g. `de-DE_phoneb` and `zh-TW_pronun`, to
+///   the locales with the corresponding collations, e.g. `de-DE-u-co-phonebk` and
+///   `zh-TW-u-co-zhuyin`. Sort orders without a collation, e.g. in `hu-HU_technl`, are dropped.
+///
+/// The comparison with the legacy names is case-insensitive.  
+/// Returns [`None`] for the invariant culture (an empty name) and for malformed names.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::culture_name_to_locale;
+///
+///
+/// assert_eq!(culture_name_to_locale("zh-CHS").as_deref(), Some("zh-Hans"));
+/// assert_eq!(culture_name_to_locale("uz-UZ-Cyrl").as_deref(), Some("uz-Cyrl-UZ"));
+/// assert_eq!(culture_name_to_locale("en-029").as_deref(), Some("en-029"));
+/// assert_eq!(culture_name_to_locale("de-DE_phoneb").as_deref(), Some("de-DE-u-co-phonebk"));
+/// assert_eq!(culture_name_to_locale(""), None);
+/// ```
+pub fn culture_name_to_locale(name: &str) -> Option<String> {
+	if let Some((_, locale)) = SORT_ORDER_NAMES.iter().find(|(sorted, _)| sorted.eq_ignore_ascii_case(name)) {
+		return Some(locale.to_string());
+	}
+	let name = match name.split_once('_') {
+		// The names of the sort orders have 6 letters, unlike e.g. the territories of POSIX locales
+		Some((name, sort_order)) if sort_order.len() == 6 && sort_order.bytes().all(|c| c.is_ascii_alphabetic()) => name,
+		_ => name,
+	};
+	let locale = match LEGACY_CULTURE_NAMES.iter().find(|(legacy, _)| legacy.eq_ignore_ascii_case(name)) {
+		Some((_, locale)) => locale.to_string(),
+		None => match name.split('-').collect::<Vec<&str>>()[..] {
+			[language, region, script] if is_region(region) && script.len() == 4 && script.bytes().all(|c| c.is_ascii_alphabetic()) => {
+				format!("{language}-{script}-{region}")
+			}
+			_ => name.to_string(),
+		},
+	};
+	LanguageTag::parse(&locale).ok().map(|_| locale)
+}
+
+/// Checks whether a subtag is a region, i.e. 2 letters or 3 digits, unlike e.g. an extended language
+/// subtag (`cmn` in `zh-cmn-Hans`) or a variant.
+fn is_region(subtag: &str) -> bool {
+	match subtag.len() {
+		2 => subtag.bytes().all(|c| c.is_ascii_alphabetic()),
+		3 => subtag.bytes().all(|c| c.is_ascii_digit()),
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_culture_name_to_locale() {
+		fn case(name: &str, expected: Option<&str>) {
+			assert_eq!(culture_name_to_locale(name).as_deref(), expected, "{name:?}");
+		}
+
+		// BCP 47 locales
+		case("en", Some("en"));
+		case("en-US", Some("en-US"));
+		case("zh-Hans-CN", Some("zh-Hans-CN"));
+		case("uz-Cyrl", Some("uz-Cyrl"));
+		case("en-029", Some("en-029"));
+		case("es-419", Some("es-419"));
+		case("zh-cmn-Hans", Some("zh-cmn-Hans"));
+		case("zh-yue-Hant", Some("zh-yue-Hant"));
+		case("zh-cmn-Hans-CN", Some("zh-cmn-Hans-CN"));
+		case("de-DE-1996", Some("de-DE-1996"));
+		case("sl-rozaj-biske", Some("sl-rozaj-biske"));
+
+		// Legacy names
+		case("zh-CHS", Some("zh-Hans"));
+		case("zh-cht", Some("zh-Hant"));
+		case("div-MV", Some("dv-MV"));
+		case("ky-KZ", Some("ky-KG"));
+		case("sr-SP-Latn", Some("sr-Latn-RS"));
+
+		// Script after the region
+		case("uz-UZ-Cyrl", Some("uz-Cyrl-UZ"));
+		case("az-AZ-Latn", Some("az-Latn-AZ"));
+		case("bs-BA-Cyrl", Some("bs-Cyrl-BA"));
+		case("sr-021-Latn", Some("sr-Latn-021"));
+
+		// Sort orders
+		case("de-DE_phoneb", Some("de-DE-u-co-phonebk"));
+		case("es-ES_TRADNL", Some("es-ES-u-co-trad"));
+		case("zh-TW_pronun", Some("zh-TW-u-co-zhuyin"));
+		case("zh-CN_phoneb", Some("zh-CN-u-co-pinyin"));
+		case("hu-HU_technl", Some("hu-HU"));
+		case("de-AT_phoneb", Some("de-AT"));
+		case("uz-UZ-Cyrl_stroke", Some("uz-Cyrl-UZ"));
+
+		// Malformed
+		case("", None);
+		case("en_", None);
+		case("en_US", None);
+		case("de-DE_pho-neb", None);
+		case("en-US-Latn-x", None);
+	}
+}
\ No newline at end of file
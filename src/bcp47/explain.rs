@@ -0,0 +1,99 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Explanation of the matching of BCP 47 locales.
+
+use crate::engine;
+use crate::matching::Explanation;
+
+use super::{Matcher, Subtag};
+
+impl Matcher {
+	/// Matches the locales like [`best_matching_locale_with_confidence`](Self::best_matching_locale_with_confidence)
+	/// and explains the match: which user locale drove it, which available locales were considered
+	/// with the scores of their subtags, and which locales were ignored as malformed.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::{Matcher, Subtag};
+	///
+	///
+	/// let explanation = Matcher::new().explain(["en-US", "pt-PT", "pt-BR", "pt_BR"], ["fr", "pt-BR"]);
+	///
+	/// let best_match = explanation.best_match.unwrap();
+	/// assert_eq!((best_match.locale, best_match.user_index), ("pt-BR", 1));
+	/// assert_eq!(explanation.candidates.len(), 2);
+	/// assert_eq!(explanation.candidates[0].subtags[2].subtag, Subtag::Region);
+	/// assert!(explanation.candidates[0].score < explanation.candidates[1].score);
+	/// assert_eq!(explanation.malformed_available_locales, [3]);
+	/// ```
+	pub fn explain<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Explanation<T1, Subtag>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::explain(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::matching::{Comparison, TieBreak};
+
+	#[test]
+	fn test_explain() {
+		fn case(matcher: Matcher, available_locales: &[&str], user_locales: &[&str]) {
+			let explanation = matcher.explain(available_locales, user_locales);
+			let best_match = matcher.best_matching_locale_with_confidence(available_locales, user_locales);
+			assert_eq!(explanation.best_match, best_match, "{available_locales:?} and {user_locales:?}");
+			for candidate in &explanation.candidates {
+				if matcher.scorer.is_none() {
+					let sum = candidate.subtags.iter().map(|s| s.score).sum::<f64>();
+					assert!((candidate.score - sum).abs() < 1e-9);
+				}
+			}
+		}
+
+		// Same matches as the matcher
+		case(Matcher::new(), &["en-US", "ru-RU"], &["ru", "en"]);
+		case(Matcher::new(), &["en-US", "en-GB", "ru-UA", "fr-FR", "it"], &["ru-RU", "ru", "en-US", "en"]);
+		case(Matcher::new(), &["zh", "zh-cmn", "zh-cmn-Hans"], &["zh-Hans"]);
+		case(Matcher::new(), &["en", "en-US"], &["en-GB"]);
+		case(Matcher::new().with_tie_break(TieBreak::MostSpecific), &["en", "en-US"], &["en-GB"]);
+		case(Matcher::new().with_mismatch_penalty(1.0), &["en-GB", "en"], &["en-US"]);
+		case(Matcher::new(), &["en-US", "ru-RU"], &["fr", "de"]);
+		case(Matcher::new(), &[], &["en"]);
+	}
+
+	#[test]
+	fn test_explain_details() {
+		let explanation = Matcher::new().explain(["en_US", "de-DE", "de-AT", "en-GB", "en"], ["de_CH", "fr", "en-US", "de"]);
+
+		assert_eq!(explanation.best_match.map(|m| (m.locale, m.user_index)), Some(("en-GB", 2)));
+		assert_eq!(explanation.malformed_available_locales, [0]);
+		// The user locales after the matched one are not tried
+		assert_eq!(explanation.malformed_user_locales, [0]);
+		assert_eq!(explanation.candidates.iter().map(|c| (c.available_index, c.user_index)).collect::<Vec<_>>(), [(3, 2), (4, 2)]);
+
+		let region = explanation.candidates[0].subtags[2];
+		assert_eq!((region.subtag, region.comparison, region.weight, region.score), (Subtag::Region, Comparison::Present(0.0), 8.0, 0.0));
+		let region = explanation.candidates[1].subtags[2];
+		assert_eq!((region.subtag, region.comparison), (Subtag::Region, Comparison::AvailableAbsent));
+	}
+}
\ No newline at end of file
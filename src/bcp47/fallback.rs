@@ -0,0 +1,169 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that yields all of the matching available locales from the best one.
+
+use std::borrow::Borrow;
+
+use crate::engine::{self, Format};
+use crate::matching::LocaleMatch;
+
+use super::Matcher;
+
+/// Returns an iterator over all of the available locales matching the user locales, from the best
+/// match to the worst, e.g. for trying the translation files one by one until one of them loads.
+///
+/// The first item is the match of [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence).
+/// Then the remaining matches of the same user locale follow by score, then the matches of the
+/// next user locales, and so on. Each available locale is yielded once, for the first user locale
+/// that matches it.  
+/// The available locales are scored once for each user locale, when the matches of the previous
+/// user locales run out.  
+/// Malformed locales are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::matches_iter;
+///
+///
+/// let available_locales = ["en-US", "de-AT", "de-DE", "fr-FR"];
+///
+/// let locales = matches_iter(available_locales, ["de-DE", "fr"]).map(|m| m.locale).collect::<Vec<_>>();
+///
+/// assert_eq!(locales, ["de-DE", "de-AT", "fr-FR"]);
+/// ```
+pub fn matches_iter<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> impl Iterator<Item = LocaleMatch<T1>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	matches(Matcher::new(), available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Returns an iterator over all of the matches like [`matches_iter`], using the configuration
+	/// of the matcher.
+	///
+	/// The observer of the matcher is called for each scored candidate, and with a random tie break,
+	/// each item is picked at random among the rest of the matches with the highest score. No
+	/// `tracing` events or `metrics` are recorded, since the iterator does not negotiate a locale.
+	pub fn matches_iter<'a, T1, T2>(&'a self, available_locales: impl IntoIterator<Item = T1> + 'a, user_locales: impl IntoIterator<Item = T2> + 'a) -> impl Iterator<Item = LocaleMatch<T1>> + 'a
+	where
+		T1: AsRef<str> + 'a,
+		T2: AsRef<str> + 'a
+	{
+		matches(self, available_locales, user_locales)
+	}
+}
+
+/// Returns an iterator over all of the matches like [`matches_iter`] with a matcher that it owns or
+/// borrows.
+fn matches<M, T1, T2>(matcher: M, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> impl Iterator<Item = LocaleMatch<T1>>
+where
+	M: Borrow<Matcher>,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let (mut locales, available): (Vec<_>, Vec<_>) = engine::parse_available(matcher.borrow(), available_locales)
+		.map(|(available_index, locale)| (Some(locale.locale), (available_index, locale.tag)))
+		.unzip();
+	let mut user_tags = user_locales.into_iter()
+		.enumerate()
+		.filter_map(|(user_index, locale)| matcher.borrow().parse_locale(locale).ok().map(|locale| (user_index, locale.tag)))
+		.collect::<Vec<_>>()
+		.into_iter();
+
+	let (mut user_index, mut ranked) = (0, Vec::<(usize, f64)>::new().into_iter());
+	std::iter::from_fn(move || loop {
+		if let Some((i, confidence)) = ranked.next() {
+			let locale = locales[i].take().unwrap();
+			return Some(LocaleMatch { locale, available_index: available[i].0, user_index, confidence });
+		}
+		let (next_user_index, user_tag) = user_tags.next()?;
+		let matcher = matcher.borrow();
+		// The locales yielded for the previous user locales are not scored again
+		let candidate = |i: usize, aval_tag: &_, user_tag: &_| locales[i].as_ref().and_then(|_| matcher.candidate(aval_tag, user_tag));
+		ranked = matcher.engine().ranked(&available, |_, (i, tag)| (*i, tag), &user_tag, candidate).into_iter();
+		user_index = next_user_index;
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::ops::ControlFlow;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::sync::Arc;
+
+	#[test]
+	fn test_matches_iter() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: &[&str]) {
+			let matches = matches_iter(available_locales, user_locales).map(|m| *m.locale).collect::<Vec<_>>();
+			assert_eq!(matches, expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Matches of a single user locale by score
+		case(&["en", "en-US", "en-GB", "ru-RU"], &["en-GB"], &["en-GB", "en", "en-US"]);
+		case(&["zh-Hans-CN", "zh-Hant-HK", "zh-Hant-TW"], &["zh-Hant-TW"], &["zh-Hant-TW", "zh-Hant-HK", "zh-Hans-CN"]);
+
+		// Matches of the next user locales follow
+		case(&["de-DE", "en-US", "de-AT", "fr-FR"], &["fr", "de-AT"], &["fr-FR", "de-AT", "de-DE"]);
+		case(&["en-US", "en-GB"], &["en-GB", "en-US"], &["en-GB", "en-US"]);
+
+		// No matches and malformed locales
+		case(&["en-US", "en-GB"], &["fr"], &[]);
+		case(&["en_US", "en-GB"], &["en_US", "en"], &["en-GB"]);
+		case(&[], &["en"], &[]);
+
+		// The first match is the best match
+		let available_locales = ["en-US", "ru-RU", "ru-UA"];
+		let user_locales = ["ru-BY", "en"];
+		let mut matches = matches_iter(available_locales, user_locales);
+		assert_eq!(matches.next(), super::super::best_matching_locale_with_confidence(available_locales, user_locales));
+		let rest = matches.map(|m| (m.locale, m.available_index, m.user_index)).collect::<Vec<_>>();
+		assert_eq!(rest, [("ru-UA", 2, 0), ("en-US", 0, 1)]);
+
+		// The first match is the best match of a configured matcher
+		let available_locales = ["en-US", "en-GB", "en-AU"];
+		let matcher = Matcher::new().with_random_tie_break(|| 0.9);
+		let first = matcher.matches_iter(available_locales, ["en"]).next();
+		assert_eq!(first, matcher.best_matching_locale_with_confidence(available_locales, ["en"]));
+		assert_eq!(first.map(|m| m.locale), Some("en-AU"));
+		let locales = matcher.matches_iter(available_locales, ["en"]).map(|m| m.locale).collect::<Vec<_>>();
+		assert_eq!(locales, ["en-AU", "en-GB", "en-US"]);
+
+		// The matches are found lazily
+		let observed = Arc::new(AtomicUsize::new(0));
+		let counter = Arc::clone(&observed);
+		let matcher = Matcher::new().with_observer(move |_, _, _| {
+			counter.fetch_add(1, Ordering::Relaxed);
+			ControlFlow::Continue(())
+		});
+		let mut matches = matcher.matches_iter(available_locales, ["en-GB"]);
+		assert_eq!(observed.load(Ordering::Relaxed), 0);
+		assert_eq!(matches.next().map(|m| m.locale), Some("en-GB"));
+		assert_eq!(observed.load(Ordering::Relaxed), 3);
+
+		// Each locale is scored once for each user locale
+		assert_eq!(matches.count(), 2);
+		assert_eq!(observed.load(Ordering::Relaxed), 3);
+		observed.store(0, Ordering::Relaxed);
+		assert_eq!(matcher.matches_iter(["en-US", "de-DE", "en-GB", "de-AT"], ["en-GB", "de", "en"]).count(), 4);
+		assert_eq!(observed.load(Ordering::Relaxed), 4);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of BCP 47 locales without heap allocations, for targets without an allocator.
+//!
+//! The locales are split with [`TagParts`] instead of being parsed into [`LanguageTag`](language_tags::LanguageTag)s.
+
+use crate::matching::{eq_ignore_ascii_case, score, Comparison, Scoring};
+
+use super::TagParts;
+
+/// Finds the best matching locale from a slice of available locales based on a slice of user
+/// locales, without allocating memory on the heap.
+///
+/// The available locales are parsed once into a buffer on the stack that holds up to `N` locales.  
+/// The locales are matched exactly like in [`best_matching_locale`](super::best_matching_locale),
+/// except that irregular grandfathered tags, e.g. `i-klingon`, are ignored as malformed.  
+/// Returns [`None`] if more than `N` available locales are given or none of them matches.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale_fixed;
+///
+///
+/// let available_locales = ["en-US", "de-DE", "ru-BY"];
+/// let user_locales = ["ru-RU", "en"];
+///
+/// let best_match = best_matching_locale_fixed::<4>(&available_locales, &user_locales);
+///
+/// assert_eq!(best_match, Some("ru-BY"));
+/// ```
+pub fn best_matching_locale_fixed<'a, const N: usize>(available_locales: &[&'a str], user_locales: &[&str]) -> Option<&'a str> {
+	if available_locales.len() > N {
+		return None;
+	}
+	let mut available_tags = [None; N];
+	for (tag, locale) in available_tags.iter_mut().zip(available_locales) {
+		*tag = TagParts::parse(locale);
+	}
+	let available_tags = &available_tags[..available_locales.len()];
+
+	let scoring = Scoring::default();
+	user_locales.iter()
+		.filter_map(|locale| TagParts::parse(locale))
+		.find_map(|user_tag| available_tags.iter()
+			.enumerate()
+			.filter_map(|(i, aval_tag)| aval_tag.map(|aval_tag| (i, aval_tag)))
+			.filter(|(_, aval_tag)| eq_ignore_ascii_case(aval_tag.language(), user_tag.language()))
+			.map(|(i, aval_tag)| (i, score(comparisons(&aval_tag, &user_tag), &scoring)))
+			.rev()
+			.max_by(|(_, a), (_, b)| a.total_cmp(b)))
+		.map(|(i, _)| available_locales[i])
+}
+
+/// Comparisons of corresponding parts of two locales (except for the language) with their weights,
+/// like the comparisons of the default [`Matcher`](super::Matcher).
+fn comparisons(aval_tag: &TagParts, user_tag: &TagParts) -> [(Comparison, f64); 6] {
+	let eq = str::eq_ignore_ascii_case;
+	[
+		(Comparison::of(aval_tag.extended_language(), user_tag.extended_language(), eq), 32.0),
+		(Comparison::of(aval_tag.script(),            user_tag.script(),            eq), 16.0),
+		(Comparison::of(aval_tag.region(),            user_tag.region(),            eq),  8.0),
+		(Comparison::of_sets(variants(aval_tag),      variants(user_tag),           eq),  4.0),
+		(Comparison::of(aval_tag.extension(),         user_tag.extension(),         eq),  2.0),
+		(Comparison::of(aval_tag.private_use(),       user_tag.private_use(),       eq),  1.0),
+	]
+}
+
+/// The variants of a locale, which are compared as sets without collecting them.
+fn variants<'a>(tag: &TagParts<'a>) -> impl Iterator<Item = &'a str> + Clone {
+	tag.variant().into_iter().flat_map(|variants| variants.split('-'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::bcp47::best_matching_locale;
+
+	#[test]
+	fn test_best_matching_locale_fixed() {
+		fn case(available_locales: &[&str], user_locales: &[&str]) {
+			assert_eq!(best_matching_locale_fixed::<16>(available_locales, user_locales), best_matching_locale(available_locales, user_locales).copied());
+		}
+
+		// Same results as the allocating matching
+		case(&["en-US", "ru-RU"], &["ru", "en"]);
+		case(&["en-US", "en-GB", "ru-UA", "fr-FR", "it"], &["ru-RU", "ru", "en-US", "en"]);
+		case(&["en", "pt-BR", "pt-PT", "es"], &["pt", "en"]);
+		case(&["zh", "zh-cmn", "zh-cmn-Hans"], &["zh-Hans"]);
+		case(&["zh-Hant-TW", "zh-Hans-CN"], &["ZH-hans"]);
+		case(&["sl-rozaj", "sl-rozaj-biske", "sl-nedis"], &["sl-rozaj-biske-1994"]);
+		case(&["de-DE-u-co-phonebk", "de-DE"], &["de-DE-u-co-phonebk"]);
+		case(&["de-x-foo", "de-x-bar", "de"], &["de-x-bar"]);
+		case(&["x-foo", "en"], &["X-FOO"]);
+		case(&["es-419", "es-ES"], &["es-MX", "es-419"]);
+		case(&["de", "de-1606nict", "de-1996"], &["de-1606nict"]);
+		case(&["en-GB", "en-GB-1694acad"], &["en-GB-1694acad", "en"]);
+		case(&["de-CH-1901", "de-CH-1606nict-1996"], &["de-CH-1606nict"]);
+		case(&["en-US", "ru-RU"], &["fr", "de"]);
+		case(&[], &["en"]);
+
+		// Malformed locales
+		case(&["en_US", "en-GB"], &["en-US"]);
+		case(&["en-US"], &["en_US", "", "en-", "-en", "e", "en-a", "en-x", "en-abcdefghi", "en"]);
+		case(&["zh-cmn-yue-wuu-nan", "zh"], &["zh-cmn"]);
+	}
+
+	#[test]
+	fn test_capacity() {
+		assert_eq!(best_matching_locale_fixed::<2>(&["en", "de"], &["de"]), Some("de"));
+		assert_eq!(best_matching_locale_fixed::<2>(&["en", "de", "fr"], &["de"]), None);
+		assert_eq!(best_matching_locale_fixed::<0>(&[], &["de"]), None);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of the keys of maps, e.g. of translations by locale.
+
+use crate::engine;
+
+use super::Matcher;
+
+/// Finds the entry of a map whose key is the best matching locale, like
+/// [`best_matching_locale`](super::best_matching_locale), and returns the key with its value.
+///
+/// Any map with string-like keys can be passed by reference, e.g. a [`HashMap`](std::collections::HashMap)
+/// or a [`BTreeMap`](std::collections::BTreeMap), as well as any iterator over key-value pairs, so
+/// the keys don't need to be collected and the value doesn't need to be looked up again.  
+/// For maps with an unspecified iteration order, such as `HashMap`, the tie break by the order of
+/// the available locales picks an arbitrary one of the tied keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use locale_match::bcp47::best_matching_key;
+///
+///
+/// let translations = HashMap::from([("en-US", "Hello"), ("ru-RU", "Привет")]);
+///
+/// assert_eq!(best_matching_key(&translations, ["ru-BY"]), Some((&"ru-RU", &"Привет")));
+/// assert_eq!(best_matching_key(&translations, ["fr"]), None);
+/// ```
+pub fn best_matching_key<'a, K, V, T>(map: impl IntoIterator<Item = (&'a K, &'a V)>, user_locales: impl IntoIterator<Item = T>) -> Option<(&'a K, &'a V)>
+where
+	K: AsRef<str> + 'a,
+	V: 'a,
+	T: AsRef<str>
+{
+	Matcher::new().best_matching_key(map, user_locales)
+}
+
+impl Matcher {
+	/// Finds the entry of a map like [`best_matching_key`], using the configuration of the matcher.
+	pub fn best_matching_key<'a, K, V, T>(&self, map: impl IntoIterator<Item = (&'a K, &'a V)>, user_locales: impl IntoIterator<Item = T>) -> Option<(&'a K, &'a V)>
+	where
+		K: AsRef<str> + 'a,
+		V: 'a,
+		T: AsRef<str>
+	{
+		engine::best_matching_key(self, map, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::collections::{BTreeMap, HashMap};
+
+	#[test]
+	fn test_best_matching_key() {
+		// Maps with different key types
+		let map = BTreeMap::from([("en-US", 1), ("ru-RU", 2), ("de-DE", 3)]);
+		assert_eq!(best_matching_key(&map, ["ru-BY"]), Some((&"ru-RU", &2)));
+		assert_eq!(best_matching_key(&map, ["de"]), Some((&"de-DE", &3)));
+		assert_eq!(best_matching_key(&map, ["fr"]), None);
+
+		let map = HashMap::from([("en-US".to_string(), "one"), ("ru-RU".to_string(), "two")]);
+		assert_eq!(best_matching_key(&map, ["ru-BY"]), Some((&"ru-RU".to_string(), &"two")));
+
+		// Empty maps and iterators over pairs
+		assert_eq!(best_matching_key(&BTreeMap::<&str, i32>::new(), ["ru-BY"]), None);
+		let pairs = [("en-US", 'a'), ("ru-RU", 'b')];
+		assert_eq!(best_matching_key(pairs.iter().map(|(k, v)| (k, v)), ["ru-BY"]), Some((&"ru-RU", &'b')));
+
+		// Matcher configuration
+		let matcher = Matcher::new().with_tie_break(crate::matching::TieBreak::MostSpecific);
+		let map = BTreeMap::from([("en", 1), ("en-US", 2)]);
+		assert_eq!(matcher.best_matching_key(&map, ["en-GB"]), Some((&"en-US", &2)));
+	}
+}
\ No newline at end of file
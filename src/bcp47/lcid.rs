@@ -0,0 +1,406 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversion between Windows locale identifiers (LCIDs) and BCP 47 locales.
+
+/// Windows locale identifiers and the corresponding BCP 47 locales, ordered by identifier.  
+/// See [\[MS-LCID\]: Windows Language Code Identifier (LCID) Reference](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-lcid/).
+const LCIDS: &[(u32, &str)] = &[
+	(0x0004, "zh-Hans"),
+	(0x0401, "ar-SA"),
+	(0x0402, "bg-BG"),
+	(0x0403, "ca-ES"),
+	(0x0404, "zh-TW"),
+	(0x0405, "cs-CZ"),
+	(0x0406, "da-DK"),
+	(0x0407, "de-DE"),
+	(0x0408, "el-GR"),
+	(0x0409, "en-US"),
+	(0x040B, "fi-FI"),
+	(0x040C, "fr-FR"),
+	(0x040D, "he-IL"),
+	(0x040E, "hu-HU"),
+	(0x040F, "is-IS"),
+	(0x0410, "it-IT"),
+	(0x0411, "ja-JP"),
+	(0x0412, "ko-KR"),
+	(0x0413, "nl-NL"),
+	(0x0414, "nb-NO"),
+	(0x0415, "pl-PL"),
+	(0x0416, "pt-BR"),
+	(0x0417, "rm-CH"),
+	(0x0418, "ro-RO"),
+	(0x0419, "ru-RU"),
+	(0x041A, "hr-HR"),
+	(0x041B, "sk-SK"),
+	(0x041C, "sq-AL"),
+	(0x041D, "sv-SE"),
+	(0x041E, "th-TH"),
+	(0x041F, "tr-TR"),
+	(0x0420, "ur-PK"),
+	(0x0421, "id-ID"),
+	(0x0422, "uk-UA"),
+	(0x0423, "be-BY"),
+	(0x0424, "sl-SI"),
+	(0x0425, "et-EE"),
+	(0x0426, "lv-LV"),
+	(0x0427, "lt-LT"),
+	(0x0428, "tg-Cyrl-TJ"),
+	(0x0429, "fa-IR"),
+	(0x042A, "vi-VN"),
+	(0x042B, "hy-AM"),
+	(0x042C, "az-Latn-AZ"),
+	(0x042D, "eu-ES"),
+	(0x042E, "hsb-DE"),
+	(0x042F, "mk-MK"),
+	(0x0430, "st-ZA"),
+	(0x0431, "ts-ZA"),
+	(0x0432, "tn-ZA"),
+	(0x0433, "ve-ZA"),
+	(0x0434, "xh-ZA"),
+	(0x0435, "zu-ZA"),
+	(0x0436, "af-ZA"),
+	(0x0437, "ka-GE"),
+	(0x0438, "fo-FO"),
+	(0x0439, "hi-IN"),
+	(0x043A, "mt-MT"),
+	(0x043B, "se-NO"),
+	(0x043E, "ms-MY"),
+	(0x043F, "kk-KZ"),
+	(0x0440, "ky-KG"),
+	(0x0441, "sw-KE"),
+	(0x0442, "tk-TM"),
+	(0x0443, "uz-Latn-UZ"),
+	(0x0444, "tt-RU"),
+	(0x0445, "bn-IN"),
+	(0x0446, "pa-IN"),
+	(0x0447, "gu-IN"),
+	(0x0448, "or-IN"),
+	(0x0449, "ta-IN"),
+	(0x044A, "te-IN"),
+	(0x044B, "kn-IN"),
+	(0x044C, "ml-IN"),
+	(0x044D, "as-IN"),
+	(0x044E, "mr-IN"),
+	(0x044F, "sa-IN"),
+	(0x0450, "mn-MN"),
+	(0x0451, "bo-CN"),
+	(0x0452, "cy-GB"),
+	(0x0453, "km-KH"),
+	(0x0454, "lo-LA"),
+	(0x0456, "gl-ES"),
+	(0x0457, "kok-IN"),
+	(0x045A, "syr-SY"),
+	(0x045B, "si-LK"),
+	(0x045D, "iu-Cans-CA"),
+	(0x045E, "am-ET"),
+	(0x0461, "ne-NP"),
+	(0x0462, "fy-NL"),
+	(0x0463, "ps-AF"),
+	(0x0464, "fil-PH"),
+	(0x0465, "dv-MV"),
+	(0x0468, "ha-Latn-NG"),
+	(0x046A, "yo-NG"),
+	(0x046B, "quz-BO"),
+	(0x046C, "nso-ZA"),
+	(0x046D, "ba-RU"),
+	(0x046E, "lb-LU"),
+	(0x046F, "kl-GL"),
+	(0x0470, "ig-NG"),
+	(0x0478, "ii-CN"),
+	(0x047A, "arn-CL"),
+	(0x047C, "moh-CA"),
+	(0x047E, "br-FR"),
+	(0x0480, "ug-CN"),
+	(0x0481, "mi-NZ"),
+	(0x0482, "oc-FR"),
+	(0x0483, "co-FR"),
+	(0x0484, "gsw-FR"),
+	(0x0485, "sah-RU"),
+	(0x0487, "rw-RW"),
+	(0x0488, "wo-SN"),
+	(0x048C, "prs-AF"),
+	(0x0491, "gd-GB"),
+	(0x0492, "ku-Arab-IQ"),
+	(0x0801, "ar-IQ"),
+	(0x0804, "zh-CN"),
+	(0x0807, "de-CH"),
+	(0x0809, "en-GB"),
+	(0x080A, "es-MX"),
+	(0x080C, "fr-BE"),
+	(0x0810, "it-CH"),
+	(0x0813, "nl-BE"),
+	(0x0814, "nn-NO"),
+	(0x0816, "pt-PT"),
+	(0x081A, "sr-Latn-CS"),
+	(0x081D, "sv-FI"),
+	(0x0820, "ur-IN"),
+	(0x082C, "az-Cyrl-AZ"),
+	(0x082E, "dsb-DE"),
+	(0x083B, "se-SE"),
+	(0x083C, "ga-IE"),
+	(0x083E, "ms-BN"),
+	(0x0843, "uz-Cyrl-UZ"),
+	(0x0845, "bn-BD"),
+	(0x0850, "mn-Mong-CN"),
+	(0x085D, "iu-Latn-CA"),
+	(0x085F, "tzm-Latn-DZ"),
+	(0x086B, "quz-EC"),
+	(0x0C01, "ar-EG"),
+	(0x0C04, "zh-HK"),
+	(0x0C07, "de-AT"),
+	(0x0C09, "en-AU"),
+	(0x0C0A, "es-ES"),
+	(0x0C0C, "fr-CA"),
+	(0x0C1A, "sr-Cyrl-CS"),
+	(0x0C3B, "se-FI"),
+	(0x0C6B, "quz-PE"),
+	(0x1001, "ar-LY"),
+	(0x1004, "zh-SG"),
+	(0x1007, "de-LU"),
+	(0x1009, "en-CA"),
+	(0x100A, "es-GT"),
+	(0x100C, "fr-CH"),
+	(0x101A, "hr-BA"),
+	(0x1401, "ar-DZ"),
+	(0x1404, "zh-MO"),
+	(0x1407, "de-LI"),
+	(0x1409, "en-NZ"),
+	(0x140A, "es-CR"),
+	(0x140C, "fr-LU"),
+	(0x141A, "bs-Latn-BA"),
+	(0x1801, "ar-MA"),
+	(0x1809, "en-IE"),
+	(0x180A, "es-PA"),
+	(0x180C, "fr-MC"),
+	(0x181A, "sr-Latn-BA"),
+	(0x1C01, "ar-TN"),
+	(0x1C09, "en-ZA"),
+	(0x1C0A, "es-DO"),
+	(0x1C1A, "sr-Cyrl-BA"),
+	(0x2001, "ar-OM"),
+	(0x2009, "en-JM"),
+	(0x200A, "es-VE"),
+	(0x201A, "bs-Cyrl-BA"),
+	(0x2401, "ar-YE"),
+	(0x2409, "en-029"),
+	(0x240A, "es-CO"),
+	(0x241A, "sr-Latn-RS"),
+	(0x2801, "ar-SY"),
+	(0x2809, "en-BZ"),
+	(0x280A, "es-PE"),
+	(0x281A, "sr-Cyrl-RS"),
+	(0x2C01, "ar-JO"),
+	(0x2C09, "en-TT"),
+	(0x2C0A, "es-AR"),
+	(0x2C1A, "sr-Latn-ME"),
+	(0x3001, "ar-LB"),
+	(0x3009, "en-ZW"),
+	(0x300A, "es-EC"),
+	(0x301A, "sr-Cyrl-ME"),
+	(0x3401, "ar-KW"),
+	(0x3409, "en-PH"),
+	(0x340A, "es-CL"),
+	(0x3801, "ar-AE"),
+	(0x380A, "es-UY"),
+	(0x3C01, "ar-BH"),
+	(0x3C0A, "es-PY"),
+	(0x4001, "ar-QA"),
+	(0x4009, "en-IN"),
+	(0x400A, "es-BO"),
+	(0x4409, "en-MY"),
+	(0x440A, "es-SV"),
+	(0x4809, "en-SG"),
+	(0x480A, "es-HN"),
+	(0x4C0A, "es-NI"),
+	(0x500A, "es-PR"),
+	(0x540A, "es-US"),
+	(0x7814, "nn"), // Neutral locales sharing the primary language with another language
+	(0x781A, "bs"),
+	(0x7C04, "zh-Hant"),
+	(0x7C14, "nb"),
+	(0x7C1A, "sr"),
+];
+
+/// Windows locale identifiers that are not the preferred identifiers of their BCP 47 locales.
+const ALTERNATIVE_LCIDS: &[(u32, &str)] = &[
+	(0x040A, "es-ES"), // Spanish with the traditional sort order
+];
+
+/// The mask of the language identifier (`LANGID`) part of an LCID.
+const LANGID_MASK: u32 = 0xFFFF;
+
+/// The mask of the primary language part of a language identifier.
+const PRIMARY_LANGUAGE_MASK: u32 = 0x03FF;
+
+/// Converts a Windows locale identifier (LCID) to a BCP 47 locale.
+///
+/// The sort order bits of the LCID are ignored.  
+/// A language-neutral LCID (e.g. `0x0009`) is converted to the language only (e.g. `en`).  
+/// Returns [`None`] if the LCID is unknown.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::lcid_to_locale;
+///
+///
+/// assert_eq!(lcid_to_locale(0x0409), Some("en-US"));
+/// assert_eq!(lcid_to_locale(0x0419), Some("ru-RU"));
+/// assert_eq!(lcid_to_locale(0x0009), Some("en"));
+/// assert_eq!(lcid_to_locale(0x7C04), Some("zh-Hant"));
+/// assert_eq!(lcid_to_locale(0xFFFF), None);
+/// ```
+pub fn lcid_to_locale(lcid: u32) -> Option<&'static str> {
+	let langid = lcid & LANGID_MASK;
+	LCIDS.iter()
+		.chain(ALTERNATIVE_LCIDS)
+		.find(|(id, _)| *id == langid)
+		.map(|(_, locale)| *locale)
+		.or_else(|| match langid & !PRIMARY_LANGUAGE_MASK {
+			0 => LCIDS.iter()
+				.find(|(id, _)| id & PRIMARY_LANGUAGE_MASK == langid)
+				.map(|(_, locale)| locale.split('-').next().unwrap()),
+			_ => None,
+		})
+}
+
+/// Converts a BCP 47 locale to a Windows locale identifier (LCID).
+///
+/// The comparison is case-insensitive.  
+/// A locale consisting of a known language only (e.g. `en`) is converted to the language-neutral
+/// LCID (e.g. `0x0009`).  
+/// Returns [`None`] if there is no LCID for the locale.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::locale_to_lcid;
+///
+///
+/// assert_eq!(locale_to_lcid("en-US"), Some(0x0409));
+/// assert_eq!(locale_to_lcid("RU-ru"), Some(0x0419));
+/// assert_eq!(locale_to_lcid("en"), Some(0x0009));
+/// assert_eq!(locale_to_lcid("en-XX"), None);
+/// ```
+pub fn locale_to_lcid(locale: &str) -> Option<u32> {
+	LCIDS.iter()
+		.find(|(_, l)| l.eq_ignore_ascii_case(locale))
+		.map(|(id, _)| *id)
+		.or_else(|| LCIDS.iter()
+			.find(|(_, l)| l.split('-').next().unwrap().eq_ignore_ascii_case(locale))
+			.map(|(id, _)| id & PRIMARY_LANGUAGE_MASK))
+}
+
+/// Parses a string containing an LCID, either hexadecimal with the `0x` prefix (e.g. `0x0409`) or
+/// decimal (e.g. `1033`), and converts it to a BCP 47 locale.
+pub(super) fn parse_lcid(input: &str) -> Option<&'static str> {
+	let lcid = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+		Some(hex) if !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit()) => u32::from_str_radix(hex, 16).ok()?,
+		None if !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit()) => input.parse().ok()?,
+		_ => return None,
+	};
+	lcid_to_locale(lcid)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_lcid_to_locale() {
+		// Specific locales
+		assert_eq!(lcid_to_locale(0x0409), Some("en-US"));
+		assert_eq!(lcid_to_locale(0x0809), Some("en-GB"));
+		assert_eq!(lcid_to_locale(0x0419), Some("ru-RU"));
+		assert_eq!(lcid_to_locale(0x0804), Some("zh-CN"));
+		assert_eq!(lcid_to_locale(0x2409), Some("en-029"));
+		assert_eq!(lcid_to_locale(0x281A), Some("sr-Cyrl-RS"));
+
+		// Alternative identifiers
+		assert_eq!(lcid_to_locale(0x040A), Some("es-ES"));
+		assert_eq!(lcid_to_locale(0x0C0A), Some("es-ES"));
+
+		// Sort order bits
+		assert_eq!(lcid_to_locale(0x10407), Some("de-DE"));
+		assert_eq!(lcid_to_locale(0x20804), Some("zh-CN"));
+
+		// Neutral locales
+		assert_eq!(lcid_to_locale(0x0009), Some("en"));
+		assert_eq!(lcid_to_locale(0x0019), Some("ru"));
+		assert_eq!(lcid_to_locale(0x0004), Some("zh-Hans"));
+		assert_eq!(lcid_to_locale(0x7C04), Some("zh-Hant"));
+		assert_eq!(lcid_to_locale(0x001A), Some("hr"));
+		assert_eq!(lcid_to_locale(0x7C1A), Some("sr"));
+		assert_eq!(lcid_to_locale(0x781A), Some("bs"));
+		assert_eq!(lcid_to_locale(0x7814), Some("nn"));
+		assert_eq!(lcid_to_locale(0x7C14), Some("nb"));
+
+		// Unknown
+		assert_eq!(lcid_to_locale(0x0000), None);
+		assert_eq!(lcid_to_locale(0x0400), None);
+		assert_eq!(lcid_to_locale(0x03FF), None);
+		assert_eq!(lcid_to_locale(0xFC09), None);
+	}
+
+	#[test]
+	fn test_locale_to_lcid() {
+		// Specific locales
+		assert_eq!(locale_to_lcid("en-US"), Some(0x0409));
+		assert_eq!(locale_to_lcid("en-gb"), Some(0x0809));
+		assert_eq!(locale_to_lcid("es-ES"), Some(0x0C0A));
+		assert_eq!(locale_to_lcid("sr-cyrl-rs"), Some(0x281A));
+
+		// Neutral locales
+		assert_eq!(locale_to_lcid("en"), Some(0x0009));
+		assert_eq!(locale_to_lcid("De"), Some(0x0007));
+		assert_eq!(locale_to_lcid("zh-Hans"), Some(0x0004));
+		assert_eq!(locale_to_lcid("hr"), Some(0x001A));
+		assert_eq!(locale_to_lcid("sr"), Some(0x7C1A));
+		assert_eq!(locale_to_lcid("bs"), Some(0x781A));
+		assert_eq!(locale_to_lcid("nn"), Some(0x7814));
+		assert_eq!(locale_to_lcid("NB"), Some(0x7C14));
+
+		// Unknown
+		assert_eq!(locale_to_lcid(""), None);
+		assert_eq!(locale_to_lcid("en-XX"), None);
+		assert_eq!(locale_to_lcid("xx"), None);
+		assert_eq!(locale_to_lcid("en_US"), None);
+
+		// Round trip
+		for (lcid, locale) in LCIDS {
+			assert_eq!(locale_to_lcid(locale), Some(*lcid));
+			assert_eq!(lcid_to_locale(*lcid), Some(*locale));
+		}
+	}
+
+	#[test]
+	fn test_parse_lcid() {
+		assert_eq!(parse_lcid("0x0409"), Some("en-US"));
+		assert_eq!(parse_lcid("0X409"), Some("en-US"));
+		assert_eq!(parse_lcid("0x0c0a"), Some("es-ES"));
+		assert_eq!(parse_lcid("1033"), Some("en-US"));
+		assert_eq!(parse_lcid("1049"), Some("ru-RU"));
+
+		assert_eq!(parse_lcid(""), None);
+		assert_eq!(parse_lcid("0x"), None);
+		assert_eq!(parse_lcid("0xZZZZ"), None);
+		assert_eq!(parse_lcid("en-US"), None);
+		assert_eq!(parse_lcid(" 1033"), None);
+		assert_eq!(parse_lcid("-1033"), None);
+		assert_eq!(parse_lcid("99999999999"), None);
+	}
+}
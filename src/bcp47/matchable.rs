@@ -0,0 +1,441 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of locale types defined outside of the crate.
+
+use language_tags::LanguageTag;
+
+use crate::matching::{confidence, score, tie_break, LocaleMatch};
+
+use super::{is_pseudo_tag, Bcp47Locale, Matcher, TagParts};
+
+/// A BCP 47 locale split into its subtags, which can be matched without serializing it to a string.
+///
+/// Applications with their own locale types can implement the trait and pass the locales to
+/// [`best_matching_matchable_locale`] directly. Only [`language`](Self::language) is required, the
+/// other subtags are absent by default.  
+/// The subtags are compared ignoring ASCII case. A locale with an empty language never matches.
+///
+/// The trait is implemented for [`LanguageTag`], [`TagParts`] and [`Bcp47Locale`], and for
+/// strings, which are split into their subtags with [`TagParts::parse`], so a malformed string has
+/// an empty language. The matching functions get all of the subtags of a locale at once with
+/// [`parts`](Self::parts), so a string is split only once.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{best_matching_matchable_locale, MatchableLocale};
+///
+///
+/// struct AppLocale {
+///     language: String,
+///     region: Option<String>,
+/// }
+///
+/// impl MatchableLocale for AppLocale {
+///     fn language(&self) -> &str {
+///         &self.language
+///     }
+///
+///     fn region(&self) -> Option<&str> {
+///         self.region.as_deref()
+///     }
+/// }
+///
+/// let locale = |language: &str, region: Option<&str>| AppLocale { language: language.to_string(), region: region.map(str::to_string) };
+///
+/// let available_locales = [locale("en", Some("US")), locale("ru", Some("BY"))];
+/// let user_locales = [locale("ru", Some("RU")), locale("en", None)];
+///
+/// let best_match = best_matching_matchable_locale(&available_locales, &user_locales).unwrap();
+///
+/// assert_eq!(best_match.region(), Some("BY"));
+///
+/// // Strings can be mixed with other locale types
+/// assert_eq!(best_matching_matchable_locale(&available_locales, ["en-GB"]).unwrap().region(), Some("US"));
+/// ```
+pub trait MatchableLocale {
+	/// Returns the primary language subtag, e.g. `zh` in `zh-cmn-Hans-CN`.
+	fn language(&self) -> &str;
+
+	/// Returns the extended language subtags, e.g. `cmn` in `zh-cmn-Hans-CN`.
+	fn extended_language(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns the script subtag, e.g. `Hans` in `zh-cmn-Hans-CN`.
+	fn script(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns the region subtag, e.g. `CN` in `zh-cmn-Hans-CN`.
+	fn region(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns the variant subtags separated by `-`, e.g. `rozaj-biske` in `sl-rozaj-biske`.
+	fn variant(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns the extension subtags, e.g. `u-nu-latn` in `ar-u-nu-latn`.
+	fn extension(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns the private use subtags with the `x` singleton, e.g. `x-foo` in `de-x-foo`.
+	fn private_use(&self) -> Option<&str> {
+		None
+	}
+
+	/// Returns all of the subtags at once.  
+	/// By default they are collected from the other methods, locale types that split a string on
+	/// each call can override it to split the string only once.
+	fn parts(&self) -> TagParts<'_> {
+		TagParts::of(self)
+	}
+}
+
+impl<T: MatchableLocale + ?Sized> MatchableLocale for &T {
+	fn language(&self) -> &str {
+		(**self).language()
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		(**self).extended_language()
+	}
+
+	fn script(&self) -> Option<&str> {
+		(**self).script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		(**self).region()
+	}
+
+	fn variant(&self) -> Option<&str> {
+		(**self).variant()
+	}
+
+	fn extension(&self) -> Option<&str> {
+		(**self).extension()
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		(**self).private_use()
+	}
+
+	fn parts(&self) -> TagParts<'_> {
+		(**self).parts()
+	}
+}
+
+impl MatchableLocale for LanguageTag {
+	fn language(&self) -> &str {
+		self.primary_language()
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		self.extended_language()
+	}
+
+	fn script(&self) -> Option<&str> {
+		self.script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.region()
+	}
+
+	fn variant(&self) -> Option<&str> {
+		self.variant()
+	}
+
+	fn extension(&self) -> Option<&str> {
+		self.extension()
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		self.private_use()
+	}
+}
+
+impl MatchableLocale for TagParts<'_> {
+	fn language(&self) -> &str {
+		TagParts::language(self)
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		TagParts::extended_language(self)
+	}
+
+	fn script(&self) -> Option<&str> {
+		TagParts::script(self)
+	}
+
+	fn region(&self) -> Option<&str> {
+		TagParts::region(self)
+	}
+
+	fn variant(&self) -> Option<&str> {
+		TagParts::variant(self)
+	}
+
+	fn extension(&self) -> Option<&str> {
+		TagParts::extension(self)
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		TagParts::private_use(self)
+	}
+
+	fn parts(&self) -> TagParts<'_> {
+		*self
+	}
+}
+
+impl MatchableLocale for str {
+	fn language(&self) -> &str {
+		self.parts().language()
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		self.parts().extended_language()
+	}
+
+	fn script(&self) -> Option<&str> {
+		self.parts().script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.parts().region()
+	}
+
+	fn variant(&self) -> Option<&str> {
+		self.parts().variant()
+	}
+
+	fn extension(&self) -> Option<&str> {
+		self.parts().extension()
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		self.parts().private_use()
+	}
+
+	fn parts(&self) -> TagParts<'_> {
+		TagParts::parse(self).unwrap_or(TagParts::MALFORMED)
+	}
+}
+
+impl MatchableLocale for String {
+	fn language(&self) -> &str {
+		self.as_str().language()
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		self.as_str().extended_language()
+	}
+
+	fn script(&self) -> Option<&str> {
+		self.as_str().script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.as_str().region()
+	}
+
+	fn variant(&self) -> Option<&str> {
+		self.as_str().variant()
+	}
+
+	fn extension(&self) -> Option<&str> {
+		self.as_str().extension()
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		self.as_str().private_use()
+	}
+
+	fn parts(&self) -> TagParts<'_> {
+		self.as_str().parts()
+	}
+}
+
+impl MatchableLocale for Bcp47Locale {
+	fn language(&self) -> &str {
+		self.as_str().language()
+	}
+
+	fn extended_language(&self) -> Option<&str> {
+		self.as_str().extended_language()
+	}
+
+	fn script(&self) -> Option<&str> {
+		self.as_str().script()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.as_str().region()
+	}
+
+	fn variant(&self) -> Option<&str> {
+		self.as_str().variant()
+	}
+
+	fn extension(&self) -> Option<&str> {
+		self.as_str().extension()
+	}
+
+	fn private_use(&self) -> Option<&str> {
+		self.as_str().private_use()
+	}
+
+	fn parts(&self) -> TagParts<'_> {
+		self.as_str().parts()
+	}
+}
+
+/// Finds the best matching locale from a list of available locales based on a list of user
+/// locales, like [`best_matching_locale`](super::best_matching_locale), for any types implementing
+/// [`MatchableLocale`].
+///
+/// The available and the user locales may be of different types. The matched available locale is
+/// returned as it was passed in.
+pub fn best_matching_matchable_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: MatchableLocale,
+	T2: MatchableLocale
+{
+	Matcher::new().best_matching_matchable_locale(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_matchable_locale`], using the
+	/// configuration of the matcher.
+	///
+	/// The scorer set with [`with_scorer`](Self::with_scorer) is not applied, since it works with
+	/// parsed language tags. Neither are [LCID inputs](Self::with_lcid_inputs) accepted.
+	pub fn best_matching_matchable_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: MatchableLocale,
+		T2: MatchableLocale
+	{
+		self.best_matching_matchable_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+	}
+
+	/// Finds the best matching locale like [`best_matching_matchable_locale`](Self::best_matching_matchable_locale),
+	/// and reports the quality of the match like
+	/// [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence).
+	pub fn best_matching_matchable_locale_with_confidence<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: MatchableLocale,
+		T2: MatchableLocale
+	{
+		let available_locales = available_locales.into_iter().collect::<Vec<T1>>();
+		// The subtags are compared with each user locale, so they are got only once
+		let available_parts = available_locales.iter()
+			.map(MatchableLocale::parts)
+			.collect::<Vec<TagParts>>();
+
+		user_locales.into_iter()
+			.enumerate()
+			.find_map(|(user_index, user_locale)| {
+				let user_parts = user_locale.parts();
+				if user_parts.language().is_empty() {
+					return None;
+				}
+				available_parts.iter()
+					.enumerate()
+					.rev() // For max_by to return the first locale with max score
+					.filter(|(_, aval_parts)| aval_parts.language().eq_ignore_ascii_case(user_parts.language()))
+					.filter(|(_, aval_parts)| !self.isolate_pseudo_locales || !is_pseudo_tag(*aval_parts) || is_pseudo_tag(&user_parts))
+					.filter_map(|(i, aval_parts)| self.comparisons(aval_parts, &user_parts).map(|c| (i, score(c, &self.scoring), c)))
+					.max_by(|(_, a, ac), (_, b, bc)| a.total_cmp(b).then_with(|| tie_break(ac, bc, self.scoring.tie_break)))
+					.map(|(i, _, c)| (i, user_index, confidence(64.0, c)))
+			})
+			.map(|(available_index, user_index, confidence)| LocaleMatch {
+				locale: available_locales.into_iter().nth(available_index).unwrap(),
+				available_index,
+				user_index,
+				confidence,
+			})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_matchable_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str]) {
+			let expected = crate::bcp47::best_matching_locale_with_confidence(available_locales.iter().copied(), user_locales)
+				.map(|m| (m.locale, m.available_index, m.user_index, m.confidence));
+			let result = best_matching_matchable_locale_with_confidence(available_locales.iter().copied(), user_locales)
+				.map(|m| (m.locale, m.available_index, m.user_index, m.confidence));
+			assert_eq!(result, expected);
+			fn parts<'a>(locales: &[&'a str]) -> Vec<TagParts<'a>> {
+				locales.iter().filter_map(|locale| TagParts::parse(locale)).collect()
+			}
+			if parts(available_locales).len() == available_locales.len() && parts(user_locales).len() == user_locales.len() {
+				let result = best_matching_matchable_locale_with_confidence(parts(available_locales), parts(user_locales))
+					.map(|m| (m.locale.language(), m.available_index, m.user_index, m.confidence));
+				assert_eq!(result.map(|(_, a, u, c)| (a, u, c)), expected.map(|(_, a, u, c)| (a, u, c)));
+			}
+		}
+
+		fn best_matching_matchable_locale_with_confidence<T1: MatchableLocale, T2: MatchableLocale>(
+			available_locales: impl IntoIterator<Item = T1>,
+			user_locales: impl IntoIterator<Item = T2>,
+		) -> Option<LocaleMatch<T1>> {
+			Matcher::new().best_matching_matchable_locale_with_confidence(available_locales, user_locales)
+		}
+
+		// Same results as for the strings
+		case(&["en-US", "en-GB", "ru-UA", "fr-FR", "it"], &["ru-RU", "ru", "en-US", "en"]);
+		case(&["en", "pt-BR", "pt-PT", "es"], &["pt", "en"]);
+		case(&["zh", "zh-cmn", "zh-cmn-Hans"], &["zh-Hans"]);
+		case(&["sl-rozaj-biske", "sl-rozaj"], &["sl-rozaj-lipaw"]);
+		case(&["de-x-foo", "de-x-bar", "de-u-nu-latn"], &["de-x-bar"]);
+		case(&["EN-us", "en-GB"], &["en-US"]);
+		case(&["en-US", "fr"], &["en_GB", "fr-CA"]);
+		case(&["en-US"], &["fr"]);
+		case(&[], &["en"]);
+
+		// Mixed types
+		let tags = ["en-US", "ru-BY"].map(|tag| LanguageTag::parse(tag).unwrap());
+		assert_eq!(best_matching_matchable_locale(&tags, ["ru"]), Some(&tags[1]));
+		let locales = ["en-US", "ru-BY"].map(|locale| locale.parse::<Bcp47Locale>().unwrap());
+		assert_eq!(best_matching_matchable_locale(&locales, [String::from("en-GB")]), Some(&locales[0]));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_MatchableLocale_for_str() {
+		assert_eq!("zh-cmn-Hans-CN".language(), "zh");
+		assert_eq!("zh-cmn-Hans-CN".extended_language(), Some("cmn"));
+		assert_eq!("zh-cmn-Hans-CN".script(), Some("Hans"));
+		assert_eq!("sl-IT-rozaj-biske".region(), Some("IT"));
+		assert_eq!("sl-IT-rozaj-biske".variant(), Some("rozaj-biske"));
+		assert_eq!("ar-u-nu-latn-x-foo".extension(), Some("u-nu-latn"));
+		assert_eq!("ar-u-nu-latn-x-foo".private_use(), Some("x-foo"));
+		assert_eq!("en_US".language(), "");
+		assert_eq!("en_US".region(), None);
+		assert_eq!("sl-IT-rozaj-biske".parts(), TagParts::parse("sl-IT-rozaj-biske").unwrap());
+		assert_eq!("en_US".parts(), TagParts::MALFORMED);
+	}
+}
\ No newline at end of file
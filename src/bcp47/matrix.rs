@@ -0,0 +1,88 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Scoring of all pairs of available and user BCP 47 locales.
+
+use crate::engine;
+use crate::matching::Matrix;
+
+use super::{Matcher, Subtag};
+
+/// Scores each of the available locales for each of the user locales like
+/// [`best_matching_locale`](super::best_matching_locale) does, without selecting a winner.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::score_matrix;
+///
+///
+/// let matrix = score_matrix(["en-US", "pt-PT", "pt-BR", "pt_BR"], ["pt-BR", "en"]);
+///
+/// assert_eq!((matrix.available_len(), matrix.user_len()), (4, 2));
+/// assert!(matrix.get(1, 0).unwrap().score < matrix.get(2, 0).unwrap().score);
+/// assert!(matrix.get(0, 0).is_none());
+/// assert!(matrix.get(0, 1).is_some());
+/// assert_eq!(matrix.malformed_available_locales(), [3]);
+/// ```
+pub fn score_matrix<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Matrix<Subtag>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().score_matrix(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Scores the locales like [`score_matrix`], using the configuration of the matcher.
+	pub fn score_matrix<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Matrix<Subtag>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::score_matrix(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_score_matrix() {
+		let available_locales = ["en-US", "en-GB", "ru_RU", "ru", "x-foo"];
+		let user_locales = ["ru-RU", "en", "de-", "en-US"];
+		let matrix = score_matrix(available_locales, user_locales);
+
+		assert_eq!((matrix.available_len(), matrix.user_len()), (5, 4));
+		assert_eq!(matrix.malformed_available_locales(), [2]);
+		assert_eq!(matrix.malformed_user_locales(), [2]);
+		let matched = |available_index: usize| matrix.row(available_index).iter().map(Option::is_some).collect::<Vec<bool>>();
+		assert_eq!(matched(0), [false, true, false, true]);
+		assert_eq!(matched(2), [false; 4]);
+		assert_eq!(matched(3), [true, false, false, false]);
+		assert_eq!(matched(4), [false; 4]);
+
+		// The same candidates and scores as in the explanation of each user locale
+		for (user_index, user_locale) in user_locales.iter().enumerate() {
+			let explanation = Matcher::new().explain(available_locales, [user_locale]);
+			for candidate in explanation.candidates {
+				let cell = matrix.get(candidate.available_index, user_index).unwrap();
+				assert_eq!((cell.score, &cell.subtags), (candidate.score, &candidate.subtags));
+			}
+		}
+	}
+}
\ No newline at end of file
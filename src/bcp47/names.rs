@@ -0,0 +1,246 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Lenient conversion of human-readable language names to BCP 47 locales.
+
+/// Language names in English and in the languages themselves, normalized with [`normalize`], and
+/// the corresponding BCP 47 locales.
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+	("arabic", "ar"),
+	("العربية", "ar"),
+	("bengali", "bn"),
+	("বাংলা", "bn"),
+	("bulgarian", "bg"),
+	("български", "bg"),
+	("catalan", "ca"),
+	("català", "ca"),
+	("chinese", "zh"),
+	("中文", "zh"),
+	("chinese simplified", "zh-Hans"),
+	("simplified chinese", "zh-Hans"),
+	("简体中文", "zh-Hans"),
+	("中文 简体", "zh-Hans"),
+	("chinese traditional", "zh-Hant"),
+	("traditional chinese", "zh-Hant"),
+	("繁體中文", "zh-Hant"),
+	("中文 繁體", "zh-Hant"),
+	("cantonese", "yue"),
+	("粵語", "yue"),
+	("croatian", "hr"),
+	("hrvatski", "hr"),
+	("czech", "cs"),
+	("čeština", "cs"),
+	("danish", "da"),
+	("dansk", "da"),
+	("dutch", "nl"),
+	("nederlands", "nl"),
+	("flemish", "nl-BE"),
+	("english", "en"),
+	("english us", "en-US"),
+	("english united states", "en-US"),
+	("american english", "en-US"),
+	("english uk", "en-GB"),
+	("english united kingdom", "en-GB"),
+	("british english", "en-GB"),
+	("estonian", "et"),
+	("eesti", "et"),
+	("filipino", "fil"),
+	("finnish", "fi"),
+	("suomi", "fi"),
+	("french", "fr"),
+	("français", "fr"),
+	("francais", "fr"),
+	("french canada", "fr-CA"),
+	("canadian french", "fr-CA"),
+	("german", "de"),
+	("deutsch", "de"),
+	("swiss german", "de-CH"),
+	("greek", "el"),
+	("ελληνικά", "el"),
+	("hebrew", "he"),
+	("עברית", "he"),
+	("hindi", "hi"),
+	("हिन्दी", "hi"),
+	("hungarian", "hu"),
+	("magyar", "hu"),
+	("indonesian", "id"),
+	("bahasa indonesia", "id"),
+	("italian", "it"),
+	("italiano", "it"),
+	("japanese", "ja"),
+	("日本語", "ja"),
+	("korean", "ko"),
+	("한국어", "ko"),
+	("latvian", "lv"),
+	("latviešu", "lv"),
+	("lithuanian", "lt"),
+	("lietuvių", "lt"),
+	("malay", "ms"),
+	("bahasa melayu", "ms"),
+	("norwegian", "no"),
+	("norsk", "no"),
+	("norwegian bokmål", "nb"),
+	("norwegian bokmal", "nb"),
+	("bokmål", "nb"),
+	("norwegian nynorsk", "nn"),
+	("nynorsk", "nn"),
+	("persian", "fa"),
+	("farsi", "fa"),
+	("فارسی", "fa"),
+	("polish", "pl"),
+	("polski", "pl"),
+	("portuguese", "pt"),
+	("português", "pt"),
+	("portugues", "pt"),
+	("portuguese brazil", "pt-BR"),
+	("brazilian portuguese", "pt-BR"),
+	("português do brasil", "pt-BR"),
+	("portuguese portugal", "pt-PT"),
+	("european portuguese", "pt-PT"),
+	("romanian", "ro"),
+	("română", "ro"),
+	("russian", "ru"),
+	("русский", "ru"),
+	("serbian", "sr"),
+	("српски", "sr-Cyrl"),
+	("srpski", "sr-Latn"),
+	("slovak", "sk"),
+	("slovenčina", "sk"),
+	("slovenian", "sl"),
+	("slovene", "sl"),
+	("slovenščina", "sl"),
+	("spanish", "es"),
+	("español", "es"),
+	("espanol", "es"),
+	("castilian", "es-ES"),
+	("castellano", "es"),
+	("spanish spain", "es-ES"),
+	("spanish latin america", "es-419"),
+	("latin american spanish", "es-419"),
+	("spanish mexico", "es-MX"),
+	("mexican spanish", "es-MX"),
+	("swedish", "sv"),
+	("svenska", "sv"),
+	("tamil", "ta"),
+	("தமிழ்", "ta"),
+	("thai", "th"),
+	("ไทย", "th"),
+	("turkish", "tr"),
+	("türkçe", "tr"),
+	("ukrainian", "uk"),
+	("українська", "uk"),
+	("urdu", "ur"),
+	("اردو", "ur"),
+	("vietnamese", "vi"),
+	("tiếng việt", "vi"),
+];
+
+/// Converts a human-readable language name, in English or in the language itself, to a BCP 47
+/// locale, e.g. `german` or `Deutsch` to `de` and `Chinese (Simplified)` to `zh-Hans`.
+///
+/// The comparison ignores case, punctuation and extra whitespace, so `chinese (simplified)`,
+/// `Chinese, Simplified` and `CHINESE SIMPLIFIED` are the same name.  
+/// Returns [`None`] if the name is unknown. Locale codes are not names, so `de` is unknown too.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::language_name_to_locale;
+///
+///
+/// assert_eq!(language_name_to_locale("german"), Some("de"));
+/// assert_eq!(language_name_to_locale("Deutsch"), Some("de"));
+/// assert_eq!(language_name_to_locale("chinese (simplified)"), Some("zh-Hans"));
+/// assert_eq!(language_name_to_locale("Klingon"), None);
+/// ```
+pub fn language_name_to_locale(name: &str) -> Option<&'static str> {
+	let name = normalize(name);
+	LANGUAGE_NAMES.iter()
+		.find(|(language_name, _)| *language_name == name)
+		.map(|(_, locale)| *locale)
+}
+
+/// Normalizes a language name for comparing names, i.e. lowercases it and replaces punctuation
+/// and whitespace between words with single spaces, e.g. `Chinese (Simplified)` to
+/// `chinese simplified`.
+fn normalize(name: &str) -> String {
+	name.split(|c: char| !c.is_alphanumeric() && !is_combining_mark(c))
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+		.collect::<Vec<String>>()
+		.join(" ")
+}
+
+/// Whether a character is a combining mark of the scripts in the language names, e.g. the vowel
+/// signs in `हिन्दी` and `தமிழ்`, which are not alphanumeric.
+fn is_combining_mark(c: char) -> bool {
+	matches!(c, '\u{0300}'..='\u{036F}' | '\u{0591}'..='\u{05C7}' | '\u{0610}'..='\u{061A}' | '\u{064B}'..='\u{065F}' | '\u{0900}'..='\u{0DFF}' | '\u{0E31}' | '\u{0E34}'..='\u{0E3A}' | '\u{0E47}'..='\u{0E4E}')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_language_name_to_locale() {
+		fn case(name: &str, expected: Option<&str>) {
+			assert_eq!(language_name_to_locale(name), expected, "{name:?}");
+		}
+
+		// English names
+		case("german", Some("de"));
+		case("German", Some("de"));
+		case("  ENGLISH ", Some("en"));
+		case("norwegian bokmål", Some("nb"));
+
+		// Native names
+		case("Deutsch", Some("de"));
+		case("Français", Some("fr"));
+		case("русский", Some("ru"));
+		case("日本語", Some("ja"));
+		case("हिन्दी", Some("hi"));
+		case("தமிழ்", Some("ta"));
+		case("ไทย", Some("th"));
+		case("العربية", Some("ar"));
+
+		// Names with scripts and regions
+		case("chinese (simplified)", Some("zh-Hans"));
+		case("Chinese, Traditional", Some("zh-Hant"));
+		case("Simplified Chinese", Some("zh-Hans"));
+		case("中文（简体）", Some("zh-Hans"));
+		case("English (US)", Some("en-US"));
+		case("english - united kingdom", Some("en-GB"));
+		case("Portuguese (Brazil)", Some("pt-BR"));
+		case("Spanish (Latin America)", Some("es-419"));
+
+		// Unknown names and codes
+		case("Klingon", None);
+		case("chinese (cantonese)", None);
+		case("de", None);
+		case("en-US", None);
+		case("", None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_LANGUAGE_NAMES() {
+		for &(name, locale) in LANGUAGE_NAMES {
+			assert_eq!(normalize(name), name, "{name:?}");
+			assert!(language_tags::LanguageTag::parse(locale).is_ok(), "{locale:?}");
+			assert_eq!(LANGUAGE_NAMES.iter().filter(|(other, _)| *other == name).count(), 1, "{name:?}");
+		}
+	}
+}
\ No newline at end of file
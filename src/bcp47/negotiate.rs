@@ -0,0 +1,164 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Negotiation of language lists compatible with `fluent-langneg`.
+
+use crate::engine::{self, Format, Locale};
+use crate::matching::tie_break;
+
+use super::Matcher;
+
+/// The strategy of [`negotiate_languages`], mirroring `fluent_langneg::NegotiationStrategy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum NegotiationStrategy {
+	/// Returns all available locales matching any of the requested locales: for each requested
+	/// locale, all of its matches ordered from the best one.  
+	/// Suitable for building a fallback chain of translations.
+	Filtering,
+	/// Returns the best available locale for each requested locale. A locale already returned for
+	/// a more preferred requested locale is not considered again.
+	Matching,
+	/// Returns only the single best match for the most preferred requested locale that has one.
+	Lookup,
+}
+
+/// Negotiates the available locales for the requested locales with the given strategy, like
+/// `fluent_langneg::negotiate_languages`.
+///
+/// Locales are matched with the scoring of [`best_matching_locale`](super::best_matching_locale),
+/// except that an available locale equal to the requested locale, ignoring case, comes first, like
+/// in `fluent-langneg`; malformed locales are ignored.  
+/// The default locale is appended to the result if it is not already there, except for the
+/// [`Lookup`](NegotiationStrategy::Lookup) strategy, where it is returned only if nothing matches.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{negotiate_languages, NegotiationStrategy};
+///
+///
+/// let requested = ["de-AT", "fr"];
+/// let available = ["en-US", "de-DE", "de", "fr-CA", "fr-FR"];
+///
+/// let filtering = negotiate_languages(&requested, &available, Some(&"en-US"), NegotiationStrategy::Filtering);
+/// assert_eq!(filtering, [&"de-DE", &"de", &"fr-CA", &"fr-FR", &"en-US"]);
+///
+/// let matching = negotiate_languages(&requested, &available, Some(&"en-US"), NegotiationStrategy::Matching);
+/// assert_eq!(matching, [&"de-DE", &"fr-CA", &"en-US"]);
+///
+/// let lookup = negotiate_languages(&requested, &available, Some(&"en-US"), NegotiationStrategy::Lookup);
+/// assert_eq!(lookup, [&"de-DE"]);
+/// ```
+pub fn negotiate_languages<'a, R, A>(requested: &[R], available: &'a [A], default: Option<&'a A>, strategy: NegotiationStrategy) -> Vec<&'a A>
+where
+	R: AsRef<str>,
+	A: AsRef<str>,
+{
+	Matcher::new().negotiate_languages(requested, available, default, strategy)
+}
+
+impl Matcher {
+	/// Negotiates the available locales like [`negotiate_languages`], using the configuration of the
+	/// matcher.
+	pub fn negotiate_languages<'a, R, A>(&self, requested: &[R], available: &'a [A], default: Option<&'a A>, strategy: NegotiationStrategy) -> Vec<&'a A>
+	where
+		R: AsRef<str>,
+		A: AsRef<str>,
+	{
+		let available_tags = engine::parse_available(self, available).collect::<Vec<_>>();
+
+		let mut supported = Vec::<usize>::new();
+		for user_locale in requested.iter().filter_map(|l| self.parse_locale(l).ok()) {
+			let mut candidates = engine::candidates(self, &available_tags, &user_locale)
+				.filter(|(i, _, _)| !supported.contains(i))
+				.collect::<Vec<_>>();
+			let exact = |i: usize| available_tags[i].1.as_str().eq_ignore_ascii_case(user_locale.as_str());
+			let rank = |i: usize| self.rank(&available_tags[i].1, &user_locale);
+			candidates.sort_by(|(i, a, ac), (j, b, bc)| exact(*j).cmp(&exact(*i))
+				.then_with(|| b.total_cmp(a))
+				.then_with(|| tie_break(bc, ac, self.scoring.tie_break))
+				.then_with(|| rank(*i).cmp(&rank(*j))));
+			match strategy {
+				NegotiationStrategy::Filtering => supported.extend(candidates.into_iter().map(|(i, _, _)| i)),
+				NegotiationStrategy::Matching => supported.extend(candidates.first().map(|(i, _, _)| *i)),
+				NegotiationStrategy::Lookup => if let Some((i, _, _)) = candidates.first() {
+					supported.push(*i);
+					break;
+				},
+			}
+		}
+
+		let mut result = supported.into_iter()
+			.map(|i| available_tags[i].1.locale)
+			.collect::<Vec<&A>>();
+		if let Some(default) = default {
+			let missing = !result.iter().any(|l| l.as_ref() == default.as_ref());
+			if missing && (strategy != NegotiationStrategy::Lookup || result.is_empty()) {
+				result.push(default);
+			}
+		}
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_negotiate_languages() {
+		fn case(requested: &[&str], available: &[&str], default: Option<&str>, strategy: NegotiationStrategy, expected: &[&str]) {
+			let result = negotiate_languages(requested, available, default.as_ref(), strategy);
+			assert_eq!(result.into_iter().copied().collect::<Vec<&str>>(), expected);
+		}
+
+		use NegotiationStrategy::*;
+
+		let available = ["en-US", "de-DE", "de", "fr-CA", "fr-FR", "it"];
+
+		// Filtering
+		case(&["de-AT", "fr"], &available, None, Filtering, &["de-DE", "de", "fr-CA", "fr-FR"]);
+		case(&["de", "de-DE"], &available, None, Filtering, &["de", "de-DE"]);
+		case(&["fr-FR", "fr"], &available, None, Filtering, &["fr-FR", "fr-CA"]);
+		case(&["es"], &available, None, Filtering, &[]);
+
+		// Matching
+		case(&["de-AT", "fr"], &available, None, Matching, &["de-DE", "fr-CA"]);
+		case(&["de-DE", "de-AT"], &available, None, Matching, &["de-DE", "de"]);
+		case(&["es", "it-IT"], &available, None, Matching, &["it"]);
+
+		// Lookup
+		case(&["es", "fr-FR", "de"], &available, None, Lookup, &["fr-FR"]);
+		case(&["es"], &available, None, Lookup, &[]);
+
+		// Default locale
+		case(&["de-AT"], &available, Some("en-US"), Filtering, &["de-DE", "de", "en-US"]);
+		case(&["en"], &available, Some("en-US"), Matching, &["en-US"]);
+		case(&["de"], &available, Some("en-US"), Lookup, &["de"]);
+		case(&["es"], &available, Some("en-US"), Lookup, &["en-US"]);
+
+		// Exact matches first, ignoring case
+		case(&["de"], &["de-DE", "de"], None, Filtering, &["de", "de-DE"]);
+		case(&["de"], &["de-DE", "de"], None, Matching, &["de"]);
+		case(&["de"], &["de-DE", "de"], None, Lookup, &["de"]);
+		case(&["DE-de"], &["de", "de-DE"], None, Lookup, &["de-DE"]);
+
+		// Malformed locales
+		case(&["de_AT", "fr"], &["de_DE", "fr-FR"], None, Filtering, &["fr-FR"]);
+	}
+}
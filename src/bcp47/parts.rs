@@ -0,0 +1,338 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Splitting of BCP 47 locales into their subtags in const context.
+
+use super::MatchableLocale;
+
+/// The subtags of a well-formed BCP 47 locale, as slices of the locale.
+///
+/// Unlike [`LanguageTag`](language_tags::LanguageTag), the locale is split without allocating
+/// memory and without changing the case of the subtags, and the splitting is available in const
+/// context, so tables of locales can be validated at compile time.  
+/// Each of the multi-valued parts, e.g. the variants, is a single slice with the subtags separated
+/// by `-`.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::TagParts;
+///
+///
+/// const AVAILABLE_LOCALES: [&str; 3] = ["en-US", "de-DE", "sr-Latn-RS"];
+///
+/// // Fails to compile if any of the locales is malformed
+/// const _: () = assert!(TagParts::all_well_formed(&AVAILABLE_LOCALES));
+///
+/// const SERBIAN: TagParts = match TagParts::parse("sr-Latn-RS") {
+///     Some(parts) => parts,
+///     None => panic!("malformed locale"),
+/// };
+///
+/// assert_eq!(SERBIAN.language(), "sr");
+/// assert_eq!(SERBIAN.script(), Some("Latn"));
+/// assert_eq!(SERBIAN.region(), Some("RS"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TagParts<'a> {
+	language: &'a str,
+	extended_language: Option<&'a str>,
+	script: Option<&'a str>,
+	region: Option<&'a str>,
+	variant: Option<&'a str>,
+	extension: Option<&'a str>,
+	private_use: Option<&'a str>,
+}
+
+/// The position of the next subtag in a locale, following the grammar of
+/// [RFC 5646](https://www.rfc-editor.org/rfc/rfc5646#section-2.1).
+#[derive(Clone, Copy)]
+enum State {
+	AfterLanguage,
+	AfterExtendedLanguage,
+	AfterScript,
+	AfterRegion,
+	InExtension { expected: bool },
+	InPrivateUse { expected: bool },
+}
+
+impl<'a> TagParts<'a> {
+	/// Splits a locale into its subtags.  
+	/// Returns [`None`] if the locale is not well-formed or is an irregular grandfathered tag, e.g.
+	/// `i-klingon`.
+	pub const fn parse(locale: &'a str) -> Option<Self> {
+		let bytes = locale.as_bytes();
+		if bytes.len() > 2 && (bytes[0] == b'x' || bytes[0] == b'X') && bytes[1] == b'-' {
+			let mut start = 2;
+			while start <= bytes.len() {
+				let end = subtag_end(bytes, start);
+				if end == start || end - start > 8 || !all_alphanumeric(bytes, start, end) {
+					return None;
+				}
+				start = end + 1;
+			}
+			return Some(Self { language: locale, extended_language: None, script: None, region: None, variant: None, extension: None, private_use: None });
+		}
+
+		let mut state = State::AfterLanguage;
+		// The ends of the parts, each of which is also the start of the next part
+		let mut ends = [0; 6];
+		let mut extended_languages = 0;
+		let mut start = 0;
+		while start <= bytes.len() {
+			let end = subtag_end(bytes, start);
+			let len = end - start;
+			let alpha = all_alphabetic(bytes, start, end);
+			let digit = len > 0 && bytes[start].is_ascii_digit();
+			if len == 0 || len > 8 || !all_alphanumeric(bytes, start, end) {
+				return None;
+			}
+			if start == 0 {
+				if len < 2 || !alpha {
+					return None;
+				}
+				fill_from(&mut ends, 0, end);
+				state = if len < 4 { State::AfterLanguage } else { State::AfterExtendedLanguage };
+			} else if let State::InPrivateUse { .. } = state {
+				state = State::InPrivateUse { expected: false };
+			} else if len == 1 && (bytes[start] == b'x' || bytes[start] == b'X') {
+				if let State::InExtension { expected: true } = state {
+					return None;
+				}
+				state = State::InPrivateUse { expected: true };
+			} else if len == 1 {
+				if let State::InExtension { expected: true } = state {
+					return None;
+				}
+				state = State::InExtension { expected: true };
+			} else if let State::InExtension { .. } = state {
+				ends[5] = end;
+				state = State::InExtension { expected: false };
+			} else if matches!(state, State::AfterLanguage) && len == 3 && alpha {
+				extended_languages += 1;
+				if extended_languages > 3 {
+					return None;
+				}
+				fill_from(&mut ends, 1, end);
+			} else if matches!(state, State::AfterLanguage | State::AfterExtendedLanguage) && len == 4 && alpha {
+				fill_from(&mut ends, 2, end);
+				state = State::AfterScript;
+			} else if matches!(state, State::AfterLanguage | State::AfterExtendedLanguage | State::AfterScript)
+				&& (len == 2 && alpha || len == 3 && all_digits(bytes, start, end))
+			{
+				fill_from(&mut ends, 3, end);
+				state = State::AfterRegion;
+			} else if len >= 5 || len == 4 && digit {
+				fill_from(&mut ends, 4, end);
+				state = State::AfterRegion;
+			} else {
+				return None;
+			}
+			start = end + 1;
+		}
+		if let State::InExtension { expected: true } | State::InPrivateUse { expected: true } = state {
+			return None;
+		}
+
+		let [language_end, extended_language_end, script_end, region_end, variant_end, extension_end] = ends;
+		Some(Self {
+			language: locale.split_at(language_end).0,
+			extended_language: span(locale, language_end + 1, extended_language_end),
+			script: span(locale, extended_language_end + 1, script_end),
+			region: span(locale, script_end + 1, region_end),
+			variant: span(locale, region_end + 1, variant_end),
+			extension: span(locale, variant_end + 1, extension_end),
+			private_use: span(locale, extension_end + 1, locale.len()),
+		})
+	}
+
+	/// Splits a locale given as bytes into its subtags like [`TagParts::parse`], e.g. from a network
+	/// buffer.  
+	/// Returns [`None`] if the locale is not valid UTF-8 or is not well-formed.
+	pub const fn parse_bytes(locale: &'a [u8]) -> Option<Self> {
+		match std::str::from_utf8(locale) {
+			Ok(locale) => Self::parse(locale),
+			Err(_) => None,
+		}
+	}
+
+	/// Checks whether all of the locales are well-formed, i.e. [`TagParts::parse`] succeeds for
+	/// each of them.
+	pub const fn all_well_formed(locales: &[&str]) -> bool {
+		let mut i = 0;
+		while i < locales.len() {
+			if TagParts::parse(locales[i]).is_none() {
+				return false;
+			}
+			i += 1;
+		}
+		true
+	}
+
+	/// The parts of a malformed locale, which has an empty language, so it never matches.
+	pub(super) const MALFORMED: TagParts<'static> = TagParts { language: "", extended_language: None, script: None, region: None, variant: None, extension: None, private_use: None };
+
+	/// Collects the subtags of a [`MatchableLocale`] returned by its accessors.
+	pub(super) fn of(locale: &'a (impl MatchableLocale + ?Sized)) -> Self {
+		Self {
+			language: locale.language(),
+			extended_language: locale.extended_language(),
+			script: locale.script(),
+			region: locale.region(),
+			variant: locale.variant(),
+			extension: locale.extension(),
+			private_use: locale.private_use(),
+		}
+	}
+
+	/// Returns the primary language subtag, e.g. `zh` in `zh-cmn-Hans-CN`.  
+	/// For a private use locale, e.g. `x-whatever`, the whole locale is returned.
+	pub const fn language(&self) -> &'a str {
+		self.language
+	}
+
+	/// Returns the extended language subtags, e.g. `cmn` in `zh-cmn-Hans-CN`.
+	pub const fn extended_language(&self) -> Option<&'a str> {
+		self.extended_language
+	}
+
+	/// Returns the script subtag, e.g. `Hans` in `zh-cmn-Hans-CN`.
+	pub const fn script(&self) -> Option<&'a str> {
+		self.script
+	}
+
+	/// Returns the region subtag, e.g. `CN` in `zh-cmn-Hans-CN`.
+	pub const fn region(&self) -> Option<&'a str> {
+		self.region
+	}
+
+	/// Returns the variant subtags, e.g. `rozaj-biske` in `sl-rozaj-biske`.
+	pub const fn variant(&self) -> Option<&'a str> {
+		self.variant
+	}
+
+	/// Returns the extension subtags, e.g. `u-nu-latn` in `ar-u-nu-latn`.
+	pub const fn extension(&self) -> Option<&'a str> {
+		self.extension
+	}
+
+	/// Returns the private use subtags, e.g. `x-foo` in `de-x-foo`.
+	pub const fn private_use(&self) -> Option<&'a str> {
+		self.private_use
+	}
+}
+
+/// The end of the subtag starting at `start`, i.e. the position of the next `-` or the end of the
+/// locale.
+const fn subtag_end(bytes: &[u8], start: usize) -> usize {
+	let mut end = start;
+	while end < bytes.len() && bytes[end] != b'-' {
+		end += 1;
+	}
+	end
+}
+
+const fn all_alphabetic(bytes: &[u8], start: usize, end: usize) -> bool {
+	let mut i = start;
+	while i < end {
+		if !bytes[i].is_ascii_alphabetic() {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+const fn all_alphanumeric(bytes: &[u8], start: usize, end: usize) -> bool {
+	let mut i = start;
+	while i < end {
+		if !bytes[i].is_ascii_alphanumeric() {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+const fn all_digits(bytes: &[u8], start: usize, end: usize) -> bool {
+	let mut i = start;
+	while i < end {
+		if !bytes[i].is_ascii_digit() {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+/// Sets the ends of the part at `from` and of all following parts, which are still empty.
+const fn fill_from(ends: &mut [usize; 6], from: usize, end: usize) {
+	let mut i = from;
+	while i < ends.len() {
+		ends[i] = end;
+		i += 1;
+	}
+}
+
+/// The slice of the locale between the positions, or [`None`] if it is empty.
+const fn span(locale: &str, start: usize, end: usize) -> Option<&str> {
+	match start < end {
+		true => Some(locale.split_at(end).0.split_at(start).1),
+		false => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_TagParts() {
+		let tag = TagParts::parse("zh-cmn-Hans-CN-variant1-u-co-pinyin-x-private").unwrap();
+		assert_eq!(tag.language(), "zh");
+		assert_eq!(tag.extended_language(), Some("cmn"));
+		assert_eq!(tag.script(), Some("Hans"));
+		assert_eq!(tag.region(), Some("CN"));
+		assert_eq!(tag.variant(), Some("variant1"));
+		assert_eq!(tag.extension(), Some("u-co-pinyin"));
+		assert_eq!(tag.private_use(), Some("x-private"));
+
+		let tag = TagParts::parse("sl-rozaj-biske-1994").unwrap();
+		assert_eq!((tag.region(), tag.variant()), (None, Some("rozaj-biske-1994")));
+
+		let tag = TagParts::parse("de-CH-1606nict-1996").unwrap();
+		assert_eq!((tag.region(), tag.variant()), (Some("CH"), Some("1606nict-1996")));
+		assert_eq!(TagParts::parse("en-GB-1694acad").unwrap().variant(), Some("1694acad"));
+
+		assert_eq!(TagParts::parse("de-x-foo").unwrap().private_use(), Some("x-foo"));
+		assert_eq!(TagParts::parse("en-u-ca-gregory").unwrap().extension(), Some("u-ca-gregory"));
+		assert_eq!(TagParts::parse("i-klingon"), None);
+		assert_eq!(TagParts::parse("en-US-u"), None);
+		assert_eq!(TagParts::parse("en-u-x-foo"), None);
+
+		assert_eq!(TagParts::parse_bytes(b"sr-Latn-RS"), TagParts::parse("sr-Latn-RS"));
+		assert_eq!(TagParts::parse_bytes(b"sr-\xFF"), None);
+		assert_eq!(TagParts::parse_bytes(b"sr_RS"), None);
+	}
+
+	#[test]
+	fn test_all_well_formed() {
+		const { assert!(TagParts::all_well_formed(&["en-US", "zh-Hans-CN", "x-foo"])) };
+		const { assert!(!TagParts::all_well_formed(&["en-US", "en_GB"])) };
+		assert!(TagParts::all_well_formed(&[]));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Ordering of language pickers by relevance to the user.
+
+use super::Matcher;
+
+/// Reorders the available locales for a language picker, e.g. a settings dropdown, so that the
+/// locales most relevant to the user come first.
+///
+/// The locales matching the user locales come first, in the order of [`matches_iter`](super::matches_iter),
+/// i.e. from the best match to the worst. The other locales, including the malformed ones, follow
+/// in their original order.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::sort_by_relevance;
+///
+///
+/// let supported_locales = ["ar", "de-DE", "en-GB", "en-US", "fr-CA", "fr-FR", "ja"];
+///
+/// let picker = sort_by_relevance(supported_locales, ["fr-FR", "en-US"]);
+///
+/// assert_eq!(picker, ["fr-FR", "fr-CA", "en-US", "en-GB", "ar", "de-DE", "ja"]);
+/// ```
+pub fn sort_by_relevance<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().sort_by_relevance(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Reorders the available locales like [`sort_by_relevance`], using the configuration of the
+	/// matcher.
+	pub fn sort_by_relevance<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		let mut locales = available_locales.into_iter().map(Some).collect::<Vec<Option<T1>>>();
+		let matched = self.matches_iter(locales.iter().map(|l| l.as_ref().unwrap().as_ref()), user_locales)
+			.map(|m| m.available_index)
+			.collect::<Vec<usize>>();
+		let mut sorted = matched.iter()
+			.map(|&i| locales[i].take().unwrap())
+			.collect::<Vec<T1>>();
+		sorted.extend(locales.into_iter().flatten());
+		sorted
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_sort_by_relevance() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: &[&str]) {
+			let sorted = sort_by_relevance(available_locales, user_locales);
+			assert_eq!(sorted.into_iter().copied().collect::<Vec<_>>(), expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Matching locales first
+		case(&["de", "en-GB", "en-US", "ru"], &["en-US"], &["en-US", "en-GB", "de", "ru"]);
+		case(&["de", "en-GB", "en-US", "ru"], &["ru", "en"], &["ru", "en-GB", "en-US", "de"]);
+		case(&["zh-Hans", "zh-Hant", "en"], &["zh-TW"], &["zh-Hans", "zh-Hant", "en"]);
+		case(&["zh-Hans", "zh-Hant", "en"], &["zh-Hant-TW"], &["zh-Hant", "zh-Hans", "en"]);
+
+		// No matches and malformed locales
+		case(&["de", "en", "ru"], &["fr"], &["de", "en", "ru"]);
+		case(&["de", "en_US", "en"], &["en-US"], &["en", "de", "en_US"]);
+		case(&["de", "en"], &[], &["de", "en"]);
+		case(&[], &["en"], &[]);
+	}
+}
\ No newline at end of file
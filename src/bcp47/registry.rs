@@ -0,0 +1,478 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Validation and matching of BCP 47 locales with the data of the [IANA Language Subtag Registry](https://www.iana.org/assignments/language-subtag-registry).
+//!
+//! The registry is embedded in the [`language_tags`](https://docs.rs/language-tags) crate, and is
+//! only linked into the binary with the `iana` feature.
+
+mod data;
+
+use language_tags::{LanguageTag, ValidationError};
+
+use crate::matching::{lookup, LocaleKind, Warning};
+
+use super::{Bcp47Locale, MatchableLocale, Matcher};
+
+/// A deprecated subtag of a locale, or a deprecated locale, with its replacement, i.e. its
+/// Preferred-Value in the IANA Language Subtag Registry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Deprecation {
+	/// The deprecated subtag, e.g. `iw` in `iw-IL`, or the whole deprecated locale, e.g. `i-klingon`.
+	pub deprecated: String,
+	/// The replacement, e.g. `he` or `tlh`.
+	pub replacement: String,
+}
+
+impl Bcp47Locale {
+	/// Checks whether all subtags of the locale, i.e. the language, the extended language, the
+	/// script, the region and the variants, are in the IANA Language Subtag Registry, and the
+	/// locale has no duplicate variants or extensions.
+	///
+	/// The reserved private use subtags, e.g. `qaa` or `XA`, are valid.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the locale is well-formed, but is not valid.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use language_tags::ValidationError;
+	/// use locale_match::bcp47::Bcp47Locale;
+	///
+	///
+	/// assert_eq!(Bcp47Locale::parse("sr-Latn-RS").unwrap().validate(), Ok(()));
+	/// assert_eq!(Bcp47Locale::parse("en-UK").unwrap().validate(), Err(ValidationError::RegionNotInRegistry));
+	/// assert_eq!(Bcp47Locale::parse("xx").unwrap().validate(), Err(ValidationError::PrimaryLanguageNotInRegistry));
+	/// ```
+	pub fn validate(&self) -> Result<(), ValidationError> {
+		// The locale was parsed on creation
+		LanguageTag::parse(self.as_str()).map_or(Ok(()), |tag| tag.validate())
+	}
+
+	/// Returns the deprecated subtags of the locale with their replacements, or the locale itself if
+	/// it is a deprecated grandfathered or redundant tag, e.g. `i-klingon` or `zh-guoyu`.
+	///
+	/// The deprecated subtags still match like any other subtags, unless the matcher canonicalizes
+	/// the locales (see [`Matcher::with_canonicalization`]).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::{Bcp47Locale, Deprecation};
+	///
+	///
+	/// let locale = Bcp47Locale::parse("iw-DD").unwrap();
+	///
+	/// assert_eq!(locale.deprecations(), [
+	///     Deprecation { deprecated: "iw".to_string(), replacement: "he".to_string() },
+	///     Deprecation { deprecated: "DD".to_string(), replacement: "DE".to_string() },
+	/// ]);
+	/// assert_eq!(locale.replace_deprecated().as_str(), "he-DE");
+	/// ```
+	pub fn deprecations(&self) -> Vec<Deprecation> {
+		deprecations(self.as_str())
+	}
+
+	/// Returns the locale with its deprecated subtags replaced, or the replacement of the locale
+	/// if it is deprecated itself, e.g. `he-IL` for `iw-IL` and `tlh` for `i-klingon`.
+	///
+	/// Unlike [`Matcher::with_canonicalization`], only the deprecated parts are replaced, and the
+	/// case and the order of the other subtags are kept.
+	pub fn replace_deprecated(&self) -> Bcp47Locale {
+		// The replacements are well-formed subtags or tags
+		Bcp47Locale(replace_deprecated(self.as_str()))
+	}
+}
+
+impl Matcher {
+	/// Sets whether locales with subtags missing from the IANA Language Subtag Registry, e.g.
+	/// `en-UK` or `xx`, are treated like malformed locales.
+	///
+	/// By default, only the structure of locales is checked. With this option enabled, invalid
+	/// locales (see [`Bcp47Locale::validate`]) are ignored during matching and are reported by
+	/// [`best_matching_locale_with_warnings`](Self::best_matching_locale_with_warnings) and
+	/// [`explain`](Self::explain).  
+	/// [`try_best_matching_locale`](Self::try_best_matching_locale) still fails only for
+	/// malformed locales, and ignores the invalid ones.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_registry_validation(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-UK", "en-US"], ["en"]), Some("en-US"));
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "xx"], ["xx"]), None);
+	/// ```
+	pub fn with_registry_validation(mut self, registry_validation: bool) -> Self {
+		self.registry_validation = registry_validation;
+		self
+	}
+
+	/// Sets whether a locale without a script is treated as having the script implied by its
+	/// language, i.e. the Suppress-Script of the language in the IANA Language Subtag Registry, when
+	/// it is compared with a locale with a script.
+	///
+	/// By default, a missing script is compared like other missing subtags (see
+	/// [`Matcher::with_missing_subtag`]), so `en` does not earn the script score against `en-Latn`,
+	/// and does not lose it against `en-Cyrl`. With this option enabled, `en` is compared as
+	/// `en-Latn`, and `ru` as `ru-Cyrl`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_implicit_scripts(true);
+	///
+	/// // "ru" is compared as "ru-Cyrl"
+	/// assert_eq!(matcher.best_matching_locale(["ru-Latn", "ru"], ["ru-Cyrl-RU"]), Some("ru"));
+	/// assert_eq!(Matcher::new().best_matching_locale(["ru-Latn", "ru"], ["ru-Cyrl-RU"]), Some("ru-Latn"));
+	/// ```
+	pub fn with_implicit_scripts(mut self, implicit_scripts: bool) -> Self {
+		self.implicit_scripts = implicit_scripts;
+		self
+	}
+
+	/// Sets whether locales are canonicalized with the IANA Language Subtag Registry before
+	/// matching, following [RFC 5646 4.5](https://www.rfc-editor.org/rfc/rfc5646#section-4.5).
+	///
+	/// The deprecated and redundant subtags and tags are replaced by their Preferred-Value, e.g.
+	/// `iw` by `he`, `BU` by `MM`, `zh-yue` by `yue` and `i-klingon` by `tlh`, so legacy
+	/// inventories match modern preferences and vice versa. The scripts implied by the
+	/// languages are removed, e.g. `en-Latn-US` becomes `en-US`, and extensions are sorted.  
+	/// The returned locale is still the original input, e.g. `iw-IL`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_canonicalization(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "iw-IL"], ["he-IL"]), Some("iw-IL"));
+	/// assert_eq!(matcher.best_matching_locale(["en-US", "he-IL"], ["iw"]), Some("he-IL"));
+	/// assert_eq!(matcher.best_matching_locale(["my", "my-MM"], ["my-BU"]), Some("my-MM"));
+	/// ```
+	pub fn with_canonicalization(mut self, canonicalization: bool) -> Self {
+		self.canonicalization = canonicalization;
+		self
+	}
+}
+
+/// Returns the canonical form of a language tag, or the tag itself if it has no canonical form,
+/// e.g. with several extended languages.
+pub(super) fn canonicalize(tag: LanguageTag) -> LanguageTag {
+	tag.canonicalize().unwrap_or(tag)
+}
+
+/// Returns whether an extended language can follow a primary language, i.e. whether the primary
+/// language is the Prefix of the extended language in the registry.
+pub(super) fn is_extlang_prefix(extlang: &str, language: &str) -> bool {
+	lookup(&data::EXTLANG_PREFIXES, extlang).is_some_and(|prefix| prefix.eq_ignore_ascii_case(language))
+}
+
+/// Returns a warning about a well-formed locale with deprecated subtags, suggesting the locale with
+/// the subtags replaced, or [`None`] if the locale has no deprecated subtags.
+pub(super) fn deprecation_warning(kind: LocaleKind, index: usize, locale: &str) -> Option<Warning> {
+	let deprecations = deprecations(locale);
+	if deprecations.is_empty() {
+		return None;
+	}
+	let reason = deprecations.iter()
+		.map(|d| format!("{:?} is deprecated, use {:?}", d.deprecated, d.replacement))
+		.collect::<Vec<_>>()
+		.join(", ");
+	Some(Warning { kind, index, input: locale.to_string(), reason, replacement: Some(replace_deprecated(locale)) })
+}
+
+fn deprecations(locale: &str) -> Vec<Deprecation> {
+	if let Some(replacement) = lookup(&data::TAG_PREFERRED_VALUES, locale) {
+		return vec![Deprecation { deprecated: locale.to_string(), replacement: replacement.to_string() }];
+	}
+	locale.split('-')
+		.enumerate()
+		.take_while(|(_, subtag)| subtag.len() > 1)
+		.filter_map(|(i, subtag)| preferred_value(i, subtag).map(|replacement| Deprecation {
+			deprecated: subtag.to_string(),
+			replacement: replacement.to_string(),
+		}))
+		.collect()
+}
+
+fn replace_deprecated(locale: &str) -> String {
+	if let Some(replacement) = lookup(&data::TAG_PREFERRED_VALUES, locale) {
+		return replacement.to_string();
+	}
+	let mut in_extensions = false;
+	locale.split('-')
+		.enumerate()
+		.map(|(i, subtag)| {
+			// The subtags of the extensions and the private use are not registered
+			in_extensions |= subtag.len() == 1;
+			match in_extensions {
+				true => subtag,
+				false => preferred_value(i, subtag).unwrap_or(subtag),
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("-")
+}
+
+/// Returns the Preferred-Value of a deprecated subtag at the given position, before the extensions.  
+/// The language is the first subtag, and the deprecated scripts, regions and variants cannot be
+/// confused with other subtags.
+fn preferred_value(position: usize, subtag: &str) -> Option<&'static str> {
+	match position {
+		0 => lookup(&data::LANGUAGE_PREFERRED_VALUES, subtag),
+		_ => lookup(&data::SCRIPT_PREFERRED_VALUES, subtag)
+			.or_else(|| lookup(&data::REGION_PREFERRED_VALUES, subtag))
+			.or_else(|| lookup(&data::VARIANT_PREFERRED_VALUES, subtag)),
+	}
+}
+
+/// Returns the scripts of two locales to compare, with the implied script of the language if one
+/// of the locales has a script and the other does not.
+pub(super) fn implicit_scripts<'a>(aval_tag: &'a (impl MatchableLocale + ?Sized), user_tag: &'a (impl MatchableLocale + ?Sized)) -> (Option<&'a str>, Option<&'a str>) {
+	match (aval_tag.script(), user_tag.script()) {
+		(None, Some(user_script)) => (suppress_script(aval_tag), Some(user_script)),
+		(Some(aval_script), None) => (Some(aval_script), suppress_script(user_tag)),
+		scripts => scripts,
+	}
+}
+
+/// Returns the script implied by the language of a locale without an extended language.
+fn suppress_script(tag: &(impl MatchableLocale + ?Sized)) -> Option<&'static str> {
+	match tag.extended_language() {
+		Some(_) => None,
+		None => lookup(&data::SUPPRESS_SCRIPTS, tag.language()),
+	}
+}
+
+/// Returns the description of why a well-formed language tag is not valid, or [`None`] if it is.
+pub(super) fn validation_error(tag: &LanguageTag) -> Option<String> {
+	tag.validate().err().map(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::cmp_ignore_ascii_case;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_validate() {
+		fn case(locale: &str, expected: Result<(), ValidationError>) {
+			assert_eq!(Bcp47Locale::parse(locale).unwrap().validate(), expected, "{locale:?}");
+		}
+
+		// Registered subtags
+		case("en", Ok(()));
+		case("EN-us", Ok(()));
+		case("zh-cmn-Hans-CN", Ok(()));
+		case("sl-rozaj-biske", Ok(()));
+		case("de-DE-u-co-phonebk-x-foo", Ok(()));
+		case("i-klingon", Ok(()));
+		case("x-whatever", Ok(()));
+
+		// Private use subtags
+		case("qaa", Ok(()));
+		case("en-Qaaa", Ok(()));
+		case("en-XA", Ok(()));
+		case("en-ZZ", Ok(()));
+
+		// Unregistered subtags
+		case("xx", Err(ValidationError::PrimaryLanguageNotInRegistry));
+		case("en-Abcd", Err(ValidationError::ScriptNotInRegistry));
+		case("en-UK", Err(ValidationError::RegionNotInRegistry));
+		case("en-999", Err(ValidationError::RegionNotInRegistry));
+		case("en-abcdefg", Err(ValidationError::VariantNotInRegistry));
+		case("zh-abc", Err(ValidationError::ExtendedLanguageNotInRegistry));
+
+		// Wrong prefixes and duplicates
+		case("en-rozaj", Err(ValidationError::WrongVariantPrefix));
+		case("sl-rozaj-rozaj", Err(ValidationError::DuplicateVariant));
+		case("en-u-co-phonebk-u-nu-latn", Err(ValidationError::DuplicateExtension));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_deprecations() {
+		fn case(locale: &str, expected: &[(&str, &str)], expected_replacement: &str) {
+			let locale = Bcp47Locale::parse(locale).unwrap();
+			let deprecations = locale.deprecations();
+			assert_eq!(deprecations.iter().map(|d| (d.deprecated.as_str(), d.replacement.as_str())).collect::<Vec<_>>(), expected, "{locale}");
+			assert_eq!(locale.replace_deprecated().as_str(), expected_replacement);
+		}
+
+		// Current subtags
+		case("en-US", &[], "en-US");
+		case("he-Hebr-IL", &[], "he-Hebr-IL");
+		case("x-iw", &[], "x-iw");
+
+		// Deprecated subtags
+		case("iw", &[("iw", "he")], "he");
+		case("in-ID", &[("in", "id")], "id-ID");
+		case("de-DD", &[("DD", "DE")], "de-DE");
+		case("my-bu", &[("bu", "MM")], "my-MM");
+		case("sgn-heploc", &[("heploc", "alalc97")], "sgn-alalc97");
+		case("und-Qaai", &[("Qaai", "Zinh")], "und-Zinh");
+		case("ar-qaai-EG", &[("qaai", "Zinh")], "ar-Zinh-EG");
+		case("IW-Hebr-DD-u-co-phonebk", &[("IW", "he"), ("DD", "DE")], "he-Hebr-DE-u-co-phonebk");
+
+		// Subtags of extensions and private use
+		case("de-x-dd", &[], "de-x-dd");
+		case("de-u-ca-dd", &[], "de-u-ca-dd");
+
+		// Deprecated tags
+		case("i-klingon", &[("i-klingon", "tlh")], "tlh");
+		case("zh-guoyu", &[("zh-guoyu", "cmn")], "cmn");
+		case("SGN-br", &[("SGN-br", "bzs")], "bzs");
+	}
+
+	#[test]
+	fn test_deprecation_warning() {
+		let warning = deprecation_warning(LocaleKind::User, 2, "iw-DD").unwrap();
+		assert_eq!((warning.kind, warning.index, warning.input.as_str()), (LocaleKind::User, 2, "iw-DD"));
+		assert_eq!(warning.reason, r#""iw" is deprecated, use "he", "DD" is deprecated, use "DE""#);
+		assert_eq!(warning.replacement.as_deref(), Some("he-DE"));
+		assert_eq!(deprecation_warning(LocaleKind::Available, 0, "he-DE"), None);
+
+		// Reported during matching
+		let (_, warnings) = Matcher::new().best_matching_locale_with_warnings(["iw", "en_US"], ["he", "i-klingon"]);
+		assert_eq!(warnings.iter().map(|w| (w.kind, w.index, w.replacement.as_deref())).collect::<Vec<_>>(), [
+			(LocaleKind::Available, 0, Some("he")),
+			(LocaleKind::Available, 1, Some("en-US")),
+			(LocaleKind::User, 1, Some("tlh")),
+		]);
+		let explanation = Matcher::new().explain(["iw", "en_US"], ["fr-FX", "iw", "i-klingon"]);
+		assert_eq!(explanation.warnings.iter().map(|w| (w.kind, w.index, w.replacement.as_deref())).collect::<Vec<_>>(), [
+			(LocaleKind::Available, 0, Some("he")),
+			(LocaleKind::User, 0, Some("fr-FR")),
+			(LocaleKind::User, 1, Some("he")),
+		]);
+	}
+
+	#[test]
+	fn test_lookup() {
+		for table in [data::SUPPRESS_SCRIPTS.as_slice(), &data::LANGUAGE_PREFERRED_VALUES, &data::REGION_PREFERRED_VALUES, &data::VARIANT_PREFERRED_VALUES, &data::TAG_PREFERRED_VALUES] {
+			assert!(table.is_sorted_by(|(a, _), (b, _)| cmp_ignore_ascii_case(a, b).is_lt()));
+		}
+		assert_eq!(lookup(&data::SUPPRESS_SCRIPTS, "en"), Some("Latn"));
+		assert_eq!(lookup(&data::SUPPRESS_SCRIPTS, "RU"), Some("Cyrl"));
+		assert_eq!(lookup(&data::SUPPRESS_SCRIPTS, "zh"), None);
+		assert_eq!(lookup(&data::SUPPRESS_SCRIPTS, ""), None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_implicit_scripts() {
+		fn case(implicit_scripts: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_implicit_scripts(implicit_scripts);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Missing scripts by default
+		case(false, &["en-Cyrl", "en"], &["en-Latn"], Some("en-Cyrl"));
+		case(false, &["ru-Latn", "ru"], &["ru-Cyrl-RU"], Some("ru-Latn"));
+
+		// Implicit scripts of the available locales
+		case(true, &["en-Cyrl", "en"], &["en-Latn"], Some("en"));
+		case(true, &["ru-Latn", "ru"], &["ru-Cyrl-RU"], Some("ru"));
+		case(true, &["ru-Latn", "ru-RU"], &["ru-Cyrl-RU"], Some("ru-RU"));
+
+		// Implicit scripts of the user locales
+		case(true, &["en-Cyrl", "en-Latn"], &["en"], Some("en-Latn"));
+		case(true, &["de-Latn-AT", "de-DE"], &["de-AT"], Some("de-Latn-AT"));
+
+		// Languages without an implied script
+		case(true, &["zh-Hant", "zh-Hans"], &["zh"], Some("zh-Hant"));
+		case(true, &["sr-Latn", "sr-Cyrl"], &["sr"], Some("sr-Latn"));
+		case(true, &["zh-yue", "zh-yue-Hant"], &["zh-yue-Hans"], Some("zh-yue"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_canonicalization() {
+		fn case(canonicalization: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_canonicalization(canonicalization);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// No canonicalization by default
+		case(false, &["en-US", "iw-IL"], &["he-IL"], None);
+		case(false, &["de-DD", "de-DE"], &["de-DE"], Some("de-DE"));
+
+		// Deprecated languages
+		case(true, &["en-US", "iw-IL"], &["he-IL"], Some("iw-IL"));
+		case(true, &["en-US", "he-IL"], &["iw"], Some("he-IL"));
+		case(true, &["in", "ms"], &["id-ID"], Some("in"));
+
+		// Deprecated regions
+		case(true, &["de-DD", "de-AT"], &["de-DE"], Some("de-DD"));
+		case(true, &["my", "my-MM"], &["my-BU"], Some("my-MM"));
+
+		// Extended languages, grandfathered and redundant tags
+		case(true, &["zh", "yue"], &["zh-yue"], Some("yue"));
+		case(true, &["en", "tlh"], &["i-klingon"], Some("tlh"));
+		case(true, &["sgn", "bzs"], &["sgn-BR"], Some("bzs"));
+
+		// Implied scripts
+		case(true, &["en-Latn-US", "en"], &["en-US"], Some("en-Latn-US"));
+
+		// Tags without a canonical form are kept
+		case(true, &["zh-cmn-yue", "zh"], &["zh-cmn-yue"], Some("zh-cmn-yue"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_registry_validation() {
+		use crate::matching::LocaleKind;
+
+		fn case(registry_validation: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_registry_validation(registry_validation);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Only well-formedness by default
+		case(false, &["en-UK", "en-US"], &["en"], Some("en-UK"));
+		case(false, &["xx", "en"], &["xx"], Some("xx"));
+
+		// Invalid locales are ignored
+		case(true, &["en-UK", "en-US"], &["en"], Some("en-US"));
+		case(true, &["xx", "en"], &["xx", "en"], Some("en"));
+		case(true, &["en-UK"], &["en"], None);
+		case(true, &["en-US", "en-GB"], &["en-UK", "en-GB"], Some("en-GB"));
+
+		// Invalid locales are reported as warnings
+		let matcher = Matcher::new().with_registry_validation(true);
+		let (_, warnings) = matcher.best_matching_locale_with_warnings(["en-UK", "en_US"], ["en"]);
+		assert_eq!(warnings.iter().map(|w| (w.kind, w.index, w.reason.as_str())).collect::<Vec<_>>(), [
+			(LocaleKind::Available, 0, "the region is not in the IANA Language Subtag Registry"),
+			(LocaleKind::Available, 1, LanguageTag::parse("en_US").unwrap_err().to_string().as_str()),
+		]);
+
+		// Strict matching rejects only malformed locales
+		assert_eq!(matcher.try_best_matching_locale(["en-UK", "en-US"], ["en"]), Ok(Some("en-US")));
+		assert!(matcher.try_best_matching_locale(["en_UK", "en-US"], ["en"]).is_err());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Data of the [IANA Language Subtag Registry](https://www.iana.org/assignments/language-subtag-registry),
+//! extracted from the copy of the registry embedded in the [`language_tags`](https://docs.rs/language-tags)
+//! crate, which does not expose it.
+//!
+//! The tables are sorted by their keys ignoring case, so they can be searched with
+//! [`lookup`](super::lookup).
+
+/// The Suppress-Script fields of the languages, i.e. the scripts that are implied by the languages.
+pub(super) const SUPPRESS_SCRIPTS: [(&str, &str); 134] = [
+	("ab", "Cyrl"), ("af", "Latn"), ("am", "Ethi"), ("ar", "Arab"), ("as", "Beng"), ("ay", "Latn"), ("be", "Cyrl"), ("bg", "Cyrl"),
+	("bn", "Beng"), ("bs", "Latn"), ("ca", "Latn"), ("ch", "Latn"), ("cs", "Latn"), ("cy", "Latn"), ("da", "Latn"), ("de", "Latn"),
+	("dsb", "Latn"), ("dv", "Thaa"), ("dz", "Tibt"), ("el", "Grek"), ("en", "Latn"), ("eo", "Latn"), ("es", "Latn"), ("et", "Latn"),
+	("eu", "Latn"), ("fa", "Arab"), ("fi", "Latn"), ("fj", "Latn"), ("fo", "Latn"), ("fr", "Latn"), ("frr", "Latn"), ("frs", "Latn"),
+	("fy", "Latn"), ("ga", "Latn"), ("gl", "Latn"), ("gn", "Latn"), ("gsw", "Latn"), ("gu", "Gujr"), ("gv", "Latn"), ("he", "Hebr"),
+	("hi", "Deva"), ("hr", "Latn"), ("hsb", "Latn"), ("ht", "Latn"), ("hu", "Latn"), ("hy", "Armn"), ("id", "Latn"), ("in", "Latn"),
+	("is", "Latn"), ("it", "Latn"), ("iw", "Hebr"), ("ja", "Jpan"), ("ka", "Geor"), ("kk", "Cyrl"), ("kl", "Latn"), ("km", "Khmr"),
+	("kn", "Knda"), ("ko", "Kore"), ("kok", "Deva"), ("la", "Latn"), ("lb", "Latn"), ("ln", "Latn"), ("lo", "Laoo"), ("lt", "Latn"),
+	("lv", "Latn"), ("mai", "Deva"), ("men", "Latn"), ("mg", "Latn"), ("mh", "Latn"), ("mk", "Cyrl"), ("ml", "Mlym"), ("mo", "Latn"),
+	("mr", "Deva"), ("ms", "Latn"), ("mt", "Latn"), ("my", "Mymr"), ("na", "Latn"), ("nb", "Latn"), ("nd", "Latn"), ("nds", "Latn"),
+	("ne", "Deva"), ("niu", "Latn"), ("nl", "Latn"), ("nn", "Latn"), ("no", "Latn"), ("nqo", "Nkoo"), ("nr", "Latn"), ("nso", "Latn"),
+	("ny", "Latn"), ("om", "Latn"), ("or", "Orya"), ("pa", "Guru"), ("pl", "Latn"), ("ps", "Arab"), ("pt", "Latn"), ("qu", "Latn"),
+	("rm", "Latn"), ("rn", "Latn"), ("ro", "Latn"), ("ru", "Cyrl"), ("rw", "Latn"), ("sg", "Latn"), ("si", "Sinh"), ("sk", "Latn"),
+	("sl", "Latn"), ("sm", "Latn"), ("so", "Latn"), ("sq", "Latn"), ("ss", "Latn"), ("st", "Latn"), ("sv", "Latn"), ("sw", "Latn"),
+	("ta", "Taml"), ("te", "Telu"), ("tem", "Latn"), ("th", "Thai"), ("ti", "Ethi"), ("tkl", "Latn"), ("tl", "Latn"), ("tmh", "Latn"),
+	("tn", "Latn"), ("to", "Latn"), ("tpi", "Latn"), ("tr", "Latn"), ("ts", "Latn"), ("tvl", "Latn"), ("uk", "Cyrl"), ("ur", "Arab"),
+	("ve", "Latn"), ("vi", "Latn"), ("xh", "Latn"), ("yi", "Hebr"), ("zbl", "Blis"), ("zu", "Latn"),
+];
+
+/// The Prefix fields of the extended languages, i.e. the primary languages they can follow.
+pub(super) const EXTLANG_PREFIXES: [(&str, &str); 245] = [
+	("aao", "ar"), ("abh", "ar"), ("abv", "ar"), ("acm", "ar"), ("acq", "ar"), ("acw", "ar"), ("acx", "ar"), ("acy", "ar"),
+	("adf", "ar"), ("ads", "sgn"), ("aeb", "ar"), ("aec", "ar"), ("aed", "sgn"), ("aen", "sgn"), ("afb", "ar"), ("afg", "sgn"),
+	("ajp", "ar"), ("apc", "ar"), ("apd", "ar"), ("arb", "ar"), ("arq", "ar"), ("ars", "ar"), ("ary", "ar"), ("arz", "ar"),
+	("ase", "sgn"), ("asf", "sgn"), ("asp", "sgn"), ("asq", "sgn"), ("asw", "sgn"), ("auz", "ar"), ("avl", "ar"), ("ayh", "ar"),
+	("ayl", "ar"), ("ayn", "ar"), ("ayp", "ar"), ("bbz", "ar"), ("bfi", "sgn"), ("bfk", "sgn"), ("bjn", "ms"), ("bog", "sgn"),
+	("bqn", "sgn"), ("bqy", "sgn"), ("btj", "ms"), ("bve", "ms"), ("bvl", "sgn"), ("bvu", "ms"), ("bzs", "sgn"), ("cdo", "zh"),
+	("cds", "sgn"), ("cjy", "zh"), ("cmn", "zh"), ("cnp", "zh"), ("coa", "ms"), ("cpx", "zh"), ("csc", "sgn"), ("csd", "sgn"),
+	("cse", "sgn"), ("csf", "sgn"), ("csg", "sgn"), ("csl", "sgn"), ("csn", "sgn"), ("csp", "zh"), ("csq", "sgn"), ("csr", "sgn"),
+	("csx", "sgn"), ("czh", "zh"), ("czo", "zh"), ("doq", "sgn"), ("dse", "sgn"), ("dsl", "sgn"), ("dup", "ms"), ("ecs", "sgn"),
+	("ehs", "sgn"), ("esl", "sgn"), ("esn", "sgn"), ("eso", "sgn"), ("eth", "sgn"), ("fcs", "sgn"), ("fse", "sgn"), ("fsl", "sgn"),
+	("fss", "sgn"), ("gan", "zh"), ("gds", "sgn"), ("gom", "kok"), ("gse", "sgn"), ("gsg", "sgn"), ("gsm", "sgn"), ("gss", "sgn"),
+	("gus", "sgn"), ("hab", "sgn"), ("haf", "sgn"), ("hak", "zh"), ("hds", "sgn"), ("hji", "ms"), ("hks", "sgn"), ("hos", "sgn"),
+	("hps", "sgn"), ("hsh", "sgn"), ("hsl", "sgn"), ("hsn", "zh"), ("icl", "sgn"), ("iks", "sgn"), ("ils", "sgn"), ("inl", "sgn"),
+	("ins", "sgn"), ("ise", "sgn"), ("isg", "sgn"), ("isr", "sgn"), ("jak", "ms"), ("jax", "ms"), ("jcs", "sgn"), ("jhs", "sgn"),
+	("jks", "sgn"), ("jls", "sgn"), ("jos", "sgn"), ("jsl", "sgn"), ("jus", "sgn"), ("kgi", "sgn"), ("knn", "kok"), ("kvb", "ms"),
+	("kvk", "sgn"), ("kvr", "ms"), ("kxd", "ms"), ("lbs", "sgn"), ("lce", "ms"), ("lcf", "ms"), ("liw", "ms"), ("lls", "sgn"),
+	("lsb", "sgn"), ("lsg", "sgn"), ("lsl", "sgn"), ("lsn", "sgn"), ("lso", "sgn"), ("lsp", "sgn"), ("lst", "sgn"), ("lsv", "sgn"),
+	("lsy", "sgn"), ("ltg", "lv"), ("lvs", "lv"), ("lws", "sgn"), ("lzh", "zh"), ("max", "ms"), ("mdl", "sgn"), ("meo", "ms"),
+	("mfa", "ms"), ("mfb", "ms"), ("mfs", "sgn"), ("min", "ms"), ("mnp", "zh"), ("mqg", "ms"), ("mre", "sgn"), ("msd", "sgn"),
+	("msi", "ms"), ("msr", "sgn"), ("mui", "ms"), ("mzc", "sgn"), ("mzg", "sgn"), ("mzy", "sgn"), ("nan", "zh"), ("nbs", "sgn"),
+	("ncs", "sgn"), ("nsi", "sgn"), ("nsl", "sgn"), ("nsp", "sgn"), ("nsr", "sgn"), ("nzs", "sgn"), ("okl", "sgn"), ("orn", "ms"),
+	("ors", "ms"), ("pel", "ms"), ("pga", "ar"), ("pgz", "sgn"), ("pks", "sgn"), ("prl", "sgn"), ("prz", "sgn"), ("psc", "sgn"),
+	("psd", "sgn"), ("pse", "ms"), ("psg", "sgn"), ("psl", "sgn"), ("pso", "sgn"), ("psp", "sgn"), ("psr", "sgn"), ("pys", "sgn"),
+	("rms", "sgn"), ("rsi", "sgn"), ("rsl", "sgn"), ("rsm", "sgn"), ("sdl", "sgn"), ("sfb", "sgn"), ("sfs", "sgn"), ("sgg", "sgn"),
+	("sgx", "sgn"), ("shu", "ar"), ("slf", "sgn"), ("sls", "sgn"), ("sqk", "sgn"), ("sqs", "sgn"), ("sqx", "sgn"), ("ssh", "ar"),
+	("ssp", "sgn"), ("ssr", "sgn"), ("svk", "sgn"), ("swc", "sw"), ("swh", "sw"), ("swl", "sgn"), ("syy", "sgn"), ("szs", "sgn"),
+	("tmw", "ms"), ("tse", "sgn"), ("tsm", "sgn"), ("tsq", "sgn"), ("tss", "sgn"), ("tsy", "sgn"), ("tza", "sgn"), ("ugn", "sgn"),
+	("ugy", "sgn"), ("ukl", "sgn"), ("uks", "sgn"), ("urk", "ms"), ("uzn", "uz"), ("uzs", "uz"), ("vgt", "sgn"), ("vkk", "ms"),
+	("vkt", "ms"), ("vsi", "sgn"), ("vsl", "sgn"), ("vsv", "sgn"), ("wbs", "sgn"), ("wuu", "zh"), ("xki", "sgn"), ("xml", "sgn"),
+	("xmm", "ms"), ("xms", "sgn"), ("yds", "sgn"), ("ygs", "sgn"), ("yhs", "sgn"), ("ysl", "sgn"), ("ysm", "sgn"), ("yue", "zh"),
+	("zib", "sgn"), ("zlm", "ms"), ("zmi", "ms"), ("zsl", "sgn"), ("zsm", "ms"),
+];
+
+/// The Preferred-Value fields of the deprecated languages.
+pub(super) const LANGUAGE_PREFERRED_VALUES: [(&str, &str); 92] = [
+	("aam", "aas"), ("adp", "dz"), ("asd", "snz"), ("aue", "ktz"), ("ayx", "nun"), ("bgm", "bcg"), ("bic", "bir"), ("bjd", "drl"),
+	("blg", "iba"), ("ccq", "rki"), ("cjr", "mom"), ("cka", "cmr"), ("cmk", "xch"), ("coy", "pij"), ("cqu", "quh"), ("dit", "dif"),
+	("drh", "khk"), ("drr", "kzk"), ("drw", "prs"), ("gav", "dev"), ("gfx", "vaj"), ("ggn", "gvr"), ("gli", "kzk"), ("gti", "nyc"),
+	("guv", "duz"), ("hrr", "jal"), ("ibi", "opa"), ("ilw", "gal"), ("in", "id"), ("iw", "he"), ("jeg", "oyb"), ("ji", "yi"),
+	("jw", "jv"), ("kgc", "tdf"), ("kgh", "kml"), ("koj", "kwv"), ("krm", "bmf"), ("ktr", "dtp"), ("kvs", "gdj"), ("kwq", "yam"),
+	("kxe", "tvd"), ("kxl", "kru"), ("kzj", "dtp"), ("kzt", "dtp"), ("lii", "raq"), ("llo", "ngt"), ("lmm", "rmx"), ("meg", "cir"),
+	("mo", "ro"), ("mst", "mry"), ("mwj", "vaj"), ("myd", "aog"), ("myt", "mry"), ("nad", "xny"), ("ncp", "kdz"), ("nns", "nbr"),
+	("nnx", "ngv"), ("nts", "pij"), ("nxu", "bpp"), ("oun", "vaj"), ("pat", "kxr"), ("pcr", "adx"), ("pmc", "huw"), ("pmu", "phr"),
+	("ppa", "bfy"), ("ppr", "lcq"), ("pry", "prt"), ("puz", "pub"), ("sca", "hle"), ("skk", "oyb"), ("tdu", "dtp"), ("thc", "tpo"),
+	("thw", "ola"), ("thx", "oyb"), ("tie", "ras"), ("tkk", "twm"), ("tlw", "weo"), ("tmp", "tyj"), ("tne", "kak"), ("tnf", "prs"),
+	("tsf", "taj"), ("uok", "ema"), ("xba", "cax"), ("xia", "acn"), ("xkh", "waw"), ("xrq", "dmw"), ("ybd", "rki"), ("yma", "lrr"),
+	("ymt", "mtm"), ("yos", "zom"), ("yuu", "yug"), ("zir", "scv"),
+];
+
+/// The Preferred-Value fields of the deprecated scripts.  
+/// The copy of the registry in `language_tags` omits them, as they are in the range of private use
+/// scripts `Qaaa..Qabx`.
+pub(super) const SCRIPT_PREFERRED_VALUES: [(&str, &str); 1] = [
+	("Qaai", "Zinh"),
+];
+
+/// The Preferred-Value fields of the deprecated regions.
+pub(super) const REGION_PREFERRED_VALUES: [(&str, &str); 6] = [
+	("BU", "MM"), ("DD", "DE"), ("FX", "FR"), ("TP", "TL"), ("YD", "YE"), ("ZR", "CD"),
+];
+
+/// The Preferred-Value fields of the deprecated variants.
+pub(super) const VARIANT_PREFERRED_VALUES: [(&str, &str); 1] = [
+	("heploc", "alalc97"),
+];
+
+/// The Preferred-Value fields of the deprecated grandfathered and redundant tags.
+pub(super) const TAG_PREFERRED_VALUES: [(&str, &str); 46] = [
+	("art-lojban", "jbo"), ("en-GB-oed", "en-GB-oxendict"), ("i-ami", "ami"), ("i-bnn", "bnn"),
+	("i-hak", "hak"), ("i-klingon", "tlh"), ("i-lux", "lb"), ("i-navajo", "nv"),
+	("i-pwn", "pwn"), ("i-tao", "tao"), ("i-tay", "tay"), ("i-tsu", "tsu"),
+	("no-bok", "nb"), ("no-nyn", "nn"), ("sgn-BE-FR", "sfb"), ("sgn-BE-NL", "vgt"),
+	("sgn-BR", "bzs"), ("sgn-CH-DE", "sgg"), ("sgn-CO", "csn"), ("sgn-DE", "gsg"),
+	("sgn-DK", "dsl"), ("sgn-ES", "ssp"), ("sgn-FR", "fsl"), ("sgn-GB", "bfi"),
+	("sgn-GR", "gss"), ("sgn-IE", "isg"), ("sgn-IT", "ise"), ("sgn-JP", "jsl"),
+	("sgn-MX", "mfs"), ("sgn-NI", "ncs"), ("sgn-NL", "dse"), ("sgn-NO", "nsl"),
+	("sgn-PT", "psr"), ("sgn-SE", "swl"), ("sgn-US", "ase"), ("sgn-ZA", "sfs"),
+	("zh-cmn", "cmn"), ("zh-cmn-Hans", "cmn-Hans"), ("zh-cmn-Hant", "cmn-Hant"), ("zh-gan", "gan"),
+	("zh-guoyu", "cmn"), ("zh-hakka", "hak"), ("zh-min-nan", "nan"), ("zh-wuu", "wuu"),
+	("zh-xiang", "hsn"), ("zh-yue", "yue"),
+];
\ No newline at end of file
@@ -0,0 +1,159 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Resolution of matched locales with the regional preferences of the user.
+
+use std::borrow::Cow;
+
+use language_tags::LanguageTag;
+
+use super::{canonical_case, TagParts};
+
+/// Combines a matched available locale with the regional preferences of the user locale it was
+/// matched for, e.g. for formatting dates and numbers, which should follow the user's conventions
+/// even if the translations are only available for a more general locale.
+///
+/// The resolved locale has the language, the extended language, the script, the variants and the
+/// private use subtags of the available locale, the region of the user locale (or of the available
+/// locale if the user locale has none), and the `-u-` extension of the user locale (or of the
+/// available locale if the user locale has none). Other extensions are taken from the available
+/// locale.  
+/// An irregular grandfathered tag is replaced by its Preferred-Value first, e.g. `i-klingon` by
+/// `tlh`.  
+/// The resolved locale is formatted with [`canonical_case`]. Returns [`None`] if any of the
+/// locales is malformed or is a grandfathered tag without a Preferred-Value, e.g. `i-default`.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::{best_matching_locale_with_confidence, resolve};
+///
+///
+/// let user_locales = ["es-AR-u-nu-latn"];
+///
+/// let best_match = best_matching_locale_with_confidence(["en", "es"], user_locales).unwrap();
+///
+/// assert_eq!(best_match.locale, "es");
+/// assert_eq!(resolve(best_match.locale, user_locales[best_match.user_index]).as_deref(), Some("es-AR-u-nu-latn"));
+///
+/// assert_eq!(resolve("sr-Cyrl", "sr-Latn-ME").as_deref(), Some("sr-Cyrl-ME"));
+/// assert_eq!(resolve("pt-BR", "pt").as_deref(), Some("pt-BR"));
+/// ```
+pub fn resolve(available_locale: &str, user_locale: &str) -> Option<String> {
+	let (available_locale, user_locale) = (splittable(available_locale)?, splittable(user_locale)?);
+	let (available_locale, user_locale) = (TagParts::parse(&available_locale)?, TagParts::parse(&user_locale)?);
+	let aval_extensions = extensions(available_locale.extension());
+	let user_extensions = extensions(user_locale.extension());
+	let is_unicode = |extension: &str| extension.starts_with(['u', 'U']);
+	let unicode_extension = user_extensions.iter().chain(&aval_extensions).copied().find(|extension| is_unicode(extension));
+
+	let mut subtags = vec![available_locale.language()];
+	subtags.extend(available_locale.extended_language());
+	subtags.extend(available_locale.script());
+	subtags.extend(user_locale.region().or(available_locale.region()));
+	subtags.extend(available_locale.variant());
+	// The extensions are ordered by their singletons
+	let mut extensions = aval_extensions.iter()
+		.copied()
+		.filter(|extension| !is_unicode(extension))
+		.chain(unicode_extension)
+		.collect::<Vec<&str>>();
+	extensions.sort_by_key(|extension| extension.as_bytes()[0].to_ascii_lowercase());
+	subtags.extend(extensions);
+	subtags.extend(available_locale.private_use());
+	canonical_case(&subtags.join("-"))
+}
+
+/// Returns a locale that can be split into its subtags with [`TagParts::parse`], i.e. the locale
+/// itself, or the Preferred-Value of an irregular grandfathered tag.  
+/// Returns [`None`] if the locale is malformed or is a grandfathered tag without a Preferred-Value.
+fn splittable(locale: &str) -> Option<Cow<'_, str>> {
+	if TagParts::parse(locale).is_some() {
+		return Some(Cow::Borrowed(locale));
+	}
+	let tag = LanguageTag::parse(locale).ok()?.canonicalize().ok()?;
+	let locale = tag.into_string();
+	TagParts::parse(&locale)?;
+	Some(Cow::Owned(locale))
+}
+
+/// Splits the extension subtags into the extensions, e.g. `t-ru-u-nu-latn` into `t-ru` and
+/// `u-nu-latn`.
+fn extensions(extension: Option<&str>) -> Vec<&str> {
+	let Some(extension) = extension else {
+		return Vec::new();
+	};
+	let mut starts = extension.split('-')
+		.scan(0, |position, subtag| {
+			let start = *position;
+			*position += subtag.len() + 1;
+			Some((start, subtag))
+		})
+		.filter(|(_, subtag)| subtag.len() == 1)
+		.map(|(start, _)| start)
+		.collect::<Vec<usize>>();
+	starts.push(extension.len() + 1);
+	starts.windows(2).map(|w| &extension[w[0]..w[1] - 1]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_resolve() {
+		fn case(available_locale: &str, user_locale: &str, expected: Option<&str>) {
+			assert_eq!(resolve(available_locale, user_locale).as_deref(), expected, "{available_locale} and {user_locale}");
+		}
+
+		// User regions
+		case("es", "es-AR", Some("es-AR"));
+		case("es-ES", "es-AR", Some("es-AR"));
+		case("es-419", "es", Some("es-419"));
+		case("zh-Hant", "zh-HK", Some("zh-Hant-HK"));
+		case("sr-Latn", "sr-Cyrl-RS", Some("sr-Latn-RS"));
+		case("de-1996", "de-CH", Some("de-CH-1996"));
+		case("de-1606nict", "de-AT", Some("de-AT-1606nict"));
+		case("de-CH-1901", "de-LI-1996", Some("de-LI-1901"));
+
+		// Unicode extensions
+		case("es", "es-AR-u-nu-latn", Some("es-AR-u-nu-latn"));
+		case("ar-u-nu-arab", "ar-EG", Some("ar-EG-u-nu-arab"));
+		case("ar-u-nu-arab", "ar-EG-u-nu-latn", Some("ar-EG-u-nu-latn"));
+		case("ja-t-it", "ja-JP-u-ca-japanese", Some("ja-JP-t-it-u-ca-japanese"));
+		case("en-x-foo", "en-US-t-de-x-bar", Some("en-US-x-foo"));
+		case("en-a-bbb", "en-US-b-ccc-u-hc-h23", Some("en-US-a-bbb-u-hc-h23"));
+
+		// Grandfathered tags
+		case("i-klingon", "tlh-US", Some("tlh-US"));
+		case("tlh", "i-klingon", Some("tlh"));
+		case("en-GB-oed", "en-US", Some("en-US-oxendict"));
+		case("zh-min-nan", "zh-TW", Some("zh-min-nan-TW"));
+		case("i-default", "en", None);
+
+		// Canonical case and malformed locales
+		case("ES", "es-ar", Some("es-AR"));
+		case("en_US", "en", None);
+		case("en", "", None);
+	}
+
+	#[test]
+	fn test_extensions() {
+		assert_eq!(extensions(None), Vec::<&str>::new());
+		assert_eq!(extensions(Some("u-nu-latn")), ["u-nu-latn"]);
+		assert_eq!(extensions(Some("t-ru-u-nu-latn-ca-buddhist")), ["t-ru", "u-nu-latn-ca-buddhist"]);
+	}
+}
\ No newline at end of file
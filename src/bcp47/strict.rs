@@ -0,0 +1,101 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that rejects malformed locales instead of ignoring them.
+
+use language_tags::ParseError;
+
+use crate::engine;
+use crate::matching::InvalidLocale;
+
+use super::Matcher;
+
+/// Finds the best matching locale like [`best_matching_locale`](super::best_matching_locale), but
+/// fails if any of the available or user locales is malformed instead of ignoring it.
+///
+/// This suits configuration-driven catalogs, where a malformed locale is a mistake to be fixed
+/// rather than an input to be tolerated.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::try_best_matching_locale;
+/// use locale_match::matching::LocaleKind;
+///
+///
+/// assert_eq!(try_best_matching_locale(["en-US", "ru-BY"], ["ru-RU", "en"]), Ok(Some("ru-BY")));
+/// assert_eq!(try_best_matching_locale(["en-US", "ru-BY"], ["fr"]), Ok(None));
+///
+/// let error = try_best_matching_locale(["en-US", "ru_BY"], ["ru-RU", "en"]).unwrap_err();
+/// assert_eq!((error.kind, error.index, error.input.as_str()), (LocaleKind::Available, 1, "ru_BY"));
+/// ```
+pub fn try_best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Result<Option<T1>, InvalidLocale<ParseError>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().try_best_matching_locale(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`try_best_matching_locale`], using the configuration of
+	/// the matcher.
+	pub fn try_best_matching_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Result<Option<T1>, InvalidLocale<ParseError>>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::try_best_matching_locale(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use language_tags::LanguageTag;
+
+	use crate::matching::LocaleKind;
+
+	#[test]
+	fn test_try_best_matching_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Result<Option<&str>, (LocaleKind, usize)>) {
+			let result = try_best_matching_locale(available_locales, user_locales)
+				.map(|best_match| best_match.copied())
+				.map_err(|error| (error.kind, error.index));
+			assert_eq!(result, expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Well-formed locales
+		case(&["en-US", "ru-RU"], &["ru", "en"], Ok(Some("ru-RU")));
+		case(&["en-US", "ru-RU"], &["fr"], Ok(None));
+		case(&[], &[], Ok(None));
+
+		// Malformed locales, the first one is reported
+		case(&["en-US", "ru_RU", "de_DE"], &["ru"], Err((LocaleKind::Available, 1)));
+		case(&["en-US"], &["en", "en-"], Err((LocaleKind::User, 1)));
+		case(&["en-"], &["en-"], Err((LocaleKind::Available, 0)));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_InvalidLocale() {
+		let error = try_best_matching_locale(["en-US"], ["en", "en-"]).unwrap_err();
+		assert_eq!(error.input, "en-");
+		assert_eq!(error.source, LanguageTag::parse("en-").unwrap_err());
+		assert!(error.to_string().starts_with("invalid user locale \"en-\" at index 1: "));
+		assert!(std::error::Error::source(&error).is_some());
+	}
+}
\ No newline at end of file
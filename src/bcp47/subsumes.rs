@@ -0,0 +1,119 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Comparison of BCP 47 locales by coverage, e.g. for deduplicating catalogs.
+
+use super::{Bcp47Locale, MatchableLocale};
+
+impl Bcp47Locale {
+	/// Checks whether the locale covers another locale, i.e. every subtag of the locale is also in
+	/// the other locale, so the other locale is the same or a more specific form of it.
+	///
+	/// The language must be the same, and the extended language, the script, the region, the
+	/// extensions and the private use subtags of the locale, if any, must be the same in the other
+	/// locale. The variants of the locale must be among the variants of the other locale.  
+	/// The subtags are compared ignoring ASCII case, and a locale subsumes itself.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Bcp47Locale;
+	///
+	///
+	/// let locale = |locale: &str| locale.parse::<Bcp47Locale>().unwrap();
+	///
+	/// assert!(locale("en").subsumes(&locale("en-US")));
+	/// assert!(locale("sr-Latn").subsumes(&locale("sr-Latn-RS")));
+	/// assert!(!locale("en-US").subsumes(&locale("en-GB")));
+	/// assert!(!locale("en-US").subsumes(&locale("en")));
+	/// ```
+	pub fn subsumes(&self, other: &Bcp47Locale) -> bool {
+		let covers = |part: Option<&str>, other_part: Option<&str>| match (part, other_part) {
+			(None, _) => true,
+			(Some(part), Some(other_part)) => part.eq_ignore_ascii_case(other_part),
+			(Some(_), None) => false,
+		};
+		let (parts, other) = (self.parts(), other.parts());
+		let other_variants = other.variant().map_or(Vec::new(), |variant| variant.split('-').collect());
+		parts.language().eq_ignore_ascii_case(other.language())
+			&& covers(parts.extended_language(), other.extended_language())
+			&& covers(parts.script(), other.script())
+			&& covers(parts.region(), other.region())
+			&& parts.variant().is_none_or(|variant| variant.split('-').all(|variant| other_variants.iter().any(|other| variant.eq_ignore_ascii_case(other))))
+			&& covers(parts.extension(), other.extension())
+			&& covers(parts.private_use(), other.private_use())
+	}
+
+	/// Checks whether the locale is a more specific form of another locale, i.e. the other locale
+	/// [subsumes](Self::subsumes) the locale, but not the other way around.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::bcp47::Bcp47Locale;
+	///
+	///
+	/// let locale = |locale: &str| locale.parse::<Bcp47Locale>().unwrap();
+	///
+	/// assert!(locale("en-US").is_more_specific(&locale("en")));
+	/// assert!(!locale("en-US").is_more_specific(&locale("en-us")));
+	/// assert!(!locale("en-US").is_more_specific(&locale("en-GB")));
+	/// ```
+	pub fn is_more_specific(&self, other: &Bcp47Locale) -> bool {
+		other.subsumes(self) && !self.subsumes(other)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Bcp47Locale_subsumes() {
+		fn case(locale: &str, other: &str, expected: bool) {
+			let (locale, other) = (Bcp47Locale::parse(locale).unwrap(), Bcp47Locale::parse(other).unwrap());
+			assert_eq!(locale.subsumes(&other), expected, "{locale} and {other}");
+			assert_eq!(other.is_more_specific(&locale), expected && !other.subsumes(&locale), "{other} and {locale}");
+		}
+
+		// Same locales
+		case("en", "en", true);
+		case("en-US", "en-us", true);
+		case("sl-rozaj-biske", "sl-biske-rozaj", true);
+
+		// More specific locales
+		case("en", "en-US", true);
+		case("zh", "zh-Hant-TW", true);
+		case("zh-Hant", "zh-Hant-TW", true);
+		case("zh-TW", "zh-Hant-TW", true);
+		case("zh", "zh-cmn-Hans", true);
+		case("sl", "sl-rozaj", true);
+		case("sl-rozaj", "sl-rozaj-biske", true);
+		case("ar", "ar-u-nu-latn", true);
+		case("de", "de-x-foo", true);
+
+		// Different or less specific locales
+		case("en-US", "en-GB", false);
+		case("en-US", "en", false);
+		case("en", "ru", false);
+		case("zh-Hans", "zh-Hant-TW", false);
+		case("zh-Hant-TW", "zh-TW", false);
+		case("sl-rozaj-biske", "sl-rozaj", false);
+		case("ar-u-nu-latn", "ar-u-nu-arab", false);
+		case("de-x-foo", "de-x-bar", false);
+	}
+}
\ No newline at end of file
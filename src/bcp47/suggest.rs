@@ -0,0 +1,305 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Suggestions of corrections for malformed BCP 47 locales.
+
+#[cfg(feature = "iana")]
+use language_tags::LanguageTag;
+
+use crate::matching::{lookup, REGION_ALIASES};
+
+use super::{canonical_case, language_name_to_locale};
+
+/// The maximum number of suggestions returned by [`suggest_locale`].
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The maximum number of alternatives for a single misspelled subtag.
+const MAX_ALTERNATIVES: usize = 3;
+
+/// The ISO 639-1 language codes, including the deprecated `in`, `iw`, `ji` and `mo`.
+const LANGUAGES: &[&str] = &[
+	"aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+	"bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+	"de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+	"fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+	"ia", "id", "ie", "ig", "ii", "ik", "in", "io", "is", "it", "iu", "iw", "ja", "ji", "jv", "ka",
+	"kg", "ki", "kj", "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb",
+	"lg", "li", "ln", "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mo", "mr", "ms",
+	"mt", "my", "na", "nb", "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om",
+	"or", "os", "pa", "pi", "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd",
+	"se", "sg", "si", "sk", "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta",
+	"te", "tg", "th", "ti", "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur",
+	"uz", "ve", "vi", "vo", "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// The ISO 3166-1 alpha-2 region codes, and the exceptionally reserved `EU` and `UN`.
+const REGIONS: &[&str] = &[
+	"AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+	"BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+	"BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+	"CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+	"EG", "EH", "ER", "ES", "ET", "EU", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE",
+	"GF", "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK",
+	"HM", "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE",
+	"JM", "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB",
+	"LC", "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH",
+	"MK", "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ",
+	"NA", "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF",
+	"PG", "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU",
+	"RW", "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR",
+	"SS", "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN",
+	"TO", "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "UN", "US", "UY", "UZ", "VA", "VC", "VE",
+	"VG", "VI", "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// ISO 639-2 codes of common languages, both the terminology and the bibliographic ones, with the
+/// corresponding ISO 639-1 codes, which BCP 47 uses instead.
+const ALPHA3_LANGUAGES: &[(&str, &str)] = &[
+	("ara", "ar"), ("ben", "bn"), ("bul", "bg"), ("cat", "ca"), ("ces", "cs"), ("chi", "zh"),
+	("cze", "cs"), ("dan", "da"), ("deu", "de"), ("dut", "nl"), ("ell", "el"), ("eng", "en"),
+	("est", "et"), ("fas", "fa"), ("fin", "fi"), ("fra", "fr"), ("fre", "fr"), ("ger", "de"),
+	("gre", "el"), ("heb", "he"), ("hin", "hi"), ("hrv", "hr"), ("hun", "hu"), ("ind", "id"),
+	("ita", "it"), ("jpn", "ja"), ("kor", "ko"), ("lav", "lv"), ("lit", "lt"), ("may", "ms"),
+	("msa", "ms"), ("nld", "nl"), ("nno", "nn"), ("nob", "nb"), ("nor", "no"), ("per", "fa"),
+	("pol", "pl"), ("por", "pt"), ("ron", "ro"), ("rum", "ro"), ("rus", "ru"), ("slk", "sk"),
+	("slo", "sk"), ("slv", "sl"), ("spa", "es"), ("srp", "sr"), ("swe", "sv"), ("tam", "ta"),
+	("tha", "th"), ("tur", "tr"), ("ukr", "uk"), ("urd", "ur"), ("vie", "vi"), ("zho", "zh"),
+];
+
+/// Suggests corrections for a malformed or misspelled BCP 47 locale, best first, e.g. `en-US` for
+/// `en-USA`, so that configuration validators and command-line tools can give actionable feedback.
+///
+/// The suggestions are made with structural rules:
+/// * POSIX-style locales are converted, e.g. `en_US.UTF-8` to `en-US`.
+/// * Empty subtags are dropped and subtags are put in order, e.g. `zh--TW-Hant` to `zh-Hant-TW`.
+/// * ISO 639-2 and ISO 3166-1 alpha-3 codes of common languages and regions are replaced by their
+///   two-letter codes, e.g. `eng-GBR` to `en-GB`, as well as `UK` and deprecated regions by their
+///   current codes, e.g. `YU` by `RS`.
+/// * Language names are converted with [`language_name_to_locale`], e.g. `German` to `de`.
+///
+/// Unknown two-letter languages and regions, and languages of other lengths, are replaced by the
+/// closest ISO 639-1 and ISO 3166-1 codes by edit distance, e.g. `engl-US` by `en-US`.
+/// With the `iana` feature, only valid locales are suggested.
+/// Returns an empty list if there is nothing to suggest, in particular if the locale is already
+/// well-formed apart from the case of its subtags.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::suggest_locale;
+///
+///
+/// assert_eq!(suggest_locale("en-USA"), ["en-US"]);
+/// assert_eq!(suggest_locale("ru_RU.UTF-8"), ["ru-RU"]);
+/// assert_eq!(suggest_locale("zh-TW-Hant"), ["zh-Hant-TW"]);
+/// assert_eq!(suggest_locale("Deutsch"), ["de"]);
+/// assert_eq!(suggest_locale("en-US"), Vec::<String>::new());
+/// ```
+pub fn suggest_locale(input: &str) -> Vec<String> {
+	let mut suggestions = Vec::new();
+	if let Some(locale) = language_name_to_locale(input) {
+		suggestions.push(locale.to_string());
+	}
+	// The codeset and the modifier of a POSIX locale, e.g. `.UTF-8` in `en_US.UTF-8`
+	let locale = input.split(['.', '@']).next().unwrap_or_default();
+	let subtags = locale.split(|c: char| c == '-' || c == '_' || c.is_whitespace())
+		.filter(|subtag| !subtag.is_empty())
+		.collect::<Vec<&str>>();
+	if let Some((&language, rest)) = subtags.split_first() {
+		// Private use and grandfathered tags, e.g. `x-pig-latin` and `i-klingon`
+		let languages = match language.len() {
+			1 => vec![language.to_string()],
+			_ => language_alternatives(language),
+		};
+		let (mut extlang, mut script, mut regions, mut tail) = (None, None, Vec::new(), Vec::new());
+		for (i, subtag) in rest.iter().enumerate() {
+			let alphabetic = subtag.bytes().all(|c| c.is_ascii_alphabetic());
+			match subtag.len() {
+				// The rest are extensions and private use subtags
+				1 => {
+					tail.extend(rest[i..].iter().copied());
+					break;
+				}
+				2 if alphabetic && regions.is_empty() => regions = region_alternatives(subtag),
+				3 if alphabetic && regions.is_empty() && lookup(REGION_ALIASES, subtag).is_some() => regions = region_alternatives(subtag),
+				3 if alphabetic && extlang.is_none() && script.is_none() => extlang = Some(*subtag),
+				3 if subtag.bytes().all(|c| c.is_ascii_digit()) && regions.is_empty() => regions = vec![subtag.to_string()],
+				4 if alphabetic && script.is_none() => script = Some(*subtag),
+				_ => tail.push(*subtag),
+			}
+		}
+		if regions.is_empty() {
+			regions.push(String::new());
+		}
+		for language in &languages {
+			for region in &regions {
+				let subtags = [language.as_str()].into_iter()
+					.chain(extlang)
+					.chain(script)
+					.chain((!region.is_empty()).then_some(region.as_str()))
+					.chain(tail.iter().copied());
+				suggestions.push(subtags.collect::<Vec<&str>>().join("-"));
+			}
+		}
+	}
+	let mut unique = Vec::<String>::new();
+	for suggestion in suggestions.iter().filter_map(|suggestion| canonical_case(suggestion)) {
+		if !suggestion.eq_ignore_ascii_case(input) && !unique.contains(&suggestion) && is_valid(&suggestion) {
+			unique.push(suggestion);
+		}
+	}
+	unique.truncate(MAX_SUGGESTIONS);
+	unique
+}
+
+/// Whether a suggested locale is valid, i.e. its subtags are in the IANA Language Subtag Registry.
+#[cfg(feature = "iana")]
+fn is_valid(locale: &str) -> bool {
+	LanguageTag::parse(locale).is_ok_and(|tag| tag.validate().is_ok())
+}
+
+/// Whether a suggested locale is valid, which can only be checked with the `iana` feature.
+#[cfg(not(feature = "iana"))]
+fn is_valid(_: &str) -> bool {
+	true
+}
+
+/// The languages that a language subtag may stand for, best first.
+fn language_alternatives(language: &str) -> Vec<String> {
+	let language = language.to_ascii_lowercase();
+	if let Some(replacement) = lookup(ALPHA3_LANGUAGES, &language) {
+		return vec![replacement.to_string()];
+	}
+	match language.len() {
+		2 if LANGUAGES.contains(&language.as_str()) => vec![language],
+		// Other ISO 639-2 and ISO 639-3 languages, e.g. `haw`, and registered languages
+		3 | 5..=8 if language.bytes().all(|c| c.is_ascii_alphabetic()) => vec![language],
+		_ => closest(LANGUAGES, &language),
+	}
+}
+
+/// The regions that a region subtag may stand for, best first.
+fn region_alternatives(region: &str) -> Vec<String> {
+	let region = region.to_ascii_uppercase();
+	if let Some(replacement) = lookup(REGION_ALIASES, &region) {
+		return vec![replacement.to_string()];
+	}
+	match REGIONS.contains(&region.as_str()) {
+		true => vec![region],
+		false => closest(REGIONS, &region),
+	}
+}
+
+/// The codes closest to a misspelled code by edit distance, up to [`MAX_ALTERNATIVES`], with the
+/// codes sharing a longer prefix with it first.
+/// Returns the code itself if no code is close enough.
+fn closest(codes: &[&str], code: &str) -> Vec<String> {
+	let max_distance = if code.len() <= 3 { 1 } else { 2 };
+	let prefix = |other: &str| code.bytes().zip(other.bytes()).take_while(|(a, b)| a == b).count();
+	let mut candidates = codes.iter()
+		.map(|other| (edit_distance(code, other), other))
+		.filter(|&(distance, _)| distance <= max_distance)
+		.collect::<Vec<(usize, &&str)>>();
+	candidates.sort_by_key(|&(distance, other)| (distance, std::cmp::Reverse(prefix(other))));
+	match candidates.is_empty() {
+		true => vec![code.to_string()],
+		false => candidates.into_iter().take(MAX_ALTERNATIVES).map(|(_, other)| other.to_string()).collect(),
+	}
+}
+
+/// The edit distance between two strings, counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters (the optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+	let a = a.chars().collect::<Vec<char>>();
+	let b = b.chars().collect::<Vec<char>>();
+	let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+	for (i, row) in distances.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, distance) in distances[0].iter_mut().enumerate() {
+		*distance = j;
+	}
+	for i in 1..=a.len() {
+		for j in 1..=b.len() {
+			let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+			let mut distance = substitution.min(distances[i - 1][j] + 1).min(distances[i][j - 1] + 1);
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				distance = distance.min(distances[i - 2][j - 2] + 1);
+			}
+			distances[i][j] = distance;
+		}
+	}
+	distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_suggest_locale() {
+		fn case(input: &str, expected: &[&str]) {
+			assert_eq!(suggest_locale(input), expected, "{input:?}");
+		}
+
+		// Well-formed locales
+		case("en-US", &[]);
+		case("en-us", &[]);
+		case("zh-yue-HK", &[]);
+		case("haw", &[]);
+		case("es-419", &[]);
+		case("x-pig-latin", &[]);
+
+		// Structural fixes
+		case("en-USA", &["en-US"]);
+		case("en_US", &["en-US"]);
+		case("ru_RU.UTF-8", &["ru-RU"]);
+		case("sr_RS@latin", &["sr-RS"]);
+		case("de--DE", &["de-DE"]);
+		case("en-US-", &["en-US"]);
+		case("zh-TW-Hant", &["zh-Hant-TW"]);
+		case("eng-GBR", &["en-GB"]);
+		case("ger_DE", &["de-DE"]);
+		case("en-UK", &["en-GB"]);
+		case("sr-YU", &["sr-RS"]);
+		case("pt BR", &["pt-BR"]);
+		case("de-DE-u-co-phonebk_", &["de-DE-u-co-phonebk"]);
+
+		// Language names
+		case("German", &["de"]);
+		case("chinese (simplified)", &["zh-Hans"]);
+
+		// Misspelled subtags
+		case("engl-US", &["en-US", "el-US", "gl-US"]);
+		case("ru-RX", &["ru-RE", "ru-RO", "ru-RS"]);
+
+		// Nothing to suggest
+		case("", &[]);
+		case("-", &[]);
+		case("12-34", &[]);
+	}
+
+	#[test]
+	fn test_edit_distance() {
+		assert_eq!(edit_distance("", ""), 0);
+		assert_eq!(edit_distance("en", "en"), 0);
+		assert_eq!(edit_distance("en", "ne"), 1);
+		assert_eq!(edit_distance("en", "es"), 1);
+		assert_eq!(edit_distance("engl", "en"), 2);
+		assert_eq!(edit_distance("", "US"), 2);
+		assert_eq!(edit_distance("abc", "ca"), 3);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,117 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of already parsed language tags.
+
+use std::borrow::Borrow;
+
+use language_tags::LanguageTag;
+
+use crate::matching::LocaleMatch;
+
+use super::Matcher;
+
+/// Finds the best matching language tag from a list of available tags based on a list of user
+/// tags, like [`best_matching_locale`](super::best_matching_locale) does for strings.
+///
+/// The tags are matched as they are, without serializing and parsing them again. The function
+/// accepts both owned tags and references to them, the matched available tag is returned as it
+/// was passed in.
+///
+/// # Examples
+///
+/// ```
+/// use language_tags::LanguageTag;
+/// use locale_match::bcp47::best_matching_tag;
+///
+///
+/// let available_tags = ["en-US", "ru-BY"].map(|tag| LanguageTag::parse(tag).unwrap());
+/// let user_tags = ["ru-RU", "en"].map(|tag| LanguageTag::parse(tag).unwrap());
+///
+/// let best_match = best_matching_tag(&available_tags, &user_tags);
+///
+/// assert_eq!(best_match, Some(&available_tags[1]));
+/// ```
+pub fn best_matching_tag<T1, T2>(available_tags: impl IntoIterator<Item = T1>, user_tags: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: Borrow<LanguageTag>,
+	T2: Borrow<LanguageTag>
+{
+	Matcher::new().best_matching_tag(available_tags, user_tags)
+}
+
+impl Matcher {
+	/// Finds the best matching language tag like [`best_matching_tag`], using the configuration of
+	/// the matcher.
+	pub fn best_matching_tag<T1, T2>(&self, available_tags: impl IntoIterator<Item = T1>, user_tags: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: Borrow<LanguageTag>,
+		T2: Borrow<LanguageTag>
+	{
+		self.best_matching_tag_with_confidence(available_tags, user_tags).map(|m| m.locale)
+	}
+
+	/// Finds the best matching language tag like [`best_matching_tag`], using the configuration of
+	/// the matcher, and reports the quality of the match like
+	/// [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence).
+	pub fn best_matching_tag_with_confidence<T1, T2>(&self, available_tags: impl IntoIterator<Item = T1>, user_tags: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: Borrow<LanguageTag>,
+		T2: Borrow<LanguageTag>
+	{
+		let available_tags = available_tags.into_iter().collect::<Vec<T1>>();
+		let best_match = self.best_match_for_tags(&available_tags, |i, tag| (i, tag.borrow()), user_tags.into_iter().enumerate());
+
+		best_match.map(|(available_index, user_index, confidence)| LocaleMatch {
+			locale: available_tags.into_iter().nth(available_index).unwrap(),
+			available_index,
+			user_index,
+			confidence,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_tag() {
+		fn case(available_locales: &[&str], user_locales: &[&str]) {
+			let parse = |locales: &[&str]| locales.iter()
+				.map(|locale| LanguageTag::parse(locale).unwrap())
+				.collect::<Vec<LanguageTag>>();
+			let expected = crate::bcp47::best_matching_locale_with_confidence(available_locales.iter().copied(), user_locales)
+				.map(|m| (m.locale, m.available_index, m.user_index, m.confidence));
+			let (available_tags, user_tags) = (parse(available_locales), parse(user_locales));
+			let result = Matcher::new().best_matching_tag_with_confidence(&available_tags, &user_tags)
+				.map(|m| (m.locale.as_str(), m.available_index, m.user_index, m.confidence));
+			assert_eq!(result, expected);
+		}
+
+		case(&["en-US", "ru-BY"], &["ru-RU", "en"]);
+		case(&["en", "pt-BR", "pt-PT"], &["pt-PT"]);
+		case(&["zh", "zh-cmn", "zh-cmn-Hans"], &["zh-Hans"]);
+		case(&["en-US", "fr"], &["de", "fr-CA"]);
+		case(&["en-US"], &["fr"]);
+		case(&[], &["en"]);
+
+		// Owned tags
+		let available_tags = ["en-US", "ru-BY"].map(|tag| LanguageTag::parse(tag).unwrap());
+		let user_tags = [LanguageTag::parse("ru").unwrap()];
+		assert_eq!(best_matching_tag(available_tags, user_tags).map(LanguageTag::into_string), Some("ru-BY".to_string()));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,247 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching against the catalogs of many tenants, see [`MatcherRegistry`].
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use language_tags::LanguageTag;
+
+use crate::matching::LocaleMatch;
+
+use super::Matcher;
+
+/// A registry of prebuilt matchers keyed by tenant, e.g. for SaaS platforms with different
+/// available locales for each customer or project.
+///
+/// The available locales of each tenant are parsed once on insertion, and the lookup of a tenant
+/// is a hash map lookup. The parsed locales are interned, so a locale available to many tenants,
+/// e.g. `en-US`, is parsed and stored only once for all of the tenants sharing a matcher.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::MatcherRegistry;
+///
+///
+/// let mut registry = MatcherRegistry::new();
+/// registry.insert("acme", ["en-US", "de-DE"]);
+/// registry.insert("globex", ["en-US", "fr-FR"]);
+///
+/// assert_eq!(registry.best_matching_locale("acme", ["de-AT", "fr"]), Some("de-DE"));
+/// assert_eq!(registry.best_matching_locale("globex", ["de-AT", "fr"]), Some("fr-FR"));
+/// assert_eq!(registry.best_matching_locale("initech", ["de-AT", "fr"]), None);
+/// ```
+#[derive(Debug)]
+pub struct MatcherRegistry<K> {
+	matcher: Arc<Matcher>,
+	tenants: HashMap<K, Tenant>,
+	/// The parsed available locales keyed by the address of the matcher that parsed them and the
+	/// locale.
+	tags: HashMap<(usize, String), Arc<LanguageTag>>,
+}
+
+/// The prebuilt matcher of a tenant.
+#[derive(Debug)]
+struct Tenant {
+	matcher: Arc<Matcher>,
+	available_tags: Vec<(usize, String, Arc<LanguageTag>)>,
+}
+
+impl<K: Eq + Hash> Default for MatcherRegistry<K> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<K: Eq + Hash> MatcherRegistry<K> {
+	/// Creates an empty registry, in which tenants are matched with [`Matcher::new`].
+	pub fn new() -> Self {
+		Self::with_matcher(Matcher::new())
+	}
+
+	/// Creates an empty registry, in which tenants inserted with [`insert`](Self::insert) are
+	/// matched with the given matcher.
+	pub fn with_matcher(matcher: Matcher) -> Self {
+		Self { matcher: Arc::new(matcher), tenants: HashMap::new(), tags: HashMap::new() }
+	}
+
+	/// Inserts a tenant with its available locales, replacing the tenant if it is already
+	/// registered. The tenant is matched with the matcher of the registry.  
+	/// Malformed available locales are ignored.
+	pub fn insert<T: AsRef<str>>(&mut self, tenant: K, available_locales: impl IntoIterator<Item = T>) {
+		let matcher = Arc::clone(&self.matcher);
+		self.insert_with_matcher(tenant, matcher, available_locales);
+	}
+
+	/// Inserts a tenant with its available locales and its own matcher, replacing the tenant if it
+	/// is already registered. The locales are interned with those of other tenants sharing the same
+	/// matcher, so a matcher shared by several tenants should be passed as clones of the same [`Arc`].  
+	/// Malformed available locales are ignored.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use std::sync::Arc;
+	///
+	/// use locale_match::bcp47::{Matcher, MatcherRegistry};
+	///
+	///
+	/// let mut registry = MatcherRegistry::new();
+	/// registry.insert_with_matcher(1, Arc::new(Matcher::new().with_dotnet_culture_names(true)), ["zh-CHS", "zh-CHT"]);
+	///
+	/// assert_eq!(registry.best_matching_locale(&1, ["zh-Hant-TW"]), Some("zh-CHT"));
+	/// ```
+	pub fn insert_with_matcher<T: AsRef<str>>(&mut self, tenant: K, matcher: Arc<Matcher>, available_locales: impl IntoIterator<Item = T>) {
+		let key = Arc::as_ptr(&matcher) as usize;
+		let available_tags = available_locales.into_iter()
+			.enumerate()
+			.filter_map(|(i, locale)| {
+				let locale = locale.as_ref();
+				let tag = match self.tags.get(&(key, locale.to_string())) {
+					Some(tag) => Arc::clone(tag),
+					None => {
+						let tag = Arc::new(matcher.parse(locale)?);
+						self.tags.insert((key, locale.to_string()), Arc::clone(&tag));
+						tag
+					}
+				};
+				Some((i, locale.to_string(), tag))
+			})
+			.collect();
+		if self.tenants.insert(tenant, Tenant { matcher, available_tags }).is_some() {
+			self.release_unused_tags();
+		}
+	}
+
+	/// Removes a tenant. Returns whether the tenant was registered.
+	pub fn remove<Q>(&mut self, tenant: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Eq + Hash + ?Sized
+	{
+		let removed = self.tenants.remove(tenant).is_some();
+		if removed {
+			self.release_unused_tags();
+		}
+		removed
+	}
+
+	/// Returns whether a tenant is registered.
+	pub fn contains<Q>(&self, tenant: &Q) -> bool
+	where
+		K: Borrow<Q>,
+		Q: Eq + Hash + ?Sized
+	{
+		self.tenants.contains_key(tenant)
+	}
+
+	/// Returns the number of registered tenants.
+	pub fn len(&self) -> usize {
+		self.tenants.len()
+	}
+
+	/// Returns whether no tenants are registered.
+	pub fn is_empty(&self) -> bool {
+		self.tenants.is_empty()
+	}
+
+	/// Returns the number of distinct parsed available locales shared by the tenants.
+	pub fn interned_locales(&self) -> usize {
+		self.tags.len()
+	}
+
+	/// Finds the best matching locale among the available locales of a tenant like
+	/// [`Matcher::best_matching_locale`], using the matcher of the tenant.  
+	/// Returns [`None`] if the tenant is not registered or none of its locales matches.
+	pub fn best_matching_locale<Q, T>(&self, tenant: &Q, user_locales: impl IntoIterator<Item = T>) -> Option<&str>
+	where
+		K: Borrow<Q>,
+		Q: Eq + Hash + ?Sized,
+		T: AsRef<str>
+	{
+		self.best_matching_locale_with_confidence(tenant, user_locales).map(|best_match| best_match.locale)
+	}
+
+	/// Finds the best matching locale among the available locales of a tenant like
+	/// [`Matcher::best_matching_locale_with_confidence`], using the matcher of the tenant.  
+	/// Returns [`None`] if the tenant is not registered or none of its locales matches.
+	pub fn best_matching_locale_with_confidence<Q, T>(&self, tenant: &Q, user_locales: impl IntoIterator<Item = T>) -> Option<LocaleMatch<&str>>
+	where
+		K: Borrow<Q>,
+		Q: Eq + Hash + ?Sized,
+		T: AsRef<str>
+	{
+		let tenant = self.tenants.get(tenant)?;
+		tenant.matcher.best_match(&tenant.available_tags, |_, (i, _, tag)| (*i, &**tag), user_locales)
+			.map(|(i, user_index, confidence)| {
+				let (available_index, locale, _) = &tenant.available_tags[i];
+				LocaleMatch { locale: locale.as_str(), available_index: *available_index, user_index, confidence }
+			})
+	}
+
+	/// Drops the interned locales that are no longer available to any tenant.
+	fn release_unused_tags(&mut self) {
+		self.tags.retain(|_, tag| Arc::strong_count(tag) > 1);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_MatcherRegistry() {
+		let mut registry = MatcherRegistry::new();
+		registry.insert("acme".to_string(), ["en-US", "de-DE", "de_AT"]);
+		registry.insert("globex".to_string(), ["en-US", "fr-FR"]);
+
+		// Lookup
+		assert_eq!(registry.best_matching_locale("acme", ["de-AT", "fr"]), Some("de-DE"));
+		assert_eq!(registry.best_matching_locale("globex", ["de-AT", "fr"]), Some("fr-FR"));
+		assert_eq!(registry.best_matching_locale("initech", ["en"]), None);
+		let best_match = registry.best_matching_locale_with_confidence("acme", ["fr", "de"]).unwrap();
+		assert_eq!((best_match.locale, best_match.available_index, best_match.user_index), ("de-DE", 1, 1));
+		assert_eq!((registry.len(), registry.contains("acme"), registry.contains("initech")), (2, true, false));
+
+		// Interning
+		assert_eq!(registry.interned_locales(), 3);
+		registry.insert("acme".to_string(), ["en-US", "ru-RU"]);
+		assert_eq!(registry.best_matching_locale("acme", ["de-AT"]), None);
+		assert_eq!(registry.interned_locales(), 3);
+		assert!(registry.remove("globex"));
+		assert!(!registry.remove("globex"));
+		assert_eq!(registry.interned_locales(), 2);
+
+		// Matchers of tenants
+		let matcher = Arc::new(Matcher::new().with_dotnet_culture_names(true));
+		registry.insert_with_matcher("initech".to_string(), Arc::clone(&matcher), ["en-US", "zh-CHS"]);
+		registry.insert_with_matcher("umbrella".to_string(), matcher, ["zh-CHS"]);
+		assert_eq!(registry.best_matching_locale("initech", ["zh-Hans"]), Some("zh-CHS"));
+		assert_eq!(registry.best_matching_locale("acme", ["zh-Hans"]), None);
+		assert_eq!(registry.interned_locales(), 4);
+
+		registry.remove("acme");
+		registry.remove("initech");
+		registry.remove("umbrella");
+		assert!(registry.is_empty());
+		assert_eq!(registry.interned_locales(), 0);
+	}
+}
\ No newline at end of file
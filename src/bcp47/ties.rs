@@ -0,0 +1,94 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that returns all of the available locales tied for the best match.
+
+use crate::engine;
+
+use super::Matcher;
+
+/// Finds all of the available locales that match the user locales equally well, i.e. those
+/// that [`best_matching_locale`](super::best_matching_locale) chooses among with its tie break.
+///
+/// The user locales are tried in the order of preference, and the available locales with the
+/// highest score for the first user locale that matches any of them are returned, in the order of
+/// the available locales. The result is empty if none of the locales match.  
+/// This lets the application apply its own tie break, e.g. by the completeness of the
+/// translations.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locales_all_ties;
+///
+///
+/// let available_locales = ["en-US", "en-GB", "en-AU", "ru-RU"];
+///
+/// assert_eq!(best_matching_locales_all_ties(available_locales, ["en-CA"]), ["en-US", "en-GB", "en-AU"]);
+/// assert_eq!(best_matching_locales_all_ties(available_locales, ["en-GB"]), ["en-GB"]);
+/// assert!(best_matching_locales_all_ties(available_locales, ["fr"]).is_empty());
+/// ```
+pub fn best_matching_locales_all_ties<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locales_all_ties(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds all of the best matching locales like [`best_matching_locales_all_ties`], using the
+	/// configuration of the matcher.
+	///
+	/// The tie break of the matcher is not applied, but its scoring is.
+	pub fn best_matching_locales_all_ties<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locales_all_ties(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_locales_all_ties() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: &[&str]) {
+			let ties = best_matching_locales_all_ties(available_locales, user_locales);
+			assert_eq!(ties.into_iter().copied().collect::<Vec<_>>(), expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Single best match
+		case(&["en-US", "en-GB", "ru-RU"], &["en-GB"], &["en-GB"]);
+		case(&["en-US", "ru-RU"], &["ru", "en"], &["ru-RU"]);
+
+		// Tied matches in the order of the available locales
+		case(&["en-US", "ru-RU", "en-GB", "en"], &["en-CA"], &["en-US", "en-GB", "en"]);
+		case(&["pt-PT", "pt-BR", "pt-PT-u-co-foo"], &["pt-PT"], &["pt-PT", "pt-PT-u-co-foo"]);
+		case(&["zh-Hant-TW", "zh-Hant-HK", "zh-Hans-CN"], &["zh-Hant-MO"], &["zh-Hant-TW", "zh-Hant-HK"]);
+
+		// The first matching user locale decides
+		case(&["de-DE", "de-AT", "en-US", "en-GB"], &["fr", "en", "de"], &["en-US", "en-GB"]);
+
+		// No match and malformed locales
+		case(&["en-US", "en-GB"], &["fr"], &[]);
+		case(&["en_US", "en-GB", "en-US"], &["en_CA", "en"], &["en-GB", "en-US"]);
+		case(&[], &["en"], &[]);
+	}
+}
\ No newline at end of file
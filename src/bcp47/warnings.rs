@@ -0,0 +1,99 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that reports the ignored malformed locales.
+
+use crate::engine;
+use crate::matching::{LocaleMatch, Warning};
+
+use super::Matcher;
+
+/// Finds the best matching locale like [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence),
+/// and reports each malformed locale ignored during matching as a [`Warning`].
+///
+/// All of the user locales are checked, including those after the one the match was found for,
+/// so typos in catalogs and configurations are surfaced even if the matching succeeds.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale_with_warnings;
+/// use locale_match::matching::LocaleKind;
+///
+///
+/// let (best_match, warnings) = best_matching_locale_with_warnings(["en-US", "ru_RU"], ["ru-RU", "en", "de-"]);
+///
+/// assert_eq!(best_match.map(|m| m.locale), Some("en-US"));
+/// assert_eq!(warnings.iter().map(|w| (w.kind, w.input.as_str())).collect::<Vec<_>>(), [
+///     (LocaleKind::Available, "ru_RU"),
+///     (LocaleKind::User, "de-"),
+/// ]);
+/// ```
+pub fn best_matching_locale_with_warnings<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> (Option<LocaleMatch<T1>>, Vec<Warning>)
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locale_with_warnings(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_locale_with_warnings`], using the
+	/// configuration of the matcher.
+	pub fn best_matching_locale_with_warnings<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> (Option<LocaleMatch<T1>>, Vec<Warning>)
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locale_with_warnings(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::LocaleKind;
+
+	#[test]
+	fn test_best_matching_locale_with_warnings() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected_warnings: &[(LocaleKind, usize, &str)]) {
+			let (best_match, warnings) = best_matching_locale_with_warnings(available_locales, user_locales);
+			assert_eq!(best_match, super::super::best_matching_locale_with_confidence(available_locales, user_locales));
+			assert_eq!(warnings.iter().map(|w| (w.kind, w.index, w.input.as_str())).collect::<Vec<_>>(), expected_warnings);
+			assert!(warnings.iter().all(|w| !w.reason.is_empty()));
+		}
+
+		// Well-formed locales
+		case(&["en-US", "ru-RU"], &["ru", "en"], &[]);
+		case(&[], &[], &[]);
+
+		// Malformed locales
+		case(&["en_US", "en-GB", ""], &["en-US"], &[(LocaleKind::Available, 0, "en_US"), (LocaleKind::Available, 2, "")]);
+		case(&["en-US"], &["en-", "en", "e"], &[(LocaleKind::User, 0, "en-"), (LocaleKind::User, 2, "e")]);
+		case(&["en-US", "de_DE"], &["fr-FR_x"], &[(LocaleKind::Available, 1, "de_DE"), (LocaleKind::User, 0, "fr-FR_x")]);
+		// Suggested replacements
+		let (_, warnings) = best_matching_locale_with_warnings(["en_US", "en-GB", "12"], ["en-USA"]);
+		assert_eq!(warnings.iter().map(|w| w.replacement.as_deref()).collect::<Vec<_>>(), [Some("en-US"), None]);
+	}
+
+	#[test]
+	fn test_lcid_inputs() {
+		let matcher = Matcher::new().with_lcid_inputs(true);
+		let (best_match, warnings) = matcher.best_matching_locale_with_warnings(["en-US", "ru-RU"], ["1049"]);
+		assert_eq!(best_match.map(|m| m.locale), Some("ru-RU"));
+		assert!(warnings.is_empty());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,689 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The `locale-match` command-line tool for selecting locales in shell scripts, packaging scripts and
+//! launchers.
+//!
+//! * `locale-match dir ./po --user "$LANG"` — finds the translation file in a directory that best
+//!   matches the user locales and prints its locale and path, separated by a tab.
+//! * `locale-match explain --available de-DE,en-US --user de-AT` — prints the scores of all pairs of
+//!   available and user locales and the reason the best match was chosen.
+//! * `locale-match env --available de_DE.UTF-8,en_US.UTF-8` — matches the locales of the environment
+//!   (`LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and `LANG`) and prints a single locale, e.g. for
+//!   `export LANG="$(locale-match env --available ...)"` in wrapper scripts and containers.
+//!
+//! With `--json`, the tool prints the match, its score, the matched user locale and the scores of
+//! all candidates as a JSON object instead, e.g. for CI jobs.  
+//! The tool exits with the status 1 if no locale matches, and with the status 2 on invalid arguments.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+
+use locale_match::auto;
+use locale_match::bcp47::{self, TagParts};
+use locale_match::files::available_locales_from_pattern;
+use locale_match::matching::{LocaleMatch, Matrix};
+use locale_match::posix::{self, PosixLocale};
+
+/// The extensions of the translation files discovered in a directory, in the order of preference
+/// for files of the same locale.
+const TRANSLATION_EXTENSIONS: [&str; 4] = ["po", "mo", "ftl", "json"];
+
+/// Selects the best match for user's preferred locales from available locales.
+#[derive(Debug, Parser)]
+#[command(name = "locale-match", version, about)]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+	/// Print the result as JSON.
+	#[arg(long, global = true)]
+	json: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Finds the translation file (`.po`, `.mo`, `.ftl` or `.json`) in a directory that best matches
+	/// the user locales and prints its locale and path.
+	Dir {
+		/// The directory with the translation files, e.g. `./po`.
+		dir: PathBuf,
+		#[command(flatten)]
+		user: UserArgs,
+	},
+	/// Prints the scores of all pairs of available and user locales and the reason the best match
+	/// was chosen.
+	Explain {
+		/// An available locale; several locales can be separated by `,`.
+		#[arg(short, long, value_name = "LOCALE", value_delimiter = ',', required_unless_present = "dir")]
+		available: Vec<String>,
+		/// The directory with the translation files to take the available locales from.
+		#[arg(short, long, conflicts_with = "available")]
+		dir: Option<PathBuf>,
+		#[command(flatten)]
+		user: UserArgs,
+	},
+	/// Matches the locales of the environment (`LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and `LANG`) and
+	/// prints the best matching available locale, e.g. as a value of `LANG`.
+	Env {
+		/// An available locale; several locales can be separated by `,`.
+		#[arg(short, long, value_name = "LOCALE", value_delimiter = ',', required = true)]
+		available: Vec<String>,
+		/// The locale to print if no available locale matches, e.g. `C.UTF-8`.
+		#[arg(long, value_name = "LOCALE")]
+		default: Option<String>,
+	},
+}
+
+#[derive(Debug, Args)]
+struct UserArgs {
+	/// A user locale, in the order of preference; several locales can be separated by `:`, like in
+	/// the `LANGUAGE` environment variable. Defaults to the locales of the environment.
+	#[arg(short, long = "user", value_name = "LOCALE")]
+	user: Vec<String>,
+}
+
+impl UserArgs {
+	/// Splits the user locales, or returns the locales of the environment if none are given.
+	fn locales(&self) -> Vec<String> {
+		if self.user.is_empty() {
+			return posix::user_locales_from_env();
+		}
+		self.user.iter()
+			.flat_map(|arg| arg.split(':'))
+			.filter(|locale| !locale.is_empty())
+			.map(str::to_string)
+			.collect()
+	}
+}
+
+/// An available locale, with the path of its translation file if it was found in a directory.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Available {
+	locale: String,
+	path: Option<PathBuf>,
+}
+
+impl AsRef<str> for Available {
+	fn as_ref(&self) -> &str {
+		&self.locale
+	}
+}
+
+/// An available locale and how well it matches the user locales.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Candidate {
+	locale: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	path: Option<PathBuf>,
+	/// The confidence of the match, or [`None`] if the locale does not match any user locale.
+	score: Option<f64>,
+	/// The user locale the locale matches, exactly as it was given.
+	user_locale: Option<String>,
+}
+
+/// The result of matching the user locales to the translation files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Report {
+	/// The best matching file, if any.
+	#[serde(rename = "match")]
+	best_match: Option<Candidate>,
+	/// All of the files, each matched on its own.
+	candidates: Vec<Candidate>,
+}
+
+/// The reasoning behind the best match of the user locales.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Explanation {
+	format: Format,
+	available_locales: Vec<String>,
+	user_locales: Vec<String>,
+	/// The score of each available locale (rows) for each user locale (columns), or [`None`] if they
+	/// cannot match.
+	scores: Vec<Vec<Option<f64>>>,
+	#[serde(rename = "match")]
+	best_match: Option<Candidate>,
+	/// The user locales preferred over the matched one, which match no available locale.
+	unmatched_user_locales: Vec<String>,
+	/// The subtags of the best match and of the user locale it matches.
+	subtags: Vec<SubtagComparison>,
+	/// The available locales scored equally with the best match, which was chosen as the first of
+	/// them in the list.
+	ties: Vec<String>,
+}
+
+/// A subtag of an available and a user locale, in the format of the available locales.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SubtagComparison {
+	subtag: &'static str,
+	available: Option<String>,
+	user: Option<String>,
+	equal: bool,
+}
+
+/// The output of a command.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Output {
+	Report(Report),
+	Explanation(Explanation),
+}
+
+fn main() -> ExitCode {
+	let cli = Cli::parse();
+	let result = match cli.command {
+		Command::Dir { dir, user } => match_dir(&dir, &user.locales()).map(Output::Report),
+		Command::Explain { available, dir, user } => {
+			let available_locales = match dir {
+				Some(dir) => translation_files(&dir),
+				None => Ok(available.into_iter().map(|locale| Available { locale, path: None }).collect()),
+			};
+			available_locales.map(|available_locales| Output::Explanation(explain(&available_locales, &user.locales())))
+		}
+		Command::Env { available, default } => Ok(Output::Report(match_env(available, &posix::user_locales_from_env(), default))),
+	};
+	let output = match result {
+		Ok(output) => output,
+		Err(message) => {
+			eprintln!("locale-match: {message}");
+			return ExitCode::from(2);
+		}
+	};
+	if cli.json {
+		match serde_json::to_string_pretty(&output) {
+			Ok(json) => println!("{json}"),
+			Err(error) => {
+				eprintln!("locale-match: {error}");
+				return ExitCode::from(2);
+			}
+		}
+	} else {
+		output.print();
+	}
+	let best_match = match &output {
+		Output::Report(report) => &report.best_match,
+		Output::Explanation(explanation) => &explanation.best_match,
+	};
+	match best_match {
+		Some(_) => ExitCode::SUCCESS,
+		None => ExitCode::from(1),
+	}
+}
+
+impl Output {
+	/// Prints the output as text.
+	fn print(&self) {
+		match self {
+			Self::Report(Report { best_match: Some(best_match), .. }) => match &best_match.path {
+				Some(path) => println!("{}\t{}", best_match.locale, path.display()),
+				None => println!("{}", best_match.locale),
+			},
+			Self::Report(Report { best_match: None, .. }) => eprintln!("locale-match: no matching locale"),
+			Self::Explanation(explanation) => print!("{explanation}"),
+		}
+	}
+}
+
+impl std::fmt::Display for Explanation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let width = |values: &mut dyn Iterator<Item = usize>| values.max().unwrap_or(0);
+		let rows_width = width(&mut self.available_locales.iter().map(String::len));
+		let columns_widths = self.user_locales.iter()
+			.map(|locale| locale.len().max(4))
+			.collect::<Vec<usize>>();
+
+		writeln!(f, "Scores of the available locales (rows) for the user locales (columns), as {}:", self.format)?;
+		write!(f, "{:rows_width$}", "")?;
+		for (locale, width) in self.user_locales.iter().zip(&columns_widths) {
+			write!(f, "  {locale:>width$}")?;
+		}
+		writeln!(f)?;
+		for (locale, scores) in self.available_locales.iter().zip(&self.scores) {
+			write!(f, "{locale:rows_width$}")?;
+			for (score, width) in scores.iter().zip(&columns_widths) {
+				match score {
+					Some(score) => write!(f, "  {score:>width$.2}")?,
+					None => write!(f, "  {:>width$}", "-")?,
+				}
+			}
+			writeln!(f)?;
+		}
+		writeln!(f)?;
+
+		let Some(best_match) = &self.best_match else {
+			return writeln!(f, "No available locale matches any user locale.");
+		};
+		let user_locale = best_match.user_locale.as_deref().unwrap_or_default();
+		writeln!(f, "Best match: {} for the user locale {user_locale}", best_match.locale)?;
+		if !self.unmatched_user_locales.is_empty() {
+			writeln!(f, "Preferred user locales without a match: {}", self.unmatched_user_locales.join(", "))?;
+		}
+		let subtag_width = width(&mut self.subtags.iter().map(|s| s.subtag.len()));
+		let value_width = width(&mut self.subtags.iter().flat_map(|s| [&s.available, &s.user]).map(|v| v.as_deref().unwrap_or("-").len()));
+		writeln!(f, "Subtags (available, user):")?;
+		for subtag in &self.subtags {
+			let verdict = match (&subtag.available, &subtag.user) {
+				(Some(_), Some(_)) if subtag.equal => "equal",
+				(Some(_), Some(_)) => "different",
+				(Some(_), None) => "only in the available locale",
+				_ => "only in the user locale",
+			};
+			let (available, user) = (subtag.available.as_deref().unwrap_or("-"), subtag.user.as_deref().unwrap_or("-"));
+			writeln!(f, "  {:subtag_width$}  {available:value_width$}  {user:value_width$}  {verdict}", subtag.subtag)?;
+		}
+		if !self.ties.is_empty() {
+			writeln!(f, "Tied with {}, chosen by the order of the available locales", self.ties.join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+/// Finds the translation file in the directory that best matches the user locales.
+fn match_dir(dir: &Path, user_locales: &[String]) -> Result<Report, String> {
+	let available_locales = translation_files(dir)?;
+	Ok(match_locales(&available_locales, user_locales))
+}
+
+/// Returns the translation files in the directory, one for each locale.
+fn translation_files(dir: &Path) -> Result<Vec<Available>, String> {
+	if !dir.is_dir() {
+		return Err(format!("{} is not a directory", dir.display()));
+	}
+	let pattern_dir = dir.to_string_lossy();
+	let pattern_dir = pattern_dir.trim_end_matches(['/', std::path::MAIN_SEPARATOR]);
+	let mut found = Vec::new();
+	for extension in TRANSLATION_EXTENSIONS {
+		let files = available_locales_from_pattern(&format!("{pattern_dir}/{{locale}}.{extension}"))
+			.map_err(|error| format!("cannot read {}: {error}", dir.display()))?;
+		found.extend(files);
+	}
+	let mut locales = HashSet::new();
+	let mut files = found.into_iter()
+		.filter(|file| locales.insert(file.locale.clone()))
+		.map(|file| Available { locale: file.locale, path: Some(file.path) })
+		.collect::<Vec<Available>>();
+	if files.is_empty() {
+		return Err(format!("no translation files found in {}", dir.display()));
+	}
+	files.sort();
+	Ok(files)
+}
+
+/// Matches the user locales to the available locales, falling back to the default locale.  
+/// The default locale is reported as a match without a score.
+fn match_env(available_locales: Vec<String>, user_locales: &[String], default: Option<String>) -> Report {
+	let available_locales = available_locales.into_iter()
+		.map(|locale| Available { locale, path: None })
+		.collect::<Vec<Available>>();
+	let mut report = match_locales(&available_locales, user_locales);
+	if report.best_match.is_none() {
+		report.best_match = default.map(|locale| Candidate { locale, path: None, score: None, user_locale: None });
+	}
+	report
+}
+
+/// Matches the user locales to each of the available locales and to all of them.
+fn match_locales(available_locales: &[Available], user_locales: &[String]) -> Report {
+	let matcher = LocaleMatcher::new(available_locales, user_locales);
+	let candidates = available_locales.iter()
+		.map(|available| matcher.candidate(available, matcher.best_match(std::slice::from_ref(available))))
+		.collect();
+	let best_match = matcher.best_match(available_locales)
+		.map(|m| matcher.candidate(m.locale, Some(m)));
+	Report { best_match, candidates }
+}
+
+/// Explains the best match of the user locales among the available locales.
+fn explain(available_locales: &[Available], user_locales: &[String]) -> Explanation {
+	let matcher = LocaleMatcher::new(available_locales, user_locales);
+	let format = matcher.format;
+	let locale_match = format.explain(available_locales, &matcher.converted);
+
+	let (mut unmatched_user_locales, mut subtags, mut ties) = (Vec::new(), Vec::new(), Vec::new());
+	if let Some(m) = &locale_match {
+		unmatched_user_locales = user_locales[..m.user_index].to_vec();
+		let user_locale = &matcher.converted[m.user_index];
+		subtags = format.compare_subtags(&m.locale.locale, user_locale);
+		ties = format.ties(available_locales, user_locale).into_iter()
+			.filter(|available| !std::ptr::eq(*available, m.locale))
+			.map(|available| available.locale.clone())
+			.collect();
+	}
+	Explanation {
+		format,
+		available_locales: available_locales.iter().map(|available| available.locale.clone()).collect(),
+		user_locales: user_locales.to_vec(),
+		scores: format.scores(available_locales, &matcher.converted),
+		best_match: locale_match.map(|m| matcher.candidate(m.locale, Some(m))),
+		unmatched_user_locales,
+		subtags,
+		ties,
+	}
+}
+
+/// The format of the available locales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+	Bcp47,
+	Posix,
+}
+
+impl std::fmt::Display for Format {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::Bcp47 => "BCP 47",
+			Self::Posix => "POSIX",
+		})
+	}
+}
+
+impl Format {
+	/// Returns the format of the available locales: BCP 47 if any of them has a `-` and none of them
+	/// is a POSIX locale according to [`auto::is_posix`], e.g. `en-US.ftl`, and POSIX otherwise, e.g.
+	/// `en_US.po` or `de.po`.
+	fn of(available_locales: &[Available]) -> Self {
+		let has_bcp47 = available_locales.iter().any(|available| available.locale.contains('-'));
+		match has_bcp47 && !available_locales.iter().any(|available| auto::is_posix(&available.locale)) {
+			true => Self::Bcp47,
+			false => Self::Posix,
+		}
+	}
+
+	/// Converts a user locale to the format.  
+	/// Returns [`None`] if the locale has no equivalent in the format, e.g. `C` in BCP 47.
+	fn convert(self, locale: &str) -> Option<String> {
+		match self {
+			Self::Bcp47 if auto::is_posix(locale) => posix::to_bcp47(locale),
+			Self::Bcp47 => Some(locale.to_string()),
+			Self::Posix => Some(to_posix(locale)),
+		}
+	}
+
+	/// Matches the converted user locales to the available locales.
+	fn best_match<'a>(self, available_locales: &'a [Available], user_locales: &[String]) -> Option<LocaleMatch<&'a Available>> {
+		match self {
+			Self::Bcp47 => bcp47::best_matching_locale_with_confidence(available_locales, user_locales),
+			Self::Posix => posix::best_matching_locale_with_confidence(available_locales, user_locales),
+		}
+	}
+
+	/// Matches the converted user locales to the available locales like [`best_match`](Self::best_match)
+	/// with the explanation of the library, taking the best match from it.
+	fn explain<'a>(self, available_locales: &'a [Available], user_locales: &[String]) -> Option<LocaleMatch<&'a Available>> {
+		match self {
+			Self::Bcp47 => bcp47::Matcher::new().explain(available_locales, user_locales).best_match,
+			Self::Posix => posix::Matcher::new().explain(available_locales, user_locales).best_match,
+		}
+	}
+
+	/// Returns the score of each available locale (rows) for each converted user locale (columns).
+	fn scores(self, available_locales: &[Available], user_locales: &[String]) -> Vec<Vec<Option<f64>>> {
+		match self {
+			Self::Bcp47 => scores(&bcp47::score_matrix(available_locales, user_locales)),
+			Self::Posix => scores(&posix::score_matrix(available_locales, user_locales)),
+		}
+	}
+
+	/// Returns the available locales scored equally with the best match for a converted user locale,
+	/// including the best match itself.
+	fn ties<'a>(self, available_locales: &'a [Available], user_locale: &str) -> Vec<&'a Available> {
+		match self {
+			Self::Bcp47 => bcp47::best_matching_locales_all_ties(available_locales, [user_locale]),
+			Self::Posix => posix::best_matching_locales_all_ties(available_locales, [user_locale]),
+		}
+	}
+
+	/// Compares the subtags of two locales in the format, skipping the subtags absent in both.
+	fn compare_subtags(self, available: &str, user: &str) -> Vec<SubtagComparison> {
+		let (available, user) = match self {
+			Self::Bcp47 => (bcp47_subtags(available), bcp47_subtags(user)),
+			Self::Posix => (posix_subtags(available), posix_subtags(user)),
+		};
+		available.into_iter()
+			.zip(user)
+			.filter(|((_, a), (_, u))| a.is_some() || u.is_some())
+			.map(|((subtag, a), (_, u))| SubtagComparison {
+				subtag,
+				equal: a.as_ref().zip(u.as_ref()).is_some_and(|(a, u)| a.eq_ignore_ascii_case(u)),
+				available: a,
+				user: u,
+			})
+			.collect()
+	}
+}
+
+/// The scores of a score matrix by available locale and then by user locale.
+fn scores<S>(matrix: &Matrix<S>) -> Vec<Vec<Option<f64>>> {
+	(0..matrix.available_len())
+		.map(|i| matrix.row(i).iter().map(|candidate| candidate.as_ref().map(|c| c.score)).collect())
+		.collect()
+}
+
+/// The subtags of a BCP 47 locale, all absent if the locale is malformed.
+fn bcp47_subtags(locale: &str) -> Vec<(&'static str, Option<String>)> {
+	let tag = TagParts::parse(locale);
+	let subtags = [
+		("language",          tag.map(|tag| tag.language())),
+		("extended language", tag.and_then(|tag| tag.extended_language())),
+		("script",            tag.and_then(|tag| tag.script())),
+		("region",            tag.and_then(|tag| tag.region())),
+		("variant",           tag.and_then(|tag| tag.variant())),
+		("extension",         tag.and_then(|tag| tag.extension())),
+		("private use",       tag.and_then(|tag| tag.private_use())),
+	];
+	subtags.into_iter().map(|(subtag, value)| (subtag, value.map(str::to_string))).collect()
+}
+
+/// The parts of a POSIX locale.
+fn posix_subtags(locale: &str) -> Vec<(&'static str, Option<String>)> {
+	let locale = PosixLocale::parse(locale);
+	let subtags = [
+		("language",  Some(locale.language()).filter(|language| !language.is_empty())),
+		("territory", locale.territory()),
+		("codeset",   locale.codeset()),
+		("modifier",  locale.modifier()),
+	];
+	subtags.into_iter().map(|(subtag, value)| (subtag, value.map(str::to_string))).collect()
+}
+
+/// Matches the user locales to the available locales in the format of the available locales.
+struct LocaleMatcher<'a> {
+	format: Format,
+	user_locales: &'a [String],
+	/// The user locales converted to the format, empty if they have no equivalent in it, so they
+	/// never match.
+	converted: Vec<String>,
+}
+
+impl<'a> LocaleMatcher<'a> {
+	fn new(available_locales: &[Available], user_locales: &'a [String]) -> Self {
+		let format = Format::of(available_locales);
+		let converted = user_locales.iter()
+			.map(|locale| format.convert(locale).unwrap_or_default())
+			.collect();
+		Self { format, user_locales, converted }
+	}
+
+	fn best_match<'b>(&self, available_locales: &'b [Available]) -> Option<LocaleMatch<&'b Available>> {
+		self.format.best_match(available_locales, &self.converted)
+	}
+
+	fn candidate(&self, available: &Available, locale_match: Option<LocaleMatch<&Available>>) -> Candidate {
+		Candidate {
+			locale: available.locale.clone(),
+			path: available.path.clone(),
+			score: locale_match.as_ref().map(|m| m.confidence),
+			user_locale: locale_match.map(|m| self.user_locales[m.user_index].clone()),
+		}
+	}
+}
+
+/// Converts a BCP 47 locale to a POSIX locale, e.g. `sr-Latn-RS` to `sr_RS@latin`.  
+/// Other locales are returned unchanged.
+fn to_posix(locale: &str) -> String {
+	let Some(tag) = TagParts::parse(locale).filter(|_| locale.contains('-')) else {
+		return locale.to_string();
+	};
+	let mut posix = tag.language().to_string();
+	if let Some(region) = tag.region() {
+		posix.push('_');
+		posix.push_str(region);
+	}
+	let modifier = match tag.script().map(str::to_ascii_lowercase).as_deref() {
+		Some("latn") => Some("latin"),
+		Some("cyrl") => Some("cyrillic"),
+		Some("deva") => Some("devanagari"),
+		Some("arab") => Some("arabic"),
+		_ => None,
+	};
+	if let Some(modifier) = modifier {
+		posix.push('@');
+		posix.push_str(modifier);
+	}
+	posix
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::fs;
+
+	#[test]
+	fn test_user_locales() {
+		let args = UserArgs { user: vec!["de_AT.UTF-8:de".to_string(), "en".to_string(), "::".to_string()] };
+		assert_eq!(args.locales(), ["de_AT.UTF-8", "de", "en"]);
+	}
+
+	#[test]
+	fn test_to_posix() {
+		assert_eq!(to_posix("sr-Latn-RS"), "sr_RS@latin");
+		assert_eq!(to_posix("pt-BR"), "pt_BR");
+		assert_eq!(to_posix("de"), "de");
+		assert_eq!(to_posix("en_US.UTF-8"), "en_US.UTF-8");
+	}
+
+	#[test]
+	fn test_match_dir() {
+		let dir = std::env::temp_dir().join(format!("locale-match-cli-{}", std::process::id()));
+		for (subdir, names) in [
+			("po", &["de.po", "de.mo", "pt_BR.po", "sr@latin.po", "README.md"][..]),
+			("ftl", &["en-US.ftl", "pt-BR.ftl", "sr-Latn.ftl"]),
+			("empty", &[]),
+		] {
+			fs::create_dir_all(dir.join(subdir)).unwrap();
+			for name in names {
+				fs::write(dir.join(subdir).join(name), []).unwrap();
+			}
+		}
+
+		let case = |subdir: &str, user_locales: &[&str]| match_dir(
+			&dir.join(subdir), &user_locales.iter().map(|l| l.to_string()).collect::<Vec<String>>()
+		).map(|report| report.best_match.map(|m| (m.locale, m.path.unwrap())));
+		let po = case("po", &["de_AT.UTF-8"]);
+		let po_from_bcp47 = case("po", &["pt-PT", "de"]);
+		let po_script = case("po", &["sr-Latn-RS"]);
+		let ftl = case("ftl", &["pt_PT.UTF-8", "en"]);
+		let ftl_modifier = case("ftl", &["C", "sr_RS@latin"]);
+		let no_match = case("ftl", &["fr_FR"]);
+		let empty = case("empty", &["en"]);
+		let missing = case("missing", &["en"]);
+		fs::remove_dir_all(&dir).unwrap();
+
+		// The `.po` file is preferred over the `.mo` file
+		assert_eq!(po, Ok(Some(("de".to_string(), dir.join("po").join("de.po")))));
+		assert_eq!(po_from_bcp47, Ok(Some(("pt_BR".to_string(), dir.join("po").join("pt_BR.po")))));
+		assert_eq!(po_script.unwrap().unwrap().0, "sr@latin");
+		assert_eq!(ftl, Ok(Some(("pt-BR".to_string(), dir.join("ftl").join("pt-BR.ftl")))));
+		assert_eq!(ftl_modifier.unwrap().unwrap().0, "sr-Latn");
+		assert_eq!(no_match, Ok(None));
+		assert!(empty.is_err());
+		assert!(missing.is_err());
+	}
+
+	#[test]
+	fn test_json() {
+		let files = ["de", "en_US", "ru"].map(|locale| Available { locale: locale.to_string(), path: Some(PathBuf::from(format!("{locale}.po"))) });
+		let report = match_locales(&files, &["ru_RU.UTF-8".to_string(), "en".to_string()]);
+		let json = serde_json::to_value(&report).unwrap();
+
+		assert_eq!(json["match"]["locale"], "ru");
+		assert_eq!(json["match"]["path"], "ru.po");
+		assert_eq!(json["match"]["user_locale"], "ru_RU.UTF-8");
+		assert!(json["match"]["score"].as_f64().unwrap() > 0.0);
+		assert_eq!(json["candidates"].as_array().unwrap().len(), 3);
+		assert_eq!(json["candidates"][0]["score"], serde_json::Value::Null);
+		assert_eq!(json["candidates"][0]["user_locale"], serde_json::Value::Null);
+		assert_eq!(json["candidates"][1]["user_locale"], "en");
+		assert_eq!(json["candidates"][2], json["match"]);
+
+		let report = match_locales(&files, &["fr".to_string()]);
+		assert_eq!(serde_json::to_value(&report).unwrap()["match"], serde_json::Value::Null);
+	}
+
+	#[test]
+	fn test_explain() {
+		let available = |locales: &[&str]| locales.iter().map(|l| Available { locale: l.to_string(), path: None }).collect::<Vec<Available>>();
+		let user = |locales: &[&str]| locales.iter().map(|l| l.to_string()).collect::<Vec<String>>();
+
+		let explanation = explain(&available(&["en-US", "pt-PT", "pt-BR"]), &user(&["fr", "pt_AO.UTF-8", "en"]));
+		assert_eq!(explanation.format, Format::Bcp47);
+		assert_eq!(explanation.scores.len(), 3);
+		assert_eq!(explanation.scores[0][0], None);
+		assert!(explanation.scores[0][2].is_some());
+		assert_eq!(explanation.scores[1][1], explanation.scores[2][1]);
+		let best_match = explanation.best_match.as_ref().unwrap();
+		assert_eq!((best_match.locale.as_str(), best_match.user_locale.as_deref()), ("pt-PT", Some("pt_AO.UTF-8")));
+		assert_eq!(explanation.unmatched_user_locales, ["fr"]);
+		assert_eq!(explanation.ties, ["pt-BR"]);
+		assert_eq!(explanation.subtags, [
+			SubtagComparison { subtag: "language", available: Some("pt".to_string()), user: Some("pt".to_string()), equal: true },
+			SubtagComparison { subtag: "region", available: Some("PT".to_string()), user: Some("AO".to_string()), equal: false },
+		]);
+		let text = explanation.to_string();
+		assert!(text.contains("Best match: pt-PT for the user locale pt_AO.UTF-8"));
+		assert!(text.contains("Tied with pt-BR"));
+
+		let explanation = explain(&available(&["de_DE.UTF-8", "de_AT"]), &user(&["de-AT"]));
+		assert_eq!(explanation.format, Format::Posix);
+		assert_eq!(explanation.best_match.unwrap().locale, "de_AT");
+		assert!(explanation.ties.is_empty());
+
+		let explanation = explain(&available(&["en"]), &user(&["fr"]));
+		assert_eq!(explanation.best_match, None);
+		assert!(explanation.to_string().contains("No available locale matches"));
+	}
+
+	#[test]
+	fn test_match_env() {
+		let case = |user_locales: &[&str], default: Option<&str>| match_env(
+			vec!["de_DE.UTF-8".to_string(), "en_US.UTF-8".to_string()],
+			&user_locales.iter().map(|l| l.to_string()).collect::<Vec<String>>(),
+			default.map(str::to_string),
+		).best_match.map(|m| (m.locale, m.score.is_some()));
+
+		assert_eq!(case(&["de_AT.UTF-8", "de_AT"], None), Some(("de_DE.UTF-8".to_string(), true)));
+		assert_eq!(case(&["fr", "en"], Some("C.UTF-8")), Some(("en_US.UTF-8".to_string(), true)));
+		assert_eq!(case(&["fr"], Some("C.UTF-8")), Some(("C.UTF-8".to_string(), false)));
+		assert_eq!(case(&["fr"], None), None);
+		assert_eq!(case(&[], None), None);
+	}
+}
\ No newline at end of file
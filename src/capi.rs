@@ -0,0 +1,159 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A C API for matching locales, so the library can be used from C and C++ applications.
+//!
+//! The functions take arrays of NUL-terminated strings and return the index of the best matching
+//! available locale, or `-1` if none of the available locales matches.  
+//! Null pointers and strings that are not valid UTF-8 are ignored like invalid locales.
+//!
+//! The header `include/locale_match.h` is generated with [cbindgen](https://github.com/mozilla/cbindgen)
+//! from the `cbindgen.toml` configuration, and the library can be built as a static or dynamic
+//! library with `cargo rustc --release --features capi --crate-type staticlib` (or `cdylib`).
+//!
+//! # Examples
+//!
+//! ```c
+//! #include "locale_match.h"
+//!
+//! const char* available[] = {"en-US", "ru-BY"};
+//! const char* user[] = {"ru-RU", "en"};
+//!
+//! ptrdiff_t index = locale_match_best_bcp47(available, 2, user, 2);
+//! // index == 1
+//! ```
+
+use std::ffi::{c_char, CStr};
+
+/// Finds the best matching locale from an array of available BCP 47 locales based on an array of
+/// user BCP 47 locales.
+///
+/// See [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) for the details of the
+/// matching.  
+/// Returns the index of the best matching available locale, or `-1` if none of them matches.
+///
+/// # Safety
+///
+/// `available` must point to `n` pointers and `user` must point to `m` pointers, each of which is
+/// either null or points to a NUL-terminated string. The arrays may be null if their lengths are `0`.
+#[cfg(feature = "bcp47")]
+#[no_mangle]
+pub unsafe extern "C" fn locale_match_best_bcp47(available: *const *const c_char, n: usize, user: *const *const c_char, m: usize) -> isize {
+	let available_locales = strings(available, n);
+	let user_locales = strings(user, m).map(|Indexed(_, locale)| locale);
+	index(crate::bcp47::best_matching_locale(available_locales, user_locales))
+}
+
+/// Finds the best matching locale from an array of available POSIX locales based on an array of
+/// user POSIX locales.
+///
+/// See [`posix::best_matching_locale`](crate::posix::best_matching_locale) for the details of the
+/// matching.  
+/// Returns the index of the best matching available locale, or `-1` if none of them matches.
+///
+/// # Safety
+///
+/// `available` must point to `n` pointers and `user` must point to `m` pointers, each of which is
+/// either null or points to a NUL-terminated string. The arrays may be null if their lengths are `0`.
+#[cfg(feature = "posix")]
+#[no_mangle]
+pub unsafe extern "C" fn locale_match_best_posix(available: *const *const c_char, n: usize, user: *const *const c_char, m: usize) -> isize {
+	let available_locales = strings(available, n);
+	let user_locales = strings(user, m).map(|Indexed(_, locale)| locale);
+	index(crate::posix::best_matching_locale(available_locales, user_locales))
+}
+
+/// A locale with its index in the array of locales.
+struct Indexed<'a>(usize, &'a str);
+
+impl AsRef<str> for Indexed<'_> {
+	fn as_ref(&self) -> &str {
+		self.1
+	}
+}
+
+/// Reads an array of C strings, skipping null pointers and strings that are not valid UTF-8.
+///
+/// # Safety
+///
+/// See the safety requirements of the public functions.
+unsafe fn strings<'a>(array: *const *const c_char, len: usize) -> impl Iterator<Item = Indexed<'a>> {
+	let pointers: &[*const c_char] = match array.is_null() {
+		true => &[],
+		false => std::slice::from_raw_parts(array, len),
+	};
+	pointers.iter()
+		.enumerate()
+		.filter(|(_, pointer)| !pointer.is_null())
+		.filter_map(|(i, &pointer)| CStr::from_ptr(pointer).to_str().ok().map(|locale| Indexed(i, locale)))
+}
+
+fn index(locale: Option<Indexed>) -> isize {
+	locale.map_or(-1, |Indexed(i, _)| i as isize)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::ffi::CString;
+	use std::ptr::null;
+
+	fn call(function: unsafe extern "C" fn(*const *const c_char, usize, *const *const c_char, usize) -> isize, available: &[Option<&[u8]>], user: &[Option<&[u8]>]) -> isize {
+		let to_c = |strings: &[Option<&[u8]>]| strings.iter()
+			.map(|s| s.map(|s| CString::new(s).unwrap()))
+			.collect::<Vec<Option<CString>>>();
+		let (available, user) = (to_c(available), to_c(user));
+		let to_pointers = |strings: &[Option<CString>]| strings.iter()
+			.map(|s| s.as_ref().map_or(null(), |s| s.as_ptr()))
+			.collect::<Vec<*const c_char>>();
+		let (available_pointers, user_pointers) = (to_pointers(&available), to_pointers(&user));
+		unsafe { function(available_pointers.as_ptr(), available.len(), user_pointers.as_ptr(), user.len()) }
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_locale_match_best_bcp47() {
+		fn case(available: &[Option<&[u8]>], user: &[Option<&[u8]>], expected: isize) {
+			assert_eq!(call(locale_match_best_bcp47, available, user), expected);
+		}
+
+		case(&[Some(b"en-US"), Some(b"ru-BY")], &[Some(b"ru-RU"), Some(b"en")], 1);
+		case(&[Some(b"en-US"), Some(b"ru-BY")], &[Some(b"fr")], -1);
+		case(&[], &[Some(b"en")], -1);
+
+		// Null pointers and invalid UTF-8 are ignored
+		case(&[None, Some(b"\xFF"), Some(b"en")], &[None, Some(b"en-US")], 2);
+		case(&[Some(b"en")], &[Some(b"\xFF"), None], -1);
+
+		// Null arrays
+		assert_eq!(unsafe { locale_match_best_bcp47(null(), 0, null(), 0) }, -1);
+	}
+
+	#[cfg(feature = "posix")]
+	#[test]
+	fn test_locale_match_best_posix() {
+		fn case(available: &[Option<&[u8]>], user: &[Option<&[u8]>], expected: isize) {
+			assert_eq!(call(locale_match_best_posix, available, user), expected);
+		}
+
+		case(&[Some(b"en_US.UTF-8"), Some(b"ru_BY.UTF-8")], &[Some(b"ru_RU.UTF-8"), Some(b"en")], 1);
+		case(&[Some(b"en_US"), Some(b"ru_BY")], &[Some(b"fr")], -1);
+
+		// Null pointers and invalid UTF-8 are ignored
+		case(&[None, Some(b"\xFF"), Some(b"en")], &[None, Some(b"en_US")], 2);
+	}
+}
@@ -0,0 +1,151 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for validating locale arguments of command-line applications built with
+//! [`clap`](https://docs.rs/clap).
+//!
+//! [`Bcp47Locale`], [`PosixLocale<String>`](PosixLocale) and [`LocaleArg`] implement
+//! [`ValueParserFactory`], so they can be used as argument types directly, and invalid locales are
+//! rejected at parse time with an error message describing the expected format.
+//!
+//! # Examples
+//!
+//! ```
+//! use clap::{value_parser, Arg, Command};
+//! use locale_match::clap::LocaleArg;
+//!
+//!
+//! let command = Command::new("app")
+//!     .arg(Arg::new("lang").long("lang").value_parser(value_parser!(LocaleArg)));
+//!
+//! let matches = command.clone().get_matches_from(["app", "--lang", "pt-BR"]);
+//! assert_eq!(matches.get_one::<LocaleArg>("lang").unwrap().as_ref(), "pt-BR");
+//!
+//! assert!(command.try_get_matches_from(["app", "--lang", "pt BR"]).is_err());
+//! ```
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use ::clap::builder::{ValueParser, ValueParserFactory};
+
+use crate::bcp47::Bcp47Locale;
+use crate::posix::PosixLocale;
+
+/// A locale argument in either the BCP 47 or the POSIX format.
+///
+/// An argument that is valid in both formats, e.g. `en`, is parsed as a BCP 47 locale.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocaleArg {
+	/// A BCP 47 locale, e.g. `en-US`.
+	Bcp47(Bcp47Locale),
+	/// A POSIX locale, e.g. `en_US.UTF-8`.
+	Posix(PosixLocale<String>),
+}
+
+impl LocaleArg {
+	/// Returns the locale as a string slice.
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::Bcp47(locale) => locale.as_str(),
+			Self::Posix(locale) => locale.as_str(),
+		}
+	}
+}
+
+impl FromStr for LocaleArg {
+	type Err = String;
+
+	fn from_str(locale: &str) -> Result<Self, Self::Err> {
+		Bcp47Locale::parse(locale).map(Self::Bcp47)
+			.or_else(|_| locale.parse().map(Self::Posix))
+			.map_err(|_| "expected a BCP 47 locale (e.g. `en-US`) or a POSIX locale (e.g. `en_US.UTF-8`)".to_string())
+	}
+}
+
+impl AsRef<str> for LocaleArg {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl Display for LocaleArg {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl ValueParserFactory for LocaleArg {
+	type Parser = ValueParser;
+
+	fn value_parser() -> Self::Parser {
+		ValueParser::new(LocaleArg::from_str)
+	}
+}
+
+impl ValueParserFactory for Bcp47Locale {
+	type Parser = ValueParser;
+
+	fn value_parser() -> Self::Parser {
+		ValueParser::new(|locale: &str| Bcp47Locale::parse(locale)
+			.map_err(|error| format!("expected a BCP 47 locale (e.g. `en-US`): {error}")))
+	}
+}
+
+impl ValueParserFactory for PosixLocale<String> {
+	type Parser = ValueParser;
+
+	fn value_parser() -> Self::Parser {
+		ValueParser::new(|locale: &str| locale.parse::<PosixLocale<String>>()
+			.map_err(|error| format!("expected a POSIX locale (e.g. `en_US.UTF-8`): {error}")))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use ::clap::{value_parser, Arg, Command};
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_LocaleArg() {
+		assert_eq!("en-US".parse(), Ok(LocaleArg::Bcp47(Bcp47Locale::parse("en-US").unwrap())));
+		assert_eq!("en".parse(), Ok(LocaleArg::Bcp47(Bcp47Locale::parse("en").unwrap())));
+		assert_eq!("en_US.UTF-8".parse(), Ok(LocaleArg::Posix(PosixLocale::parse("en_US.UTF-8".to_string()))));
+		assert_eq!("ca_ES@valencia".parse::<LocaleArg>().unwrap().as_str(), "ca_ES@valencia");
+		assert!("en US".parse::<LocaleArg>().is_err());
+		assert!("".parse::<LocaleArg>().is_err());
+	}
+
+	#[test]
+	fn test_value_parsers() {
+		let command = Command::new("app")
+			.arg(Arg::new("any").long("any").value_parser(value_parser!(LocaleArg)))
+			.arg(Arg::new("bcp47").long("bcp47").value_parser(value_parser!(Bcp47Locale)))
+			.arg(Arg::new("posix").long("posix").value_parser(value_parser!(PosixLocale<String>)));
+
+		let matches = command.clone().try_get_matches_from(["app", "--any", "en_US", "--bcp47", "en-US", "--posix", "en_US.UTF-8"]).unwrap();
+		assert_eq!(matches.get_one::<LocaleArg>("any").map(LocaleArg::as_str), Some("en_US"));
+		assert_eq!(matches.get_one::<Bcp47Locale>("bcp47").map(Bcp47Locale::as_str), Some("en-US"));
+		assert_eq!(matches.get_one::<PosixLocale<String>>("posix").map(PosixLocale::as_str), Some("en_US.UTF-8"));
+
+		let error = |args: &[&str]| command.clone().try_get_matches_from(args).unwrap_err().to_string();
+		assert!(error(&["app", "--any", "en US"]).contains("expected a BCP 47 locale (e.g. `en-US`) or a POSIX locale"));
+		assert!(error(&["app", "--bcp47", "en_US"]).contains("expected a BCP 47 locale (e.g. `en-US`)"));
+		assert!(error(&["app", "--posix", "en-US"]).contains("expected a POSIX locale (e.g. `en_US.UTF-8`): invalid language"));
+	}
+}
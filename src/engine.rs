@@ -0,0 +1,556 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The format-agnostic matching engine, which the locale format modules are front-ends of.
+//!
+//! A format module parses the locales and scores an available locale for a user locale with the
+//! same language, while the engine selects the best of them for the most preferred user locale
+//! that has any, calling the observer and applying the tie break, and reports the outcome with the
+//! `tracing` and `metrics` features.  
+//! A new format implements [`Locale`] for its parsed locales and [`Format`] for its matcher, and
+//! gets the matching functions of the submodules, e.g. [`explain()`], for free.
+
+#[cfg(feature = "bumpalo")]
+mod bump;
+mod bytes;
+mod diff;
+mod explain;
+mod keys;
+mod matrix;
+mod strict;
+mod ties;
+mod warnings;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ops::ControlFlow;
+
+#[cfg(feature = "bumpalo")]
+pub(crate) use bump::best_matching_locale_with_confidence_in;
+pub(crate) use bytes::best_matching_locale_from_bytes;
+pub(crate) use diff::diff;
+pub(crate) use explain::explain;
+pub(crate) use keys::best_matching_key;
+pub(crate) use matrix::score_matrix;
+pub(crate) use strict::try_best_matching_locale;
+pub(crate) use ties::best_matching_locales_all_ties;
+pub(crate) use warnings::best_matching_locale_with_warnings;
+
+use crate::matching::{confidence, tie_break, Comparison, Folded, LocaleKind, LocaleMatch, Scoring, TieBreak, Warning};
+
+/// A parsed locale of any format.
+///
+/// The parts of the locale other than the ones all of the formats have are compared by its format
+/// module.
+pub(crate) trait Locale {
+	/// Returns the locale as it was passed in.
+	fn as_str(&self) -> &str;
+
+	/// Returns the language, e.g. `zh` in `zh-Hant-TW` or `sr` in `sr_RS@latin`, which is equal
+	/// ignoring ASCII case in every match.
+	fn language(&self) -> &str;
+
+	/// Returns the script of a BCP 47 locale or the modifier of a POSIX locale, e.g. `Hant` in
+	/// `zh-Hant-TW` or `latin` in `sr_RS@latin`.
+	#[cfg_attr(not(feature = "posix"), allow(dead_code))]
+	fn script_or_modifier(&self) -> Option<&str>;
+
+	/// Returns the region of a BCP 47 locale or the territory of a POSIX locale, e.g. `TW` in
+	/// `zh-Hant-TW` or `RS` in `sr_RS@latin`.
+	#[cfg_attr(not(feature = "posix"), allow(dead_code))]
+	fn region(&self) -> Option<&str>;
+}
+
+/// A locale format with the configuration of the matching, implemented by the matcher of the
+/// format.
+///
+/// The matching functions of the submodules are implemented once over the format, and the format
+/// modules expose them with their own documentation and examples.
+pub(crate) trait Format {
+	/// A parsed locale, which keeps the locale as it was passed in.
+	type Parsed<T: AsRef<str>>: Locale;
+	/// The categories of the parts of a locale other than the language.
+	type Subtag: Copy + 'static;
+	/// The weighted comparisons of the parts of two locales, in the order of
+	/// [`SUBTAGS`](Self::SUBTAGS).
+	type Comparisons: AsRef<[(Comparison, f64)]>;
+	/// The parse error of a malformed locale.
+	type Error: std::fmt::Display;
+
+	/// The categories of the parts of a locale in the order of the comparisons.
+	const SUBTAGS: &'static [Self::Subtag];
+	/// The weight of the language, which is always equal in a match, for the confidence.
+	const LANGUAGE_WEIGHT: f64;
+
+	/// Parses a locale according to the configuration of the matcher.  
+	/// Returns the locale back if it is ignored, e.g. a malformed BCP 47 locale.
+	fn parse_locale<T: AsRef<str>>(&self, locale: T) -> Result<Self::Parsed<T>, T>;
+
+	/// Returns the locale a parsed locale was parsed from.
+	fn into_inner<T: AsRef<str>>(locale: Self::Parsed<T>) -> T;
+
+	/// Checks that a parsed or an ignored locale is well-formed.  
+	/// An ignored well-formed locale, e.g. an invalid BCP 47 locale, is not an error.
+	fn check<T: AsRef<str>>(&self, locale: Result<&Self::Parsed<T>, &str>) -> Result<(), Self::Error>;
+
+	/// Returns the warning about a parsed or an ignored locale if it is malformed.
+	fn warning<T: AsRef<str>>(&self, kind: LocaleKind, index: usize, locale: Result<&Self::Parsed<T>, &str>) -> Option<Warning> {
+		let reason = self.check(locale).err()?.to_string();
+		let input = locale.map_or_else(|locale| locale, Locale::as_str).to_string();
+		Some(Warning { kind, index, input, reason, replacement: None })
+	}
+
+	/// Returns the warning about a parsed locale if it is deprecated.
+	fn deprecation_warning(&self, _kind: LocaleKind, _index: usize, _locale: &str) -> Option<Warning> {
+		None
+	}
+
+	/// Returns the scoring options of the matcher.
+	fn scoring(&self) -> &Scoring;
+
+	/// Returns the rank of an available locale for a user locale, lower first, for choosing among
+	/// the candidates that are equal after the tie break, e.g. by the preferred POSIX codesets.
+	fn rank<A: AsRef<str>, U: AsRef<str>>(&self, _aval_locale: &Self::Parsed<A>, _user_locale: &Self::Parsed<U>) -> usize {
+		0
+	}
+
+	/// Returns the score of an available locale for a user locale with the same language, along
+	/// with the comparisons of their parts, or [`None`] if the available locale cannot match the
+	/// user locale.
+	fn candidate<A: AsRef<str>, U: AsRef<str>>(&self, aval_locale: &Self::Parsed<A>, user_locale: &Self::Parsed<U>) -> Option<(f64, Self::Comparisons)>;
+
+	/// Finds the best match among the parsed available locales with their indices for the parsed
+	/// user locales with their indices.  
+	/// Returns the index in `available` of the matched locale, the index of the user locale and the
+	/// confidence of the match.
+	fn best_match<A: AsRef<str>, U: AsRef<str>>(&self, available: &[(usize, Self::Parsed<A>)], user_locales: impl IntoIterator<Item = (usize, Self::Parsed<U>)>) -> Option<(usize, usize, f64)>;
+
+	/// Finds the best match like [`best_match`](Self::best_match), parsing the user locales.
+	fn best_match_for_locales<A: AsRef<str>, T: AsRef<str>>(&self, available: &[(usize, Self::Parsed<A>)], user_locales: impl IntoIterator<Item = T>) -> Option<(usize, usize, f64)> {
+		let user_locales = user_locales.into_iter()
+			.enumerate()
+			.filter_map(|(i, locale)| self.parse_locale(locale).ok().map(|locale| (i, locale)));
+		self.best_match(available, user_locales)
+	}
+}
+
+/// Finds the best matching locale like the `best_matching_locale_with_confidence` methods of the
+/// matchers.
+pub(crate) fn best_matching_locale_with_confidence<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let available = parse_available(format, available_locales).collect::<Vec<_>>();
+	let best_match = format.best_match_for_locales(&available, user_locales);
+	best_match.map(|best_match| locale_match::<F, T1>(available, best_match))
+}
+
+/// Parses the available locales along with their indices, skipping the ignored ones.
+pub(crate) fn parse_available<'a, F: Format, T: AsRef<str>>(format: &'a F, available_locales: impl IntoIterator<Item = T> + 'a) -> impl Iterator<Item = (usize, F::Parsed<T>)> + 'a {
+	available_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| format.parse_locale(locale).ok().map(|locale| (i, locale)))
+}
+
+/// Takes the matched locale out of the parsed available locales, given the index of the matched
+/// locale among them, the index of the user locale and the confidence.
+fn locale_match<F: Format, T: AsRef<str>>(available: impl IntoIterator<Item = (usize, F::Parsed<T>)>, (i, user_index, confidence): (usize, usize, f64)) -> LocaleMatch<T> {
+	let (available_index, locale) = available.into_iter().nth(i).unwrap();
+	LocaleMatch { locale: F::into_inner(locale), available_index, user_index, confidence }
+}
+
+/// Checks whether a parsed or an ignored locale is malformed, i.e. is ignored or fails the check
+/// of the format.
+fn is_malformed<F: Format, T: AsRef<str>>(format: &F, locale: &Result<F::Parsed<T>, T>) -> bool {
+	locale.is_err() || format.check(locale.as_ref().map_err(AsRef::as_ref)).is_err()
+}
+
+/// The available locales that can match the user locale, with their indices in `available`,
+/// scores and comparisons, in the order of `available`.
+pub(crate) fn candidates<'a, F, A, U>(format: &'a F, available: &'a [(usize, F::Parsed<A>)], user_locale: &'a F::Parsed<U>) -> impl Iterator<Item = (usize, f64, F::Comparisons)> + 'a
+where
+	F: Format,
+	A: AsRef<str>,
+	U: AsRef<str>
+{
+	let user_language = Folded::new(user_locale.language());
+	available.iter()
+		.enumerate()
+		.filter(move |(_, (_, aval_locale))| Folded::new(aval_locale.language()).eq_ignore_ascii_case(&user_language))
+		.filter_map(move |(i, (_, aval_locale))| format.candidate(aval_locale, user_locale).map(|(score, c)| (i, score, c)))
+}
+
+/// The format-independent options of a matcher, with its observer and random tie break adapted to
+/// the parsed locales: `A` for available and `U` for user locales.
+pub(crate) struct Engine<'a, A: ?Sized, U: ?Sized> {
+	/// The name of the format in `tracing` events and metrics, e.g. `bcp47`.
+	#[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+	pub(crate) format: &'static str,
+	/// The weight of the language, which is always equal in a match, for the confidence.
+	pub(crate) language_weight: f64,
+	pub(crate) tie_break: TieBreak,
+	/// The rank of an available locale by its index in `available` for a user locale, lower first,
+	/// for choosing among the candidates that are equal after the tie break.
+	pub(crate) rank: Option<Rank<'a, U>>,
+	pub(crate) observer: Option<Observer<'a, U, A>>,
+	pub(crate) random_tie_break: Option<RandomTieBreak<'a, A>>,
+}
+
+/// A function ranking an available locale by its index for a user locale.
+type Rank<'a, U> = &'a dyn Fn(usize, &U) -> usize;
+
+/// A function observing the scored candidates with the user and the available locale.
+type Observer<'a, U, A> = &'a dyn Fn(&U, &A, f64) -> ControlFlow<()>;
+
+/// A source of random numbers and the weight of an available locale.
+type RandomTieBreak<'a, A> = (&'a dyn Fn() -> f64, &'a dyn Fn(&A) -> f64);
+
+impl<A: Locale + ?Sized, U: Locale + ?Sized> Engine<'_, A, U> {
+	/// Finds the best match among the available locales for the user locales with their indices.  
+	/// `locale` returns the original index and the parsed locale of an available locale by its index
+	/// in `available`, and `candidate` returns the score and the comparisons of the available locale
+	/// with the given index in `available` for a user locale with the same language, or [`None`] if
+	/// it cannot match the user locale.  
+	/// Returns the index in `available` of the matched locale, the index of the user locale and the
+	/// confidence of the match.
+	pub(crate) fn best_match<X, L, C>(
+		&self,
+		available: &[X],
+		locale: impl Fn(usize, &X) -> (usize, &A),
+		user_locales: impl IntoIterator<Item = (usize, L)>,
+		candidate: impl Fn(usize, &A, &U) -> Option<(f64, C)>,
+	) -> Option<(usize, usize, f64)>
+	where
+		L: Borrow<U>,
+		C: AsRef<[(Comparison, f64)]>,
+	{
+		#[cfg(feature = "tracing")]
+		let _span = tracing::debug_span!("best_match", format = self.format, available = available.len()).entered();
+		let weight = |i: usize| self.random_tie_break.map_or(1.0, |(_, weight)| weight(locale(i, &available[i]).1));
+		let best_match = user_locales.into_iter()
+			.find_map(|(user_index, user_locale)| {
+				let user_locale = user_locale.borrow();
+				let random_pick = self.random_tie_break.map(|(random, _)| RandomPick { random, weight: &weight });
+				let rank = self.rank.map(|rank| move |i| rank(i, user_locale));
+				let user_language = Folded::new(user_locale.language());
+				let candidates = available.iter()
+					.enumerate()
+					.map(|(i, x)| (i, locale(i, x).1))
+					.filter(|(_, aval_locale)| Folded::new(aval_locale.language()).eq_ignore_ascii_case(&user_language))
+					.filter_map(|(i, aval_locale)| candidate(i, aval_locale, user_locale).map(|(score, c)| (i, score, c)));
+				let best = select_best(candidates, self.tie_break, rank.as_ref().map(|rank| rank as &dyn Fn(usize) -> usize), random_pick, |i, score| match self.observer {
+					Some(observer) => observer(user_locale, locale(i, &available[i]).1, score),
+					None => ControlFlow::Continue(()),
+				});
+				best.map(|(i, _, c)| (i, user_index, confidence(self.language_weight, c.as_ref().iter().copied())))
+			});
+		#[cfg(feature = "tracing")]
+		match best_match {
+			Some((i, user_index, confidence)) => tracing::debug!(available_index = locale(i, &available[i]).0, user_index, confidence, "matched locale"),
+			None => tracing::debug!("no locale matched"),
+		}
+		#[cfg(feature = "metrics")]
+		crate::matching::record_outcome(self.format, best_match.map(|(i, user_index, _)| (locale(i, &available[i]).1.as_str(), user_index)));
+		best_match
+	}
+
+	/// Orders the available locales that can match a user locale from the best to the worst, as if
+	/// the best match were taken out of them over and over, but scoring each of them only once.  
+	/// The arguments are those of [`best_match`](Self::best_match) for a single user locale.
+	/// The observer is called for each candidate, and no `tracing` events or metrics are recorded,
+	/// since no locale is negotiated.  
+	/// Returns the indices in `available` of the locales with the confidences of their matches.
+	#[cfg_attr(not(feature = "bcp47"), allow(dead_code))]
+	pub(crate) fn ranked<X, C>(
+		&self,
+		available: &[X],
+		locale: impl Fn(usize, &X) -> (usize, &A),
+		user_locale: &U,
+		candidate: impl Fn(usize, &A, &U) -> Option<(f64, C)>,
+	) -> Vec<(usize, f64)>
+	where
+		C: AsRef<[(Comparison, f64)]>,
+	{
+		let user_language = Folded::new(user_locale.language());
+		let mut stopped = false;
+		let mut candidates = available.iter()
+			.enumerate()
+			.map(|(i, x)| (i, locale(i, x).1))
+			.filter(|(_, aval_locale)| Folded::new(aval_locale.language()).eq_ignore_ascii_case(&user_language))
+			.filter_map(|(i, aval_locale)| candidate(i, aval_locale, user_locale).map(|(score, c)| (i, aval_locale, score, c)))
+			.map_while(|(i, aval_locale, score, c)| {
+				if stopped {
+					return None;
+				}
+				stopped = self.observer.is_some_and(|observer| observer(user_locale, aval_locale, score).is_break());
+				Some((i, score, c))
+			})
+			.collect::<Vec<_>>();
+		let confidence = |c: &C| confidence(self.language_weight, c.as_ref().iter().copied());
+
+		let Some((random, weight)) = self.random_tie_break else {
+			// The stable sort keeps the order of the candidates for full ties, like `select_best`
+			let rank = |i: usize| self.rank.map_or(0, |rank| rank(i, user_locale));
+			candidates.sort_by(|(a_i, a_score, a_c), (b_i, b_score, b_c)| {
+				b_score.total_cmp(a_score)
+					.then_with(|| tie_break(b_c.as_ref(), a_c.as_ref(), self.tie_break))
+					.then_with(|| rank(*a_i).cmp(&rank(*b_i)))
+			});
+			return candidates.iter().map(|(i, _, c)| (*i, confidence(c))).collect();
+		};
+
+		// Each locale is picked at random among the rest of the candidates with the highest score
+		candidates.sort_by(|(_, a_score, _), (_, b_score, _)| b_score.total_cmp(a_score));
+		let weight = |i: usize| weight(locale(i, &available[i]).1);
+		let random_pick = RandomPick { random, weight: &weight };
+		let mut ranked = Vec::with_capacity(candidates.len());
+		while let Some(&(_, highest, _)) = candidates.first() {
+			let tied = candidates.iter().take_while(|(_, score, _)| score.total_cmp(&highest).is_eq());
+			let (picked, _, _) = random_pick.select(tied.map(|(i, score, _)| (*i, *score, ()))).unwrap();
+			let (i, _, c) = candidates.remove(candidates.iter().position(|(i, _, _)| *i == picked).unwrap());
+			ranked.push((i, confidence(&c)));
+		}
+		ranked
+	}
+}
+
+/// Selects the candidate with the highest score, using the tie break for equal scores, then the
+/// rank, and the order of the candidates for full ties, or picking one of the candidates with the
+/// highest score at random with a random tie break.  
+/// `observe` is called with the index and the score of each candidate, and the remaining
+/// candidates are skipped once it returns [`ControlFlow::Break`].
+fn select_best<C: AsRef<[(Comparison, f64)]>>(
+	candidates: impl IntoIterator<Item = (usize, f64, C)>,
+	tie_break_kind: TieBreak,
+	rank: Option<&dyn Fn(usize) -> usize>,
+	random_pick: Option<RandomPick<'_>>,
+	mut observe: impl FnMut(usize, f64) -> ControlFlow<()>,
+) -> Option<(usize, f64, C)> {
+	let mut stopped = false;
+	let candidates = candidates.into_iter().map_while(|candidate| {
+		if stopped {
+			return None;
+		}
+		stopped = observe(candidate.0, candidate.1).is_break();
+		Some(candidate)
+	});
+	if let Some(random_pick) = random_pick {
+		return random_pick.select(candidates);
+	}
+	candidates.fold(None, |best, (i, score, comparisons)| match best {
+		Some((best_i, best_score, ref best_comparisons))
+			if score.total_cmp(&best_score)
+				.then_with(|| tie_break(comparisons.as_ref(), best_comparisons.as_ref(), tie_break_kind))
+				.then_with(|| rank.map_or(Ordering::Equal, |rank| rank(best_i).cmp(&rank(i)))) != Ordering::Greater => best,
+		_ => Some((i, score, comparisons)),
+	})
+}
+
+/// A random tie break of a matcher: a source of random numbers in the range `0.0..1.0` and the
+/// weights of the candidates by their indices.
+struct RandomPick<'a> {
+	random: &'a dyn Fn() -> f64,
+	weight: &'a dyn Fn(usize) -> f64,
+}
+
+impl RandomPick<'_> {
+	/// Picks one of the candidates with the highest score with a probability proportional to its
+	/// weight. Negative and NaN weights count as zero, and the first candidate is picked if all of
+	/// the weights are zero.  
+	/// No random number is drawn if there are no ties.
+	fn select<C>(&self, candidates: impl Iterator<Item = (usize, f64, C)>) -> Option<(usize, f64, C)> {
+		let mut tied: Vec<(usize, f64, C)> = Vec::new();
+		for candidate in candidates {
+			match tied.first().map(|(_, score, _)| candidate.1.total_cmp(score)) {
+				Some(Ordering::Less) => {}
+				Some(Ordering::Equal) => tied.push(candidate),
+				_ => {
+					tied.clear();
+					tied.push(candidate);
+				}
+			}
+		}
+		if tied.len() > 1 {
+			let weights = tied.iter().map(|(i, _, _)| (self.weight)(*i).max(0.0)).collect::<Vec<f64>>();
+			let total = weights.iter().sum::<f64>();
+			if total > 0.0 {
+				let mut target = (self.random)().clamp(0.0, 1.0) * total;
+				let picked = weights.iter()
+					.position(|&weight| {
+						let picked = target < weight;
+						target -= weight;
+						picked
+					})
+					// Rounding errors or a random number of 1.0
+					.unwrap_or_else(|| weights.iter().rposition(|&weight| weight > 0.0).unwrap());
+				return Some(tied.swap_remove(picked));
+			}
+		}
+		tied.into_iter().next()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// The language of a test locale is its first letter
+	impl Locale for str {
+		fn as_str(&self) -> &str {
+			self
+		}
+
+		fn language(&self) -> &str {
+			self.get(..1).unwrap_or(self)
+		}
+
+		fn script_or_modifier(&self) -> Option<&str> {
+			None
+		}
+
+		fn region(&self) -> Option<&str> {
+			None
+		}
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Engine() {
+		fn case(observe_until: usize, random: Option<f64>, available: &[&str], user: &[&str], expected: Option<(usize, usize)>) {
+			let observed = std::cell::Cell::new(0);
+			let observer = |_: &str, _: &str, _| {
+				observed.set(observed.get() + 1);
+				if observed.get() >= observe_until { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+			};
+			let random = random.map(|random| move || random);
+			let weight = |_: &str| 1.0;
+			let engine = Engine {
+				format: "test",
+				language_weight: 1.0,
+				tie_break: TieBreak::ListOrder,
+				rank: None,
+				observer: Some(&observer),
+				random_tie_break: random.as_ref().map(|random| (random as &dyn Fn() -> f64, &weight as &dyn Fn(&str) -> f64)),
+			};
+			// Locales score higher the longer they are
+			let candidate = |_, aval: &str, _: &str| Some((aval.len() as f64, [(Comparison::Present(1.0), 1.0)]));
+			let best_match = engine.best_match(available, |i, aval| (i, *aval), user.iter().copied().enumerate(), candidate);
+			assert_eq!(best_match.map(|(i, user_index, _)| (i, user_index)), expected, "{available:?}, {user:?}");
+		}
+
+		// Best candidate of the first matching user locale
+		case(usize::MAX, None, &["a", "bb", "b"], &["c", "b", "a"], Some((1, 1)));
+		case(usize::MAX, None, &["a", "b"], &["c"], None);
+
+		// Ties
+		case(usize::MAX, None, &["aa", "ab"], &["a"], Some((0, 0)));
+		case(usize::MAX, Some(0.75), &["aa", "ab"], &["a"], Some((1, 0)));
+
+		// Early termination by the observer
+		case(1, None, &["a", "aa"], &["a"], Some((0, 0)));
+	}
+
+	#[test]
+	fn test_ranked() {
+		fn case(tie_break: TieBreak, random: Option<f64>, observe_until: usize, available: &[&str], user: &str, expected: &[usize]) {
+			let observed = std::cell::Cell::new(0);
+			let observer = |_: &str, _: &str, _| {
+				observed.set(observed.get() + 1);
+				if observed.get() >= observe_until { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+			};
+			let random = random.map(|random| move || random);
+			let weight = |_: &str| 1.0;
+			let engine = Engine {
+				format: "test",
+				language_weight: 1.0,
+				tie_break,
+				rank: None,
+				observer: Some(&observer),
+				random_tie_break: random.as_ref().map(|random| (random as &dyn Fn() -> f64, &weight as &dyn Fn(&str) -> f64)),
+			};
+			// Locales score higher the longer they are, and "x" marks an absent subtag
+			let candidate = |_, aval: &str, _: &str| {
+				let comparison = if aval.ends_with('x') { Comparison::AvailableAbsent } else { Comparison::Present(1.0) };
+				Some((aval.len() as f64, [(comparison, 1.0)]))
+			};
+			let ranked = engine.ranked(available, |i, aval| (i, *aval), user, candidate);
+			assert_eq!(ranked.iter().map(|(i, _)| *i).collect::<Vec<_>>(), expected, "{available:?}, {user:?}");
+			assert_eq!(observed.get(), expected.len().min(observe_until), "{available:?}, {user:?}");
+
+			// The first locale is the best match
+			observed.set(0);
+			let best_match = engine.best_match(available, |i, aval| (i, *aval), [(0, user)], candidate);
+			assert_eq!(best_match.map(|(i, _, _)| i), expected.first().copied(), "{available:?}, {user:?}");
+		}
+
+		// By score, then by the order of the locales
+		case(TieBreak::ListOrder, None, usize::MAX, &["a", "aaa", "b", "aa", "ab"], "a", &[1, 3, 4, 0]);
+		case(TieBreak::ListOrder, None, usize::MAX, &["a", "b"], "c", &[]);
+
+		// Tie break
+		case(TieBreak::MostSpecific, None, usize::MAX, &["ax", "aa", "a"], "a", &[1, 0, 2]);
+
+		// Random tie break among the rest of the candidates with the highest score
+		case(TieBreak::ListOrder, Some(0.75), usize::MAX, &["aa", "ab", "a", "ac"], "a", &[3, 1, 0, 2]);
+		case(TieBreak::ListOrder, Some(0.0), usize::MAX, &["aa", "ab", "a"], "a", &[0, 1, 2]);
+
+		// Early termination by the observer
+		case(TieBreak::ListOrder, None, 2, &["a", "aa", "aaa"], "a", &[1, 0]);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_RandomPick() {
+		fn case(scores: &[f64], random: f64, weights: &[f64], expected: Option<usize>) {
+			let draws = std::cell::Cell::new(0);
+			let draw = || { draws.set(draws.get() + 1); random };
+			let weight = |i: usize| weights[i];
+			let random_pick = RandomPick { random: &draw, weight: &weight };
+			let candidates = scores.iter().enumerate().map(|(i, &score)| (i, score, [(Comparison::BothAbsent, 1.0)]));
+			let picked = select_best(candidates, TieBreak::ListOrder, None, Some(random_pick), |_, _| ControlFlow::Continue(()));
+			assert_eq!(picked.map(|(i, _, _)| i), expected, "{scores:?}, {random}, {weights:?}");
+			let max = scores.iter().copied().fold(f64::MIN, f64::max);
+			if scores.iter().filter(|&&score| score == max).count() < 2 {
+				assert_eq!(draws.get(), 0, "{scores:?}, {random}, {weights:?}");
+			}
+		}
+
+		// No ties
+		case(&[], 0.5, &[], None);
+		case(&[1.0], 0.5, &[1.0], Some(0));
+		case(&[1.0, 2.0, 1.0], 0.5, &[1.0, 1.0, 1.0], Some(1));
+
+		// Uniform
+		case(&[2.0, 1.0, 2.0], 0.0, &[1.0, 1.0, 1.0], Some(0));
+		case(&[2.0, 1.0, 2.0], 0.49, &[1.0, 1.0, 1.0], Some(0));
+		case(&[2.0, 1.0, 2.0], 0.5, &[1.0, 1.0, 1.0], Some(2));
+		case(&[2.0, 1.0, 2.0], 0.99, &[1.0, 1.0, 1.0], Some(2));
+		case(&[2.0, 1.0, 2.0], 1.0, &[1.0, 1.0, 1.0], Some(2));
+
+		// Weighted
+		case(&[1.0, 1.0], 0.89, &[9.0, 1.0], Some(0));
+		case(&[1.0, 1.0], 0.9, &[9.0, 1.0], Some(1));
+		case(&[1.0, 1.0, 1.0], 0.5, &[0.0, 1.0, 0.0], Some(1));
+		case(&[1.0, 1.0, 1.0], 1.0, &[1.0, 1.0, 0.0], Some(1));
+
+		// Invalid weights
+		case(&[1.0, 1.0], 0.0, &[-1.0, 1.0], Some(1));
+		case(&[1.0, 1.0], 0.0, &[f64::NAN, 1.0], Some(1));
+		case(&[1.0, 1.0], 0.5, &[0.0, 0.0], Some(0));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching with the parsed available locales allocated in a [`bumpalo`](https://docs.rs/bumpalo)
+//! arena, see the `best_matching_locale_in` methods of the matchers.
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+
+use crate::matching::LocaleMatch;
+
+use super::{locale_match, parse_available, Format};
+
+/// Finds the best matching locale like [`super::best_matching_locale_with_confidence`],
+/// allocating the list of parsed available locales in the arena instead of the heap.
+pub(crate) fn best_matching_locale_with_confidence_in<F, T1, T2>(format: &F, bump: &Bump, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let available = Vec::from_iter_in(parse_available(format, available_locales), bump);
+	let best_match = format.best_match_for_locales(&available, user_locales);
+	best_match.map(|best_match| locale_match::<F, T1>(available, best_match))
+}
\ No newline at end of file
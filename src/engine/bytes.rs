@@ -0,0 +1,35 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of locales given as bytes, see the `best_matching_locale_from_bytes` functions.
+
+use crate::matching::LossyBytes;
+
+use super::{best_matching_locale_with_confidence, Format};
+
+/// Finds the best matching locale like [`best_matching_locale_with_confidence`], for locales given
+/// as bytes. Locales that are not valid UTF-8 are matched by their lossy conversion, so they are
+/// malformed.
+pub(crate) fn best_matching_locale_from_bytes<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	F: Format,
+	T1: AsRef<[u8]>,
+	T2: AsRef<[u8]>
+{
+	let available_locales = available_locales.into_iter().map(LossyBytes::new);
+	let user_locales = user_locales.into_iter().map(LossyBytes::new);
+	best_matching_locale_with_confidence(format, available_locales, user_locales).map(|m| m.locale.locale)
+}
\ No newline at end of file
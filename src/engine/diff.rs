@@ -0,0 +1,38 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Comparison of the parts of two locales, see the `diff` functions.
+
+use crate::matching::{Difference, LocaleDiff};
+
+/// Compares the languages and the other parts of two locales.  
+/// `language` and `part` return the language and the part of a category of a locale, and `eq`
+/// compares two parts of a category. The parts absent in both locales are omitted.
+pub(crate) fn diff<'a, L, S: Copy>(
+	a: &L,
+	b: &L,
+	language: impl Fn(&L) -> &'a str,
+	subtags: &[S],
+	part: impl Fn(&L, S) -> Option<&'a str>,
+	eq: impl Fn(S, &str, &str) -> bool,
+) -> LocaleDiff<'a, S> {
+	LocaleDiff {
+		language: Difference::of(Some(language(a)), Some(language(b)), str::eq_ignore_ascii_case).unwrap(),
+		subtags: subtags.iter()
+			.filter_map(|&subtag| Difference::of(part(a, subtag), part(b, subtag), |x, y| eq(subtag, x, y)).map(|difference| (subtag, difference)))
+			.collect(),
+	}
+}
\ No newline at end of file
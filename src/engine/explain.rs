@@ -0,0 +1,85 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Explanation of the matching, see the `explain` methods of the matchers.
+
+use std::ops::ControlFlow;
+
+use crate::matching::{subtag_scores, Candidate, Explanation, LocaleKind};
+
+use super::{candidates, confidence, is_malformed, locale_match, select_best, Format, Locale};
+
+/// Matches the locales like [`super::best_matching_locale_with_confidence`]
+/// and explains the match.
+///
+/// The malformed locales are reported, and the ignored ones are skipped. The candidates of the user
+/// locales are recorded up to the one the match is found for.
+pub(crate) fn explain<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Explanation<T1, F::Subtag>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let mut malformed_available_locales = Vec::new();
+	let mut warnings = Vec::new();
+	let available = available_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| {
+			let locale = format.parse_locale(locale);
+			if is_malformed(format, &locale) {
+				malformed_available_locales.push(i);
+			}
+			let locale = locale.ok()?;
+			warnings.extend(format.deprecation_warning(LocaleKind::Available, i, locale.as_str()));
+			Some((i, locale))
+		})
+		.collect::<Vec<_>>();
+
+	let mut explained = Vec::new();
+	let mut malformed_user_locales = Vec::new();
+	let mut best_match = None;
+	for (user_index, locale) in user_locales.into_iter().enumerate() {
+		let locale = format.parse_locale(locale);
+		if is_malformed(format, &locale) {
+			malformed_user_locales.push(user_index);
+		}
+		let Ok(user_locale) = locale else {
+			continue;
+		};
+		warnings.extend(format.deprecation_warning(LocaleKind::User, user_index, user_locale.as_str()));
+		let considered = candidates(format, &available, &user_locale).collect::<Vec<_>>();
+		explained.extend(considered.iter().map(|(i, score, comparisons)| Candidate {
+			available_index: available[*i].0,
+			user_index,
+			score: *score,
+			subtags: subtag_scores(F::SUBTAGS, comparisons.as_ref(), format.scoring()),
+		}));
+		let rank = |i: usize| format.rank(&available[i].1, &user_locale);
+		best_match = select_best(considered, format.scoring().tie_break, Some(&rank), None, |_, _| ControlFlow::Continue(()))
+			.map(|(i, _, c)| (i, user_index, confidence(F::LANGUAGE_WEIGHT, c.as_ref().iter().copied())));
+		if best_match.is_some() {
+			break;
+		}
+	}
+
+	Explanation {
+		best_match: best_match.map(|best_match| locale_match::<F, T1>(available, best_match)),
+		candidates: explained,
+		malformed_available_locales,
+		malformed_user_locales,
+		warnings,
+	}
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of the keys of maps, see the `best_matching_key` functions.
+
+use crate::matching::KeyEntry;
+
+use super::{best_matching_locale_with_confidence, Format};
+
+/// Finds the entry of a map whose key is the best matching locale, like
+/// [`best_matching_locale_with_confidence`], and returns the key with its value.
+pub(crate) fn best_matching_key<'a, F, K, V, T>(format: &F, map: impl IntoIterator<Item = (&'a K, &'a V)>, user_locales: impl IntoIterator<Item = T>) -> Option<(&'a K, &'a V)>
+where
+	F: Format,
+	K: AsRef<str> + 'a,
+	V: 'a,
+	T: AsRef<str>
+{
+	let entries = map.into_iter().map(|(key, value)| KeyEntry(key, value));
+	best_matching_locale_with_confidence(format, entries, user_locales).map(|m| (m.locale.0, m.locale.1))
+}
\ No newline at end of file
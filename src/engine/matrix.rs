@@ -0,0 +1,60 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Scoring of all pairs of available and user locales, see the `score_matrix` functions.
+
+use crate::matching::{subtag_scores, Candidate, LocaleKind, Matrix};
+
+use super::{candidates, is_malformed, Format};
+
+/// Scores each of the available locales for each of the user locales like
+/// [`super::best_matching_locale_with_confidence`] does,
+/// without selecting a winner.
+pub(crate) fn score_matrix<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Matrix<F::Subtag>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let available_locales = available_locales.into_iter().map(|locale| format.parse_locale(locale)).collect::<Vec<_>>();
+	let user_locales = user_locales.into_iter().map(|locale| format.parse_locale(locale)).collect::<Vec<_>>();
+	let mut matrix = Matrix::new(available_locales.len(), user_locales.len());
+
+	let mut available = Vec::new();
+	for (i, locale) in available_locales.into_iter().enumerate() {
+		if is_malformed(format, &locale) {
+			matrix.insert_malformed(LocaleKind::Available, i);
+		}
+		available.extend(locale.ok().map(|locale| (i, locale)));
+	}
+	for (user_index, locale) in user_locales.into_iter().enumerate() {
+		if is_malformed(format, &locale) {
+			matrix.insert_malformed(LocaleKind::User, user_index);
+		}
+		let Ok(user_locale) = locale else {
+			continue;
+		};
+		for (i, score, comparisons) in candidates(format, &available, &user_locale) {
+			matrix.insert(Candidate {
+				available_index: available[i].0,
+				user_index,
+				score,
+				subtags: subtag_scores(F::SUBTAGS, comparisons.as_ref(), format.scoring()),
+			});
+		}
+	}
+	matrix
+}
\ No newline at end of file
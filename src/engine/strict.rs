@@ -0,0 +1,56 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that rejects malformed locales, see the `try_best_matching_locale` functions.
+
+use crate::matching::{InvalidLocale, LocaleKind};
+
+use super::{locale_match, Format, Locale};
+
+/// Finds the best matching locale like [`super::best_matching_locale_with_confidence`],
+/// but fails on the first malformed available or user locale.  
+/// Well-formed locales ignored by the matcher, e.g. invalid BCP 47 locales, are still ignored.
+pub(crate) fn try_best_matching_locale<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Result<Option<T1>, InvalidLocale<F::Error>>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let available = available_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| parse(format, LocaleKind::Available, i, locale).map(|locale| locale.map(|locale| (i, locale))).transpose())
+		.collect::<Result<Vec<_>, _>>()?;
+	let user = user_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| parse(format, LocaleKind::User, i, locale).map(|locale| locale.map(|locale| (i, locale))).transpose())
+		.collect::<Result<Vec<_>, _>>()?;
+
+	Ok(format.best_match(&available, user)
+		.map(|best_match| locale_match::<F, T1>(available, best_match).locale))
+}
+
+/// Parses a locale with its kind and index for the error.  
+/// Returns [`None`] if the locale is ignored, and the error if it is malformed.
+fn parse<F: Format, T: AsRef<str>>(format: &F, kind: LocaleKind, index: usize, locale: T) -> Result<Option<F::Parsed<T>>, InvalidLocale<F::Error>> {
+	let locale = format.parse_locale(locale);
+	match format.check(locale.as_ref().map_err(AsRef::as_ref)) {
+		Ok(()) => Ok(locale.ok()),
+		Err(source) => {
+			let input = locale.as_ref().map_or_else(AsRef::as_ref, Locale::as_str).to_string();
+			Err(InvalidLocale { kind, index, input, source })
+		}
+	}
+}
\ No newline at end of file
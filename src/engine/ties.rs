@@ -0,0 +1,50 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that returns all of the available locales tied for the best match, see the
+//! `best_matching_locales_all_ties` functions.
+
+use super::{candidates, parse_available, Format};
+
+/// Finds all of the available locales with the highest score for the first user locale that
+/// matches any of them, in the order of the available locales.
+///
+/// The tie break of the matcher is not applied, but its scoring is.
+pub(crate) fn best_matching_locales_all_ties<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let available = parse_available(format, available_locales).collect::<Vec<_>>();
+
+	let tied = user_locales.into_iter()
+		.filter_map(|locale| format.parse_locale(locale).ok())
+		.find_map(|user_locale| {
+			let scores = candidates(format, &available, &user_locale)
+				.map(|(i, score, _)| (i, score))
+				.collect::<Vec<_>>();
+			let max_score = scores.iter().map(|&(_, score)| score).max_by(f64::total_cmp)?;
+			Some(scores.into_iter().filter(|&(_, score)| score == max_score).map(|(i, _)| i).collect::<Vec<_>>())
+		})
+		.unwrap_or_default();
+
+	available.into_iter()
+		.enumerate()
+		.filter(|(i, _)| tied.binary_search(i).is_ok())
+		.map(|(_, (_, locale))| F::into_inner(locale))
+		.collect()
+}
\ No newline at end of file
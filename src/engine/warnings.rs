@@ -0,0 +1,58 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that reports the malformed and the deprecated locales, see the
+//! `best_matching_locale_with_warnings` functions.
+
+use crate::matching::{LocaleKind, LocaleMatch, Warning};
+
+use super::{locale_match, Format, Locale};
+
+/// Finds the best matching locale like [`super::best_matching_locale_with_confidence`],
+/// and reports each malformed or deprecated locale as a [`Warning`].
+///
+/// All of the locales are checked, including the user locales after the one the match was found
+/// for.
+pub(crate) fn best_matching_locale_with_warnings<F, T1, T2>(format: &F, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> (Option<LocaleMatch<T1>>, Vec<Warning>)
+where
+	F: Format,
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let mut warnings = Vec::new();
+	let available = available_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| parse(format, LocaleKind::Available, i, locale, &mut warnings).map(|locale| (i, locale)))
+		.collect::<Vec<_>>();
+	let user = user_locales.into_iter()
+		.enumerate()
+		.filter_map(|(i, locale)| parse(format, LocaleKind::User, i, locale, &mut warnings).map(|locale| (i, locale)))
+		.collect::<Vec<_>>();
+
+	let best_match = format.best_match(&available, user)
+		.map(|best_match| locale_match::<F, T1>(available, best_match));
+	(best_match, warnings)
+}
+
+/// Parses a locale, adding the warnings about it.  
+/// Returns [`None`] if the locale is ignored.
+fn parse<F: Format, T: AsRef<str>>(format: &F, kind: LocaleKind, index: usize, locale: T, warnings: &mut Vec<Warning>) -> Option<F::Parsed<T>> {
+	let locale = format.parse_locale(locale);
+	warnings.extend(format.warning(kind, index, locale.as_ref().map_err(AsRef::as_ref)));
+	let locale = locale.ok()?;
+	warnings.extend(format.deprecation_warning(kind, index, locale.as_str()));
+	Some(locale)
+}
\ No newline at end of file
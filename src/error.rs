@@ -0,0 +1,211 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The error type shared by the fallible functions of the library.
+
+use std::fmt::{self, Display};
+
+use crate::matching::InvalidLocale;
+
+/// An error of the library.
+///
+/// The format-specific errors, e.g. [`InvalidLocale`] returned by the `try_best_matching_locale`
+/// functions, convert into it with [`From`], so they can be handled uniformly with the `?`
+/// operator. The helpers reading the file system, e.g.
+/// [`files::available_locales_from_pattern`](crate::files::available_locales_from_pattern), return
+/// it directly.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::try_best_matching_locale;
+/// use locale_match::Error;
+///
+///
+/// fn select_locale(available_locales: &[&str], user_locales: &[&str]) -> Result<String, Error> {
+///     let best_match = try_best_matching_locale(available_locales, user_locales)?;
+///     Ok(best_match.unwrap_or(&available_locales[0]).to_string())
+/// }
+///
+/// assert_eq!(select_locale(&["en-US", "ru-BY"], &["ru-RU"]).unwrap(), "ru-BY");
+///
+/// match select_locale(&["en-US", "ru-BY"], &["ru-RU-"]) {
+///     Err(Error::Parse { input, position, .. }) => assert_eq!((input.as_str(), position), ("ru-RU-", 6)),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+	/// A locale is malformed.
+	Parse {
+		/// The malformed locale, exactly as it was passed in.
+		input: String,
+		/// The byte position in the locale where the malformed part starts.
+		position: usize,
+		/// The description of the problem.
+		reason: String,
+	},
+	/// A locale is empty.
+	Empty,
+	/// The operation is not supported, e.g. on the current platform.
+	Unsupported(&'static str),
+	/// An I/O error, e.g. while detecting user locales or reading translation files.
+	Io(std::io::Error),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Parse { input, position, reason } => write!(f, "malformed locale {input:?} at position {position}: {reason}"),
+			Self::Empty => f.write_str("empty locale"),
+			Self::Unsupported(operation) => write!(f, "unsupported operation: {operation}"),
+			Self::Io(error) => write!(f, "I/O error: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(error: std::io::Error) -> Self {
+		Self::Io(error)
+	}
+}
+
+/// Converts the errors of watching translation files, keeping the underlying I/O errors.
+#[cfg(feature = "notify")]
+impl From<::notify::Error> for Error {
+	fn from(error: ::notify::Error) -> Self {
+		let kind = match error.kind {
+			::notify::ErrorKind::Io(error) => return Self::Io(error),
+			::notify::ErrorKind::PathNotFound | ::notify::ErrorKind::WatchNotFound => std::io::ErrorKind::NotFound,
+			_ => std::io::ErrorKind::Other,
+		};
+		Self::Io(std::io::Error::new(kind, error))
+	}
+}
+
+#[cfg(feature = "bcp47")]
+impl From<InvalidLocale<language_tags::ParseError>> for Error {
+	fn from(error: InvalidLocale<language_tags::ParseError>) -> Self {
+		if error.input.is_empty() {
+			return Self::Empty;
+		}
+		let position = bcp47_error_position(&error.input);
+		Self::Parse { input: error.input, position, reason: error.source.to_string() }
+	}
+}
+
+#[cfg(feature = "posix")]
+impl From<InvalidLocale<crate::posix::ParseError>> for Error {
+	fn from(error: InvalidLocale<crate::posix::ParseError>) -> Self {
+		if error.input.is_empty() {
+			return Self::Empty;
+		}
+		let position = crate::posix::PosixLocale::parse(error.input.as_str()).error_position(error.source);
+		Self::Parse { input: error.input, position, reason: error.source.to_string() }
+	}
+}
+
+/// Returns the byte position of the first subtag that makes a BCP 47 locale malformed, i.e. the end
+/// of its longest well-formed prefix of whole subtags.  
+/// Prefixes ending with a singleton, e.g. `en-u`, are incomplete rather than malformed, so they
+/// are skipped.
+#[cfg(feature = "bcp47")]
+fn bcp47_error_position(locale: &str) -> usize {
+	let mut position = 0;
+	let mut start = 0;
+	for (end, _) in locale.match_indices('-').chain([(locale.len(), "")]) {
+		let singleton = end - start == 1;
+		start = end + 1;
+		if singleton && end < locale.len() {
+			continue;
+		}
+		if language_tags::LanguageTag::parse(&locale[..end]).is_err() {
+			return position;
+		}
+		position = start;
+	}
+	0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_bcp47_error_position() {
+		fn case(locale: &str, expected: usize) {
+			assert_eq!(bcp47_error_position(locale), expected, "{locale:?}");
+		}
+
+		case("abcdefghi", 0);
+		case("en_US", 0);
+		case("en-", 3);
+		case("en-US-", 6);
+		case("en-a", 3);
+		case("en-a-", 3);
+		case("en-US-x", 6);
+		case("en-US-u-co-phonebk-abcdefghi", 19);
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_from_bcp47_error() {
+		let error = Error::from(crate::bcp47::try_best_matching_locale(["en-US", "ru_RU"], ["ru"]).unwrap_err());
+		assert!(matches!(error, Error::Parse { ref input, position: 0, .. } if input == "ru_RU"));
+		let error = Error::from(crate::bcp47::try_best_matching_locale(["en-US"], [""]).unwrap_err());
+		assert!(matches!(error, Error::Empty));
+	}
+
+	#[cfg(feature = "posix")]
+	#[test]
+	fn test_from_posix_error() {
+		fn case(locale: &str, expected: Option<usize>) {
+			let error = crate::posix::try_best_matching_locale([locale], ["en"]).unwrap_err();
+			match Error::from(error) {
+				Error::Parse { input, position, .. } => assert_eq!((input.as_str(), Some(position)), (locale, expected)),
+				Error::Empty => assert_eq!(expected, None),
+				error => panic!("unexpected error {error}"),
+			}
+		}
+
+		case("", None);
+		case("e n", Some(0));
+		case("en_", Some(3));
+		case("en_U S.UTF-8", Some(3));
+		case("en_US.", Some(6));
+		case("en_US.UTF-8@", Some(12));
+	}
+
+	#[test]
+	fn test_display() {
+		let error = Error::Parse { input: "en-".to_string(), position: 3, reason: "empty subtag".to_string() };
+		assert_eq!(error.to_string(), "malformed locale \"en-\" at position 3: empty subtag");
+		assert_eq!(Error::Empty.to_string(), "empty locale");
+		let error = Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+		assert!(std::error::Error::source(&error).is_some());
+	}
+}
\ No newline at end of file
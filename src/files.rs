@@ -0,0 +1,152 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Discovery of available locales from the names of translation files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// The placeholder for the locale in a file name pattern.
+const PLACEHOLDER: &str = "{locale}";
+
+/// A translation file found by [`available_locales_from_pattern`].
+///
+/// The type implements [`AsRef<str>`] by returning the locale, so a list of files can be passed to
+/// the `best_matching_locale` functions directly as the available locales, and the matched file is
+/// returned together with its path.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LocaleFile {
+	/// The locale extracted from the file name.
+	pub locale: String,
+	/// The path of the file, with the placeholder replaced by the locale.
+	pub path: PathBuf,
+}
+
+impl AsRef<str> for LocaleFile {
+	fn as_ref(&self) -> &str {
+		&self.locale
+	}
+}
+
+/// Returns the locales, for which a file matching the pattern exists.
+///
+/// The pattern is a path with a single `{locale}` placeholder, e.g. `i18n/{locale}.ftl`,
+/// `locales/{locale}.json` or `po/{locale}/messages.po`. The placeholder must be within a single
+/// path component; it is matched against the names in the directory of that component, and the
+/// rest of the pattern, if any, must exist for the locale to be included.  
+/// The format of the locales is not checked, names that are not valid Unicode are skipped.  
+/// The result is sorted by locale. It is empty if the pattern has no placeholder.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the directory cannot be read, e.g. if it does not exist.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::files::available_locales_from_pattern;
+///
+///
+/// let available_locales = available_locales_from_pattern("i18n/{locale}.ftl").unwrap_or_default();
+///
+/// if let Some(file) = best_matching_locale(available_locales, ["de-AT", "en"]) {
+///     println!("Loading {} translations from {}", file.locale, file.path.display());
+/// }
+/// ```
+pub fn available_locales_from_pattern(pattern: &str) -> Result<Vec<LocaleFile>, Error> {
+	let Some(start) = pattern.find(PLACEHOLDER) else {
+		return Ok(Vec::new());
+	};
+	let (head, tail) = (&pattern[..start], &pattern[start + PLACEHOLDER.len()..]);
+	let (dir, prefix) = match head.rfind(is_separator) {
+		Some(i) => (&head[..=i], &head[i + 1..]),
+		None => ("", head),
+	};
+	let (suffix, rest) = match tail.find(is_separator) {
+		Some(i) => (&tail[..i], &tail[i..]),
+		None => (tail, ""),
+	};
+
+	let dir = Path::new(dir);
+	let entries = fs::read_dir(if dir.as_os_str().is_empty() { Path::new(".") } else { dir })?;
+	let mut files = entries
+		.filter_map(Result::ok)
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.filter_map(|name| {
+			let locale = name.strip_prefix(prefix)?.strip_suffix(suffix).filter(|locale| !locale.is_empty())?;
+			let mut path = dir.join(&name);
+			path.extend(rest.split(is_separator).filter(|component| !component.is_empty()));
+			path.exists().then(|| LocaleFile { locale: locale.to_string(), path })
+		})
+		.collect::<Vec<LocaleFile>>();
+	files.sort();
+	Ok(files)
+}
+
+fn is_separator(c: char) -> bool {
+	c == '/' || std::path::is_separator(c)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_available_locales_from_pattern() {
+		let dir = std::env::temp_dir().join(format!("locale-match-files-{}", std::process::id()));
+		fs::create_dir_all(dir.join("i18n")).unwrap();
+		for name in ["en-US.ftl", "ru.ftl", "de.json", ".ftl", "messages-fr.ftl"] {
+			fs::write(dir.join("i18n").join(name), []).unwrap();
+		}
+		for locale in ["pt_BR", "sr@latin", "uk"] {
+			fs::create_dir_all(dir.join("po").join(locale)).unwrap();
+		}
+		fs::write(dir.join("po").join("pt_BR").join("messages.po"), []).unwrap();
+		fs::write(dir.join("po").join("sr@latin").join("messages.po"), []).unwrap();
+
+		let pattern = |pattern: &str| available_locales_from_pattern(&format!("{}/{pattern}", dir.display()));
+		let locales = |files: &[LocaleFile], expected: &[&str]| assert_eq!(
+			files.iter().map(|f| f.locale.as_str()).collect::<Vec<&str>>(), expected
+		);
+		let ftl = pattern("i18n/{locale}.ftl").unwrap();
+		let prefixed = pattern("i18n/messages-{locale}.ftl").unwrap();
+		let po = pattern("po/{locale}/messages.po").unwrap();
+		let no_placeholder = pattern("i18n/en-US.ftl").unwrap();
+		let missing = pattern("missing/{locale}.ftl");
+		fs::remove_dir_all(&dir).unwrap();
+
+		// The format of the locales is not checked
+		locales(&ftl, &["en-US", "messages-fr", "ru"]);
+		assert_eq!(ftl[0].path, dir.join("i18n").join("en-US.ftl"));
+		locales(&prefixed, &["fr"]);
+		locales(&po, &["pt_BR", "sr@latin"]);
+		assert_eq!(po[1].path, dir.join("po").join("sr@latin").join("messages.po"));
+		locales(&no_placeholder, &[]);
+		assert!(matches!(missing, Err(Error::Io(ref error)) if error.kind() == std::io::ErrorKind::NotFound));
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_LocaleFile_as_available_locale() {
+		let files = ["en", "ru"].map(|locale| LocaleFile { locale: locale.to_string(), path: PathBuf::from(format!("{locale}.ftl")) });
+		let best_match = crate::bcp47::best_matching_locale(files, ["ru-RU"]);
+		assert_eq!(best_match.map(|f| f.path), Some(PathBuf::from("ru.ftl")));
+	}
+}
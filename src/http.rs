@@ -0,0 +1,173 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching locales against the `Accept-Language` header of HTTP requests and building
+//! the `Content-Language` header of responses, using the types of the [`http`](https://docs.rs/http) crate.
+
+mod pipeline;
+
+use ::http::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE};
+
+pub use pipeline::NegotiationPipeline;
+
+use crate::bcp47::{content_language_header, AcceptLanguage};
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on the
+/// `Accept-Language` headers of a request.
+///
+/// Multiple `Accept-Language` headers are combined as a single comma-separated list, as
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-5.3) prescribes.
+/// Header values that are not visible ASCII are ignored.  
+/// Returns [`None`] if the request has no usable `Accept-Language` header or none of the languages
+/// matches. See [`AcceptLanguage`] for the details of the matching.
+///
+/// # Examples
+///
+/// ```
+/// use http::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE};
+/// use locale_match::http::best_matching_locale_for_request;
+///
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("ru-RU, en;q=0.5"));
+///
+/// let best_match = best_matching_locale_for_request(&headers, ["en-US", "ru-BY"])
+///     .unwrap_or("en-US");
+///
+/// assert_eq!(best_match, "ru-BY");
+/// ```
+pub fn best_matching_locale_for_request<T: AsRef<str>>(headers: &HeaderMap, available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+	AcceptLanguage::parse(&accept_language(headers)).best_matching_locale(available_locales)
+}
+
+/// Builds the value of a `Content-Language` header from the locales of the content.
+///
+/// See [`content_language_header`] for the details.  
+/// Returns [`None`] if none of the locales is a valid BCP 47 locale, in which case the header should
+/// not be sent.
+///
+/// # Examples
+///
+/// ```
+/// use http::header::{HeaderMap, CONTENT_LANGUAGE};
+/// use locale_match::http::{best_matching_locale_for_request, content_language};
+///
+///
+/// let request_headers = HeaderMap::new();
+/// let mut response_headers = HeaderMap::new();
+///
+/// let locale = best_matching_locale_for_request(&request_headers, ["en-us", "ru-ru"]).unwrap_or("en-us");
+///
+/// if let Some(value) = content_language([locale]) {
+///     response_headers.insert(CONTENT_LANGUAGE, value);
+/// }
+///
+/// assert_eq!(response_headers[CONTENT_LANGUAGE], "en-US");
+/// ```
+pub fn content_language<T: AsRef<str>>(locales: impl IntoIterator<Item = T>) -> Option<HeaderValue> {
+	let value = content_language_header(locales);
+	match value.is_empty() {
+		true => None,
+		false => HeaderValue::from_str(&value).ok(),
+	}
+}
+
+/// Combines the `Accept-Language` headers into a single value, skipping values that are not
+/// visible ASCII.
+pub(crate) fn accept_language(headers: &HeaderMap) -> String {
+	headers.get_all(ACCEPT_LANGUAGE)
+		.iter()
+		.filter_map(|value| value.to_str().ok())
+		.collect::<Vec<&str>>()
+		.join(",")
+}
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on the value of an
+/// `Accept-Language` header.
+///
+/// Returns [`None`] if the value is not visible ASCII or none of the languages matches.
+/// See [`AcceptLanguage`] for the details of the matching.
+///
+/// # Examples
+///
+/// ```
+/// use http::header::HeaderValue;
+/// use locale_match::http::best_matching_locale_for_header_value;
+///
+///
+/// let value = HeaderValue::from_static("de-CH, de;q=0.9");
+///
+/// assert_eq!(best_matching_locale_for_header_value(&value, ["en", "de-DE"]), Some("de-DE"));
+/// ```
+pub fn best_matching_locale_for_header_value<T: AsRef<str>>(value: &HeaderValue, available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+	AcceptLanguage::parse(value.to_str().ok()?).best_matching_locale(available_locales)
+}
+
+/// Runs a future that completes without waiting, e.g. of a filter or a service that only reads the
+/// headers of a request, so the integrations can be tested without an async runtime.
+#[cfg(all(test, any(feature = "warp", feature = "tower")))]
+pub(crate) fn complete<F: std::future::Future>(future: F) -> F::Output {
+	let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+	match std::future::Future::poll(std::pin::pin!(future), &mut context) {
+		std::task::Poll::Ready(output) => output,
+		std::task::Poll::Pending => panic!("the future is not complete"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_locale_for_request() {
+		fn case(values: &[&[u8]], expected: Option<&str>) {
+			let mut headers = HeaderMap::new();
+			for value in values {
+				headers.append(ACCEPT_LANGUAGE, HeaderValue::from_bytes(value).unwrap());
+			}
+			assert_eq!(best_matching_locale_for_request(&headers, ["en-US", "de-DE", "ru-RU"]), expected);
+		}
+
+		case(&[b"de-AT, en;q=0.5"], Some("de-DE"));
+		case(&[b"fr, en;q=0.5"], Some("en-US"));
+		case(&[b"fr"], None);
+
+		// Multiple headers
+		case(&[b"fr", b"ru;q=0.9"], Some("ru-RU"));
+		case(&[b"en;q=0.5", b"ru"], Some("ru-RU"));
+		case(&[b"ru;q=0", b"ru-RU"], Some("ru-RU"));
+
+		// Missing and non-ASCII headers
+		case(&[], None);
+		case(&["fr-\u{00E9}".as_bytes(), b"de"], Some("de-DE"));
+	}
+
+	#[test]
+	fn test_best_matching_locale_for_header_value() {
+		let available_locales = ["en-US", "de-DE"];
+		assert_eq!(best_matching_locale_for_header_value(&HeaderValue::from_static("de"), available_locales), Some("de-DE"));
+		assert_eq!(best_matching_locale_for_header_value(&HeaderValue::from_static("de;q=0, en"), available_locales), Some("en-US"));
+		assert_eq!(best_matching_locale_for_header_value(&HeaderValue::from_bytes("d\u{00E9}".as_bytes()).unwrap(), available_locales), None);
+	}
+
+	#[test]
+	fn test_content_language() {
+		assert_eq!(content_language(["de-at"]), Some(HeaderValue::from_static("de-AT")));
+		assert_eq!(content_language(["en", "de"]), Some(HeaderValue::from_static("en, de")));
+		assert_eq!(content_language(["en_US"]), None);
+		assert_eq!(content_language([] as [&str; 0]), None);
+	}
+}
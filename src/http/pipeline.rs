@@ -0,0 +1,205 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A negotiation pipeline combining several locale sources of an HTTP request.
+
+use ::http::header::{HeaderMap, COOKIE};
+use ::http::Uri;
+
+use crate::bcp47::{AcceptLanguage, Matcher};
+
+/// A source of user locales in an HTTP request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+	/// A query parameter with the given name, e.g. `?lang=de`.
+	Query(String),
+	/// A cookie with the given name.
+	Cookie(String),
+	/// The `Accept-Language` headers.
+	AcceptLanguage,
+}
+
+/// A pipeline that negotiates the locale of an HTTP request from several sources tried in priority
+/// order.
+///
+/// Websites usually honor an explicit choice of the user, made with a query parameter or stored in a
+/// cookie, before the `Accept-Language` header of the browser. The sources are registered with
+/// [`query`](Self::query), [`cookie`](Self::cookie) and [`accept_language`](Self::accept_language)
+/// in the order they should be tried, and the first source that produces a match wins.  
+/// The value of a query parameter or a cookie is matched as a single user locale, so `?lang=de`
+/// selects `de-DE` if it is available. The values are used as is, without percent-decoding.  
+/// If no source produces a match, the default locale set with [`with_default`](Self::with_default)
+/// is returned.
+///
+/// # Examples
+///
+/// ```
+/// use http::Request;
+/// use locale_match::http::NegotiationPipeline;
+///
+///
+/// let pipeline = NegotiationPipeline::new(["en-US", "de-DE", "ru-RU"])
+///     .query("lang")
+///     .cookie("lang")
+///     .accept_language()
+///     .with_default("en-US");
+///
+/// let request = Request::get("/?lang=de")
+///     .header("cookie", "lang=ru")
+///     .header("accept-language", "ru-RU")
+///     .body(())
+///     .unwrap();
+///
+/// // The query parameter takes precedence over the cookie and the header
+/// assert_eq!(pipeline.negotiate(request.uri(), request.headers()), Some("de-DE"));
+///
+/// let request = Request::get("/").body(()).unwrap();
+///
+/// assert_eq!(pipeline.negotiate(request.uri(), request.headers()), Some("en-US"));
+/// ```
+#[derive(Debug)]
+pub struct NegotiationPipeline<T> {
+	available_locales: Vec<T>,
+	matcher: Matcher,
+	sources: Vec<Source>,
+	default: Option<T>,
+}
+
+impl<T: AsRef<str> + Clone> NegotiationPipeline<T> {
+	/// Creates a pipeline without sources for the available BCP 47 locales.
+	pub fn new(available_locales: impl IntoIterator<Item = T>) -> Self {
+		Self {
+			available_locales: available_locales.into_iter().collect(),
+			matcher: Matcher::new(),
+			sources: Vec::new(),
+			default: None,
+		}
+	}
+
+	/// Sets the [`Matcher`] used for all sources.
+	pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+		self.matcher = matcher;
+		self
+	}
+
+	/// Sets the locale returned when no source produces a match.
+	pub fn with_default(mut self, locale: T) -> Self {
+		self.default = Some(locale);
+		self
+	}
+
+	/// Adds the query parameter with the given name as the next source.
+	pub fn query(mut self, name: impl Into<String>) -> Self {
+		self.sources.push(Source::Query(name.into()));
+		self
+	}
+
+	/// Adds the cookie with the given name as the next source.
+	pub fn cookie(mut self, name: impl Into<String>) -> Self {
+		self.sources.push(Source::Cookie(name.into()));
+		self
+	}
+
+	/// Adds the `Accept-Language` headers as the next source.
+	///
+	/// The headers are matched as described in [`AcceptLanguage`].
+	pub fn accept_language(mut self) -> Self {
+		self.sources.push(Source::AcceptLanguage);
+		self
+	}
+
+	/// Negotiates the locale of a request with the given URI and headers.
+	///
+	/// Returns the match of the first source that produces one, or the default locale otherwise.
+	/// Returns [`None`] if no source produces a match and no default locale is set.
+	pub fn negotiate(&self, uri: &Uri, headers: &HeaderMap) -> Option<T> {
+		self.sources.iter()
+			.find_map(|source| match source {
+				Source::Query(name) => uri.query()
+					.and_then(|query| parameter(query.split('&'), name))
+					.and_then(|value| self.matcher.best_matching_locale(&self.available_locales, [value])),
+				Source::Cookie(name) => headers.get_all(COOKIE)
+					.iter()
+					.filter_map(|value| value.to_str().ok())
+					.find_map(|cookies| parameter(cookies.split(';'), name))
+					.and_then(|value| self.matcher.best_matching_locale(&self.available_locales, [value.trim_matches('"')])),
+				Source::AcceptLanguage => AcceptLanguage::parse(&super::accept_language(headers))
+					.best_matching_locale_with(&self.matcher, &self.available_locales),
+			})
+			.or(self.default.as_ref())
+			.cloned()
+	}
+}
+
+/// Finds the value of the first non-empty `name=value` pair with the given name.
+fn parameter<'a>(pairs: impl Iterator<Item = &'a str>, name: &str) -> Option<&'a str> {
+	pairs
+		.filter_map(|pair| pair.trim().split_once('='))
+		.find(|&(n, value)| n == name && !value.is_empty())
+		.map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use ::http::Request;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_NegotiationPipeline() {
+		fn case(pipeline: &NegotiationPipeline<&str>, uri: &str, headers: &[(&str, &str)], expected: Option<&str>) {
+			let request = headers.iter()
+				.fold(Request::get(uri), |request, (name, value)| request.header(*name, *value))
+				.body(())
+				.unwrap();
+			assert_eq!(pipeline.negotiate(request.uri(), request.headers()), expected);
+		}
+
+		let pipeline = NegotiationPipeline::new(["en-US", "de-DE", "ru-RU"])
+			.query("lang")
+			.cookie("lang")
+			.accept_language();
+
+		// Priority of sources
+		case(&pipeline, "/?lang=de", &[("cookie", "lang=ru"), ("accept-language", "en")], Some("de-DE"));
+		case(&pipeline, "/", &[("cookie", "lang=ru"), ("accept-language", "en")], Some("ru-RU"));
+		case(&pipeline, "/", &[("accept-language", "en")], Some("en-US"));
+		case(&pipeline, "/", &[], None);
+
+		// Sources without a match are skipped
+		case(&pipeline, "/?lang=fr", &[("cookie", "lang=ru")], Some("ru-RU"));
+		case(&pipeline, "/?lang=", &[("cookie", "lang=xx-invalid-locale")], None);
+		case(&pipeline, "/?lang=fr&lang=de", &[], None);
+
+		// Query and cookie parsing
+		case(&pipeline, "/page?a=1&lang=de-AT&b=2", &[], Some("de-DE"));
+		case(&pipeline, "/?language=de", &[], None);
+		case(&pipeline, "/", &[("cookie", "session=abc; lang=\"ru\"")], Some("ru-RU"));
+		case(&pipeline, "/", &[("cookie", "session=abc"), ("cookie", "lang=ru")], Some("ru-RU"));
+
+		// Default locale
+		let pipeline = pipeline.with_default("en-US");
+		case(&pipeline, "/?lang=fr", &[], Some("en-US"));
+
+		// Order of registration
+		let pipeline = NegotiationPipeline::new(["en-US", "de-DE", "ru-RU"])
+			.accept_language()
+			.query("lang");
+		case(&pipeline, "/?lang=de", &[("accept-language", "ru")], Some("ru-RU"));
+		case(&pipeline, "/?lang=de", &[("accept-language", "fr")], Some("de-DE"));
+	}
+}
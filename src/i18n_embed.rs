@@ -0,0 +1,195 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for selecting the languages of [`i18n-embed`](https://docs.rs/i18n-embed) localizers
+//! with the scoring of this library instead of `fluent-langneg`.
+//!
+//! [`MatchingLocalizer`] wraps any [`Localizer`], so the languages are selected with a [`Matcher`]
+//! both when [`Localizer::select`] is called directly and when a [`LanguageRequester`](::i18n_embed::LanguageRequester)
+//! notifies its listeners.
+
+use ::i18n_embed::{I18nAssets, I18nEmbedError, LanguageLoader, Localizer};
+use unic_langid::LanguageIdentifier;
+
+use crate::bcp47::{Matcher, NegotiationStrategy};
+use crate::unic::negotiate_languages_with;
+
+/// Selects and loads the available languages for the requested languages like [`i18n_embed::select`](https://docs.rs/i18n-embed/latest/i18n_embed/fn.select.html),
+/// but negotiates them with the given [`Matcher`].
+///
+/// The available languages are negotiated with the [`Filtering`](NegotiationStrategy::Filtering)
+/// strategy, and the fallback language of the loader is appended as the default one.  
+/// Returns the selected languages, which are loaded unless there are none.
+///
+/// # Errors
+///
+/// Returns an error if the available languages cannot be listed or the selected languages cannot be
+/// loaded.
+pub fn select(matcher: &Matcher, language_loader: &dyn LanguageLoader, i18n_assets: &dyn I18nAssets, requested_languages: &[LanguageIdentifier]) -> Result<Vec<LanguageIdentifier>, I18nEmbedError> {
+	let available_languages = language_loader.available_languages(i18n_assets)?;
+	let default_language = language_loader.fallback_language();
+	let supported_languages = negotiate_languages_with(matcher, requested_languages, &available_languages, Some(default_language), NegotiationStrategy::Filtering)
+		.into_iter()
+		.cloned()
+		.collect::<Vec<LanguageIdentifier>>();
+	if !supported_languages.is_empty() {
+		language_loader.load_languages(i18n_assets, &supported_languages)?;
+	}
+	Ok(supported_languages)
+}
+
+/// A [`Localizer`] that selects languages with a [`Matcher`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use i18n_embed::{DefaultLocalizer, I18nAssets, LanguageLoader, Localizer};
+/// use locale_match::bcp47::Matcher;
+/// use locale_match::i18n_embed::MatchingLocalizer;
+///
+///
+/// fn localizer(loader: &'static (dyn LanguageLoader + Send + Sync), assets: &'static (dyn I18nAssets + Send + Sync)) -> Arc<dyn Localizer> {
+///     let localizer = MatchingLocalizer::new(DefaultLocalizer::new(loader, assets))
+///         .with_matcher(Matcher::new().with_isolated_pseudo_locales(true));
+///     Arc::new(localizer)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MatchingLocalizer<L> {
+	localizer: L,
+	matcher: Matcher,
+}
+
+impl<L: Localizer> MatchingLocalizer<L> {
+	/// Wraps a localizer, selecting its languages with the default [`Matcher`].
+	pub fn new(localizer: L) -> Self {
+		Self { localizer, matcher: Matcher::new() }
+	}
+
+	/// Sets the matcher used for selecting the languages.
+	pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+		self.matcher = matcher;
+		self
+	}
+
+	/// Returns the wrapped localizer.
+	pub fn into_inner(self) -> L {
+		self.localizer
+	}
+}
+
+impl<L: Localizer> Localizer for MatchingLocalizer<L> {
+	fn language_loader(&self) -> &'_ dyn LanguageLoader {
+		self.localizer.language_loader()
+	}
+
+	fn i18n_assets(&self) -> &'_ dyn I18nAssets {
+		self.localizer.i18n_assets()
+	}
+
+	fn select(&self, requested_languages: &[LanguageIdentifier]) -> Result<Vec<LanguageIdentifier>, I18nEmbedError> {
+		select(&self.matcher, self.language_loader(), self.i18n_assets(), requested_languages)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::borrow::Cow;
+	use std::sync::Mutex;
+
+	struct Loader {
+		fallback: LanguageIdentifier,
+		loaded: Mutex<Vec<LanguageIdentifier>>,
+	}
+
+	impl LanguageLoader for Loader {
+		fn fallback_language(&self) -> &LanguageIdentifier {
+			&self.fallback
+		}
+
+		fn domain(&self) -> &str {
+			"test"
+		}
+
+		fn language_file_name(&self) -> String {
+			"test.ftl".to_string()
+		}
+
+		fn current_language(&self) -> LanguageIdentifier {
+			self.loaded.lock().unwrap().first().unwrap_or(&self.fallback).clone()
+		}
+
+		fn reload(&self, _: &dyn I18nAssets) -> Result<(), I18nEmbedError> {
+			Ok(())
+		}
+
+		fn load_languages(&self, _: &dyn I18nAssets, language_ids: &[LanguageIdentifier]) -> Result<(), I18nEmbedError> {
+			*self.loaded.lock().unwrap() = language_ids.to_vec();
+			Ok(())
+		}
+	}
+
+	struct Assets(&'static [&'static str]);
+
+	impl I18nAssets for Assets {
+		fn get_files(&self, _: &str) -> Vec<Cow<'_, [u8]>> {
+			Vec::new()
+		}
+
+		fn filenames_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+			Box::new(self.0.iter().map(|locale| format!("{locale}/test.ftl")))
+		}
+	}
+
+	struct TestLocalizer(Loader, Assets);
+
+	impl Localizer for TestLocalizer {
+		fn language_loader(&self) -> &'_ dyn LanguageLoader {
+			&self.0
+		}
+
+		fn i18n_assets(&self) -> &'_ dyn I18nAssets {
+			&self.1
+		}
+	}
+
+	fn langids(locales: &[&str]) -> Vec<LanguageIdentifier> {
+		locales.iter().map(|l| l.parse().unwrap()).collect()
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_MatchingLocalizer() {
+		let loader = Loader { fallback: "en-US".parse().unwrap(), loaded: Mutex::new(Vec::new()) };
+		let localizer = MatchingLocalizer::new(TestLocalizer(loader, Assets(&["en-US", "de-DE", "fr", "en-XA"])));
+
+		assert_eq!(localizer.select(&langids(&["de-AT", "fr-CA"])).unwrap(), langids(&["de-DE", "fr", "en-US"]));
+		assert_eq!(localizer.language_loader().current_language(), langids(&["de-DE"])[0]);
+
+		assert_eq!(localizer.select(&langids(&["es"])).unwrap(), langids(&["en-US"]));
+		assert_eq!(localizer.language_loader().current_language(), langids(&["en-US"])[0]);
+
+		// The matcher is used for the selection
+		assert_eq!(localizer.select(&langids(&["en"])).unwrap(), langids(&["en-US", "en-XA"]));
+		let localizer = localizer.with_matcher(Matcher::new().with_isolated_pseudo_locales(true));
+		assert_eq!(localizer.select(&langids(&["en"])).unwrap(), langids(&["en-US"]));
+		assert_eq!(localizer.into_inner().0.loaded.into_inner().unwrap(), langids(&["en-US"]));
+	}
+}
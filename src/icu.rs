@@ -0,0 +1,131 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching [`icu_locid`] locales and converting them to and from
+//! [`LanguageTag`].
+
+use std::fmt::Display;
+
+use icu_locid::{LanguageIdentifier, Locale, ParserError};
+use language_tags::LanguageTag;
+
+use crate::bcp47::Matcher;
+
+/// Finds the best matching locale from a list of available locales based on a list of user locales,
+/// where the locales are [`icu_locid`] types, e.g. [`Locale`] or [`LanguageIdentifier`], or
+/// references to them.
+///
+/// The locales are matched exactly like in [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale),
+/// and the returned locale is one of the available locales.
+///
+/// # Examples
+///
+/// ```
+/// use icu_locid::{langid, locale};
+/// use locale_match::icu::best_matching_locale;
+///
+///
+/// let available_locales = [langid!("en-US"), langid!("ru-BY")];
+/// let user_locales = [locale!("ru-RU"), locale!("en")];
+///
+/// let best_match = best_matching_locale(&available_locales, &user_locales);
+///
+/// assert_eq!(best_match, Some(&langid!("ru-BY")));
+/// ```
+pub fn best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: Display,
+	T2: Display,
+{
+	best_matching_locale_with(&Matcher::new(), available_locales, user_locales)
+}
+
+/// Finds the best matching locale exactly like [`best_matching_locale`], but with the given
+/// [`Matcher`] instead of the default one.
+pub fn best_matching_locale_with<T1, T2>(matcher: &Matcher, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: Display,
+	T2: Display,
+{
+	matcher.best_matching_displayed_locale(available_locales, user_locales)
+}
+
+/// Converts an [`icu_locid`] locale, e.g. a [`Locale`] or a [`LanguageIdentifier`], to a
+/// [`LanguageTag`].
+///
+/// # Errors
+///
+/// Returns an error if the serialized locale is not a well-formed BCP 47 language tag, which is
+/// only possible for locales with extensions that BCP 47 does not allow.
+pub fn to_language_tag(locale: &impl Display) -> Result<LanguageTag, language_tags::ParseError> {
+	LanguageTag::parse(&locale.to_string())
+}
+
+/// Converts a [`LanguageTag`] to an [`icu_locid`] [`Locale`].
+///
+/// # Errors
+///
+/// Returns an error if the tag cannot be represented as a Unicode locale identifier, e.g. if it has
+/// an extended language subtag or is grandfathered.
+pub fn to_locale(tag: &LanguageTag) -> Result<Locale, ParserError> {
+	Locale::try_from_bytes(tag.as_str().as_bytes())
+}
+
+/// Converts a [`LanguageTag`] to an [`icu_locid`] [`LanguageIdentifier`].
+///
+/// Extensions and private use subtags of the tag are dropped.
+///
+/// # Errors
+///
+/// Returns an error if the tag cannot be represented as a Unicode language identifier, e.g. if it
+/// has an extended language subtag or is grandfathered.
+pub fn to_language_identifier(tag: &LanguageTag) -> Result<LanguageIdentifier, ParserError> {
+	LanguageIdentifier::try_from_locale_bytes(tag.as_str().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use icu_locid::{langid, locale};
+
+	#[test]
+	fn test_best_matching_locale() {
+		let available_locales = [locale!("en-US"), locale!("pt-BR"), locale!("pt-PT")];
+		assert_eq!(best_matching_locale(&available_locales, [langid!("pt-PT")]), Some(&locale!("pt-PT")));
+		assert_eq!(best_matching_locale(&available_locales, [langid!("pt")]), Some(&locale!("pt-BR")));
+		assert_eq!(best_matching_locale(&available_locales, [langid!("fr"), langid!("en-GB")]), Some(&locale!("en-US")));
+		assert_eq!(best_matching_locale(&available_locales, [langid!("fr")]), None);
+
+		// Mixed types
+		assert_eq!(best_matching_locale(available_locales, ["en"]), Some(locale!("en-US")));
+	}
+
+	#[test]
+	fn test_conversions() {
+		let tag = to_language_tag(&"sr-Latn-RS-u-ca-gregory".parse::<Locale>().unwrap()).unwrap();
+		assert_eq!(tag.as_str(), "sr-Latn-RS-u-ca-gregory");
+		assert_eq!(to_language_tag(&langid!("en-US")).unwrap().as_str(), "en-US");
+
+		let tag = LanguageTag::parse("de-CH-1996-x-private").unwrap();
+		assert_eq!(to_locale(&tag), "de-CH-1996-x-private".parse::<Locale>());
+		assert_eq!(to_language_identifier(&tag), Ok(langid!("de-CH-1996")));
+
+		// Not representable
+		assert!(to_locale(&LanguageTag::parse("zh-cmn-Hans").unwrap()).is_err());
+		assert!(to_language_identifier(&LanguageTag::parse("i-klingon").unwrap()).is_err());
+	}
+}
@@ -0,0 +1,164 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching the keys of [`IndexMap`]s, where the insertion order of the available
+//! locales defines their priority.
+//!
+//! Like the lists of available locales, an earlier key wins a tie between keys that match a user
+//! locale equally well, so catalogs can be kept in a map without losing their order.
+
+use std::hash::BuildHasher;
+
+use ::indexmap::IndexMap;
+
+use crate::matching::KeyEntry;
+
+/// Finds the entry of an [`IndexMap`] whose key is the best matching BCP 47 locale, like
+/// [`bcp47::best_matching_key`](crate::bcp47::best_matching_key), and returns its index, key and
+/// value, like [`IndexMap::get_full`].
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::IndexMap;
+/// use locale_match::indexmap::best_matching_entry;
+///
+///
+/// let translations = IndexMap::from([("en-GB", "Colour"), ("en-US", "Color"), ("ru-RU", "Цвет")]);
+///
+/// // The earlier key wins the tie
+/// assert_eq!(best_matching_entry(&translations, ["en-CA"]), Some((0, &"en-GB", &"Colour")));
+/// assert_eq!(best_matching_entry(&translations, ["en-US"]), Some((1, &"en-US", &"Color")));
+/// assert_eq!(best_matching_entry(&translations, ["fr"]), None);
+/// ```
+#[cfg(feature = "bcp47")]
+pub fn best_matching_entry<K, V, S, T>(map: &IndexMap<K, V, S>, user_locales: impl IntoIterator<Item = T>) -> Option<(usize, &K, &V)>
+where
+	K: AsRef<str>,
+	S: BuildHasher,
+	T: AsRef<str>
+{
+	best_matching_entry_with(&crate::bcp47::Matcher::new(), map, user_locales)
+}
+
+/// Finds the entry of an [`IndexMap`] exactly like [`best_matching_entry`], but with the given
+/// [`Matcher`](crate::bcp47::Matcher) instead of the default one.
+#[cfg(feature = "bcp47")]
+pub fn best_matching_entry_with<'a, K, V, S, T>(matcher: &crate::bcp47::Matcher, map: &'a IndexMap<K, V, S>, user_locales: impl IntoIterator<Item = T>) -> Option<(usize, &'a K, &'a V)>
+where
+	K: AsRef<str>,
+	S: BuildHasher,
+	T: AsRef<str>
+{
+	let entries = map.iter().map(|(key, value)| KeyEntry(key, value));
+	matcher.best_matching_locale_with_confidence(entries, user_locales)
+		.map(|m| (m.available_index, m.locale.0, m.locale.1))
+}
+
+/// Finds the entry of an [`IndexMap`] whose key is the best matching POSIX locale, like
+/// [`posix::best_matching_key`](crate::posix::best_matching_key), and returns its index, key and
+/// value, like [`IndexMap::get_full`].
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::IndexMap;
+/// use locale_match::indexmap::best_matching_posix_entry;
+///
+///
+/// let catalogs = IndexMap::from([("en_GB.UTF-8", 1), ("en_US.UTF-8", 2), ("ru_RU.UTF-8", 3)]);
+///
+/// assert_eq!(best_matching_posix_entry(&catalogs, ["en_CA.UTF-8"]), Some((0, &"en_GB.UTF-8", &1)));
+/// assert_eq!(best_matching_posix_entry(&catalogs, ["ru_UA.UTF-8"]), Some((2, &"ru_RU.UTF-8", &3)));
+/// ```
+#[cfg(feature = "posix")]
+pub fn best_matching_posix_entry<K, V, S, T>(map: &IndexMap<K, V, S>, user_locales: impl IntoIterator<Item = T>) -> Option<(usize, &K, &V)>
+where
+	K: AsRef<str>,
+	S: BuildHasher,
+	T: AsRef<str>
+{
+	best_matching_posix_entry_with(&crate::posix::Matcher::new(), map, user_locales)
+}
+
+/// Finds the entry of an [`IndexMap`] exactly like [`best_matching_posix_entry`], but with the
+/// given [`Matcher`](crate::posix::Matcher) instead of the default one.
+#[cfg(feature = "posix")]
+pub fn best_matching_posix_entry_with<'a, K, V, S, T>(matcher: &crate::posix::Matcher, map: &'a IndexMap<K, V, S>, user_locales: impl IntoIterator<Item = T>) -> Option<(usize, &'a K, &'a V)>
+where
+	K: AsRef<str>,
+	S: BuildHasher,
+	T: AsRef<str>
+{
+	let entries = map.iter().map(|(key, value)| KeyEntry(key, value));
+	matcher.best_matching_locale_with_confidence(entries, user_locales)
+		.map(|m| (m.available_index, m.locale.0, m.locale.1))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[cfg(feature = "bcp47")]
+	fn test_best_matching_entry() {
+		fn case(map: &[(&str, i32)], user_locales: &[&str], expected: Option<(usize, &str, i32)>) {
+			let map = map.iter().copied().collect::<IndexMap<&str, i32>>();
+			let entry = best_matching_entry(&map, user_locales);
+			assert_eq!(entry.map(|(i, k, v)| (i, *k, *v)), expected, "{map:?} and {user_locales:?}");
+		}
+
+		// Insertion order breaks ties
+		case(&[("en-US", 1), ("en-GB", 2)], &["en"], Some((0, "en-US", 1)));
+		case(&[("en-GB", 2), ("en-US", 1)], &["en"], Some((0, "en-GB", 2)));
+
+		// Better matches and user preferences
+		case(&[("en-US", 1), ("en-GB", 2), ("ru", 3)], &["en-GB"], Some((1, "en-GB", 2)));
+		case(&[("en-US", 1), ("ru", 3)], &["fr", "ru-RU"], Some((1, "ru", 3)));
+
+		// No match and malformed keys
+		case(&[("en-US", 1)], &["fr"], None);
+		case(&[("en_US", 1), ("en", 2)], &["en-US"], Some((1, "en", 2)));
+		case(&[], &["en"], None);
+
+		// Matcher configuration
+		let map = IndexMap::from([("en", 1), ("en-US", 2)]);
+		let matcher = crate::bcp47::Matcher::new().with_tie_break(crate::matching::TieBreak::MostSpecific);
+		assert_eq!(best_matching_entry_with(&matcher, &map, ["en-GB"]), Some((1, &"en-US", &2)));
+	}
+
+	#[test]
+	#[cfg(feature = "posix")]
+	fn test_best_matching_posix_entry() {
+		fn case(map: &[(&str, i32)], user_locales: &[&str], expected: Option<(usize, &str, i32)>) {
+			let map = map.iter().copied().collect::<IndexMap<&str, i32>>();
+			let entry = best_matching_posix_entry(&map, user_locales);
+			assert_eq!(entry.map(|(i, k, v)| (i, *k, *v)), expected, "{map:?} and {user_locales:?}");
+		}
+
+		// Insertion order breaks ties
+		case(&[("en_US", 1), ("en_GB", 2)], &["en"], Some((0, "en_US", 1)));
+		case(&[("en_GB", 2), ("en_US", 1)], &["en"], Some((0, "en_GB", 2)));
+
+		// Better matches and user preferences
+		case(&[("en_US.UTF-8", 1), ("en_GB.UTF-8", 2)], &["en_GB"], Some((1, "en_GB.UTF-8", 2)));
+		case(&[("en_US", 1), ("ru_RU", 3)], &["fr_FR", "ru"], Some((1, "ru_RU", 3)));
+
+		// No match
+		case(&[("en_US", 1)], &["fr"], None);
+		case(&[], &["en"], None);
+	}
+}
\ No newline at end of file
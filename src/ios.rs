@@ -0,0 +1,51 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for detecting user locales on iOS.
+
+/// Returns the preferred languages of the user, as returned by
+/// [`NSLocale.preferredLanguages`](https://developer.apple.com/documentation/foundation/nslocale/1415614-preferredlanguages),
+/// which reflects the language order in the Settings app.
+///
+/// The locales are in the BCP 47 format and ordered by priority, so the result is ready to be passed
+/// to [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) as the user locales.  
+/// Returns an empty list if the languages cannot be retrieved.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::ios::user_locales;
+///
+///
+/// let available_locales = ["en-US", "ru-RU"];
+///
+/// let best_match = best_matching_locale(available_locales, user_locales())
+///     .unwrap_or("en-US");
+/// ```
+pub fn user_locales() -> Vec<String> {
+	crate::apple::preferred_languages()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_locales() {
+		assert!(user_locales().iter().all(|l| !l.is_empty()));
+	}
+}
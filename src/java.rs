@@ -0,0 +1,296 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching locales in the format of Java's
+//! [`Locale.toString`](https://docs.oracle.com/en/java/javase/21/docs/api/java.base/java/util/Locale.html#toString()),
+//! e.g. `en_US`, `en_US_POSIX` or `zh_CN_#Hans`, and converting them to and from BCP 47.
+//!
+//! The locales are converted to BCP 47 with [`to_bcp47`] and matched with the
+//! [`bcp47`](crate::bcp47) module, while the matched locale is returned as it was passed in.
+//!
+//! # Examples
+//!
+//! ```
+//! use locale_match::java::best_matching_locale;
+//!
+//!
+//! let available_locales = ["en_US", "sr_RS_#Latn", "zh_TW_#Hant"];
+//!
+//! assert_eq!(best_matching_locale(available_locales, ["sr_Latn_BA", "en"]), Some("sr_RS_#Latn"));
+//! assert_eq!(best_matching_locale(available_locales, ["zh__#Hant"]), Some("zh_TW_#Hant"));
+//! ```
+
+use language_tags::LanguageTag;
+
+use crate::bcp47::{canonical_case, Matcher};
+
+/// Finds the best matching locale from a list of available Java locales based on a list of user
+/// Java locales.
+///
+/// The locales are matched exactly like in [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale)
+/// after converting them with [`to_bcp47`], and the locales that cannot be converted are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::java::best_matching_locale;
+///
+///
+/// let available_locales = ["en_US", "en_GB", "de_DE", "th_TH_TH_#u-nu-thai"];
+///
+/// assert_eq!(best_matching_locale(available_locales, ["en_US_POSIX"]), Some("en_US"));
+/// assert_eq!(best_matching_locale(available_locales, ["de_AT", "en"]), Some("de_DE"));
+/// assert_eq!(best_matching_locale(available_locales, ["th"]), Some("th_TH_TH_#u-nu-thai"));
+/// assert_eq!(best_matching_locale(available_locales, ["fr_FR"]), None);
+/// ```
+pub fn best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	best_matching_locale_with(&Matcher::new(), available_locales, user_locales)
+}
+
+/// Finds the best matching locale exactly like [`best_matching_locale`], but with the given
+/// [`Matcher`] instead of the default one.
+pub fn best_matching_locale_with<T1, T2>(matcher: &Matcher, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	let mut available_locales = available_locales.into_iter()
+		.filter_map(|locale| to_bcp47(locale.as_ref()).map(|tag| (locale, tag)))
+		.collect::<Vec<(T1, String)>>();
+	let user_locales = user_locales.into_iter()
+		.filter_map(|locale| to_bcp47(locale.as_ref()));
+	let index = matcher.best_matching_locale_with_confidence(available_locales.iter().map(|(_, tag)| tag), user_locales)?.available_index;
+	Some(available_locales.swap_remove(index).0)
+}
+
+/// Converts a Java locale to a BCP 47 locale, like Java's `Locale.toLanguageTag`, e.g.
+/// `sr_RS_#Latn` to `sr-Latn-RS`.
+///
+/// The locale is in the form `language[_country[_variant]][_#script][_extensions]`, where the
+/// extensions follow `#` if there is no script, e.g. `th_TH_TH_#u-nu-thai`. The form with the
+/// script after the language, e.g. `sr_Latn_RS`, which is common in configuration files, is
+/// accepted too.  
+/// Variants that are not valid BCP 47 variants, e.g. `NY`, are converted to private use subtags
+/// like in Java, e.g. `no_NO_NY` to `no-NO-x-lvariant-ny`.  
+/// Returns [`None`] if the locale has no language or is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::java::to_bcp47;
+///
+///
+/// assert_eq!(to_bcp47("sr_RS_#Latn").as_deref(), Some("sr-Latn-RS"));
+/// assert_eq!(to_bcp47("sr_Latn_RS").as_deref(), Some("sr-Latn-RS"));
+/// assert_eq!(to_bcp47("zh__#Hans").as_deref(), Some("zh-Hans"));
+/// assert_eq!(to_bcp47("ca_ES_VALENCIA").as_deref(), Some("ca-ES-valencia"));
+/// assert_eq!(to_bcp47("no_NO_NY").as_deref(), Some("no-NO-x-lvariant-ny"));
+/// assert_eq!(to_bcp47("th_TH_TH_#u-nu-thai").as_deref(), Some("th-TH-u-nu-thai-x-lvariant-th"));
+/// assert_eq!(to_bcp47("_US"), None);
+/// ```
+pub fn to_bcp47(locale: &str) -> Option<String> {
+	let (base, rest) = locale.split_once("_#").unwrap_or((locale, ""));
+	if base.contains('-') {
+		return None;
+	}
+	let (mut script, extensions) = match rest.split_once('_') {
+		Some((script, extensions)) => (script, extensions),
+		None if is_script(rest) => (rest, ""),
+		None => ("", rest),
+	};
+	let mut parts = base.split('_');
+	let language = parts.next().filter(|language| !language.is_empty())?;
+	let mut country = parts.next().unwrap_or("");
+	if script.is_empty() && is_script(country) {
+		script = country;
+		country = parts.next().unwrap_or("");
+	}
+	let variants = parts.collect::<Vec<&str>>();
+	let (variants, private_variants) = variants.iter()
+		.partition::<Vec<&str>, _>(|variant| is_variant(variant));
+	let mut subtags = [language, script, country].into_iter()
+		.chain(variants)
+		.chain((!extensions.is_empty()).then_some(extensions))
+		.filter(|subtag| !subtag.is_empty())
+		.collect::<Vec<&str>>();
+	if !private_variants.is_empty() {
+		subtags.push(if extensions.contains("x-") { "lvariant" } else { "x-lvariant" });
+		subtags.extend(private_variants);
+	}
+	canonical_case(&subtags.join("-"))
+}
+
+/// Converts a BCP 47 locale to a Java locale, like Java's `Locale.forLanguageTag(tag).toString()`,
+/// e.g. `sr-Latn-RS` to `sr_RS_#Latn`.  
+/// Returns [`None`] if the locale is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::java::from_bcp47;
+///
+///
+/// assert_eq!(from_bcp47("sr-Latn-RS").as_deref(), Some("sr_RS_#Latn"));
+/// assert_eq!(from_bcp47("zh-Hans").as_deref(), Some("zh__#Hans"));
+/// assert_eq!(from_bcp47("en-US-x-lvariant-POSIX").as_deref(), Some("en_US_POSIX"));
+/// assert_eq!(from_bcp47("th-TH-u-nu-thai").as_deref(), Some("th_TH_#u-nu-thai"));
+/// assert_eq!(from_bcp47("en_US"), None);
+/// ```
+pub fn from_bcp47(locale: &str) -> Option<String> {
+	let tag = LanguageTag::parse(locale).ok()?;
+	let (private_use, lvariant) = match tag.private_use() {
+		Some(private_use) => match private_use.to_ascii_lowercase().find("-lvariant-") {
+			Some(i) => (Some(&private_use[..i]).filter(|p| *p != "x"), Some(&private_use[i + "-lvariant-".len()..])),
+			None => (Some(private_use), None),
+		},
+		None => (None, None),
+	};
+	let language = tag.primary_language();
+	let country = tag.region().unwrap_or("");
+	let variant = tag.variant().map(|variant| variant.replace('-', "_"))
+		.into_iter()
+		.chain(lvariant.map(|lvariant| lvariant.replace('-', "_").to_ascii_uppercase()))
+		.collect::<Vec<String>>()
+		.join("_");
+	let script = tag.script().unwrap_or("");
+	let extensions = [tag.extension(), private_use].into_iter().flatten().collect::<Vec<&str>>().join("-");
+
+	let mut result = language.to_string();
+	if !country.is_empty() || !variant.is_empty() || !script.is_empty() || !extensions.is_empty() {
+		result.push('_');
+		result.push_str(country);
+	}
+	if !variant.is_empty() {
+		result.push('_');
+		result.push_str(&variant);
+	}
+	if !script.is_empty() {
+		result.push_str("_#");
+		result.push_str(script);
+	}
+	if !extensions.is_empty() {
+		result.push_str(if script.is_empty() { "_#" } else { "_" });
+		result.push_str(&extensions);
+	}
+	Some(result)
+}
+
+/// Checks whether a subtag is a script, e.g. `Latn`.
+fn is_script(subtag: &str) -> bool {
+	subtag.len() == 4 && subtag.bytes().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Checks whether a subtag is a valid BCP 47 variant, e.g. `valencia` or `1901`.
+fn is_variant(subtag: &str) -> bool {
+	subtag.bytes().all(|c| c.is_ascii_alphanumeric())
+		&& match subtag.len() {
+			5..=8 => true,
+			4 => subtag.as_bytes()[0].is_ascii_digit(),
+			_ => false,
+		}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_bcp47() {
+		fn case(locale: &str, expected: Option<&str>) {
+			assert_eq!(to_bcp47(locale).as_deref(), expected, "{locale:?}");
+		}
+
+		// Language, country and script
+		case("en", Some("en"));
+		case("en_US", Some("en-US"));
+		case("EN_us", Some("en-US"));
+		case("zh_CN_#Hans", Some("zh-Hans-CN"));
+		case("zh__#hant", Some("zh-Hant"));
+		case("sr_Latn_RS", Some("sr-Latn-RS"));
+		case("sr_Latn", Some("sr-Latn"));
+
+		// Variants
+		case("ca_ES_VALENCIA", Some("ca-ES-valencia"));
+		case("de_DE_1901", Some("de-DE-1901"));
+		case("sl_SI_ROZAJ_BISKE", Some("sl-SI-rozaj-biske"));
+		case("en_US_POSIX", Some("en-US-posix"));
+		case("no_NO_NY", Some("no-NO-x-lvariant-ny"));
+		case("no__NY", Some("no-x-lvariant-ny"));
+
+		// Extensions
+		case("th_TH_#u-nu-thai", Some("th-TH-u-nu-thai"));
+		case("ja_JP_#Jpan_u-ca-japanese", Some("ja-Jpan-JP-u-ca-japanese"));
+		case("th_TH_TH_#u-nu-thai", Some("th-TH-u-nu-thai-x-lvariant-th"));
+		case("no_NO_NY_#x-foo", Some("no-NO-x-foo-lvariant-ny"));
+
+		// Malformed
+		case("", None);
+		case("_US", None);
+		case("en_U$", None);
+		case("en-US", None);
+	}
+
+	#[test]
+	fn test_from_bcp47() {
+		fn case(locale: &str, expected: Option<&str>) {
+			assert_eq!(from_bcp47(locale).as_deref(), expected, "{locale:?}");
+		}
+
+		// Language, region and script
+		case("en", Some("en"));
+		case("en-US", Some("en_US"));
+		case("zh-Hans-CN", Some("zh_CN_#Hans"));
+		case("zh-Hant", Some("zh__#Hant"));
+
+		// Variants
+		case("ca-ES-valencia", Some("ca_ES_valencia"));
+		case("sl-rozaj-biske", Some("sl__rozaj_biske"));
+		case("en-US-x-lvariant-POSIX", Some("en_US_POSIX"));
+		case("en-US-x-foo-lvariant-posix", Some("en_US_POSIX_#x-foo"));
+
+		// Extensions
+		case("th-TH-u-nu-thai", Some("th_TH_#u-nu-thai"));
+		case("ja-Jpan-JP-u-ca-japanese", Some("ja_JP_#Jpan_u-ca-japanese"));
+
+		// Malformed
+		case("en_US", None);
+		case("", None);
+	}
+
+	#[test]
+	fn test_round_trip() {
+		for locale in ["en", "en_US", "zh_CN_#Hans", "ca_ES_valencia", "no_NO_NY", "th_TH_#u-nu-thai", "ja_JP_#Jpan_u-ca-japanese"] {
+			assert_eq!(to_bcp47(locale).and_then(|tag| from_bcp47(&tag)).as_deref(), Some(locale), "{locale:?}");
+		}
+	}
+
+	#[test]
+	fn test_best_matching_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(best_matching_locale(available_locales, user_locales).copied(), expected, "{available_locales:?}, {user_locales:?}");
+		}
+
+		case(&["en_US", "ru_RU"], &["ru_BY"], Some("ru_RU"));
+		case(&["sr_RS_#Cyrl", "sr_RS_#Latn"], &["sr_Latn_RS"], Some("sr_RS_#Latn"));
+		case(&["en_US", "en_US_POSIX"], &["en_US_POSIX"], Some("en_US_POSIX"));
+		case(&["_US", "en"], &["en_US"], Some("en"));
+		case(&["en_US", "ru_RU"], &["fr_FR", "_RU"], None);
+	}
+}
\ No newline at end of file
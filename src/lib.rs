@@ -20,7 +20,85 @@
 //! * [`bcp47`] — for matching locales in the [BCP 47](https://www.ietf.org/rfc/bcp/bcp47.html) format.
 //! * [`posix`] — for matching locales in the [POSIX](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html) format.
 //!
-//! Both modules provide the `best_matching_locale` function.
+//! Both modules provide the `best_matching_locale` function and its `best_matching_locale_with_confidence`
+//! variant, which also reports the quality of the match as a [`LocaleMatch`](matching::LocaleMatch).  
+//! The matching can be fine-tuned with the `Matcher` type of each module, and its `explain` method
+//! reports how a match was found as a [`matching::Explanation`], while the `score_matrix` functions
+//! score all pairs of available and user locales as a [`matching::Matrix`]. The
+//! `best_matching_locales_all_ties` functions return all of the available locales tied for the
+//! best match, so the application can break the tie itself.  
+//! Malformed locales, which are otherwise silently ignored, can be reported with the
+//! `best_matching_locale_with_warnings` functions, or rejected with the `try_best_matching_locale`
+//! functions. The errors of the fallible functions convert into the crate-level [`Error`].  
+//! Locales given in a mix of both formats, e.g. from several sources, or BCP 47 user locales
+//! against POSIX available locales and vice versa, can be matched with the [`auto`] module, which
+//! detects the format of each locale.  
+//! The [`system_best_match`] function matches available BCP 47 locales against the user locales
+//! of the operating system in one call. Where the user locales come from, e.g. the environment
+//! variables, an `Accept-Language` header or the operating system with fallbacks, can be composed
+//! with the [`source`] module, and the [`negotiator`] module negotiates the locale of an
+//! application from the sources with a default locale in a single call.  
+//! The available locales can be discovered from translation files with the [`files`] module, or at
+//! compile time with the `include_available_locales!` macro (the `macros` feature). Catalogs of
+//! available locales, e.g. translation files, embedded assets or a database, can be cached and
+//! refreshed with the [`source`] module, or updated as translation files are deployed with the
+//! `notify` module (the `notify` feature). Catalogs kept in an `IndexMap`, where the insertion
+//! order defines the priority of the available locales, can be matched with the `indexmap` module
+//! (the `indexmap` feature).
+//! Locales can be validated and kept as [`bcp47::Bcp47Locale`] and [`posix::PosixLocale`], which,
+//! along with the matcher options, implement `Serialize` and `Deserialize` with the `serde` feature
+//! and `JsonSchema` with the `schemars` feature. Locale arguments of command-line
+//! applications can be validated with the `clap` module (the `clap` feature).
+//! Locales in the format of Java's `Locale.toString()`, e.g. `sr_RS_#Latn`, can be matched and
+//! converted to and from BCP 47 with the `java` module (the `java` feature).
+//! With the `iana` feature, BCP 47 locales can be checked against the IANA Language Subtag Registry
+//! with [`Bcp47Locale::validate`](bcp47::Bcp47Locale), and the BCP 47 matcher can ignore locales
+//! with unregistered subtags, e.g. `en-UK`, or compare a locale without a script as having the script
+//! implied by its language, e.g. `en` as `en-Latn`, and canonicalize locales with deprecated
+//! subtags, e.g. `iw` as `he`. The deprecated subtags are also reported as warnings with their
+//! replacements.
+//! BCP 47 locale literals can be validated at compile time with the `locale!` macro (the `macros` feature).
+//! With the `bumpalo` feature, the matchers can allocate the temporary parsed locales in a
+//! [`bumpalo`](https://docs.rs/bumpalo) arena with their `best_matching_locale_in` methods.
+//! With the `tracing` feature, the matchers emit [`tracing`](https://docs.rs/tracing) events for
+//! ignored malformed locales, the scores of the candidates and the selected locale. For custom
+//! logging, metrics or early termination, the matchers can call an observer function for each
+//! evaluated candidate with their `with_observer` methods.
+//! For A/B testing, the matchers can pick one of the equally matching available locales at random,
+//! optionally weighted, with their `with_random_tie_break` methods.
+//! With the `metrics` feature, the matchers record the matched locales, the number of skipped user
+//! locales and the failed matches with the [`metrics`](https://docs.rs/metrics) facade, showing
+//! which locales users receive and which are missing.
+//! The library can be used from C and C++ with the `capi` module (the `capi` feature), from
+//! JavaScript with the `wasm` module (the `wasm` feature) and from Node.js with the `node` module
+//! (the `node` feature).
+//! The `locale-match` command-line tool (the `cli` feature) matches locales in shell scripts, e.g.
+//! `locale-match dir ./po --user "$LANG"` prints the locale and the path of the best matching
+//! translation file in a directory, or, with `--json`, the match and the scores of all candidates
+//! as JSON, and `locale-match explain` prints the scores of all pairs of locales and the reason the
+//! best match was chosen. `locale-match env --available ...` prints the available locale that best
+//! matches the environment, e.g. for `export LANG=...` in wrapper scripts.
+//!
+//! Locales of HTTP requests can be negotiated with the following optional modules:
+//! * `http` — for matching the `Accept-Language` header of [`http`](https://docs.rs/http) requests (the `http` feature).
+//! * `warp` — for negotiating locales in a [`warp`](https://docs.rs/warp) filter (the `warp` feature).
+//! * `tower` — for negotiating locales in a [`tower`](https://docs.rs/tower) middleware (the `tower` feature).
+//!
+//! Locale types of other crates can be matched with the following optional modules:
+//! * `icu` — for matching [`icu_locid`](https://docs.rs/icu_locid) locales (the `icu` feature).
+//! * `unic` — for matching [`unic_langid`](https://docs.rs/unic-langid) language identifiers (the `unic` feature).
+//! * `oxilangtag` — for matching [`oxilangtag`](https://docs.rs/oxilangtag) language tags (the `oxilangtag` feature).
+//! * `i18n_embed` — for selecting the languages of [`i18n-embed`](https://docs.rs/i18n-embed) localizers (the `i18n-embed` feature).
+//! * `rust_i18n` — for selecting the translations of [`rust-i18n`](https://docs.rs/rust-i18n) (the `rust-i18n` feature).
+//!
+//! User locales can be detected with the following optional modules:
+//! * `windows` — for detecting user locales on Windows (the `windows` feature).
+//! * `macos` — for detecting user locales on macOS (the `macos` feature).
+//! * `ios` — for detecting user locales on iOS (the `ios` feature).
+//! * `web` — for detecting user locales in web browsers on WebAssembly targets (the `web` feature).
+//! * `android` — for detecting user locales on Android (the `android` feature).
+//! * `sys_locale` — for detecting user locales on all major platforms with the
+//!   [`sys-locale`](https://docs.rs/sys-locale) crate (the `sys-locale` feature).
 //! 
 //! ## Examples
 //! 
@@ -50,8 +128,107 @@
 //! assert_eq!(best_match, Some("ru_BY.UTF-8"));
 //! ```
 
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+pub mod matching;
+
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+mod engine;
+
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+pub mod files;
+
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+pub mod source;
+
+#[cfg(feature = "bcp47")]
+pub mod negotiator;
+
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+pub mod error;
+
+#[cfg(any(feature = "bcp47", feature = "posix"))]
+pub use error::Error;
+
 #[cfg(feature = "bcp47")]
 pub mod bcp47;
 
 #[cfg(feature = "posix")]
-pub mod posix;
\ No newline at end of file
+pub mod posix;
+
+#[cfg(feature = "java")]
+pub mod java;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "warp")]
+pub mod warp;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+#[cfg(feature = "icu")]
+pub mod icu;
+
+#[cfg(feature = "unic")]
+pub mod unic;
+
+#[cfg(feature = "oxilangtag")]
+pub mod oxilangtag;
+
+#[cfg(feature = "i18n-embed")]
+pub mod i18n_embed;
+
+#[cfg(feature = "rust-i18n")]
+pub mod rust_i18n;
+
+#[cfg(feature = "clap")]
+pub mod clap;
+
+#[cfg(feature = "notify")]
+pub mod notify;
+
+#[cfg(all(feature = "indexmap", any(feature = "bcp47", feature = "posix")))]
+pub mod indexmap;
+
+#[cfg(feature = "macros")]
+pub use locale_match_macros::{include_available_locales, locale};
+
+#[cfg(all(feature = "capi", any(feature = "bcp47", feature = "posix")))]
+pub mod capi;
+
+#[cfg(all(feature = "wasm", any(feature = "bcp47", feature = "posix")))]
+pub mod wasm;
+
+#[cfg(all(feature = "node", any(feature = "bcp47", feature = "posix")))]
+pub mod node;
+
+#[cfg(all(feature = "bcp47", feature = "posix"))]
+pub mod auto;
+
+#[cfg(all(feature = "bcp47", feature = "posix"))]
+pub mod system;
+
+#[cfg(feature = "sys-locale")]
+pub mod sys_locale;
+
+#[cfg(all(feature = "bcp47", feature = "posix"))]
+pub use system::system_best_match;
+
+#[cfg(all(feature = "windows", windows))]
+pub mod windows;
+
+#[cfg(all(feature = "macos", target_os = "macos"))]
+pub mod macos;
+
+#[cfg(all(feature = "ios", target_os = "ios"))]
+pub mod ios;
+
+#[cfg(any(all(feature = "macos", target_os = "macos"), all(feature = "ios", target_os = "ios")))]
+mod apple;
+
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod web;
+
+#[cfg(all(feature = "android", target_os = "android"))]
+pub mod android;
\ No newline at end of file
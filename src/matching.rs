@@ -0,0 +1,837 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Types shared by the locale format modules.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// The result of matching user locales against available locales.
+///
+/// Besides the matched locale itself, it describes how good the match is, so the caller can, for
+/// example, display a "content is not available in your language" notice for weak matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocaleMatch<T> {
+	/// The matched available locale, exactly as it was passed in.
+	pub locale: T,
+	/// The index of the matched locale in the list of available locales.
+	pub available_index: usize,
+	/// The index of the user locale the match was found for.
+	/// `0` means the match was found for the most preferred user locale, larger values mean that
+	/// the more preferred user locales could not be matched at all.
+	pub user_index: usize,
+	/// The confidence of the match in the range `0.0..=1.0`.
+	/// `1.0` means that every subtag present in either of the two locales is equal in both of them.
+	/// The value decreases as more subtags differ or are present in only one of the locales.
+	/// A match is never found for locales with different languages, so the confidence of a found
+	/// match is always above `0.0`.
+	pub confidence: f64,
+}
+
+/// A problem with a locale found during matching, e.g. a malformed locale, reported by the
+/// `best_matching_locale_with_warnings` functions and the `explain` methods of the matchers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Warning {
+	/// Whether the locale is an available or a user locale.
+	pub kind: LocaleKind,
+	/// The index of the locale in its list.
+	pub index: usize,
+	/// The locale, exactly as it was passed in.
+	pub input: String,
+	/// The description of the problem.
+	pub reason: String,
+	/// The suggested replacement of the locale, e.g. with its deprecated BCP 47 subtags replaced
+	/// (with the `iana` feature), or the best suggestion of
+	/// [`bcp47::suggest_locale`](crate::bcp47::suggest_locale) for a malformed BCP 47 locale.
+	pub replacement: Option<String>,
+}
+
+/// A malformed locale rejected by the `try_best_matching_locale` functions.
+///
+/// `E` is the parse error of the locale format, e.g. [`posix::ParseError`](crate::posix::ParseError).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidLocale<E> {
+	/// Whether the locale is an available or a user locale.
+	pub kind: LocaleKind,
+	/// The index of the locale in its list.
+	pub index: usize,
+	/// The locale, exactly as it was passed in.
+	pub input: String,
+	/// The reason the locale is malformed.
+	pub source: E,
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for InvalidLocale<E> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let kind = match self.kind {
+			LocaleKind::Available => "available",
+			LocaleKind::User => "user",
+		};
+		write!(f, "invalid {kind} locale {:?} at index {}: {}", self.input, self.index, self.source)
+	}
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for InvalidLocale<E> {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.source)
+	}
+}
+
+/// The list a locale comes from, see [`Warning`] and [`InvalidLocale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocaleKind {
+	/// An available locale.
+	Available,
+	/// A user locale.
+	User,
+}
+
+/// A machine-readable explanation of matching user locales against available locales, returned by
+/// the `explain` methods of the matchers.
+///
+/// The user locales are tried in the order of preference like in the `best_matching_locale`
+/// functions, so the explanation covers the user locales up to the one the match was found for.  
+/// `S` is the type of the subtag categories of the locale format, e.g. [`bcp47::Subtag`](crate::bcp47::Subtag).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation<T, S> {
+	/// The best match, the same as found by the `best_matching_locale_with_confidence` functions.
+	/// Its user index tells the user locale that drove the match.
+	pub best_match: Option<LocaleMatch<T>>,
+	/// The available locales considered for each of the tried user locales, i.e. those with the
+	/// same language, in the order of the user locales and then of the available locales.
+	pub candidates: Vec<Candidate<S>>,
+	/// The indices of the malformed available locales.
+	pub malformed_available_locales: Vec<usize>,
+	/// The indices of the malformed user locales among the tried ones.
+	pub malformed_user_locales: Vec<usize>,
+	/// The problems with the well-formed available locales and the tried user locales, e.g.
+	/// deprecated BCP 47 subtags (with the `iana` feature).
+	pub warnings: Vec<Warning>,
+}
+
+/// The scores of all pairs of available and user locales, returned by the `score_matrix` functions.
+///
+/// Unlike an [`Explanation`], the matrix covers all of the user locales and does not select a
+/// winner, so it shows the whole landscape of a catalog, e.g. for tuning it before a release.  
+/// `S` is the type of the subtag categories of the locale format, e.g. [`bcp47::Subtag`](crate::bcp47::Subtag).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<S> {
+	available_len: usize,
+	user_len: usize,
+	/// The cells by available locale and then by user locale.
+	cells: Vec<Option<Candidate<S>>>,
+	malformed_available_locales: Vec<usize>,
+	malformed_user_locales: Vec<usize>,
+}
+
+impl<S> Matrix<S> {
+	/// Creates an empty matrix of the given size.
+	pub(crate) fn new(available_len: usize, user_len: usize) -> Self {
+		Self {
+			available_len,
+			user_len,
+			cells: std::iter::repeat_with(|| None).take(available_len * user_len).collect(),
+			malformed_available_locales: Vec::new(),
+			malformed_user_locales: Vec::new(),
+		}
+	}
+
+	/// Sets the cell of the candidate.
+	pub(crate) fn insert(&mut self, candidate: Candidate<S>) {
+		let index = candidate.available_index * self.user_len + candidate.user_index;
+		self.cells[index] = Some(candidate);
+	}
+
+	/// Marks a locale as malformed.
+	pub(crate) fn insert_malformed(&mut self, kind: LocaleKind, index: usize) {
+		match kind {
+			LocaleKind::Available => self.malformed_available_locales.push(index),
+			LocaleKind::User => self.malformed_user_locales.push(index),
+		}
+	}
+
+	/// Returns the number of available locales, i.e. of the rows.
+	pub fn available_len(&self) -> usize {
+		self.available_len
+	}
+
+	/// Returns the number of user locales, i.e. of the columns.
+	pub fn user_len(&self) -> usize {
+		self.user_len
+	}
+
+	/// Returns the score of the available locale for the user locale with its subtag breakdown, or
+	/// [`None`] if the locales cannot match, e.g. because their languages differ or one of them is
+	/// malformed.
+	///
+	/// # Panics
+	///
+	/// Panics if either of the indices is out of bounds.
+	pub fn get(&self, available_index: usize, user_index: usize) -> Option<&Candidate<S>> {
+		assert!(available_index < self.available_len && user_index < self.user_len, "index out of bounds");
+		self.cells[available_index * self.user_len + user_index].as_ref()
+	}
+
+	/// Returns the scores of the available locale for each of the user locales.
+	///
+	/// # Panics
+	///
+	/// Panics if the index is out of bounds.
+	pub fn row(&self, available_index: usize) -> &[Option<Candidate<S>>] {
+		assert!(available_index < self.available_len, "index out of bounds");
+		&self.cells[available_index * self.user_len..(available_index + 1) * self.user_len]
+	}
+
+	/// Returns the indices of the malformed available locales.
+	pub fn malformed_available_locales(&self) -> &[usize] {
+		&self.malformed_available_locales
+	}
+
+	/// Returns the indices of the malformed user locales.
+	pub fn malformed_user_locales(&self) -> &[usize] {
+		&self.malformed_user_locales
+	}
+}
+
+/// An available locale considered for a user locale, see [`Explanation`] and [`Matrix`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate<S> {
+	/// The index of the locale in the list of available locales.
+	pub available_index: usize,
+	/// The index of the user locale in the list of user locales.
+	pub user_index: usize,
+	/// The score of the locale; the locale with the highest score wins among the candidates for the
+	/// same user locale.
+	pub score: f64,
+	/// The scores of the subtags, except for the language, which is the same in all candidates.  
+	/// Their sum is the score of the locale, unless it is adjusted by a custom scorer.
+	pub subtags: Vec<SubtagScore<S>>,
+}
+
+/// The score of a subtag of a candidate, see [`Candidate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubtagScore<S> {
+	/// The category of the subtag.
+	pub subtag: S,
+	/// The comparison of the subtag in the available and the user locale.
+	pub comparison: Comparison,
+	/// The weight of the subtag category.
+	pub weight: f64,
+	/// The score earned (or, if negative, lost) by the subtag.
+	pub score: f64,
+}
+
+/// The differences between two locales, returned by the `diff` functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleDiff<'a, S> {
+	/// The difference of the languages.
+	pub language: Difference<'a>,
+	/// The differences of the other subtags present in any of the locales, in the order of the
+	/// subtag categories.
+	pub subtags: Vec<(S, Difference<'a>)>,
+}
+
+impl<S> LocaleDiff<'_, S> {
+	/// Checks whether all subtags of the locales are equal.
+	pub fn is_equal(&self) -> bool {
+		matches!(self.language, Difference::Equal(_)) && self.subtags.iter().all(|(_, difference)| matches!(difference, Difference::Equal(_)))
+	}
+}
+
+/// The difference of a subtag present in at least one of two locales, see [`LocaleDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difference<'a> {
+	/// The subtag is equal in both locales, with the value from the first locale.
+	Equal(&'a str),
+	/// The subtag differs, with the values from the first and the second locale.
+	Different(&'a str, &'a str),
+	/// The subtag is present only in the first locale.
+	OnlyFirst(&'a str),
+	/// The subtag is present only in the second locale.
+	OnlySecond(&'a str),
+}
+
+impl<'a> Difference<'a> {
+	/// Compares a subtag of two locales with the given equality function.  
+	/// Returns [`None`] if the subtag is absent in both locales.
+	pub(crate) fn of(a: Option<&'a str>, b: Option<&'a str>, eq: impl Fn(&str, &str) -> bool) -> Option<Self> {
+		match (a, b) {
+			(None, None) => None,
+			(Some(a), None) => Some(Self::OnlyFirst(a)),
+			(None, Some(b)) => Some(Self::OnlySecond(b)),
+			(Some(a), Some(b)) if eq(a, b) => Some(Self::Equal(a)),
+			(Some(a), Some(b)) => Some(Self::Different(a, b)),
+		}
+	}
+}
+
+/// The result of comparing two subtags of the same category.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+	/// The subtag is absent in both locales.
+	BothAbsent,
+	/// The subtag is present only in the user locale.
+	AvailableAbsent,
+	/// The subtag is present only in the available locale.
+	UserAbsent,
+	/// The subtag is present in both locales and has the given similarity in the range `0.0..=1.0`,
+	/// where `1.0` means the subtags are equal.
+	Present(f64),
+}
+
+impl Comparison {
+	/// Compare two single-valued subtags with the given equality function.
+	pub(crate) fn of<S: ?Sized>(aval: Option<&S>, user: Option<&S>, eq: impl Fn(&S, &S) -> bool) -> Self {
+		match (aval, user) {
+			(None, None) => Self::BothAbsent,
+			(None, Some(_)) => Self::AvailableAbsent,
+			(Some(_), None) => Self::UserAbsent,
+			(Some(a), Some(u)) => Self::Present(if eq(a, u) { 1.0 } else { 0.0 }),
+		}
+	}
+
+	/// Compare two multi-valued subtags as sets.  
+	/// The similarity is the number of common values divided by the number of distinct values in
+	/// both sets.
+	#[cfg_attr(not(feature = "bcp47"), allow(dead_code))]
+	pub(crate) fn of_sets<T, A, U>(aval: A, user: U, eq: impl Fn(T, T) -> bool) -> Self
+	where
+		T: Copy,
+		A: IntoIterator<Item = T>,
+		A::IntoIter: Clone,
+		U: IntoIterator<Item = T>,
+		U::IntoIter: Clone,
+	{
+		// The values are iterated several times instead of being collected
+		fn distinct<T: Copy>(values: impl Iterator<Item = T> + Clone, eq: impl Fn(T, T) -> bool) -> usize {
+			values.clone()
+				.enumerate()
+				.filter(|&(i, v)| !values.clone().take(i).any(|w| eq(v, w)))
+				.count()
+		}
+
+		let (aval, user) = (aval.into_iter(), user.into_iter());
+		match (aval.clone().next().is_none(), user.clone().next().is_none()) {
+			(true, true) => Self::BothAbsent,
+			(true, false) => Self::AvailableAbsent,
+			(false, true) => Self::UserAbsent,
+			(false, false) => {
+				let common = aval.clone()
+					.enumerate()
+					.filter(|&(i, a)| !aval.clone().take(i).any(|w| eq(a, w)) && user.clone().any(|u| eq(a, u)))
+					.count();
+				Self::Present(common as f64 / (distinct(aval, &eq) + distinct(user, &eq) - common) as f64)
+			}
+		}
+	}
+
+	/// The contribution of the comparison to the score of a match.
+	pub(crate) fn score(&self, weight: f64, missing: MissingSubtag, scoring: &Scoring) -> f64 {
+		let mismatch = |similarity: f64| similarity * weight - (1.0 - similarity) * weight * scoring.mismatch_penalty;
+		match (self, missing) {
+			(Self::Present(similarity), _) => mismatch(*similarity),
+			(Self::BothAbsent, MissingSubtag::Neutral) => 0.0,
+			(Self::BothAbsent, _) => weight,
+			(_, MissingSubtag::Neutral) => 0.0,
+			(_, MissingSubtag::Wildcard) => weight,
+			(_, MissingSubtag::Partial(fraction)) => fraction.clamp(0.0, 1.0) * weight,
+			(_, MissingSubtag::RequireAbsence) => mismatch(0.0),
+		}
+	}
+}
+
+/// Describes how a subtag present in only one of two compared locales is scored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MissingSubtag {
+	/// An absent subtag neither earns nor costs anything, whether it is absent in one or both of
+	/// the locales.  
+	/// This is the default.
+	#[default]
+	Neutral,
+	/// An absent subtag matches any subtag at full weight.
+	Wildcard,
+	/// An absent subtag matches any subtag at the given fraction of its weight, clamped to the range
+	/// `0.0..=1.0`. A subtag absent in both locales matches at full weight.
+	Partial(f64),
+	/// A subtag matches only if it is absent in both locales.
+	/// A subtag absent in only one of the locales counts as a contradiction, see the
+	/// `with_mismatch_penalty` method of the matchers.
+	RequireAbsence,
+}
+
+/// Describes how to choose among available locales that match a user locale equally well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TieBreak {
+	/// Choose the locale that appears earlier in the list of available locales.  
+	/// This is the default.
+	#[default]
+	ListOrder,
+	/// Choose the locale with subtags in the most categories, e.g. `en-US` over `en`. The subtags of
+	/// the same category count once, e.g. the variants of `sl-rozaj-biske`.  
+	/// Locales with subtags in the same number of categories are chosen by list order.
+	MostSpecific,
+	/// Choose the locale with subtags in the fewest categories, e.g. `en` over `en-US`. The subtags
+	/// of the same category count once, e.g. the variants of `sl-rozaj-biske`.  
+	/// Locales with subtags in the same number of categories are chosen by list order.
+	LeastSpecific,
+}
+
+/// Scoring options shared by the matchers of all locale formats.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scoring {
+	/// The fraction of the weight of a subtag subtracted from the score if the subtag is present in
+	/// both locales with different values.
+	pub(crate) mismatch_penalty: f64,
+	/// The treatment of absent subtags, indexed by the position of the subtag in the comparisons.
+	/// Subtags without an entry are [`MissingSubtag::Neutral`].
+	pub(crate) missing_subtags: Vec<MissingSubtag>,
+	/// The way to choose among equally scored available locales.
+	pub(crate) tie_break: TieBreak,
+}
+
+impl Scoring {
+	/// Set the treatment of the absent subtag at the given position in the comparisons.
+	pub(crate) fn set_missing_subtag(&mut self, index: usize, missing: MissingSubtag) {
+		if self.missing_subtags.len() <= index {
+			self.missing_subtags.resize(index + 1, MissingSubtag::Neutral);
+		}
+		self.missing_subtags[index] = missing;
+	}
+}
+
+/// Compares two equally scored available locales by their comparisons with the same user locale.  
+/// The greater locale is preferred; [`Ordering::Equal`] means the list order decides.
+pub(crate) fn tie_break(a: &[(Comparison, f64)], b: &[(Comparison, f64)], tie_break: TieBreak) -> Ordering {
+	// The number of categories with subtags in the available locale
+	let specificity = |comparisons: &[(Comparison, f64)]| comparisons.iter()
+		.filter(|(comparison, _)| matches!(comparison, Comparison::Present(_) | Comparison::UserAbsent))
+		.count();
+	match tie_break {
+		TieBreak::ListOrder => Ordering::Equal,
+		TieBreak::MostSpecific => specificity(a).cmp(&specificity(b)),
+		TieBreak::LeastSpecific => specificity(b).cmp(&specificity(a)),
+	}
+}
+
+/// Calculates the confidence of a match from the weight of the (already matched) language and the
+/// weighted comparisons of the other subtags.
+pub(crate) fn confidence(language_weight: f64, comparisons: impl IntoIterator<Item = (Comparison, f64)>) -> f64 {
+	let (matched, considered) = comparisons.into_iter()
+		.fold((language_weight, language_weight), |(matched, considered), (comparison, weight)| match comparison {
+			Comparison::BothAbsent => (matched, considered),
+			Comparison::Present(similarity) => (matched + similarity * weight, considered + weight),
+			Comparison::AvailableAbsent | Comparison::UserAbsent => (matched, considered + weight),
+		});
+	matched / considered
+}
+
+/// Scores each of the weighted comparisons of the subtags like [`score`], for an [`Explanation`].
+pub(crate) fn subtag_scores<S: Copy>(subtags: &[S], comparisons: &[(Comparison, f64)], scoring: &Scoring) -> Vec<SubtagScore<S>> {
+	subtags.iter()
+		.zip(comparisons)
+		.enumerate()
+		.map(|(i, (&subtag, &(comparison, weight)))| {
+			let missing = scoring.missing_subtags.get(i).copied().unwrap_or_default();
+			SubtagScore { subtag, comparison, weight, score: comparison.score(weight, missing, scoring) }
+		})
+		.collect()
+}
+
+/// Calculates the score of a match from the weighted comparisons of the subtags.
+pub(crate) fn score(comparisons: impl IntoIterator<Item = (Comparison, f64)>, scoring: &Scoring) -> f64 {
+	comparisons.into_iter()
+		.enumerate()
+		.map(|(i, (comparison, weight))| {
+			let missing = scoring.missing_subtags.get(i).copied().unwrap_or_default();
+			comparison.score(weight, missing, scoring)
+		})
+		.sum()
+}
+
+/// A locale given as bytes, with its lossy conversion if it is not valid UTF-8.  
+/// Locales are ASCII, so the conversion is needed only for malformed locales.
+pub(crate) struct LossyBytes<T> {
+	pub(crate) locale: T,
+	converted: Option<String>,
+}
+
+impl<T: AsRef<[u8]>> LossyBytes<T> {
+	pub(crate) fn new(locale: T) -> Self {
+		let converted = match String::from_utf8_lossy(locale.as_ref()) {
+			Cow::Borrowed(_) => None,
+			Cow::Owned(converted) => Some(converted),
+		};
+		Self { locale, converted }
+	}
+}
+
+impl<T: AsRef<[u8]>> AsRef<str> for LossyBytes<T> {
+	fn as_ref(&self) -> &str {
+		self.converted.as_deref()
+			.or_else(|| std::str::from_utf8(self.locale.as_ref()).ok())
+			.unwrap_or_default()
+	}
+}
+
+/// The word with the lowest bit of every byte set.
+const LOW_BITS: u64 = u64::MAX / 0xFF;
+/// The word with the highest bit of every byte set.
+const HIGH_BITS: u64 = LOW_BITS << 7;
+
+/// Loads up to 8 bytes into a word, padding it with zeros.
+#[inline]
+fn load_word(bytes: &[u8]) -> u64 {
+	bytes.iter().rev().fold(0, |word, &byte| word << 8 | byte as u64)
+}
+
+/// Folds the ASCII uppercase letters among the bytes of a word to lowercase, all 8 bytes at once.  
+/// Non-ASCII bytes are left as is, like by [`u8::to_ascii_lowercase`].
+#[inline]
+const fn fold_word(word: u64) -> u64 {
+	let ascii = word & !HIGH_BITS;
+	let from_a = ascii + LOW_BITS * (0x80 - b'A' as u64);
+	let after_z = ascii + LOW_BITS * (0x80 - b'Z' as u64 - 1);
+	let uppercase = from_a & !after_z & !word & HIGH_BITS;
+	word | (uppercase >> 2)
+}
+
+/// Checks that two byte strings are equal ignoring ASCII case, comparing 8 bytes at a time.
+#[inline]
+fn eq_bytes_ignore_ascii_case(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.chunks(8).zip(b.chunks(8)).all(|(a, b)| fold_word(load_word(a)) == fold_word(load_word(b)))
+}
+
+/// Checks that two strings are equal ignoring ASCII case, like [`str::eq_ignore_ascii_case`], but
+/// comparing 8 bytes at a time.
+#[inline]
+#[cfg_attr(not(feature = "bcp47"), allow(dead_code))]
+pub(crate) fn eq_ignore_ascii_case(a: &str, b: &str) -> bool {
+	eq_bytes_ignore_ascii_case(a.as_bytes(), b.as_bytes())
+}
+
+/// Common aliases and legacy codes of regions with the current ISO 3166-1 alpha-2 codes: the
+/// deprecated codes from the territory aliases of CLDR, the ISO 3166-1 alpha-3 codes of common
+/// regions and `UK`, sorted ignoring case.  
+/// See [`territoryAlias`](https://github.com/unicode-org/cldr/blob/main/common/supplemental/supplementalMetadata.xml).
+pub(crate) const REGION_ALIASES: &[(&str, &str)] = &[
+	("AN", "CW"), ("ARE", "AE"), ("ARG", "AR"), ("AUS", "AU"), ("AUT", "AT"), ("BEL", "BE"),
+	("BGR", "BG"), ("BRA", "BR"), ("BU", "MM"), ("CAN", "CA"), ("CHE", "CH"), ("CHL", "CL"),
+	("CHN", "CN"), ("COL", "CO"), ("CS", "RS"), ("CT", "KI"), ("CZE", "CZ"), ("DD", "DE"),
+	("DEU", "DE"), ("DNK", "DK"), ("DY", "BJ"), ("EGY", "EG"), ("ESP", "ES"), ("FIN", "FI"),
+	("FRA", "FR"), ("FX", "FR"), ("GBR", "GB"), ("GRC", "GR"), ("HKG", "HK"), ("HRV", "HR"),
+	("HUN", "HU"), ("HV", "BF"), ("IDN", "ID"), ("IND", "IN"), ("IRL", "IE"), ("ISR", "IL"),
+	("ITA", "IT"), ("JPN", "JP"), ("KOR", "KR"), ("MEX", "MX"), ("MYS", "MY"), ("NH", "VU"),
+	("NLD", "NL"), ("NOR", "NO"), ("NZL", "NZ"), ("PER", "PE"), ("PHL", "PH"), ("POL", "PL"),
+	("PRT", "PT"), ("RH", "ZW"), ("ROU", "RO"), ("RUS", "RU"), ("SAU", "SA"), ("SGP", "SG"),
+	("SRB", "RS"), ("SU", "RU"), ("SVK", "SK"), ("SVN", "SI"), ("SWE", "SE"), ("THA", "TH"),
+	("TP", "TL"), ("TUR", "TR"), ("TWN", "TW"), ("UK", "GB"), ("UKR", "UA"), ("USA", "US"),
+	("VD", "VN"), ("VNM", "VN"), ("YD", "YE"), ("YU", "RS"), ("ZAF", "ZA"), ("ZR", "CD"),
+];
+
+/// Finds the value for a key in a table sorted ignoring case, ignoring case.
+pub(crate) fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+	table.binary_search_by(|(k, _)| cmp_ignore_ascii_case(k, key))
+		.ok()
+		.map(|i| table[i].1)
+}
+
+/// Compares two strings ignoring ASCII case.
+pub(crate) fn cmp_ignore_ascii_case(a: &str, b: &str) -> Ordering {
+	a.bytes().map(|c| c.to_ascii_lowercase()).cmp(b.bytes().map(|c| c.to_ascii_lowercase()))
+}
+
+/// An entry of a map matched by its key, see the `best_matching_key` functions.
+pub(crate) struct KeyEntry<'a, K, V>(pub(crate) &'a K, pub(crate) &'a V);
+
+impl<K: AsRef<str>, V> AsRef<str> for KeyEntry<'_, K, V> {
+	fn as_ref(&self) -> &str {
+		self.0.as_ref()
+	}
+}
+
+/// A string with its first 8 bytes folded to ASCII lowercase in advance, for comparing it
+/// repeatedly ignoring ASCII case.  
+/// Strings of up to 8 bytes, e.g. languages and territories, are compared as a single word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "posix"), allow(dead_code))]
+pub(crate) struct Folded<'a> {
+	len: usize,
+	head: u64,
+	tail: &'a [u8],
+}
+
+#[cfg_attr(not(feature = "posix"), allow(dead_code))]
+impl<'a> Folded<'a> {
+	#[inline]
+	pub(crate) fn new(s: &'a str) -> Self {
+		let (head, tail) = s.as_bytes().split_at(s.len().min(8));
+		Self { len: s.len(), head: fold_word(load_word(head)), tail }
+	}
+
+	/// Checks that the strings are equal ignoring ASCII case.
+	#[inline]
+	pub(crate) fn eq_ignore_ascii_case(&self, other: &Folded) -> bool {
+		self.len == other.len && self.head == other.head && eq_bytes_ignore_ascii_case(self.tail, other.tail)
+	}
+}
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event at the given level with the `tracing`
+/// feature, and does nothing without it.
+macro_rules! event {
+	($level:ident, $($arg:tt)+) => {{
+		#[cfg(feature = "tracing")]
+		::tracing::$level!($($arg)+);
+	}};
+}
+
+pub(crate) use event;
+
+/// Records the outcome of matching with the [`metrics`](https://docs.rs/metrics) facade:
+/// * `locale_match_negotiations` — a counter with the `format` and `outcome` (`matched` or
+///   `no_match`) labels.
+/// * `locale_match_matched_locales` — a counter with the `format` and `locale` labels, where
+///   `locale` is the matched available locale.
+/// * `locale_match_fallback_depth` — a histogram with the `format` label of the index of the
+///   matched user locale, i.e. the number of preferred user locales that were not available.
+#[cfg(feature = "metrics")]
+pub(crate) fn record_outcome(format: &'static str, matched: Option<(&str, usize)>) {
+	match matched {
+		Some((locale, user_index)) => {
+			metrics::counter!("locale_match_negotiations", "format" => format, "outcome" => "matched").increment(1);
+			metrics::counter!("locale_match_matched_locales", "format" => format, "locale" => locale.to_string()).increment(1);
+			metrics::histogram!("locale_match_fallback_depth", "format" => format).record(user_index as f64);
+		}
+		None => metrics::counter!("locale_match_negotiations", "format" => format, "outcome" => "no_match").increment(1),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Comparison() {
+		let eq = |a: &str, u: &str| a.eq_ignore_ascii_case(u);
+
+		// Single values
+		assert_eq!(Comparison::of(None, None, eq), Comparison::BothAbsent);
+		assert_eq!(Comparison::of(None, Some("US"), eq), Comparison::AvailableAbsent);
+		assert_eq!(Comparison::of(Some("US"), None, eq), Comparison::UserAbsent);
+		assert_eq!(Comparison::of(Some("US"), Some("us"), eq), Comparison::Present(1.0));
+		assert_eq!(Comparison::of(Some("US"), Some("GB"), eq), Comparison::Present(0.0));
+
+		// Sets
+		assert_eq!(Comparison::of_sets([], [], eq), Comparison::BothAbsent);
+		assert_eq!(Comparison::of_sets([], ["rozaj"], eq), Comparison::AvailableAbsent);
+		assert_eq!(Comparison::of_sets(["rozaj"], [], eq), Comparison::UserAbsent);
+		assert_eq!(Comparison::of_sets(["rozaj", "biske"], ["biske", "rozaj"], eq), Comparison::Present(1.0));
+		assert_eq!(Comparison::of_sets(["rozaj", "biske"], ["rozaj"], eq), Comparison::Present(0.5));
+		assert_eq!(Comparison::of_sets(["rozaj", "biske"], ["rozaj", "lipaw"], eq), Comparison::Present(1.0 / 3.0));
+		assert_eq!(Comparison::of_sets(["rozaj", "ROZAJ"], ["rozaj"], eq), Comparison::Present(1.0));
+		assert_eq!(Comparison::of_sets(["1901"], ["1996"], eq), Comparison::Present(0.0));
+	}
+
+	#[test]
+	fn test_lookup() {
+		assert!(REGION_ALIASES.is_sorted_by(|(a, _), (b, _)| cmp_ignore_ascii_case(a, b).is_lt()));
+		assert_eq!(lookup(REGION_ALIASES, "UK"), Some("GB"));
+		assert_eq!(lookup(REGION_ALIASES, "yu"), Some("RS"));
+		assert_eq!(lookup(REGION_ALIASES, "Gbr"), Some("GB"));
+		assert_eq!(lookup(REGION_ALIASES, "GB"), None);
+		assert_eq!(lookup(REGION_ALIASES, ""), None);
+	}
+
+	#[test]
+	fn test_eq_ignore_ascii_case() {
+		fn case(a: &str, b: &str) {
+			let expected = a.eq_ignore_ascii_case(b);
+			assert_eq!(eq_ignore_ascii_case(a, b), expected, "{a:?} and {b:?}");
+			assert_eq!(Folded::new(a).eq_ignore_ascii_case(&Folded::new(b)), expected, "{a:?} and {b:?}");
+		}
+
+		// Short strings
+		case("", "");
+		case("en", "EN");
+		case("en", "en_");
+		case("a", "a\0");
+		case("US", "us");
+		case("US", "UT");
+
+		// Long strings
+		case("ISO-8859-15", "iso-8859-15");
+		case("ISO-8859-15", "ISO-8859-1");
+		case("valencia-latin", "VALENCIA-LATIN");
+		case("valencia-latin", "valencia-latiN_");
+		case("abcdefgh", "ABCDEFGH");
+
+		// Every ASCII character against every other
+		for a in 0..=0x7F_u8 {
+			for b in 0..=0x7F_u8 {
+				case(&String::from_utf8(vec![a; 9]).unwrap(), &String::from_utf8(vec![b; 9]).unwrap());
+			}
+		}
+
+		// Non-ASCII
+		case("ö", "Ö");
+		case("ö", "ö");
+		case("Straße", "STRASSE");
+	}
+
+	#[test]
+	fn test_tie_break() {
+		let en = [(Comparison::BothAbsent, 8.0), (Comparison::BothAbsent, 4.0)];
+		let en_us = [(Comparison::UserAbsent, 8.0), (Comparison::BothAbsent, 4.0)];
+		let en_us_posix = [(Comparison::UserAbsent, 8.0), (Comparison::UserAbsent, 4.0)];
+
+		assert_eq!(tie_break(&en, &en_us, TieBreak::ListOrder), Ordering::Equal);
+		assert_eq!(tie_break(&en, &en_us, TieBreak::MostSpecific), Ordering::Less);
+		assert_eq!(tie_break(&en_us_posix, &en_us, TieBreak::MostSpecific), Ordering::Greater);
+		assert_eq!(tie_break(&en, &en_us, TieBreak::LeastSpecific), Ordering::Greater);
+		assert_eq!(tie_break(&en_us_posix, &en_us, TieBreak::LeastSpecific), Ordering::Less);
+		assert_eq!(tie_break(&en_us, &en_us, TieBreak::MostSpecific), Ordering::Equal);
+	}
+
+	#[test]
+	fn test_score_and_confidence() {
+		let comparisons = [
+			(Comparison::Present(1.0), 4.0),
+			(Comparison::Present(0.5), 2.0),
+			(Comparison::AvailableAbsent, 1.0),
+			(Comparison::BothAbsent, 8.0),
+		];
+		assert_eq!(score(comparisons, &Scoring::default()), 5.0);
+		assert_eq!(score(comparisons, &Scoring { mismatch_penalty: 1.0, ..Default::default() }), 4.0);
+		assert_eq!(score(comparisons, &Scoring { mismatch_penalty: 2.0, ..Default::default() }), 3.0);
+
+		let scoring = |mismatch_penalty, missing| {
+			let mut scoring = Scoring { mismatch_penalty, ..Default::default() };
+			scoring.set_missing_subtag(2, missing);
+			scoring.set_missing_subtag(3, missing);
+			scoring
+		};
+		assert_eq!(score(comparisons, &scoring(0.0, MissingSubtag::Neutral)), 5.0);
+		assert_eq!(score(comparisons, &scoring(0.0, MissingSubtag::Wildcard)), 14.0);
+		assert_eq!(score(comparisons, &scoring(0.0, MissingSubtag::Partial(0.5))), 13.5);
+		assert_eq!(score(comparisons, &scoring(0.0, MissingSubtag::Partial(2.0))), 14.0);
+		assert_eq!(score(comparisons, &scoring(0.0, MissingSubtag::RequireAbsence)), 13.0);
+		assert_eq!(score(comparisons, &scoring(1.0, MissingSubtag::RequireAbsence)), 11.0);
+		assert_eq!(confidence(8.0, comparisons), 13.0 / 15.0);
+		assert_eq!(confidence(8.0, []), 1.0);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		assert_eq!(serde_json::to_string(&TieBreak::MostSpecific).unwrap(), r#""most_specific""#);
+		assert_eq!(serde_json::from_str::<MissingSubtag>(r#"{"partial":0.5}"#).unwrap(), MissingSubtag::Partial(0.5));
+		assert_eq!(serde_json::from_str::<MissingSubtag>(r#""require_absence""#).unwrap(), MissingSubtag::RequireAbsence);
+		assert!(serde_json::from_str::<TieBreak>(r#""random""#).is_err());
+	}
+
+	#[cfg(all(feature = "tracing", feature = "bcp47", feature = "posix"))]
+	#[test]
+	fn test_event() {
+		use std::sync::{Arc, Mutex};
+
+		use tracing::field::{Field, Visit};
+		use tracing::span::{Attributes, Id, Record};
+		use tracing::{Event, Metadata, Subscriber};
+
+		/// Collects the messages of the events.
+		struct Messages(Arc<Mutex<Vec<String>>>);
+
+		impl Visit for &Messages {
+			fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+				if field.name() == "message" {
+					self.0.lock().unwrap().push(format!("{value:?}"));
+				}
+			}
+		}
+
+		impl Subscriber for Messages {
+			fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+			fn new_span(&self, _: &Attributes<'_>) -> Id { Id::from_u64(1) }
+			fn record(&self, _: &Id, _: &Record<'_>) {}
+			fn record_follows_from(&self, _: &Id, _: &Id) {}
+			fn event(&self, event: &Event<'_>) { event.record(&mut &*self); }
+			fn enter(&self, _: &Id) {}
+			fn exit(&self, _: &Id) {}
+		}
+
+		let messages = Arc::new(Mutex::new(Vec::new()));
+		tracing::subscriber::with_default(Messages(messages.clone()), || {
+			crate::bcp47::best_matching_locale(["en-US", "en_GB", "ru"], ["en"]);
+			crate::posix::best_matching_locale(["en_US", "ru"], ["fr"]);
+		});
+		assert_eq!(*messages.lock().unwrap(), [
+			"ignored malformed locale",
+			"scored candidate",
+			"matched locale",
+			"no locale matched",
+		]);
+	}
+
+	#[cfg(all(feature = "metrics", feature = "bcp47", feature = "posix"))]
+	#[test]
+	fn test_record_outcome() {
+		use std::sync::{Arc, Mutex};
+
+		use metrics::{Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+		/// Collects the recorded values with the names and the labels of the metrics.
+		#[derive(Clone, Default)]
+		struct Values(Arc<Mutex<Vec<String>>>);
+
+		struct Metric(Key, Values);
+
+		impl CounterFn for Metric {
+			fn increment(&self, value: u64) { self.1.0.lock().unwrap().push(format!("{} += {value}", self.0)); }
+			fn absolute(&self, _: u64) {}
+		}
+
+		impl HistogramFn for Metric {
+			fn record(&self, value: f64) { self.1.0.lock().unwrap().push(format!("{} <- {value}", self.0)); }
+		}
+
+		impl Recorder for Values {
+			fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+			fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter { Counter::from_arc(Arc::new(Metric(key.clone(), self.clone()))) }
+			fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge { Gauge::noop() }
+			fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram { Histogram::from_arc(Arc::new(Metric(key.clone(), self.clone()))) }
+		}
+
+		let values = Values::default();
+		metrics::with_local_recorder(&values, || {
+			crate::bcp47::best_matching_locale(["en-US", "ru-RU"], ["fr", "ru"]);
+			crate::posix::best_matching_locale(["en_US", "ru_RU"], ["fr"]);
+			// Not a negotiation
+			crate::bcp47::matches_iter(["en-US", "en-GB", "ru-RU"], ["en", "ru"]).for_each(drop);
+		});
+		assert_eq!(*values.0.lock().unwrap(), [
+			"Key(locale_match_negotiations, [format = bcp47, outcome = matched]) += 1",
+			"Key(locale_match_matched_locales, [format = bcp47, locale = ru-RU]) += 1",
+			"Key(locale_match_fallback_depth, [format = bcp47]) <- 1",
+			"Key(locale_match_negotiations, [format = posix, outcome = no_match]) += 1",
+		]);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Negotiation of BCP 47 locales from composable sources in a single call.
+
+use crate::bcp47::Matcher;
+use crate::matching::{LocaleMatch, Warning};
+use crate::source::{AvailableLocaleSource, UserLocaleSource};
+
+/// Negotiates the locale of an application, wiring the sources of user and available locales, the
+/// configuration of the [`Matcher`] and a default locale together.
+///
+/// The sources are read on each call of [`negotiate`](Self::negotiate), so the result follows
+/// changes of the user preferences and of the catalog; use
+/// [`AvailableLocaleSource::cached`] to read the catalog only once.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::negotiator::Negotiator;
+/// use locale_match::source::{Header, Static, UserLocaleSource};
+///
+///
+/// let negotiator = Negotiator::new(Header::new("de-AT, en;q=0.5").or(Static::new(["en"])), Static::new(["en-US", "de-DE"]))
+///     .with_default("en-US");
+///
+/// let negotiation = negotiator.negotiate();
+///
+/// assert_eq!(negotiation.locale.as_deref(), Some("de-DE"));
+/// assert_eq!(negotiation.user_locale(), Some("de-AT"));
+/// assert!(!negotiation.is_default());
+/// ```
+#[derive(Debug)]
+pub struct Negotiator<U, A> {
+	user_source: U,
+	available_source: A,
+	matcher: Matcher,
+	default: Option<String>,
+}
+
+/// The result of [`Negotiator::negotiate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negotiation {
+	/// The negotiated locale: the best matching available locale, or the default locale if none
+	/// matches, or [`None`] if there is no default locale either.
+	pub locale: Option<String>,
+	/// The best match, or [`None`] if no available locale matches.
+	pub best_match: Option<LocaleMatch<String>>,
+	/// The user locales the negotiation was based on, ordered by priority.
+	pub user_locales: Vec<String>,
+	/// The malformed locales ignored during the negotiation.
+	pub warnings: Vec<Warning>,
+}
+
+impl Negotiation {
+	/// Returns the user locale the best match was found for, or [`None`] if no available locale
+	/// matches.
+	pub fn user_locale(&self) -> Option<&str> {
+		self.best_match.as_ref().map(|best_match| self.user_locales[best_match.user_index].as_str())
+	}
+
+	/// Returns whether the negotiated locale is the default locale, because no available locale
+	/// matches.
+	pub fn is_default(&self) -> bool {
+		self.best_match.is_none() && self.locale.is_some()
+	}
+}
+
+impl<U: UserLocaleSource, A: AvailableLocaleSource> Negotiator<U, A> {
+	/// Creates a negotiator of the user locales of `user_source` and the available locales of
+	/// `available_source`, with the default matcher and without a default locale.
+	pub fn new(user_source: U, available_source: A) -> Self {
+		Self { user_source, available_source, matcher: Matcher::new(), default: None }
+	}
+
+	/// Sets the matcher used for the negotiation.
+	///
+	/// By default, [`Matcher::new`] is used.
+	pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+		self.matcher = matcher;
+		self
+	}
+
+	/// Sets the locale negotiated if no available locale matches the user locales.
+	///
+	/// By default, there is no default locale, so the negotiated locale is [`None`] in that case.
+	pub fn with_default(mut self, default: impl Into<String>) -> Self {
+		self.default = Some(default.into());
+		self
+	}
+
+	/// Negotiates the locale: reads the user and the available locales from the sources and finds
+	/// the best match with the matcher, falling back to the default locale.
+	pub fn negotiate(&self) -> Negotiation {
+		let user_locales = self.user_source.user_locales();
+		let (best_match, warnings) = self.matcher.best_matching_locale_with_warnings(self.available_source.available_locales(), &user_locales);
+		let locale = best_match.as_ref().map(|best_match| best_match.locale.clone()).or_else(|| self.default.clone());
+		Negotiation { locale, best_match, user_locales, warnings }
+	}
+
+	/// Refreshes the source of the available locales, see [`AvailableLocaleSource::refresh`].
+	pub fn refresh(&self) {
+		self.available_source.refresh();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::LocaleKind;
+	use crate::source::Static;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Negotiator() {
+		fn case(user_locales: &[&str], available_locales: &[&str], default: Option<&str>, expected: Option<&str>, expected_default: bool) {
+			let mut negotiator = Negotiator::new(Static::new(user_locales.to_vec()), Static::new(available_locales.to_vec()));
+			if let Some(default) = default {
+				negotiator = negotiator.with_default(default);
+			}
+			let negotiation = negotiator.negotiate();
+			assert_eq!(negotiation.locale.as_deref(), expected);
+			assert_eq!(negotiation.is_default(), expected_default);
+			assert_eq!(negotiation.user_locales, user_locales);
+		}
+
+		// Matches
+		case(&["ru-RU", "en"], &["en-US", "ru-BY"], None, Some("ru-BY"), false);
+		case(&["ru-RU", "en"], &["en-US", "ru-BY"], Some("en-US"), Some("ru-BY"), false);
+		case(&["fr", "en"], &["en-US", "ru-BY"], Some("ru-BY"), Some("en-US"), false);
+
+		// Defaults
+		case(&["fr"], &["en-US", "ru-BY"], Some("en-US"), Some("en-US"), true);
+		case(&[], &["en-US"], Some("en-US"), Some("en-US"), true);
+		case(&["fr"], &[], Some("en-US"), Some("en-US"), true);
+		case(&["fr"], &["en-US"], None, None, false);
+
+		// Details of the match
+		let negotiator = Negotiator::new(Static::new(["de_DE", "fr-CA", "en"]), Static::new(["en-US", "fr-FR"]));
+		let negotiation = negotiator.negotiate();
+		assert_eq!(negotiation.user_locale(), Some("fr-CA"));
+		let best_match = negotiation.best_match.unwrap();
+		assert_eq!((best_match.locale.as_str(), best_match.available_index, best_match.user_index), ("fr-FR", 1, 1));
+		assert_eq!(negotiation.warnings.iter().map(|w| (w.kind, w.index)).collect::<Vec<_>>(), [(LocaleKind::User, 0)]);
+
+		// Matcher configuration
+		let negotiator = Negotiator::new(Static::new(["de-DE_phoneb"]), Static::new(["en-US", "de-DE"]))
+			.with_matcher(Matcher::new().with_dotnet_culture_names(true));
+		assert_eq!(negotiator.negotiate().locale.as_deref(), Some("de-DE"));
+	}
+}
\ No newline at end of file
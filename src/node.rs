@@ -0,0 +1,82 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module exporting the matching functions to Node.js with [`napi-rs`](https://napi.rs), so
+//! Node.js servers can negotiate locales exactly like the Rust services.
+//!
+//! The functions take arrays of strings and return the best matching available locale, or
+//! `null` if none of them matches.
+//!
+//! The module can be built as a Node.js addon with `cargo rustc --release --features node --crate-type cdylib`,
+//! renaming the resulting library to `locale_match.node`. On macOS, the addon must be linked with
+//! `-C link-arg=-undefined -C link-arg=dynamic_lookup`.
+//!
+//! # Examples
+//!
+//! ```js
+//! const { bestMatchingBcp47Locale } = require("./locale_match.node");
+//!
+//! const locale = bestMatchingBcp47Locale(["en-US", "ru-BY"], ["ru-RU", "en"]) ?? "en-US";
+//! ```
+
+use napi_derive::napi;
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on a list of user
+/// BCP 47 locales.
+///
+/// Exported to JavaScript as `bestMatchingBcp47Locale`.  
+/// See [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) for the details of the
+/// matching.
+#[cfg(feature = "bcp47")]
+#[napi]
+pub fn best_matching_bcp47_locale(available_locales: Vec<String>, user_locales: Vec<String>) -> Option<String> {
+	crate::bcp47::best_matching_locale(available_locales, user_locales)
+}
+
+/// Finds the best matching locale from a list of available POSIX locales based on a list of user
+/// POSIX locales.
+///
+/// Exported to JavaScript as `bestMatchingPosixLocale`.  
+/// See [`posix::best_matching_locale`](crate::posix::best_matching_locale) for the details of the
+/// matching.
+#[cfg(feature = "posix")]
+#[napi]
+pub fn best_matching_posix_locale(available_locales: Vec<String>, user_locales: Vec<String>) -> Option<String> {
+	crate::posix::best_matching_locale(available_locales, user_locales)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn strings(strings: &[&str]) -> Vec<String> {
+		strings.iter().map(|s| s.to_string()).collect()
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_best_matching_bcp47_locale() {
+		assert_eq!(best_matching_bcp47_locale(strings(&["en-US", "ru-BY"]), strings(&["ru-RU", "en"])).as_deref(), Some("ru-BY"));
+		assert_eq!(best_matching_bcp47_locale(strings(&["en-US", "ru-BY"]), strings(&["fr"])), None);
+	}
+
+	#[cfg(feature = "posix")]
+	#[test]
+	fn test_best_matching_posix_locale() {
+		assert_eq!(best_matching_posix_locale(strings(&["en_US.UTF-8", "ru_BY.UTF-8"]), strings(&["ru_RU", "en"])).as_deref(), Some("ru_BY.UTF-8"));
+		assert_eq!(best_matching_posix_locale(strings(&["en_US", "ru_BY"]), strings(&["fr"])), None);
+	}
+}
@@ -0,0 +1,189 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for hot-reloading available locales from a watched directory of translation files
+//! with the [`notify`](https://docs.rs/notify) crate.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use locale_match::notify::WatchedCatalog;
+//!
+//!
+//! let catalog = WatchedCatalog::watch("i18n/{locale}.ftl").unwrap();
+//!
+//! // Newly deployed translation files are picked up without a restart
+//! let locale = catalog.best_matching_locale(["de-AT", "en"]).unwrap_or_else(|| "en-US".to_string());
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use ::notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::bcp47::Matcher;
+use crate::files::available_locales_from_pattern;
+use crate::source::AvailableLocaleSource;
+use crate::Error;
+
+/// The available locales of the translation files matching a pattern, e.g. `i18n/{locale}.ftl`,
+/// updated when files are added to or removed from the directory.
+///
+/// The locales are found like in [`available_locales_from_pattern`], and the whole list is replaced
+/// at once on each change, so a match never sees a partially updated catalog. The directory is
+/// watched until the catalog is dropped.
+#[derive(Debug)]
+pub struct WatchedCatalog {
+	pattern: Arc<str>,
+	locales: Arc<RwLock<Arc<[String]>>>,
+	_watcher: RecommendedWatcher,
+}
+
+impl WatchedCatalog {
+	/// Reads the locales of the translation files matching the pattern and starts watching the
+	/// directory containing the `{locale}` placeholder.  
+	/// The directory is watched recursively if the placeholder is followed by more path components,
+	/// e.g. in `po/{locale}/messages.po`.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error::Io`] if the directory cannot be read or watched, e.g. if it does not exist.
+	pub fn watch(pattern: &str) -> Result<Self, Error> {
+		let pattern = Arc::<str>::from(pattern);
+		let locales = Arc::new(RwLock::new(read_locales(&pattern)?));
+		let mut watcher = {
+			let (pattern, locales) = (Arc::clone(&pattern), Arc::clone(&locales));
+			recommended_watcher(move |event: Result<Event, ::notify::Error>| {
+				if event.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_))) {
+					// There is nobody to report the error to, and the catalog becomes empty anyway
+					let _ = update_locales(&locales, &pattern);
+				}
+			})?
+		};
+		let (dir, rest) = split_pattern(&pattern);
+		let mode = if rest.is_empty() { RecursiveMode::NonRecursive } else { RecursiveMode::Recursive };
+		watcher.watch(Path::new(if dir.is_empty() { "." } else { dir }), mode)?;
+		Ok(Self { pattern, locales, _watcher: watcher })
+	}
+
+	/// Returns the current available locales, sorted.
+	pub fn locales(&self) -> Arc<[String]> {
+		Arc::clone(&self.locales.read().unwrap_or_else(PoisonError::into_inner))
+	}
+
+	/// Reads the locales of the translation files again, e.g. if changes could have been missed.
+	///
+	/// # Errors
+	///
+	/// Returns [`Error::Io`] if the directory cannot be read, e.g. if it was removed, in which case
+	/// the catalog becomes empty, like after a change of a directory that cannot be read.
+	pub fn reload(&self) -> Result<(), Error> {
+		update_locales(&self.locales, &self.pattern)
+	}
+
+	/// Finds the best matching locale among the current available locales like
+	/// [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale).
+	pub fn best_matching_locale<T: AsRef<str>>(&self, user_locales: impl IntoIterator<Item = T>) -> Option<String> {
+		self.best_matching_locale_with(&Matcher::new(), user_locales)
+	}
+
+	/// Finds the best matching locale among the current available locales with the given matcher.
+	pub fn best_matching_locale_with<T: AsRef<str>>(&self, matcher: &Matcher, user_locales: impl IntoIterator<Item = T>) -> Option<String> {
+		let locales = self.locales();
+		matcher.best_matching_locale(locales.iter(), user_locales).cloned()
+	}
+}
+
+impl AvailableLocaleSource for WatchedCatalog {
+	fn available_locales(&self) -> Vec<String> {
+		self.locales().to_vec()
+	}
+
+	/// Reloads the locales, leaving the catalog empty if the directory cannot be read.
+	fn refresh(&self) {
+		let _ = self.reload();
+	}
+}
+
+/// Reads the locales of the translation files matching the pattern.
+fn read_locales(pattern: &str) -> Result<Arc<[String]>, Error> {
+	Ok(available_locales_from_pattern(pattern)?.into_iter().map(|file| file.locale).collect())
+}
+
+/// Replaces the locales with the ones read again, or with no locales if they cannot be read.
+fn update_locales(locales: &RwLock<Arc<[String]>>, pattern: &str) -> Result<(), Error> {
+	let read = read_locales(pattern);
+	*locales.write().unwrap_or_else(PoisonError::into_inner) = read.as_ref().map_or_else(|_| Arc::from([]), Arc::clone);
+	read.map(drop)
+}
+
+/// Splits the pattern into the directory containing the placeholder and the path components after
+/// the component with the placeholder, e.g. `po/{locale}/messages.po` into `po/` and `/messages.po`.
+fn split_pattern(pattern: &str) -> (&str, &str) {
+	let is_separator = |c: char| c == '/' || std::path::is_separator(c);
+	let (head, tail) = pattern.split_once("{locale}").unwrap_or((pattern, ""));
+	let dir = head.rfind(is_separator).map_or("", |i| &head[..=i]);
+	let rest = tail.find(is_separator).map_or("", |i| &tail[i..]);
+	(dir, rest)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::fs;
+	use std::time::{Duration, Instant};
+
+	#[test]
+	fn test_split_pattern() {
+		assert_eq!(split_pattern("i18n/{locale}.ftl"), ("i18n/", ""));
+		assert_eq!(split_pattern("po/{locale}/messages.po"), ("po/", "/messages.po"));
+		assert_eq!(split_pattern("{locale}.json"), ("", ""));
+		assert_eq!(split_pattern("/srv/app/i18n/messages-{locale}.ftl"), ("/srv/app/i18n/", ""));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_WatchedCatalog() {
+		let dir = std::env::temp_dir().join(format!("locale-match-notify-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("en-US.ftl"), []).unwrap();
+
+		let catalog = WatchedCatalog::watch(&format!("{}/{{locale}}.ftl", dir.display())).unwrap();
+		assert_eq!(&*catalog.locales(), ["en-US"]);
+		assert_eq!(catalog.best_matching_locale(["de-AT", "en"]).as_deref(), Some("en-US"));
+
+		// Wait for the watcher to pick up the changes
+		let wait_for = |expected: &[&str]| {
+			let start = Instant::now();
+			while &*catalog.locales() != expected && start.elapsed() < Duration::from_secs(10) {
+				std::thread::sleep(Duration::from_millis(20));
+			}
+			assert_eq!(&*catalog.locales(), expected);
+		};
+		fs::write(dir.join("de-DE.ftl"), []).unwrap();
+		wait_for(&["de-DE", "en-US"]);
+		assert_eq!(catalog.best_matching_locale(["de-AT", "en"]).as_deref(), Some("de-DE"));
+		fs::remove_file(dir.join("en-US.ftl")).unwrap();
+		wait_for(&["de-DE"]);
+		assert_eq!(catalog.available_locales(), ["de-DE"]);
+
+		fs::remove_dir_all(&dir).unwrap();
+		assert!(matches!(catalog.reload(), Err(Error::Io(_))));
+		assert!(catalog.locales().is_empty());
+		assert!(matches!(WatchedCatalog::watch(&format!("{}/{{locale}}.ftl", dir.display())), Err(Error::Io(_))));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,114 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching [`oxilangtag`](https://docs.rs/oxilangtag) language tags and converting
+//! them to and from [`LanguageTag`].
+
+use std::ops::Deref;
+
+use language_tags::LanguageTag;
+use ::oxilangtag::{LanguageTag as OxiLanguageTag, LanguageTagParseError};
+
+use crate::bcp47::Matcher;
+
+/// Finds the best matching language tag from a list of available tags based on a list of user tags,
+/// where the tags are [`oxilangtag::LanguageTag`]s or references to them.
+///
+/// The tags are matched exactly like in [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale),
+/// and the returned tag is one of the available tags, so it does not have to be parsed again.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::oxilangtag::best_matching_locale;
+/// use oxilangtag::LanguageTag;
+///
+///
+/// let available_locales = [LanguageTag::parse("en-US").unwrap(), LanguageTag::parse("ru-BY").unwrap()];
+/// let user_locales = [LanguageTag::parse("ru-RU").unwrap(), LanguageTag::parse("en").unwrap()];
+///
+/// let best_match = best_matching_locale(&available_locales, &user_locales);
+///
+/// assert_eq!(best_match, Some(&available_locales[1]));
+/// ```
+pub fn best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>,
+{
+	best_matching_locale_with(&Matcher::new(), available_locales, user_locales)
+}
+
+/// Finds the best matching language tag exactly like [`best_matching_locale`], but with the given
+/// [`Matcher`] instead of the default one.
+pub fn best_matching_locale_with<T1, T2>(matcher: &Matcher, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>,
+{
+	matcher.best_matching_locale(available_locales, user_locales)
+}
+
+/// Converts an [`oxilangtag::LanguageTag`] to a [`LanguageTag`].
+///
+/// # Errors
+///
+/// Returns an error if the tag is not accepted by [`LanguageTag::parse`], which is only possible
+/// for tags that `oxilangtag` accepts more leniently.
+pub fn to_language_tag<T: Deref<Target = str>>(tag: &OxiLanguageTag<T>) -> Result<LanguageTag, language_tags::ParseError> {
+	LanguageTag::parse(tag.as_str())
+}
+
+/// Converts a [`LanguageTag`] to an [`oxilangtag::LanguageTag`].
+///
+/// # Errors
+///
+/// Returns an error if the tag is not accepted by `oxilangtag`, which is not expected for
+/// well-formed tags.
+pub fn to_oxilangtag(tag: &LanguageTag) -> Result<OxiLanguageTag<String>, LanguageTagParseError> {
+	OxiLanguageTag::parse(tag.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tag(tag: &str) -> OxiLanguageTag<&str> {
+		OxiLanguageTag::parse(tag).unwrap()
+	}
+
+	#[test]
+	fn test_best_matching_locale() {
+		let available_locales = [tag("en-US"), tag("pt-BR"), tag("pt-PT")];
+		assert_eq!(best_matching_locale(&available_locales, [tag("pt-PT")]), Some(&tag("pt-PT")));
+		assert_eq!(best_matching_locale(&available_locales, [tag("pt")]), Some(&tag("pt-BR")));
+		assert_eq!(best_matching_locale(&available_locales, [tag("fr"), tag("en-GB")]), Some(&tag("en-US")));
+		assert_eq!(best_matching_locale(&available_locales, [tag("fr")]), None);
+
+		// Mixed types
+		assert_eq!(best_matching_locale(available_locales, ["EN"]), Some(tag("en-US")));
+	}
+
+	#[test]
+	fn test_conversions() {
+		assert_eq!(to_language_tag(&tag("sr-latn-rs")).unwrap().as_str(), "sr-Latn-RS");
+		assert_eq!(to_language_tag(&tag("de-CH-1996-x-private")).unwrap().as_str(), "de-CH-1996-x-private");
+
+		let converted = to_oxilangtag(&LanguageTag::parse("zh-Hant-TW-u-ca-chinese").unwrap()).unwrap();
+		assert_eq!(converted, tag("zh-Hant-TW-u-ca-chinese"));
+		assert_eq!(converted.script(), Some("Hant"));
+	}
+}
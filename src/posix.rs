@@ -16,6 +16,39 @@
 
 //! A module for matching locales in the [POSIX](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html) format.
 
+#[cfg(feature = "bumpalo")]
+mod bump;
+mod bytes;
+mod desktop_entry;
+mod diff;
+mod explain;
+mod gettext;
+mod installed;
+mod keys;
+mod matrix;
+mod os;
+mod strict;
+mod ties;
+mod warnings;
+
+pub use bytes::best_matching_locale_from_bytes;
+pub use desktop_entry::{best_localized_key, best_matching_desktop_entry_locale};
+pub use diff::diff;
+pub use gettext::{best_matching_gettext_catalog, gettext_locales};
+pub use installed::{best_installed_locale, installed_locales};
+pub use keys::best_matching_key;
+pub use matrix::score_matrix;
+pub use os::best_matching_locale_os;
+pub use strict::try_best_matching_locale;
+pub use ties::best_matching_locales_all_ties;
+pub use warnings::best_matching_locale_with_warnings;
+
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use crate::engine::{self, Engine, Format, Locale};
+use crate::matching::{event, lookup, score, Comparison, LocaleMatch, MissingSubtag, Scoring, TieBreak, REGION_ALIASES};
+
 /// Finds the best matching locale from a list of available locales based on a list of user locales.  
 /// The function expects locales to be valid POSIX locales according to
 /// [The Open Group Base Specifications Issue 8 - 8. Environment Variables](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html),
@@ -37,6 +70,8 @@
 /// next user locale in the list.  
 /// If no matches are found for any user locale, the function returns [`None`].
 ///
+/// Codesets are compared like in glibc, ignoring case and punctuation, so `utf8` is the same
+/// codeset as `UTF-8`.  
 /// Malformed locales are ignored.
 ///
 /// # Arguments
@@ -96,38 +131,736 @@ where
 	T1: AsRef<str>,
 	T2: AsRef<str>
 {
-	let available_parsed_locales = available_locales.into_iter()
-		.map(|l| PosixLocale::parse(l))
-		.collect::<Vec<PosixLocale<T1>>>();
-
-	user_locales.into_iter()
-		.map(|locale| PosixLocale::parse(locale))
-		.find_map(|user_locale|
-			available_parsed_locales.iter()
-				.enumerate()
-				.rev() // For max_by_key to return the first locale with max score
-				.filter(|(_, aval_locale)| aval_locale.language().eq_ignore_ascii_case(user_locale.language()))
-				.max_by_key(|(_, aval_locale)| {
-					let mut score = 0;
-					for (aval, user, weight) in [
-						(aval_locale.territory(), user_locale.territory(), 4),
-						(aval_locale.codeset(),   user_locale.codeset(),   2),
-						(aval_locale.modifier(),  user_locale.modifier(),  1),
-					] {
-						match (aval, user) {
-							(Some(a), Some(u)) if a.eq_ignore_ascii_case(u) => score += weight,
-							_ => {} // Ignore if both are None
-						}
-					}
-					score
-				})
-				.map(|(i, _)| i)
-		)
-		.map(|i| available_parsed_locales.into_iter().nth(i).unwrap().into_inner())
+	best_matching_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+}
+
+/// Finds the best matching locale exactly like [`best_matching_locale`], but also returns
+/// information about the quality of the match.
+///
+/// See [`LocaleMatch`] for the description of the returned information.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::best_matching_locale_with_confidence;
+///
+///
+/// let available_locales = ["en_US", "pt_BR", "pt_PT"];
+/// let user_locales = ["pt_PT", "en"];
+///
+/// let best_match = best_matching_locale_with_confidence(available_locales, user_locales).unwrap();
+///
+/// // "pt_PT" matches the most preferred user locale exactly
+/// assert_eq!(best_match.locale, "pt_PT");
+/// assert_eq!(best_match.user_index, 0);
+/// assert_eq!(best_match.confidence, 1.0);
+///
+///
+/// let available_locales = ["en_US", "pt_BR"];
+/// let user_locales = ["fr_FR", "pt_PT", "en"];
+///
+/// let best_match = best_matching_locale_with_confidence(available_locales, user_locales).unwrap();
+///
+/// // Only the language of the second user locale is matched
+/// assert_eq!(best_match.locale, "pt_BR");
+/// assert_eq!(best_match.user_index, 1);
+/// assert!(best_match.confidence < 1.0);
+/// ```
+pub fn best_matching_locale_with_confidence<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locale_with_confidence(available_locales, user_locales)
+}
+
+/// Returns the user locales for messages as defined by the environment of the process, in the
+/// same way gettext-based programs do.
+///
+/// The effective locale is read with the precedence defined by POSIX: `LC_ALL` overrides
+/// `LC_MESSAGES`, which overrides `LANG`.  
+/// Unless the effective locale is unset, `C` or `POSIX`, the GNU `LANGUAGE` variable (see
+/// [`parse_language_variable`]) takes precedence over it: the locales listed in `LANGUAGE` come
+/// first, followed by the effective locale.  
+/// Variables that are unset, empty or not valid Unicode are skipped, duplicate locales are
+/// removed.
+///
+/// The result is ordered by priority and is ready to be passed to [`best_matching_locale`] as the
+/// user locales. It is empty if none of the variables is set.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_locale, user_locales_from_env};
+///
+///
+/// let available_locales = ["en_US.UTF-8", "ru_RU.UTF-8"];
+///
+/// let best_match = best_matching_locale(available_locales, user_locales_from_env())
+///     .unwrap_or("en_US.UTF-8");
+/// ```
+pub fn user_locales_from_env() -> Vec<String> {
+	user_locales_from_vars(|name| std::env::var(name).ok())
+}
+
+/// Resolves the user locales for messages from the environment variables returned by `var`.
+fn user_locales_from_vars(var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+	let locale = EnvLocales::from_vars(&var).messages;
+	let language = match locale.as_deref() {
+		None | Some("C" | "POSIX") => None,
+		_ => var("LANGUAGE"),
+	};
+
+	let mut locales = Vec::<String>::new();
+	for l in language.iter().flat_map(|language| parse_language_variable(language)).chain(locale.as_deref()) {
+		if !locales.iter().any(|added| added == l) {
+			locales.push(l.to_string());
+		}
+	}
+	locales
+}
+
+/// The locales of the process for each locale category, as defined by the environment.
+///
+/// Each locale is resolved with the precedence defined by POSIX: `LC_ALL` overrides the variable
+/// of the category (e.g. `LC_TIME`), which overrides `LANG`. Variables that are empty or not valid
+/// Unicode are skipped.  
+/// A locale is [`None`] if none of the variables is set, which means the implementation-defined
+/// default locale, usually `C`, is in effect.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_locale, EnvLocales};
+///
+///
+/// let env_locales = EnvLocales::detect();
+///
+/// let date_format_locale = best_matching_locale(["en_US", "de_DE", "ja_JP"], env_locales.time);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvLocales {
+	/// The locale for messages (`LC_MESSAGES`).
+	pub messages: Option<String>,
+	/// The locale for date and time formatting (`LC_TIME`).
+	pub time: Option<String>,
+	/// The locale for numeric formatting (`LC_NUMERIC`).
+	pub numeric: Option<String>,
+	/// The locale for monetary formatting (`LC_MONETARY`).
+	pub monetary: Option<String>,
+	/// The locale for collation (`LC_COLLATE`).
+	pub collate: Option<String>,
+	/// The locale for character classification and case conversion (`LC_CTYPE`).
+	pub ctype: Option<String>,
+}
+
+impl EnvLocales {
+	/// Resolves the locales of all categories from the environment of the process.
+	pub fn detect() -> Self {
+		Self::from_vars(|name| std::env::var(name).ok())
+	}
+
+	/// Resolves the locales of all categories from the environment variables returned by `var`.
+	fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+		let all = var("LC_ALL").filter(|value| !value.is_empty());
+		let lang = var("LANG").filter(|value| !value.is_empty());
+		let resolve = |category: &str| all.clone()
+			.or_else(|| var(category).filter(|value| !value.is_empty()))
+			.or_else(|| lang.clone());
+		Self {
+			messages: resolve("LC_MESSAGES"),
+			time: resolve("LC_TIME"),
+			numeric: resolve("LC_NUMERIC"),
+			monetary: resolve("LC_MONETARY"),
+			collate: resolve("LC_COLLATE"),
+			ctype: resolve("LC_CTYPE"),
+		}
+	}
+}
+
+/// Parses the value of the GNU `LANGUAGE` environment variable.
+///
+/// The variable contains a colon-separated list of locales ordered by priority, e.g.
+/// `de_DE:fr:en`. Empty entries are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::parse_language_variable;
+///
+///
+/// assert_eq!(parse_language_variable("de_DE:fr::en"), ["de_DE", "fr", "en"]);
+/// ```
+pub fn parse_language_variable(value: &str) -> Vec<&str> {
+	value.split(':')
+		.filter(|l| !l.is_empty())
+		.collect()
+}
+
+/// A configurable matcher of POSIX locales.
+///
+/// A default matcher behaves exactly like the [`best_matching_locale`] function.
+/// The behavior can be adjusted with the `with_*` methods.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::Matcher;
+///
+///
+/// let matcher = Matcher::new().with_mismatch_penalty(1.0);
+///
+/// let available_locales = ["fr_FR.UTF-8", "fr.UTF-8"];
+/// let user_locales = ["fr_CA.UTF-8"];
+///
+/// let best_match = matcher.best_matching_locale(available_locales, user_locales);
+///
+/// // The neutral "fr.UTF-8" wins over the wrong territory
+/// assert_eq!(best_match, Some("fr.UTF-8"));
+/// ```
+#[derive(Clone, Default)]
+pub struct Matcher {
+	scoring: Scoring,
+	observer: Option<Arc<Observer>>,
+	random_tie_break: Option<RandomTieBreak>,
+	codeset_preference: Vec<String>,
+	modifier_preference: Vec<String>,
+	ignore_codesets: bool,
+	ignore_modifiers: bool,
+	territory_aliases: bool,
+}
+
+/// A function observing the scored candidates, see [`Matcher::with_observer`].
+type Observer = dyn Fn(&str, &str, f64) -> ControlFlow<()> + Send + Sync;
+
+/// A source of random numbers and the weights of the available locales, see
+/// [`Matcher::with_weighted_random_tie_break`].
+#[derive(Clone)]
+struct RandomTieBreak {
+	random: Arc<dyn Fn() -> f64 + Send + Sync>,
+	weight: Arc<dyn Fn(&str) -> f64 + Send + Sync>,
+}
+
+/// A category of subtags (parts) of a POSIX locale, except for the language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Subtag {
+	/// The territory, e.g. `US` in `en_US.UTF-8@dict`.
+	Territory,
+	/// The codeset, e.g. `UTF-8` in `en_US.UTF-8@dict`.
+	Codeset,
+	/// The modifier, e.g. `dict` in `en_US.UTF-8@dict`.
+	Modifier,
+}
+
+/// The subtag categories in the order of the comparisons of the matcher.
+const SUBTAGS: [Subtag; 3] = [Subtag::Territory, Subtag::Codeset, Subtag::Modifier];
+
+impl std::fmt::Debug for Matcher {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Matcher")
+			.field("scoring", &self.scoring)
+			.field("observer", &self.observer.as_ref().map(|_| ".."))
+			.field("random_tie_break", &self.random_tie_break.as_ref().map(|_| ".."))
+			.field("codeset_preference", &self.codeset_preference)
+			.field("modifier_preference", &self.modifier_preference)
+			.field("ignore_codesets", &self.ignore_codesets)
+			.field("ignore_modifiers", &self.ignore_modifiers)
+			.field("territory_aliases", &self.territory_aliases)
+			.finish()
+	}
+}
+
+impl Matcher {
+	/// Creates a matcher with the default configuration.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the penalty for contradicting parts of locales.
+	///
+	/// By default, a part present in both locales with different values earns nothing, just like
+	/// a part absent in one of the locales. With a positive penalty, such a part reduces the score
+	/// by `penalty` times the weight of the part, so neutral locales win over locales with wrong
+	/// parts. For example, with the penalty of `1.0` a wrong territory costs exactly as much as a
+	/// matching territory earns.  
+	/// Negative values are treated as `0.0`.
+	pub fn with_mismatch_penalty(mut self, penalty: f64) -> Self {
+		self.scoring.mismatch_penalty = penalty.max(0.0);
+		self
+	}
+
+	/// Sets how a part of the given category is scored if it is present in only one of the locales.
+	///
+	/// By default, all categories use [`MissingSubtag::Neutral`].
+	pub fn with_missing_subtag(mut self, subtag: Subtag, missing: MissingSubtag) -> Self {
+		self.scoring.set_missing_subtag(subtag as usize, missing);
+		self
+	}
+
+	/// Sets how to choose among available locales that match a user locale equally well.
+	///
+	/// By default, the locale that appears earlier in the list of available locales is chosen.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	/// use locale_match::matching::TieBreak;
+	///
+	///
+	/// let matcher = Matcher::new().with_tie_break(TieBreak::MostSpecific);
+	///
+	/// let best_match = matcher.best_matching_locale(["en", "en_US.UTF-8"], ["en_GB.UTF-8"]);
+	///
+	/// assert_eq!(best_match, Some("en_US.UTF-8"));
+	/// ```
+	pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+		self.scoring.tie_break = tie_break;
+		self
+	}
+
+	/// Sets a random tie break, which picks one of the available locales that match a user locale
+	/// equally well uniformly at random, e.g. for A/B testing of regional variants.
+	///
+	/// `random` is the source of random numbers in the range `0.0..1.0`, e.g. `rand::random` or a
+	/// seeded generator for reproducible experiments. It is called only when there is a tie.  
+	/// The random tie break replaces the one set with [`with_tie_break`](Self::with_tie_break), and
+	/// is not applied by the functions that do not select a single locale, e.g.
+	/// [`best_matching_locales_all_ties`](Self::best_matching_locales_all_ties).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_random_tie_break(|| 0.75);
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en_US.UTF-8", "en_GB.UTF-8"], ["en_CA.UTF-8"]), Some("en_GB.UTF-8"));
+	/// ```
+	pub fn with_random_tie_break(self, random: impl Fn() -> f64 + Send + Sync + 'static) -> Self {
+		self.with_weighted_random_tie_break(random, |_| 1.0)
+	}
+
+	/// Sets a random tie break like [`with_random_tie_break`](Self::with_random_tie_break), but
+	/// picks the tied available locales with probabilities proportional to their weights.
+	///
+	/// Negative weights count as zero, and the first of the tied locales is picked if all of their
+	/// weights are zero.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// // Serve the first locale to 90% of the users
+	/// let matcher = Matcher::new().with_weighted_random_tie_break(|| 0.95, |locale| if locale.starts_with("en_US") { 9.0 } else { 1.0 });
+	///
+	/// assert_eq!(matcher.best_matching_locale(["en_US.UTF-8", "en_GB.UTF-8"], ["en_CA.UTF-8"]), Some("en_GB.UTF-8"));
+	/// ```
+	pub fn with_weighted_random_tie_break(mut self, random: impl Fn() -> f64 + Send + Sync + 'static, weight: impl Fn(&str) -> f64 + Send + Sync + 'static) -> Self {
+		self.random_tie_break = Some(RandomTieBreak { random: Arc::new(random), weight: Arc::new(weight) });
+		self
+	}
+
+	/// Sets the preferred codesets, most preferred first, for choosing among available locales that
+	/// match a user locale equally well, e.g. that differ only in the codeset.
+	///
+	/// The preference applies after the tie break set with [`with_tie_break`](Self::with_tie_break),
+	/// and locales with other codesets or without a codeset come after the preferred ones, in list
+	/// order.  
+	/// Codesets are compared ignoring case and punctuation, so `UTF-8` also prefers `utf8`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new().with_codeset_preference(["UTF-8", "ISO-8859-5"]);
+	///
+	/// let available_locales = ["ru_RU.KOI8-R", "ru_RU.ISO-8859-5", "ru_RU.utf8"];
+	///
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["ru_RU"]), Some("ru_RU.utf8"));
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["ru_RU.KOI8-R"]), Some("ru_RU.KOI8-R"));
+	/// ```
+	pub fn with_codeset_preference<T: AsRef<str>>(mut self, codesets: impl IntoIterator<Item = T>) -> Self {
+		self.codeset_preference = codesets.into_iter().map(|codeset| normalize_codeset(codeset.as_ref())).collect();
+		self
+	}
+
+	/// Sets the preferred modifiers, most preferred first, for choosing among available locales
+	/// that match a user locale equally well, e.g. `sr_RS` and `sr_RS@latin` for `sr_RS`.
+	///
+	/// An empty string stands for the absence of a modifier. The preference applies after the
+	/// preferred codesets set with [`with_codeset_preference`](Self::with_codeset_preference), and
+	/// locales with other modifiers come after the preferred ones, in list order.  
+	/// The preference applies only to user locales without a modifier, so an explicit modifier of
+	/// the user, e.g. `@cyrillic` in `sr_RS@cyrillic`, is not overridden.  
+	/// Modifiers are compared case-insensitively.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let available_locales = ["sr_RS", "sr_RS@latin"];
+	///
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, ["sr_RS"]), Some("sr_RS"));
+	///
+	/// let matcher = Matcher::new().with_modifier_preference(["latin", ""]);
+	///
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["sr_RS"]), Some("sr_RS@latin"));
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["sr_RS@cyrillic"]), Some("sr_RS"));
+	/// ```
+	pub fn with_modifier_preference<T: AsRef<str>>(mut self, modifiers: impl IntoIterator<Item = T>) -> Self {
+		self.modifier_preference = modifiers.into_iter().map(|modifier| modifier.as_ref().to_owned()).collect();
+		self
+	}
+
+	/// Sets whether codesets are excluded from matching, e.g. for applications that convert all text
+	/// to UTF-8 anyway.
+	///
+	/// By default, the codeset weighs more than the modifier, so a locale with the codeset of the
+	/// user locale can win over a better match of the other parts, e.g. `sr_RS.ISO-8859-5` over
+	/// `sr_RS.UTF-8@latin` for `sr_RS.ISO-8859-5@latin`. With this option enabled, only the other
+	/// parts are compared, and the codesets can still break ties with
+	/// [`with_codeset_preference`](Self::with_codeset_preference).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let available_locales = ["sr_RS.ISO-8859-5", "sr_RS.UTF-8@latin"];
+	/// let user_locales = ["sr_RS.ISO-8859-5@latin"];
+	///
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, user_locales), Some("sr_RS.ISO-8859-5"));
+	///
+	/// let matcher = Matcher::new().with_ignored_codesets(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(available_locales, user_locales), Some("sr_RS.UTF-8@latin"));
+	/// ```
+	pub fn with_ignored_codesets(mut self, ignore: bool) -> Self {
+		self.ignore_codesets = ignore;
+		self
+	}
+
+	/// Sets whether modifiers are excluded from matching, e.g. for applications where modifiers of
+	/// the system locales like `@euro` are irrelevant.
+	///
+	/// By default, a locale with the modifier of the user locale is preferred. With this option
+	/// enabled, only the other parts are compared. Note that this also ignores the modifiers that
+	/// name scripts, e.g. `@latin` in `sr_RS@latin`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let available_locales = ["de_DE.UTF-8", "de_DE@euro"];
+	///
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, ["de_DE@euro"]), Some("de_DE@euro"));
+	///
+	/// let matcher = Matcher::new().with_ignored_modifiers(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["de_DE@euro"]), Some("de_DE.UTF-8"));
+	/// ```
+	pub fn with_ignored_modifiers(mut self, ignore: bool) -> Self {
+		self.ignore_modifiers = ignore;
+		self
+	}
+
+	/// Sets whether common aliases and legacy codes of territories are replaced by the current
+	/// codes before matching, e.g. `UK` by `GB`, `YU` by `RS` or `GBR` by `GB`.
+	///
+	/// By default, territories are compared as they are, so `en_UK` gets no territory score for
+	/// `en_GB`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let available_locales = ["en_US.UTF-8", "en_GB.UTF-8"];
+	///
+	/// assert_eq!(Matcher::new().best_matching_locale(available_locales, ["en_UK.UTF-8"]), Some("en_US.UTF-8"));
+	///
+	/// let matcher = Matcher::new().with_territory_aliases(true);
+	///
+	/// assert_eq!(matcher.best_matching_locale(available_locales, ["en_UK.UTF-8"]), Some("en_GB.UTF-8"));
+	/// ```
+	pub fn with_territory_aliases(mut self, territory_aliases: bool) -> Self {
+		self.territory_aliases = territory_aliases;
+		self
+	}
+
+	/// Sets a function called with a user locale, an available locale and its score for each
+	/// candidate evaluated during matching, e.g. for custom logging or metrics.
+	///
+	/// The candidates for a user locale are evaluated in the order of the available locales.
+	/// If the function returns [`ControlFlow::Break`], the remaining candidates for the user locale
+	/// are skipped, and the best of the evaluated ones is chosen.  
+	/// The function is not called by [`Matcher::explain`] and [`Matcher::score_matrix`].
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use std::ops::ControlFlow;
+	/// use std::sync::Mutex;
+	/// use std::sync::Arc;
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let log = Arc::new(Mutex::new(Vec::new()));
+	/// let observed = log.clone();
+	/// let matcher = Matcher::new().with_observer(move |user, aval, score| {
+	///     observed.lock().unwrap().push(format!("{user} -> {aval}: {score}"));
+	///     ControlFlow::Continue(())
+	/// });
+	///
+	/// let best_match = matcher.best_matching_locale(["en_US.UTF-8", "de_DE.UTF-8", "en_GB.UTF-8"], ["en_GB.UTF-8"]);
+	///
+	/// assert_eq!(best_match, Some("en_GB.UTF-8"));
+	/// assert_eq!(*log.lock().unwrap(), ["en_GB.UTF-8 -> en_US.UTF-8: 2", "en_GB.UTF-8 -> en_GB.UTF-8: 6"]);
+	/// ```
+	pub fn with_observer(mut self, observer: impl Fn(&str, &str, f64) -> ControlFlow<()> + Send + Sync + 'static) -> Self {
+		self.observer = Some(Arc::new(observer));
+		self
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale`], using the configuration of
+	/// the matcher.
+	pub fn best_matching_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		self.best_matching_locale_with_confidence(available_locales, user_locales).map(|m| m.locale)
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale_with_confidence`], using the
+	/// configuration of the matcher.
+	pub fn best_matching_locale_with_confidence<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locale_with_confidence(self, available_locales, user_locales)
+	}
+
+	/// Comparisons of corresponding parts of two locales (except for the language) with their weights,
+	/// in the order of [`Subtag`].  
+	/// The ignored parts are compared as absent in both locales, with a zero weight.
+	fn comparisons<T1: AsRef<str>, T2: AsRef<str>>(&self, aval_locale: &PosixLocale<T1>, user_locale: &PosixLocale<T2>) -> [(Comparison, f64); 3] {
+		let eq = str::eq_ignore_ascii_case;
+		let codeset = match self.ignore_codesets {
+			true => (Comparison::BothAbsent, 0.0),
+			false => (Comparison::of(aval_locale.codeset(), user_locale.codeset(), eq_codesets), 2.0),
+		};
+		let modifier = match self.ignore_modifiers {
+			true => (Comparison::BothAbsent, 0.0),
+			false => (Comparison::of(aval_locale.script_or_modifier(), user_locale.script_or_modifier(), eq), 1.0),
+		};
+		let (aval_territory, user_territory) = match self.territory_aliases {
+			true => (aval_locale.region().map(resolve_territory_alias), user_locale.region().map(resolve_territory_alias)),
+			false => (aval_locale.region(), user_locale.region()),
+		};
+		[
+			(Comparison::of(aval_territory, user_territory, eq), 4.0),
+			codeset,
+			modifier,
+		]
+	}
+}
+
+impl<T: AsRef<str>> Locale for PosixLocale<T> {
+	fn as_str(&self) -> &str {
+		self.as_str()
+	}
+
+	fn language(&self) -> &str {
+		self.language()
+	}
+
+	fn script_or_modifier(&self) -> Option<&str> {
+		self.modifier()
+	}
+
+	fn region(&self) -> Option<&str> {
+		self.territory()
+	}
+}
+
+impl Format for Matcher {
+	type Parsed<T: AsRef<str>> = PosixLocale<T>;
+	type Subtag = Subtag;
+	type Comparisons = [(Comparison, f64); 3];
+	type Error = ParseError;
+
+	const SUBTAGS: &'static [Subtag] = &SUBTAGS;
+	const LANGUAGE_WEIGHT: f64 = 8.0;
+
+	/// Malformed POSIX locales, e.g. `en US`, are not ignored, but matched by their parts.
+	fn parse_locale<T: AsRef<str>>(&self, locale: T) -> Result<PosixLocale<T>, T> {
+		Ok(PosixLocale::parse(locale))
+	}
+
+	fn into_inner<T: AsRef<str>>(locale: PosixLocale<T>) -> T {
+		locale.into_inner()
+	}
+
+	fn check<T: AsRef<str>>(&self, locale: Result<&PosixLocale<T>, &str>) -> Result<(), ParseError> {
+		match locale {
+			Ok(locale) => locale.validate(),
+			Err(locale) => PosixLocale::parse(locale).validate(),
+		}
+	}
+
+	fn scoring(&self) -> &Scoring {
+		&self.scoring
+	}
+
+	/// Ranks the available locales by the preferred codesets, then by the preferred modifiers.
+	fn rank<A: AsRef<str>, U: AsRef<str>>(&self, aval_locale: &PosixLocale<A>, user_locale: &PosixLocale<U>) -> usize {
+		let codeset = aval_locale.codeset().map(normalize_codeset);
+		let codeset_rank = self.codeset_preference.iter()
+			.position(|preferred| Some(preferred) == codeset.as_ref())
+			.unwrap_or(self.codeset_preference.len());
+		// An explicit modifier of the user takes precedence over the preferred modifiers
+		let modifier = aval_locale.modifier().unwrap_or("");
+		let modifier_rank = match user_locale.modifier() {
+			Some(_) => 0,
+			None => self.modifier_preference.iter()
+				.position(|preferred| preferred.eq_ignore_ascii_case(modifier))
+				.unwrap_or(self.modifier_preference.len()),
+		};
+		codeset_rank * (self.modifier_preference.len() + 1) + modifier_rank
+	}
+
+	fn candidate<A: AsRef<str>, U: AsRef<str>>(&self, aval_locale: &PosixLocale<A>, user_locale: &PosixLocale<U>) -> Option<(f64, [(Comparison, f64); 3])> {
+		let comparisons = self.comparisons(aval_locale, user_locale);
+		let score = score(comparisons, &self.scoring);
+		event!(trace, available = aval_locale.as_str(), user = user_locale.as_str(), score, "scored candidate");
+		Some((score, comparisons))
+	}
+
+	fn best_match<A: AsRef<str>, U: AsRef<str>>(&self, available: &[(usize, PosixLocale<A>)], user_locales: impl IntoIterator<Item = (usize, PosixLocale<U>)>) -> Option<(usize, usize, f64)> {
+		let observe = |user_locale: &PosixLocale<U>, aval_locale: &PosixLocale<A>, score| match &self.observer {
+			Some(observer) => observer(user_locale.as_str(), aval_locale.as_str(), score),
+			None => ControlFlow::Continue(()),
+		};
+		let weight = |aval_locale: &PosixLocale<A>| self.random_tie_break.as_ref().map_or(1.0, |r| (r.weight)(aval_locale.as_str()));
+		let rank = |i: usize, user_locale: &PosixLocale<U>| self.rank(&available[i].1, user_locale);
+		let engine = Engine {
+			format: "posix",
+			language_weight: Self::LANGUAGE_WEIGHT,
+			tie_break: self.scoring.tie_break,
+			rank: (!self.codeset_preference.is_empty() || !self.modifier_preference.is_empty()).then_some(&rank),
+			observer: self.observer.as_ref().map(|_| &observe as &dyn Fn(&PosixLocale<U>, &PosixLocale<A>, f64) -> ControlFlow<()>),
+			random_tie_break: self.random_tie_break.as_ref().map(|r| (&*r.random as &dyn Fn() -> f64, &weight as &dyn Fn(&PosixLocale<A>) -> f64)),
+		};
+		engine.best_match(available, |_, (i, aval_locale)| (*i, aval_locale), user_locales, |_, aval_locale, user_locale| self.candidate(aval_locale, user_locale))
+	}
+}
+
+/// Replaces an alias or a legacy code of a territory by the current code, e.g. `UK` by `GB`.
+fn resolve_territory_alias(territory: &str) -> &str {
+	lookup(REGION_ALIASES, territory).unwrap_or(territory)
+}
+
+/// Normalizes a codeset like glibc for comparing codesets, e.g. `UTF-8` to `utf8`.
+pub(crate) fn normalize_codeset(codeset: &str) -> String {
+	codeset.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.map(|c| c.to_ascii_lowercase())
+		.collect()
+}
+
+/// Compares two codesets like glibc, ignoring case and punctuation, e.g. `UTF-8` and `utf8`.
+fn eq_codesets(a: &str, b: &str) -> bool {
+	fn normalized(codeset: &str) -> impl Iterator<Item = u8> + '_ {
+		codeset.bytes().filter(u8::is_ascii_alphanumeric).map(|b| b.to_ascii_lowercase())
+	}
+	normalized(a).eq(normalized(b))
+}
+
+/// Converts a POSIX locale to a BCP 47 locale, e.g. `sr_RS.UTF-8@latin` to `sr-Latn-RS`.
+///
+/// The codeset is dropped, and the modifiers that name a script or a variant are converted, other
+/// modifiers (e.g. `euro`) are dropped.  
+/// Returns [`None`] for the `C` and `POSIX` locales and for locales without a language.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::to_bcp47;
+///
+///
+/// assert_eq!(to_bcp47("sr_RS.UTF-8@latin").as_deref(), Some("sr-Latn-RS"));
+/// assert_eq!(to_bcp47("de_DE@euro").as_deref(), Some("de-DE"));
+/// assert_eq!(to_bcp47("C.UTF-8"), None);
+/// ```
+#[cfg(feature = "bcp47")]
+pub fn to_bcp47(locale: &str) -> Option<String> {
+	let locale = PosixLocale::parse(locale);
+	let language = locale.language();
+	if language.is_empty() || language == "C" || language == "POSIX" {
+		return None;
+	}
+	let (script, variant) = match locale.modifier().map(str::to_ascii_lowercase).as_deref() {
+		Some("latin") => (Some("Latn"), None),
+		Some("cyrillic") => (Some("Cyrl"), None),
+		Some("devanagari") => (Some("Deva"), None),
+		Some("arabic") => (Some("Arab"), None),
+		Some("valencia") => (None, Some("valencia")),
+		_ => (None, None),
+	};
+	let tag = [Some(language), script, locale.territory().filter(|t| !t.is_empty()), variant]
+		.into_iter()
+		.flatten()
+		.collect::<Vec<&str>>()
+		.join("-");
+	Some(tag)
 }
 
 /// A POSIX locale as described in [The Open Group Base Specifications Issue 8 - 8. Environment Variables](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html).
-struct PosixLocale<T: AsRef<str>> {
+///
+/// The locale keeps its original string, so it can be passed to the matching functions and returned
+/// from them as is.  
+/// [`PosixLocale::parse`] only splits the locale into its parts, while parsing a `PosixLocale<String>`
+/// with [`str::parse`] also validates it.  
+/// With the `serde` feature, the locale is serialized as a string and validated on deserialization.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_locale, PosixLocale};
+///
+///
+/// let locale = PosixLocale::parse("sr_RS.UTF-8@latin");
+///
+/// assert_eq!(locale.language(), "sr");
+/// assert_eq!(locale.territory(), Some("RS"));
+/// assert_eq!(locale.codeset(), Some("UTF-8"));
+/// assert_eq!(locale.modifier(), Some("latin"));
+///
+/// let available_locales = ["en_US.UTF-8".parse::<PosixLocale<String>>().unwrap(), "ru_BY.UTF-8".parse().unwrap()];
+///
+/// let best_match = best_matching_locale(&available_locales, ["ru_RU.UTF-8"]);
+///
+/// assert_eq!(best_match.map(PosixLocale::as_str), Some("ru_BY.UTF-8"));
+/// assert!("en US".parse::<PosixLocale<String>>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PosixLocale<T: AsRef<str>> {
 	locale: T,
 	language_end: usize,
 	territory_end: usize,
@@ -135,49 +868,347 @@ struct PosixLocale<T: AsRef<str>> {
 }
 
 impl<T: AsRef<str>> PosixLocale<T> {
-	const TERRITORY_DELIMITER: char = '_';
-	const CODESET_DELIMITER: char = '.';
-	const MODIFIER_DELIMITER: char = '@';
-
 	/// Parse a POSIX locale string into a `PosixLocale`.
 	///
 	/// The `locale` string should be in the form `language[_territory][.codeset][@modifier]`.
 	///
 	/// The function does not perform any validation on the input string.
-	fn parse(locale: T) -> Self {
-		let locale_ref = locale.as_ref();
-		let codeset_end = locale_ref.find(Self::MODIFIER_DELIMITER).unwrap_or(locale_ref.len());
-		let territory_end = locale_ref.find(Self::CODESET_DELIMITER).unwrap_or(codeset_end);
-		let language_end = locale_ref.find(Self::TERRITORY_DELIMITER).unwrap_or(territory_end);
+	pub fn parse(locale: T) -> Self {
+		let (language_end, territory_end, codeset_end) = part_ends(locale.as_ref());
 		Self { locale, language_end, territory_end, codeset_end }
 	}
 
-	fn language(&self) -> &str {
+	/// Returns the language of the locale.
+	pub fn language(&self) -> &str {
 		&self.locale.as_ref()[0..self.language_end]
 	}
 
-	fn territory(&self) -> Option<&str> {
+	/// Returns the territory of the locale, if any.
+	pub fn territory(&self) -> Option<&str> {
 		self.locale.as_ref().get(self.language_end + 1..self.territory_end)
 	}
 
-	fn codeset(&self) -> Option<&str> {
+	/// Returns the codeset of the locale, if any.
+	pub fn codeset(&self) -> Option<&str> {
 		self.locale.as_ref().get(self.territory_end + 1..self.codeset_end)
 	}
 
-	fn modifier(&self) -> Option<&str> {
+	/// Returns the modifier of the locale, if any.
+	pub fn modifier(&self) -> Option<&str> {
 		self.locale.as_ref().get(self.codeset_end + 1..)
 	}
 
-	fn into_inner(self) -> T {
+	/// Returns the locale as a string slice.
+	pub fn as_str(&self) -> &str {
+		self.locale.as_ref()
+	}
+
+	/// Returns the original locale.
+	pub fn into_inner(self) -> T {
 		self.locale
 	}
+
+	/// Checks whether the locale covers another locale, i.e. every part of the locale is also in
+	/// the other locale, so the other locale is the same or a more specific form of it.
+	///
+	/// The language must be the same, and the territory, the codeset and the modifier of the
+	/// locale, if any, must be the same in the other locale. The parts are compared ignoring ASCII
+	/// case, and codesets also ignoring punctuation, so `UTF-8` covers `utf8`.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::PosixLocale;
+	///
+	///
+	/// assert!(PosixLocale::parse("en").subsumes(&PosixLocale::parse("en_US.UTF-8")));
+	/// assert!(PosixLocale::parse("de_DE.UTF-8").subsumes(&PosixLocale::parse("de_DE.utf8@euro")));
+	/// assert!(!PosixLocale::parse("en_US").subsumes(&PosixLocale::parse("en_GB")));
+	/// assert!(!PosixLocale::parse("en_US").subsumes(&PosixLocale::parse("en")));
+	/// ```
+	pub fn subsumes<T2: AsRef<str>>(&self, other: &PosixLocale<T2>) -> bool {
+		let covers = |part: Option<&str>, other_part: Option<&str>, eq: fn(&str, &str) -> bool| match (part, other_part) {
+			(None, _) => true,
+			(Some(part), Some(other_part)) => eq(part, other_part),
+			(Some(_), None) => false,
+		};
+		self.language().eq_ignore_ascii_case(other.language())
+			&& covers(self.territory(), other.territory(), str::eq_ignore_ascii_case)
+			&& covers(self.codeset(), other.codeset(), eq_codesets)
+			&& covers(self.modifier(), other.modifier(), str::eq_ignore_ascii_case)
+	}
+
+	/// Checks whether the locale is a more specific form of another locale, i.e. the other locale
+	/// [subsumes](Self::subsumes) the locale, but not the other way around.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::PosixLocale;
+	///
+	///
+	/// assert!(PosixLocale::parse("en_US.UTF-8").is_more_specific(&PosixLocale::parse("en_US")));
+	/// assert!(!PosixLocale::parse("en_US.UTF-8").is_more_specific(&PosixLocale::parse("en_US.utf8")));
+	/// ```
+	pub fn is_more_specific<T2: AsRef<str>>(&self, other: &PosixLocale<T2>) -> bool {
+		other.subsumes(self) && !self.subsumes(other)
+	}
+
+	fn validate(&self) -> Result<(), ParseError> {
+		validate_parts(self.locale.as_ref(), self.language_end, self.territory_end, self.codeset_end)
+	}
+
+	/// Returns the byte position in the locale of the part the error is about.
+	pub(crate) fn error_position(&self, error: ParseError) -> usize {
+		let position = match error {
+			ParseError::InvalidLanguage => 0,
+			ParseError::InvalidTerritory => self.language_end + 1,
+			ParseError::InvalidCodeset => self.territory_end + 1,
+			ParseError::InvalidModifier => self.codeset_end + 1,
+		};
+		position.min(self.locale.as_ref().len())
+	}
+}
+
+impl<'a> PosixLocale<&'a str> {
+	/// Returns the part of the given category, if any, borrowed from the original locale rather
+	/// than from the parsed locale.
+	pub(crate) fn part(&self, subtag: Subtag) -> Option<&'a str> {
+		let locale: &'a str = self.locale;
+		match subtag {
+			Subtag::Territory => locale.get(self.language_end + 1..self.territory_end),
+			Subtag::Codeset => locale.get(self.territory_end + 1..self.codeset_end),
+			Subtag::Modifier => locale.get(self.codeset_end + 1..),
+		}
+	}
+
+	/// Parse a POSIX locale string into a `PosixLocale` like [`PosixLocale::parse`], but in const
+	/// context, so tables of locales can be parsed at compile time.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::PosixLocale;
+	///
+	///
+	/// static AVAILABLE_LOCALES: [PosixLocale<&str>; 2] = [
+	///     PosixLocale::parse_const("en_US.UTF-8"),
+	///     PosixLocale::parse_const("ru_RU.UTF-8"),
+	/// ];
+	///
+	/// assert_eq!(AVAILABLE_LOCALES[1].territory(), Some("RU"));
+	/// ```
+	pub const fn parse_const(locale: &'a str) -> Self {
+		let (language_end, territory_end, codeset_end) = part_ends(locale);
+		Self { locale, language_end, territory_end, codeset_end }
+	}
+
+	/// Parse a POSIX locale given as bytes like [`PosixLocale::parse`], e.g. from a network buffer.  
+	/// Returns [`None`] if the locale is not valid UTF-8.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::PosixLocale;
+	///
+	///
+	/// let locale = PosixLocale::parse_bytes(b"sr_RS.UTF-8@latin").unwrap();
+	///
+	/// assert_eq!(locale.modifier(), Some("latin"));
+	/// assert_eq!(PosixLocale::parse_bytes(b"sr_RS.\xFF"), None);
+	/// ```
+	pub const fn parse_bytes(locale: &'a [u8]) -> Option<Self> {
+		match std::str::from_utf8(locale) {
+			Ok(locale) => Some(Self::parse_const(locale)),
+			Err(_) => None,
+		}
+	}
+
+	/// Parse and validate a POSIX locale string like [`str::parse`] does for `PosixLocale<String>`,
+	/// but in const context, so tables of locales can be validated at compile time.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the locale is invalid.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::PosixLocale;
+	///
+	///
+	/// // Fails to compile if the locale is invalid
+	/// const GERMAN: PosixLocale<&str> = match PosixLocale::try_parse_const("de_DE.UTF-8") {
+	///     Ok(locale) => locale,
+	///     Err(_) => panic!("invalid locale"),
+	/// };
+	///
+	/// assert_eq!(GERMAN.language(), "de");
+	/// assert!(PosixLocale::try_parse_const("de-DE").is_err());
+	/// ```
+	pub const fn try_parse_const(locale: &'a str) -> Result<Self, ParseError> {
+		let parsed = Self::parse_const(locale);
+		match validate_parts(locale, parsed.language_end, parsed.territory_end, parsed.codeset_end) {
+			Ok(()) => Ok(parsed),
+			Err(error) => Err(error),
+		}
+	}
+}
+
+const TERRITORY_DELIMITER: u8 = b'_';
+const CODESET_DELIMITER: u8 = b'.';
+const MODIFIER_DELIMITER: u8 = b'@';
+
+/// The ends of the language, the territory and the codeset of a POSIX locale.  
+/// The end of a missing part is the end of the previous part.
+const fn part_ends(locale: &str) -> (usize, usize, usize) {
+	const fn find(bytes: &[u8], delimiter: u8) -> Option<usize> {
+		let mut i = 0;
+		while i < bytes.len() {
+			if bytes[i] == delimiter {
+				return Some(i);
+			}
+			i += 1;
+		}
+		None
+	}
+	let bytes = locale.as_bytes();
+	let codeset_end = match find(bytes, MODIFIER_DELIMITER) { Some(i) => i, None => bytes.len() };
+	let territory_end = match find(bytes, CODESET_DELIMITER) { Some(i) => i, None => codeset_end };
+	let language_end = match find(bytes, TERRITORY_DELIMITER) { Some(i) => i, None => territory_end };
+	(language_end, territory_end, codeset_end)
+}
+
+/// Checks that the language is not empty and consists of ASCII letters and digits, and that the
+/// other parts, if present, are not empty and contain no delimiters, spaces or control characters.
+const fn validate_parts(locale: &str, language_end: usize, territory_end: usize, codeset_end: usize) -> Result<(), ParseError> {
+	/// Checks that the part between the positions is not empty and consists of visible ASCII
+	/// characters, except for the delimiters not allowed in it. A part starting after the end is
+	/// missing, which is valid.
+	const fn valid(bytes: &[u8], start: usize, end: usize, alphanumeric: bool, modifier: bool) -> bool {
+		if start > end || end > bytes.len() {
+			return true;
+		}
+		let mut i = start;
+		while i < end {
+			let b = bytes[i];
+			let valid = match alphanumeric {
+				true => b.is_ascii_alphanumeric(),
+				false => b.is_ascii_graphic() && b != MODIFIER_DELIMITER && (modifier || b != TERRITORY_DELIMITER && b != CODESET_DELIMITER),
+			};
+			if !valid {
+				return false;
+			}
+			i += 1;
+		}
+		start < end
+	}
+	let bytes = locale.as_bytes();
+	if !valid(bytes, 0, language_end, true, false) {
+		return Err(ParseError::InvalidLanguage);
+	}
+	if !valid(bytes, language_end + 1, territory_end, false, false) {
+		return Err(ParseError::InvalidTerritory);
+	}
+	if !valid(bytes, territory_end + 1, codeset_end, false, false) {
+		return Err(ParseError::InvalidCodeset);
+	}
+	if !valid(bytes, codeset_end + 1, bytes.len(), false, true) {
+		return Err(ParseError::InvalidModifier);
+	}
+	Ok(())
+}
+
+impl std::str::FromStr for PosixLocale<String> {
+	type Err = ParseError;
+
+	fn from_str(locale: &str) -> Result<Self, Self::Err> {
+		let locale = Self::parse(locale.to_string());
+		locale.validate()?;
+		Ok(locale)
+	}
+}
+
+impl<T: AsRef<str>> AsRef<str> for PosixLocale<T> {
+	fn as_ref(&self) -> &str {
+		self.locale.as_ref()
+	}
 }
 
+impl<T: AsRef<str>> std::fmt::Display for PosixLocale<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.locale.as_ref())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<T: AsRef<str>> serde::Serialize for PosixLocale<T> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.locale.as_ref())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PosixLocale<String> {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let locale = Self::parse(String::deserialize(deserializer)?);
+		locale.validate().map_err(serde::de::Error::custom)?;
+		Ok(locale)
+	}
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for PosixLocale<String> {
+	fn inline_schema() -> bool {
+		true
+	}
+
+	fn schema_name() -> std::borrow::Cow<'static, str> {
+		"PosixLocale".into()
+	}
+
+	/// The pattern mirrors the validation of [`PosixLocale::from_str`](std::str::FromStr::from_str).
+	fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+		schemars::json_schema!({
+			"type": "string",
+			"pattern": r"^[A-Za-z0-9]+(_[\x21-\x2D\x2F-\x3F\x41-\x5E\x60-\x7E]+)?(\.[\x21-\x2D\x2F-\x3F\x41-\x5E\x60-\x7E]+)?(@[\x21-\x3F\x41-\x7E]+)?$",
+			"description": "A POSIX locale, e.g. `en_US.UTF-8`.",
+		})
+	}
+}
+
+/// An error of parsing an invalid [`PosixLocale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseError {
+	/// The language is empty or contains characters other than ASCII letters and digits.
+	InvalidLanguage,
+	/// The territory is empty or contains invalid characters.
+	InvalidTerritory,
+	/// The codeset is empty or contains invalid characters.
+	InvalidCodeset,
+	/// The modifier is empty or contains invalid characters.
+	InvalidModifier,
+}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::InvalidLanguage => "invalid language in POSIX locale",
+			Self::InvalidTerritory => "invalid territory in POSIX locale",
+			Self::InvalidCodeset => "invalid codeset in POSIX locale",
+			Self::InvalidModifier => "invalid modifier in POSIX locale",
+		})
+	}
+}
+
+impl std::error::Error for ParseError {}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 
 	#[test]
+	#[allow(clippy::needless_borrows_for_generic_args)]
 	fn test_best_matching_locale() {
 
 		fn case<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>, expected: Option<T1>)
@@ -272,6 +1303,11 @@ mod tests {
 		case(["ru_ru.utf-8@icase"], ["en", "RU_RU.UTF-8@ICASE"], Some("ru_ru.utf-8@icase"));
 		case(["fr_FR.CP1252@euRO"], ["FR", "en"], Some("fr_FR.CP1252@euRO"));
 
+		// Codesets spelled differently
+		case(["ru_RU.KOI8-R", "ru_RU.utf8"], ["ru_RU.UTF-8"], Some("ru_RU.utf8"));
+		case(["ru_RU.KOI8-R", "ru_RU.UTF-8"], ["ru_RU.utf8"], Some("ru_RU.UTF-8"));
+		case(["en_US.ISO-8859-1", "en_US.ISO-8859-15"], ["en_US.iso885915"], Some("en_US.ISO-8859-15"));
+
 		// Various template parameter types
 		// &str and &&str
 		case(["en_US", "ru_RU"], ["ru", "en"], Some("ru_RU"));
@@ -295,6 +1331,478 @@ mod tests {
 		case([Box::from("en_US"), Box::from("ru_RU")], ["ru", "en"], Some(Box::from("ru_RU")));
 	}
 
+	#[test]
+	fn test_best_matching_locale_with_confidence() {
+
+		fn case<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>, expected: Option<(T1, usize, usize, f64)>)
+		where
+			T1: AsRef<str> + PartialEq + std::fmt::Debug,
+			T2: AsRef<str>
+		{
+			let expected = expected.map(|(locale, available_index, user_index, confidence)| LocaleMatch { locale, available_index, user_index, confidence });
+			assert_eq!(best_matching_locale_with_confidence(available_locales, user_locales), expected);
+		}
+
+		// Exact matches
+		case(["en"], ["en"], Some(("en", 0, 0, 1.0)));
+		case(["en_US", "ru_RU"], ["ru_RU", "en_US"], Some(("ru_RU", 1, 0, 1.0)));
+		case(["ru_RU.UTF-8@icase"], ["RU_ru.utf-8@ICASE"], Some(("ru_RU.UTF-8@icase", 0, 0, 1.0)));
+
+		// Partial matches
+		case(["en", "ru"], ["ru_RU"], Some(("ru", 1, 0, 8.0 / 12.0)));
+		case(["ru_UA"], ["ru_RU"], Some(("ru_UA", 0, 0, 8.0 / 12.0)));
+		case(["ru_RU.KOI8-R"], ["ru_RU.UTF-8"], Some(("ru_RU.KOI8-R", 0, 0, 12.0 / 14.0)));
+		case(["ru_RU.UTF-8@icase"], ["ru"], Some(("ru_RU.UTF-8@icase", 0, 0, 8.0 / 15.0)));
+
+		// Fallback to less preferred user locales
+		case(["en_US", "pt_BR"], ["fr_FR", "pt_PT", "en"], Some(("pt_BR", 1, 1, 8.0 / 12.0)));
+		case(["en"], ["fr", "de", "en"], Some(("en", 0, 2, 1.0)));
+
+		// Malformed locales keep their positions
+		case(["!!!", "en", "ru-RU", "ru"], ["", "ru"], Some(("ru", 3, 1, 1.0)));
+
+		// Not found
+		case(["en"], ["ru"], None);
+		case(&[] as &[&str], &[] as &[&str], None);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_mismatch_penalty() {
+
+		fn case(penalty: f64, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_mismatch_penalty(penalty);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// No penalty
+		case(0.0, &["fr_FR", "fr"], &["fr_CA"], Some("fr_FR"));
+		case(-1.0, &["fr_FR", "fr"], &["fr_CA"], Some("fr_FR"));
+
+		// Neutral locales win over contradicting ones
+		case(1.0, &["fr_FR", "fr"], &["fr_CA"], Some("fr"));
+		case(0.1, &["fr_FR.UTF-8", "fr.UTF-8"], &["fr_CA.UTF-8"], Some("fr.UTF-8"));
+		case(1.0, &["ru_RU.KOI8-R", "ru_RU"], &["ru_RU.UTF-8"], Some("ru_RU"));
+
+		// Matching parts still win
+		case(1.0, &["fr", "fr_CA"], &["fr_CA"], Some("fr_CA"));
+		case(0.5, &["ru.UTF-8", "ru_RU.KOI8-R"], &["ru_RU.UTF-8"], Some("ru_RU.KOI8-R"));
+		case(2.0, &["ru.UTF-8", "ru_RU.KOI8-R"], &["ru_RU.UTF-8"], Some("ru.UTF-8"));
+
+		// Contradicting locales are still better than nothing
+		case(1.0, &["en", "fr_FR"], &["fr_CA"], Some("fr_FR"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_missing_subtag() {
+
+		fn case(matcher: &Matcher, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Neutral
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Codeset, MissingSubtag::Neutral);
+		case(&matcher, &["ru_RU.KOI8-R", "ru_RU"], &["ru_RU"], Some("ru_RU.KOI8-R"));
+
+		// Wildcard
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Codeset, MissingSubtag::Wildcard);
+		case(&matcher, &["ru_RU.KOI8-R", "ru_RU"], &["ru_RU.UTF-8"], Some("ru_RU"));
+		case(&matcher, &["ru_RU.KOI8-R", "ru_RU"], &["ru_RU"], Some("ru_RU.KOI8-R"));
+
+		// Partial
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Territory, MissingSubtag::Partial(0.5));
+		case(&matcher, &["fr_FR", "fr"], &["fr"], Some("fr"));
+		case(&matcher, &["fr_FR", "fr"], &["fr_CA"], Some("fr"));
+		case(&matcher, &["fr", "fr_CA"], &["fr_CA"], Some("fr_CA"));
+
+		// Require absence
+		let matcher = Matcher::new().with_missing_subtag(Subtag::Modifier, MissingSubtag::RequireAbsence);
+		case(&matcher, &["sr_RS@latin", "sr_RS"], &["sr_RS"], Some("sr_RS"));
+		case(&matcher, &["sr_RS", "sr_RS@latin"], &["sr_RS@latin"], Some("sr_RS@latin"));
+		case(&matcher, &["sr_RS@latin"], &["sr_RS"], Some("sr_RS@latin"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_tie_break() {
+
+		fn case(tie_break: TieBreak, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_tie_break(tie_break);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// List order
+		case(TieBreak::ListOrder, &["en_US", "en", "en_US.UTF-8"], &["en_GB"], Some("en_US"));
+		case(TieBreak::ListOrder, &["en", "en_US", "en_US.UTF-8"], &["en_GB"], Some("en"));
+
+		// Most specific
+		case(TieBreak::MostSpecific, &["en", "en_US", "en_US.UTF-8"], &["en_GB"], Some("en_US.UTF-8"));
+		case(TieBreak::MostSpecific, &["en", "en_US", "en_CA"], &["en_GB"], Some("en_US"));
+		case(TieBreak::MostSpecific, &["en", "en_US.UTF-8", "en_GB"], &["en_GB"], Some("en_GB"));
+
+		// Least specific
+		case(TieBreak::LeastSpecific, &["en_US.UTF-8", "en_US", "en"], &["en_GB"], Some("en"));
+		case(TieBreak::LeastSpecific, &["en_US.UTF-8", "en_US", "en_CA"], &["en_GB"], Some("en_US"));
+		case(TieBreak::LeastSpecific, &["en", "en_US", "en_GB.UTF-8"], &["en_GB"], Some("en_GB.UTF-8"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_observer() {
+		use std::sync::Mutex;
+
+		fn case(break_at: f64, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>, expected_calls: &[(&str, &str, f64)]) {
+			let calls = Arc::new(Mutex::new(Vec::new()));
+			let observed = calls.clone();
+			let matcher = Matcher::new().with_observer(move |user, aval, score| {
+				observed.lock().unwrap().push((user.to_string(), aval.to_string(), score));
+				if score >= break_at { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+			});
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+			let calls = calls.lock().unwrap();
+			assert_eq!(calls.iter().map(|(u, a, s)| (u.as_str(), a.as_str(), *s)).collect::<Vec<_>>(), expected_calls);
+		}
+
+		// All candidates are observed in order
+		case(f64::INFINITY, &["en_US", "ru_RU", "en_GB"], &["en_GB"], Some("en_GB"), &[("en_GB", "en_US", 0.0), ("en_GB", "en_GB", 4.0)]);
+		case(f64::INFINITY, &["en_US", "ru_RU"], &["fr", "ru"], Some("ru_RU"), &[("ru", "ru_RU", 0.0)]);
+		case(f64::INFINITY, &["en_US", "ru_RU"], &["fr"], None, &[]);
+
+		// Early termination
+		case(0.0, &["en", "en_GB"], &["en_GB"], Some("en"), &[("en_GB", "en", 0.0)]);
+		case(4.0, &["en", "en_GB", "en_GB.UTF-8"], &["en_GB.UTF-8"], Some("en_GB"), &[("en_GB.UTF-8", "en", 0.0), ("en_GB.UTF-8", "en_GB", 4.0)]);
+
+		// Cloned matchers share the observer
+		let calls = Arc::new(Mutex::new(0));
+		let observed = calls.clone();
+		let matcher = Matcher::new().with_observer(move |_, _, _| {
+			*observed.lock().unwrap() += 1;
+			ControlFlow::Continue(())
+		});
+		matcher.clone().best_matching_locale(["en_US", "en_GB"], ["en"]);
+		assert_eq!(*calls.lock().unwrap(), 2);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_random_tie_break() {
+		fn case(random: f64, weights: &[(&'static str, f64)], available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let weights = weights.to_vec();
+			let matcher = Matcher::new().with_weighted_random_tie_break(move || random, move |locale| {
+				weights.iter().find(|(weighted, _)| *weighted == locale).map_or(1.0, |(_, weight)| *weight)
+			});
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Uniform
+		case(0.0, &[], &["en_US", "en_GB", "en_AU"], &["en_CA"], Some("en_US"));
+		case(0.5, &[], &["en_US", "en_GB", "en_AU"], &["en_CA"], Some("en_GB"));
+		case(0.99, &[], &["en_US", "en_GB", "en_AU"], &["en_CA"], Some("en_AU"));
+
+		// Weighted
+		case(0.5, &[("en_US", 9.0)], &["en_US", "en_GB"], &["en_CA"], Some("en_US"));
+		case(0.95, &[("en_US", 9.0)], &["en_US", "en_GB"], &["en_CA"], Some("en_GB"));
+		case(0.0, &[("en_US", 0.0)], &["en_US", "en_GB"], &["en_CA"], Some("en_GB"));
+
+		// No ties
+		case(0.99, &[], &["en_US", "en_GB", "en_AU"], &["en_GB"], Some("en_GB"));
+		case(0.99, &[], &["en_US", "fr_FR"], &["fr_CA", "en"], Some("fr_FR"));
+		case(0.99, &[], &["en_US", "en_GB"], &["fr"], None);
+
+		// Cloned matchers share the random tie break
+		let matcher = Matcher::new().with_random_tie_break(|| 0.5);
+		assert_eq!(matcher.clone().best_matching_locale(["en_US", "en_GB"], ["en"]), Some("en_GB"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_codeset_preference() {
+		fn case(codesets: &[&str], tie_break: TieBreak, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_codeset_preference(codesets).with_tie_break(tie_break);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// List order without a preference
+		case(&[], TieBreak::ListOrder, &["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU"], Some("ru_RU.KOI8-R"));
+
+		// Preferred codesets
+		case(&["UTF-8"], TieBreak::ListOrder, &["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU"], Some("ru_RU.UTF-8"));
+		case(&["utf8"], TieBreak::ListOrder, &["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru"], Some("ru_RU.UTF-8"));
+		case(&["UTF-8", "KOI8-R"], TieBreak::ListOrder, &["ru_RU.CP1251", "ru_RU.KOI8-R"], &["ru_RU"], Some("ru_RU.KOI8-R"));
+		case(&["UTF-8"], TieBreak::ListOrder, &["ru_RU", "ru_RU.UTF-8"], &["ru_RU"], Some("ru_RU.UTF-8"));
+		case(&["UTF-8"], TieBreak::ListOrder, &["ru_RU.CP1251", "ru_RU.KOI8-R"], &["ru_RU"], Some("ru_RU.CP1251"));
+
+		// Better matches and tie breaks come first
+		case(&["UTF-8"], TieBreak::ListOrder, &["ru_UA.UTF-8", "ru_RU.KOI8-R"], &["ru_RU"], Some("ru_RU.KOI8-R"));
+		case(&["UTF-8"], TieBreak::ListOrder, &["ru_RU.KOI8-R", "ru_RU.UTF-8"], &["ru_RU.KOI8-R"], Some("ru_RU.KOI8-R"));
+		case(&["UTF-8"], TieBreak::LeastSpecific, &["ru_RU.UTF-8", "ru_RU"], &["ru_RU"], Some("ru_RU"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_modifier_preference() {
+		fn case(modifiers: &[&str], available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_modifier_preference(modifiers);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// List order without a preference
+		case(&[], &["sr_RS", "sr_RS@latin"], &["sr_RS"], Some("sr_RS"));
+		case(&[], &["sr_RS@latin", "sr_RS"], &["sr_RS"], Some("sr_RS@latin"));
+
+		// Preferred modifiers
+		case(&["latin"], &["sr_RS", "sr_RS@latin"], &["sr_RS"], Some("sr_RS@latin"));
+		case(&["LATIN"], &["sr_RS", "sr_RS@latin"], &["sr"], Some("sr_RS@latin"));
+		case(&[""], &["sr_RS@latin", "sr_RS"], &["sr_RS"], Some("sr_RS"));
+		case(&["cyrillic", ""], &["sr_RS@latin", "sr_RS"], &["sr_RS"], Some("sr_RS"));
+		case(&["euro"], &["sr_RS@latin", "sr_RS"], &["sr_RS"], Some("sr_RS@latin"));
+
+		// Better matches come first
+		case(&["latin"], &["sr_ME@latin", "sr_RS"], &["sr_RS"], Some("sr_RS"));
+		case(&["latin"], &["sr_RS@latin", "sr_RS@cyrillic"], &["sr_RS@cyrillic"], Some("sr_RS@cyrillic"));
+
+		// Explicit modifiers of the user are not overridden
+		case(&["latin"], &["sr_RS", "sr_RS@latin"], &["sr_RS@cyrillic"], Some("sr_RS"));
+		case(&[""], &["sr_RS@latin", "sr_RS"], &["sr_RS@cyrillic"], Some("sr_RS@latin"));
+		case(&["latin"], &["sr_RS", "sr_RS@latin"], &["sr_RS@cyrillic", "sr_RS"], Some("sr_RS"));
+
+		// Preferred codesets come first
+		let matcher = Matcher::new().with_codeset_preference(["UTF-8"]).with_modifier_preference(["latin"]);
+		assert_eq!(matcher.best_matching_locale(["sr_RS@latin", "sr_RS.UTF-8"], ["sr_RS"]), Some("sr_RS.UTF-8"));
+		assert_eq!(matcher.best_matching_locale(["sr_RS.UTF-8", "sr_RS.UTF-8@latin"], ["sr_RS"]), Some("sr_RS.UTF-8@latin"));
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_territory_aliases() {
+		fn case(territory_aliases: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_territory_aliases(territory_aliases);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Territories are compared as they are by default
+		case(false, &["en_US.UTF-8", "en_GB.UTF-8"], &["en_UK.UTF-8"], Some("en_US.UTF-8"));
+		case(false, &["en_US.UTF-8", "en_UK.UTF-8"], &["en_GB.UTF-8"], Some("en_US.UTF-8"));
+
+		// Aliases of user and available territories
+		case(true, &["en_US.UTF-8", "en_GB.UTF-8"], &["en_UK.UTF-8"], Some("en_GB.UTF-8"));
+		case(true, &["en_US.UTF-8", "en_UK.UTF-8"], &["en_GB.UTF-8"], Some("en_UK.UTF-8"));
+		case(true, &["en_US", "en_GB"], &["en_uk"], Some("en_GB"));
+		case(true, &["sr_ME", "sr_RS"], &["sr_YU"], Some("sr_RS"));
+		case(true, &["sr_ME", "sr_RS"], &["sr_CS"], Some("sr_RS"));
+		case(true, &["ru_UA", "ru_RU"], &["ru_SU"], Some("ru_RU"));
+		case(true, &["de_AT", "de_DE"], &["de_DD"], Some("de_DE"));
+
+		// Other territories
+		case(true, &["en_US", "en_GB"], &["en_CA"], Some("en_US"));
+		case(true, &["en_GB", "en_US"], &["en_US"], Some("en_US"));
+
+		// Confidence
+		let matcher = Matcher::new().with_territory_aliases(true);
+		let best_match = matcher.best_matching_locale_with_confidence(["en_GB.UTF-8"], ["en_UK.UTF-8"]).unwrap();
+		assert_eq!(best_match.confidence, 1.0);
+	}
+
+	#[test]
+	fn test_resolve_territory_alias() {
+		assert_eq!(resolve_territory_alias("UK"), "GB");
+		assert_eq!(resolve_territory_alias("uk"), "GB");
+		assert_eq!(resolve_territory_alias("YU"), "RS");
+		assert_eq!(resolve_territory_alias("GBR"), "GB");
+		assert_eq!(resolve_territory_alias("GB"), "GB");
+		assert_eq!(resolve_territory_alias("us"), "us");
+		assert_eq!(resolve_territory_alias("419"), "419");
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_ignored_codesets() {
+		fn case(ignore: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_ignored_codesets(ignore);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Codesets are compared by default
+		case(false, &["sr_RS.ISO-8859-5", "sr_RS.UTF-8@latin"], &["sr_RS.ISO-8859-5@latin"], Some("sr_RS.ISO-8859-5"));
+		case(false, &["ru_RU.UTF-8", "ru_RU.KOI8-R"], &["ru_RU.KOI8-R"], Some("ru_RU.KOI8-R"));
+
+		// Ignored codesets
+		case(true, &["sr_RS.ISO-8859-5", "sr_RS.UTF-8@latin"], &["sr_RS.ISO-8859-5@latin"], Some("sr_RS.UTF-8@latin"));
+		case(true, &["ru_RU.UTF-8", "ru_RU.KOI8-R"], &["ru_RU.KOI8-R"], Some("ru_RU.UTF-8"));
+		case(true, &["ru_UA.KOI8-U", "ru_RU.UTF-8"], &["ru_RU.KOI8-U"], Some("ru_RU.UTF-8"));
+		case(true, &["en_US.UTF-8", "ru_RU.UTF-8"], &["fr_FR.UTF-8"], None);
+
+		// Confidence
+		let matcher = Matcher::new().with_ignored_codesets(true);
+		let best_match = matcher.best_matching_locale_with_confidence(["ru_RU.KOI8-R"], ["ru_RU.UTF-8"]).unwrap();
+		assert_eq!(best_match.confidence, 1.0);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_with_ignored_modifiers() {
+		fn case(ignore: bool, available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			let matcher = Matcher::new().with_ignored_modifiers(ignore).with_mismatch_penalty(1.0);
+			assert_eq!(matcher.best_matching_locale(available_locales, user_locales).copied(), expected);
+		}
+
+		// Modifiers are compared by default
+		case(false, &["de_DE.UTF-8", "de_DE@euro"], &["de_DE@euro"], Some("de_DE@euro"));
+		case(false, &["de_DE@icase", "de_DE"], &["de_DE@euro"], Some("de_DE"));
+		case(false, &["sr_RS", "sr_RS@latin"], &["sr_RS@latin"], Some("sr_RS@latin"));
+
+		// Ignored modifiers
+		case(true, &["de_DE.UTF-8", "de_DE@euro"], &["de_DE@euro"], Some("de_DE.UTF-8"));
+		case(true, &["de_DE@icase", "de_DE"], &["de_DE@euro"], Some("de_DE@icase"));
+		case(true, &["sr_RS", "sr_RS@latin"], &["sr_RS@latin"], Some("sr_RS"));
+		case(true, &["de_AT@euro", "de_DE"], &["de_DE@euro"], Some("de_DE"));
+
+		// Confidence
+		let matcher = Matcher::new().with_ignored_modifiers(true);
+		let best_match = matcher.best_matching_locale_with_confidence(["de_DE@euro"], ["de_DE"]).unwrap();
+		assert_eq!(best_match.confidence, 1.0);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_PosixLocale_subsumes() {
+		fn case(locale: &str, other: &str, expected: bool) {
+			let (locale, other) = (PosixLocale::parse(locale), PosixLocale::parse(other));
+			assert_eq!(locale.subsumes(&other), expected, "{locale} and {other}");
+			assert_eq!(other.is_more_specific(&locale), expected && !other.subsumes(&locale), "{other} and {locale}");
+		}
+
+		// Same locales
+		case("en", "en", true);
+		case("en_US.UTF-8", "en_us.utf8", true);
+
+		// More specific locales
+		case("en", "en_US", true);
+		case("en", "en.UTF-8", true);
+		case("en_US", "en_US.UTF-8@dict", true);
+		case("sr_RS@latin", "sr_RS.UTF-8@latin", true);
+
+		// Different or less specific locales
+		case("en_US", "en_GB", false);
+		case("en_US", "en", false);
+		case("en", "ru", false);
+		case("en_US.UTF-8", "en_US.ISO-8859-1", false);
+		case("sr_RS@latin", "sr_RS", false);
+		case("sr_RS@latin", "sr_RS@cyrillic", false);
+	}
+
+	#[test]
+	fn test_user_locales_from_vars() {
+
+		fn case(vars: &[(&str, &str)], expected: &[&str]) {
+			let var = |name: &str| vars.iter().find(|(n, _)| *n == name).map(|(_, value)| value.to_string());
+			assert_eq!(user_locales_from_vars(var), expected);
+		}
+
+		// Nothing set
+		case(&[], &[]);
+		case(&[("LC_TIME", "de_DE.UTF-8"), ("LANGUAGE", "de")], &[]);
+
+		// Single variable
+		case(&[("LANG", "ru_RU.UTF-8")], &["ru_RU.UTF-8"]);
+		case(&[("LC_MESSAGES", "ru_RU.UTF-8")], &["ru_RU.UTF-8"]);
+		case(&[("LC_ALL", "ru_RU.UTF-8")], &["ru_RU.UTF-8"]);
+
+		// Precedence
+		case(&[("LANG", "en_US.UTF-8"), ("LC_MESSAGES", "ru_RU.UTF-8")], &["ru_RU.UTF-8"]);
+		case(&[("LANG", "en_US.UTF-8"), ("LC_MESSAGES", "ru_RU.UTF-8"), ("LC_ALL", "fr_FR.UTF-8")], &["fr_FR.UTF-8"]);
+		case(&[("LANG", "en_US.UTF-8"), ("LC_ALL", "fr_FR.UTF-8")], &["fr_FR.UTF-8"]);
+
+		// Empty variables are ignored
+		case(&[("LANG", "en_US.UTF-8"), ("LC_MESSAGES", ""), ("LC_ALL", "")], &["en_US.UTF-8"]);
+		case(&[("LANG", ""), ("LC_MESSAGES", ""), ("LC_ALL", "")], &[]);
+
+		// Other categories are ignored
+		case(&[("LANG", "en_US.UTF-8"), ("LC_TIME", "de_DE.UTF-8"), ("LC_CTYPE", "C")], &["en_US.UTF-8"]);
+
+		// LANGUAGE
+		case(&[("LANGUAGE", "de_DE:fr:en"), ("LANG", "ru_RU.UTF-8")], &["de_DE", "fr", "en", "ru_RU.UTF-8"]);
+		case(&[("LANGUAGE", "de_DE:fr"), ("LANG", "en_US.UTF-8"), ("LC_ALL", "ru_RU.UTF-8")], &["de_DE", "fr", "ru_RU.UTF-8"]);
+		case(&[("LANGUAGE", ":de_DE::fr:"), ("LANG", "ru_RU.UTF-8")], &["de_DE", "fr", "ru_RU.UTF-8"]);
+		case(&[("LANGUAGE", ""), ("LANG", "ru_RU.UTF-8")], &["ru_RU.UTF-8"]);
+
+		// LANGUAGE is ignored for the C locale
+		case(&[("LANGUAGE", "de_DE:fr:en")], &[]);
+		case(&[("LANGUAGE", "de_DE:fr"), ("LANG", "C")], &["C"]);
+		case(&[("LANGUAGE", "de_DE:fr"), ("LANG", "en_US.UTF-8"), ("LC_ALL", "POSIX")], &["POSIX"]);
+		case(&[("LANGUAGE", "de_DE:fr"), ("LANG", "C.UTF-8")], &["de_DE", "fr", "C.UTF-8"]);
+
+		// Duplicates
+		case(&[("LANGUAGE", "de:fr:de"), ("LANG", "fr")], &["de", "fr"]);
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_EnvLocales() {
+
+		fn case(vars: &[(&str, &str)], expected: [Option<&str>; 6]) {
+			let var = |name: &str| vars.iter().find(|(n, _)| *n == name).map(|(_, value)| value.to_string());
+			let env_locales = EnvLocales::from_vars(var);
+			let [messages, time, numeric, monetary, collate, ctype] = expected.map(|l| l.map(str::to_string));
+			assert_eq!(env_locales, EnvLocales { messages, time, numeric, monetary, collate, ctype });
+		}
+
+		// Nothing set
+		case(&[], [None; 6]);
+		case(&[("LANGUAGE", "de")], [None; 6]);
+
+		// LANG only
+		case(&[("LANG", "en_US.UTF-8")], [Some("en_US.UTF-8"); 6]);
+
+		// Category overrides LANG
+		case(
+			&[("LANG", "en_US.UTF-8"), ("LC_TIME", "de_DE.UTF-8"), ("LC_NUMERIC", "ru_RU.UTF-8"), ("LC_MONETARY", "ja_JP.UTF-8")],
+			[Some("en_US.UTF-8"), Some("de_DE.UTF-8"), Some("ru_RU.UTF-8"), Some("ja_JP.UTF-8"), Some("en_US.UTF-8"), Some("en_US.UTF-8")]
+		);
+		case(
+			&[("LC_MESSAGES", "fr_FR.UTF-8"), ("LC_COLLATE", "C"), ("LC_CTYPE", "C.UTF-8")],
+			[Some("fr_FR.UTF-8"), None, None, None, Some("C"), Some("C.UTF-8")]
+		);
+
+		// LC_ALL overrides everything
+		case(&[("LANG", "en_US.UTF-8"), ("LC_TIME", "de_DE.UTF-8"), ("LC_ALL", "C")], [Some("C"); 6]);
+
+		// Empty variables are ignored
+		case(&[("LANG", "en_US.UTF-8"), ("LC_TIME", ""), ("LC_ALL", "")], [Some("en_US.UTF-8"); 6]);
+	}
+
+	#[test]
+	fn test_parse_language_variable() {
+		assert_eq!(parse_language_variable(""), [] as [&str; 0]);
+		assert_eq!(parse_language_variable(":::"), [] as [&str; 0]);
+		assert_eq!(parse_language_variable("de"), ["de"]);
+		assert_eq!(parse_language_variable("de_DE:fr:en"), ["de_DE", "fr", "en"]);
+		assert_eq!(parse_language_variable(":de_DE::fr:en:"), ["de_DE", "fr", "en"]);
+		assert_eq!(parse_language_variable("sr_RS@latin:ru_RU.UTF-8"), ["sr_RS@latin", "ru_RU.UTF-8"]);
+	}
+
+	#[test]
+	fn test_user_locales_from_env() {
+		let locales = user_locales_from_env();
+		assert!(locales.iter().all(|l| !l.is_empty()));
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_to_bcp47() {
+		assert_eq!(to_bcp47("en").as_deref(), Some("en"));
+		assert_eq!(to_bcp47("en_US.UTF-8").as_deref(), Some("en-US"));
+		assert_eq!(to_bcp47("de_DE@euro").as_deref(), Some("de-DE"));
+		assert_eq!(to_bcp47("sr_RS.UTF-8@latin").as_deref(), Some("sr-Latn-RS"));
+		assert_eq!(to_bcp47("uz@cyrillic").as_deref(), Some("uz-Cyrl"));
+		assert_eq!(to_bcp47("ca_ES.UTF-8@valencia").as_deref(), Some("ca-ES-valencia"));
+		assert_eq!(to_bcp47("C"), None);
+		assert_eq!(to_bcp47("C.UTF-8"), None);
+		assert_eq!(to_bcp47("POSIX"), None);
+		assert_eq!(to_bcp47(""), None);
+	}
+
 	#[test]
 	#[allow(non_snake_case)]
 	fn test_PosixLocale() {
@@ -307,6 +1815,7 @@ mod tests {
 			assert_eq!(posix_locale.codeset(), parts.2);
 			assert_eq!(posix_locale.modifier(), parts.3);
 			assert_eq!(posix_locale.into_inner(), locale);
+			assert_eq!(PosixLocale::parse_const(locale), PosixLocale::parse(locale));
 		}
 
 		// Language only
@@ -401,4 +1910,52 @@ mod tests {
 		case("\0\x01\x02\x03", ("\0\x01\x02\x03", None, None, None));
 		case("\x03\x02\x01", ("\x03\x02\x01", None, None, None));
 	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_PosixLocale_from_str() {
+
+		fn case(locale: &str, expected: Result<(), ParseError>) {
+			assert_eq!(locale.parse::<PosixLocale<String>>().map(PosixLocale::into_inner), expected.map(|_| locale.to_string()));
+			assert_eq!(PosixLocale::try_parse_const(locale).map(PosixLocale::into_inner), expected.map(|_| locale));
+		}
+
+		// Valid locales
+		case("en", Ok(()));
+		case("C", Ok(()));
+		case("en_US.UTF-8", Ok(()));
+		case("sr_RS.ISO-8859-5@latin", Ok(()));
+		case("ca_ES@valencia", Ok(()));
+
+		// Invalid locales
+		case("", Err(ParseError::InvalidLanguage));
+		case("_US", Err(ParseError::InvalidLanguage));
+		case("en-US", Err(ParseError::InvalidLanguage));
+		case("en_", Err(ParseError::InvalidTerritory));
+		case("en_U S", Err(ParseError::InvalidTerritory));
+		case("en_US.", Err(ParseError::InvalidCodeset));
+		case("en_US.UTF-8@", Err(ParseError::InvalidModifier));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_PosixLocale_serde() {
+		let locale = PosixLocale::parse("en_US.UTF-8".to_string());
+		assert_eq!(serde_json::to_string(&locale).unwrap(), r#""en_US.UTF-8""#);
+		assert_eq!(serde_json::to_string(&PosixLocale::parse("ru_RU")).unwrap(), r#""ru_RU""#);
+		assert_eq!(serde_json::from_str::<PosixLocale<String>>(r#""en_US.UTF-8""#).unwrap(), locale);
+		assert!(serde_json::from_str::<PosixLocale<String>>(r#""en_US.""#).is_err());
+		assert!(serde_json::from_str::<PosixLocale<String>>("42").is_err());
+	}
+
+	#[cfg(feature = "schemars")]
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_PosixLocale_schema() {
+		let schema = serde_json::to_value(schemars::schema_for!(PosixLocale<String>)).unwrap();
+		let pattern = schema["pattern"].as_str().unwrap();
+		assert!(pattern.starts_with("^[A-Za-z0-9]+(_"));
+		assert_eq!(schema["type"], "string");
+	}
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching with the temporary parsed locales allocated in a [`bumpalo`](https://docs.rs/bumpalo) arena.
+
+use bumpalo::Bump;
+
+use crate::engine;
+use crate::matching::LocaleMatch;
+
+use super::Matcher;
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_locale`](Self::best_matching_locale),
+	/// allocating the parsed available locales in the arena instead of the heap.
+	///
+	/// An arena reused for many calls and [reset](Bump::reset) between them makes matching free of
+	/// heap allocations, e.g. in high-throughput servers.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use bumpalo::Bump;
+	/// use locale_match::posix::Matcher;
+	///
+	///
+	/// let matcher = Matcher::new();
+	/// let available_locales = ["en_US.UTF-8", "ru_BY.UTF-8"];
+	///
+	/// let mut bump = Bump::new();
+	/// for (user_locales, expected) in [(["ru_RU"], Some("ru_BY.UTF-8")), (["en_GB"], Some("en_US.UTF-8"))] {
+	///     assert_eq!(matcher.best_matching_locale_in(&bump, available_locales, user_locales), expected);
+	///     bump.reset();
+	/// }
+	/// ```
+	pub fn best_matching_locale_in<T1, T2>(&self, bump: &Bump, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		self.best_matching_locale_with_confidence_in(bump, available_locales, user_locales).map(|m| m.locale)
+	}
+
+	/// Finds the best matching locale like
+	/// [`best_matching_locale_with_confidence`](Self::best_matching_locale_with_confidence),
+	/// allocating the parsed available locales in the arena instead of the heap.
+	pub fn best_matching_locale_with_confidence_in<T1, T2>(&self, bump: &Bump, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locale_with_confidence_in(self, bump, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_Matcher_best_matching_locale_in() {
+		fn case(matcher: &Matcher, bump: &Bump, available_locales: &[&str], user_locales: &[&str]) {
+			assert_eq!(
+				matcher.best_matching_locale_with_confidence_in(bump, available_locales, user_locales),
+				matcher.best_matching_locale_with_confidence(available_locales, user_locales),
+			);
+		}
+
+		let matcher = Matcher::new();
+		let mut bump = Bump::new();
+
+		case(&matcher, &bump, &["en_US.UTF-8", "ru_BY.UTF-8", "ru"], &["ru_RU", "en"]);
+		case(&matcher, &bump, &["en", "pt_BR", "pt_PT"], &["PT_pt"]);
+		case(&matcher, &bump, &["en_US"], &["fr"]);
+		case(&matcher, &bump, &[], &["en"]);
+		bump.reset();
+		case(&Matcher::new().with_mismatch_penalty(1.0), &bump, &["fr_FR.UTF-8", "fr.UTF-8"], &["fr_CA.UTF-8"]);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of locales given as bytes.
+
+use crate::engine;
+
+use super::Matcher;
+
+/// Finds the best matching locale from a list of available locales based on a list of user
+/// locales, like [`best_matching_locale`](super::best_matching_locale), for locales given as bytes,
+/// e.g. from network buffers or FFI.
+///
+/// POSIX locales are ASCII, so the bytes do not have to be validated as UTF-8 beforehand. Bytes
+/// that are not valid UTF-8 are replaced with `U+FFFD`, so the ASCII parts of such a locale are
+/// still matched.  
+/// The matched available locale is returned as it was passed in.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::best_matching_locale_from_bytes;
+///
+///
+/// let available_locales = [b"en_US.UTF-8".as_slice(), b"ru_RU.UTF-8"];
+/// let user_locales = [b"ru_BY.UTF-8", b"en_GB.UTF-8"];
+///
+/// let best_match = best_matching_locale_from_bytes(available_locales, user_locales);
+///
+/// assert_eq!(best_match, Some(b"ru_RU.UTF-8".as_slice()));
+/// ```
+pub fn best_matching_locale_from_bytes<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<[u8]>,
+	T2: AsRef<[u8]>
+{
+	Matcher::new().best_matching_locale_from_bytes(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_locale_from_bytes`], using the
+	/// configuration of the matcher.
+	pub fn best_matching_locale_from_bytes<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: AsRef<[u8]>,
+		T2: AsRef<[u8]>
+	{
+		engine::best_matching_locale_from_bytes(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_locale_from_bytes() {
+		fn case(available_locales: &[&[u8]], user_locales: &[&[u8]], expected: Option<&[u8]>) {
+			assert_eq!(best_matching_locale_from_bytes(available_locales, user_locales).copied(), expected);
+		}
+
+		// ASCII
+		case(&[b"en_US.UTF-8", b"ru_RU.UTF-8"], &[b"ru_BY", b"en"], Some(b"ru_RU.UTF-8"));
+		case(&[b"en_US.UTF-8", b"ru_RU.UTF-8"], &[b"fr_FR"], None);
+		case(&[], &[b"fr_FR"], None);
+
+		// Not valid UTF-8
+		case(&[b"de_DE.ISO-8859-1@\xFF", b"en_US"], &[b"de_AT", b"en"], Some(b"de_DE.ISO-8859-1@\xFF"));
+		case(&[b"de_DE", b"de_AT"], &[b"de_AT.\xFF", b"en"], Some(b"de_AT"));
+
+		// Owned bytes
+		assert_eq!(best_matching_locale_from_bytes([b"en_US".to_vec()], [b"en".to_vec()]), Some(b"en_US".to_vec()));
+	}
+}
\ No newline at end of file
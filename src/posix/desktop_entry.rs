@@ -0,0 +1,177 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Selection of localized keys in freedesktop.org desktop entries.
+
+use super::PosixLocale;
+
+/// Finds the locale of a localized desktop entry key that matches the user locales, following the
+/// [Desktop Entry Specification](https://specifications.freedesktop.org/desktop-entry-spec/latest/localized-keys.html).
+///
+/// Unlike [`best_matching_locale`](super::best_matching_locale), the specification does not score
+/// partial matches. For a user locale `lang_COUNTRY.ENCODING@MODIFIER`, the available locales are
+/// searched for the following forms, in order:
+/// 1. `lang_COUNTRY@MODIFIER`
+/// 2. `lang_COUNTRY`
+/// 3. `lang@MODIFIER`
+/// 4. `lang`
+///
+/// Forms that need a part missing from the user locale are skipped, and the encoding is ignored.
+/// Thus, `sr_RS@latin` is never selected for the user locale `sr_RS`, and `de_AT` is never
+/// selected for the user locale `de`.  
+/// The user locales are tried in order, e.g. those from [`user_locales_from_env`](super::user_locales_from_env).
+/// Parts of the locales are compared case-insensitively.  
+/// Returns [`None`] if none of the user locales has a match, in which case the unlocalized key
+/// should be used.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::best_matching_desktop_entry_locale;
+///
+///
+/// let available_locales = ["sr", "sr@latin", "sr_RS", "de_AT"];
+///
+/// assert_eq!(best_matching_desktop_entry_locale(available_locales, ["sr_RS.UTF-8@latin"]), Some("sr_RS"));
+/// assert_eq!(best_matching_desktop_entry_locale(available_locales, ["sr_ME.UTF-8@latin"]), Some("sr@latin"));
+/// assert_eq!(best_matching_desktop_entry_locale(available_locales, ["de_DE.UTF-8"]), None);
+/// ```
+pub fn best_matching_desktop_entry_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>,
+{
+	let available_locales = available_locales.into_iter()
+		.map(PosixLocale::parse)
+		.collect::<Vec<PosixLocale<T1>>>();
+	let index = user_locales.into_iter().find_map(|user_locale| {
+		let user_locale = PosixLocale::parse(user_locale);
+		let (territory, modifier) = (user_locale.territory(), user_locale.modifier());
+		let index = [(territory, modifier), (territory, None), (None, modifier), (None, None)]
+			.into_iter()
+			.find_map(|(t, m)| available_locales.iter().position(|aval| {
+				aval.language().eq_ignore_ascii_case(user_locale.language())
+					&& eq_ignore_ascii_case(aval.territory(), t)
+					&& eq_ignore_ascii_case(aval.modifier(), m)
+			}));
+		index
+	})?;
+	available_locales.into_iter().nth(index).map(PosixLocale::into_inner)
+}
+
+/// Finds the localized variant of a desktop entry key that matches the user locales.
+///
+/// The keys are the keys of a desktop entry group, e.g. `Name`, `Name[de]` and `Comment[de]`.
+/// The locales of the variants of `key` are matched with [`best_matching_desktop_entry_locale`].
+/// If none of them matches, the unlocalized `key` itself is returned, if present.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::best_localized_key;
+///
+///
+/// let keys = ["Name", "Name[de]", "Name[sr@latin]", "Comment", "Comment[fr]"];
+///
+/// assert_eq!(best_localized_key(keys, "Name", ["de_AT.UTF-8"]), Some("Name[de]"));
+/// assert_eq!(best_localized_key(keys, "Comment", ["de_AT.UTF-8"]), Some("Comment"));
+/// assert_eq!(best_localized_key(keys, "GenericName", ["de_AT.UTF-8"]), None);
+/// ```
+pub fn best_localized_key<T1, T2>(keys: impl IntoIterator<Item = T1>, key: &str, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>,
+{
+	let mut unlocalized = None;
+	let mut localized = Vec::new();
+	for k in keys {
+		if k.as_ref() == key {
+			unlocalized.get_or_insert(k);
+		} else if k.as_ref().strip_prefix(key).and_then(|rest| rest.strip_prefix('[')?.strip_suffix(']')).is_some() {
+			localized.push(LocalizedKey(k, key.len() + 1));
+		}
+	}
+	best_matching_desktop_entry_locale(localized, user_locales)
+		.map(|LocalizedKey(k, _)| k)
+		.or(unlocalized)
+}
+
+/// A localized key, which yields the locale in its brackets as a string.
+struct LocalizedKey<T: AsRef<str>>(T, usize);
+
+impl<T: AsRef<str>> AsRef<str> for LocalizedKey<T> {
+	fn as_ref(&self) -> &str {
+		let key = self.0.as_ref();
+		&key[self.1..key.len() - 1]
+	}
+}
+
+fn eq_ignore_ascii_case(a: Option<&str>, b: Option<&str>) -> bool {
+	match (a, b) {
+		(Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+		(a, b) => a.is_none() && b.is_none(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_desktop_entry_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(best_matching_desktop_entry_locale(available_locales.iter().copied(), user_locales), expected);
+		}
+
+		// Precedence
+		case(&["sr", "sr@latin", "sr_RS", "sr_RS@latin"], &["sr_RS.UTF-8@latin"], Some("sr_RS@latin"));
+		case(&["sr", "sr@latin", "sr_RS"], &["sr_RS.UTF-8@latin"], Some("sr_RS"));
+		case(&["sr", "sr@latin"], &["sr_RS.UTF-8@latin"], Some("sr@latin"));
+		case(&["sr"], &["sr_RS.UTF-8@latin"], Some("sr"));
+
+		// Parts missing from the user locale
+		case(&["sr_RS@latin", "sr@latin", "sr"], &["sr_RS"], Some("sr"));
+		case(&["sr_RS@latin", "sr_RS", "sr@latin"], &["sr@latin"], Some("sr@latin"));
+		case(&["de_AT", "de_DE"], &["de"], None);
+
+		// Case and encoding
+		case(&["pt_br"], &["pt_BR.UTF-8"], Some("pt_br"));
+		case(&["de_DE.UTF-8"], &["de_DE.ISO-8859-1"], Some("de_DE.UTF-8"));
+
+		// Multiple user locales
+		case(&["fr", "de"], &["es_ES", "de_DE", "fr_FR"], Some("de"));
+		case(&["fr", "de"], &["es_ES", "it_IT"], None);
+		case(&["fr", "de"], &[], None);
+	}
+
+	#[test]
+	fn test_best_localized_key() {
+		fn case(keys: &[&str], key: &str, user_locales: &[&str], expected: Option<&str>) {
+			assert_eq!(best_localized_key(keys.iter().copied(), key, user_locales), expected);
+		}
+
+		let keys = ["Name", "Name[de]", "Name[de_AT]", "Name[sr@latin]", "GenericName", "GenericName[de]", "Comment[fr]"];
+		case(&keys, "Name", &["de_AT.UTF-8"], Some("Name[de_AT]"));
+		case(&keys, "Name", &["de_CH.UTF-8"], Some("Name[de]"));
+		case(&keys, "Name", &["sr_RS@latin"], Some("Name[sr@latin]"));
+		case(&keys, "GenericName", &["de_AT.UTF-8"], Some("GenericName[de]"));
+
+		// Unlocalized fallback
+		case(&keys, "Name", &["ja_JP.UTF-8"], Some("Name"));
+		case(&keys, "Comment", &["ja_JP.UTF-8"], None);
+		case(&keys, "Icon", &["de_DE.UTF-8"], None);
+	}
+}
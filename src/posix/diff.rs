@@ -0,0 +1,90 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Comparison of the parts of two POSIX locales, e.g. for explaining how a served locale differs
+//! from the requested one.
+
+use crate::engine;
+use crate::matching::LocaleDiff;
+
+use super::{normalize_codeset, PosixLocale, Subtag, SUBTAGS};
+
+/// Compares the parts of two POSIX locales, and returns which of them are equal, differ, or are
+/// present in only one of the locales.
+///
+/// The parts are compared ignoring ASCII case, and codesets also ignoring punctuation, so `UTF-8`
+/// equals `utf8`. The parts absent in both locales are omitted.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{diff, Subtag};
+/// use locale_match::matching::Difference;
+///
+///
+/// let diff = diff("de_DE.UTF-8@euro", "de_AT.utf8");
+///
+/// assert_eq!(diff.language, Difference::Equal("de"));
+/// assert_eq!(diff.subtags, [
+///     (Subtag::Territory, Difference::Different("DE", "AT")),
+///     (Subtag::Codeset, Difference::Equal("UTF-8")),
+///     (Subtag::Modifier, Difference::OnlyFirst("euro")),
+/// ]);
+/// ```
+pub fn diff<'a>(a: &'a str, b: &'a str) -> LocaleDiff<'a, Subtag> {
+	let (a, b) = (PosixLocale::parse(a), PosixLocale::parse(b));
+	let language = |locale: &PosixLocale<&'a str>| &locale.locale[..locale.language().len()];
+	engine::diff(&a, &b, language, &SUBTAGS, PosixLocale::part, |subtag, a, b| match subtag {
+		Subtag::Codeset => normalize_codeset(a) == normalize_codeset(b),
+		_ => a.eq_ignore_ascii_case(b),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::Difference;
+
+	#[test]
+	fn test_diff() {
+		fn case(a: &str, b: &str, language: Difference, subtags: &[(Subtag, Difference)]) {
+			let diff = diff(a, b);
+			assert_eq!((diff.language, diff.subtags.as_slice()), (language, subtags), "{a} and {b}");
+		}
+
+		// Equal locales
+		case("en", "en", Difference::Equal("en"), &[]);
+		case("en_US.UTF-8", "EN_us.utf8", Difference::Equal("en"), &[
+			(Subtag::Territory, Difference::Equal("US")),
+			(Subtag::Codeset, Difference::Equal("UTF-8")),
+		]);
+
+		// Different parts
+		case("en_US", "en_GB", Difference::Equal("en"), &[(Subtag::Territory, Difference::Different("US", "GB"))]);
+		case("en", "ru", Difference::Different("en", "ru"), &[]);
+		case("sr_RS@latin", "sr_RS@cyrillic", Difference::Equal("sr"), &[
+			(Subtag::Territory, Difference::Equal("RS")),
+			(Subtag::Modifier, Difference::Different("latin", "cyrillic")),
+		]);
+
+		// Parts present in one of the locales
+		case("en_US", "en", Difference::Equal("en"), &[(Subtag::Territory, Difference::OnlyFirst("US"))]);
+		case("de", "de.ISO-8859-1@euro", Difference::Equal("de"), &[
+			(Subtag::Codeset, Difference::OnlySecond("ISO-8859-1")),
+			(Subtag::Modifier, Difference::OnlySecond("euro")),
+		]);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Explanation of the matching of POSIX locales.
+
+use crate::engine;
+use crate::matching::Explanation;
+
+use super::{Matcher, Subtag};
+
+impl Matcher {
+	/// Matches the locales like [`best_matching_locale_with_confidence`](Self::best_matching_locale_with_confidence)
+	/// and explains the match: which user locale drove it, which available locales were considered
+	/// with the scores of their parts, and which locales are malformed.
+	///
+	/// Unlike BCP 47 locales, malformed POSIX locales, e.g. `en US`, are not ignored, but still
+	/// matched by their parts, so they are only reported.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::posix::{Matcher, Subtag};
+	///
+	///
+	/// let explanation = Matcher::new().explain(["en_US", "pt_PT", "pt_BR", "pt BR"], ["fr", "pt_BR.UTF-8"]);
+	///
+	/// let best_match = explanation.best_match.unwrap();
+	/// assert_eq!((best_match.locale, best_match.user_index), ("pt_BR", 1));
+	/// assert_eq!(explanation.candidates.len(), 2);
+	/// assert_eq!(explanation.candidates[0].subtags[0].subtag, Subtag::Territory);
+	/// assert!(explanation.candidates[0].score < explanation.candidates[1].score);
+	/// assert_eq!(explanation.malformed_available_locales, [3]);
+	/// ```
+	pub fn explain<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Explanation<T1, Subtag>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::explain(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::matching::{Comparison, TieBreak};
+
+	#[test]
+	fn test_explain() {
+		fn case(matcher: Matcher, available_locales: &[&str], user_locales: &[&str]) {
+			let explanation = matcher.explain(available_locales, user_locales);
+			let best_match = matcher.best_matching_locale_with_confidence(available_locales, user_locales);
+			assert_eq!(explanation.best_match, best_match, "{available_locales:?} and {user_locales:?}");
+			for candidate in &explanation.candidates {
+				let sum = candidate.subtags.iter().map(|s| s.score).sum::<f64>();
+				assert!((candidate.score - sum).abs() < 1e-9);
+			}
+		}
+
+		// Same matches as the matcher
+		case(Matcher::new(), &["en_US.UTF-8", "ru_RU.UTF-8"], &["ru", "en"]);
+		case(Matcher::new(), &["en_US", "en_GB", "ru_UA", "fr_FR", "it"], &["ru_RU", "ru", "en_US", "en"]);
+		case(Matcher::new(), &["sr_RS", "sr_RS@latin"], &["sr_RS.UTF-8@latin"]);
+		case(Matcher::new(), &["en", "en_US"], &["en_GB"]);
+		case(Matcher::new().with_tie_break(TieBreak::MostSpecific), &["en", "en_US"], &["en_GB"]);
+		case(Matcher::new().with_mismatch_penalty(1.0), &["en_GB", "en"], &["en_US"]);
+		case(Matcher::new().with_codeset_preference(["UTF-8"]), &["en_US.ISO-8859-1", "en_US.UTF-8"], &["en_US"]);
+		case(Matcher::new().with_modifier_preference(["latin", ""]), &["sr_RS", "sr_RS@latin"], &["sr_RS"]);
+		case(Matcher::new().with_modifier_preference(["latin", ""]), &["sr_RS", "sr_RS@latin"], &["sr_RS@cyrillic"]);
+		case(Matcher::new(), &["en_US", "ru_RU"], &["fr", "de"]);
+		case(Matcher::new(), &[], &["en"]);
+	}
+
+	#[test]
+	fn test_explain_details() {
+		let explanation = Matcher::new().explain(["de_DE", "en_GB.UTF-8", "EN"], ["fr", "en_US.UTF-8", "de_DE"]);
+
+		assert_eq!(explanation.best_match.map(|m| (m.locale, m.user_index)), Some(("en_GB.UTF-8", 1)));
+		assert!(explanation.malformed_available_locales.is_empty());
+		assert_eq!(explanation.candidates.iter().map(|c| (c.available_index, c.user_index)).collect::<Vec<_>>(), [(1, 1), (2, 1)]);
+
+		let codeset = explanation.candidates[0].subtags[1];
+		assert_eq!((codeset.subtag, codeset.comparison, codeset.weight, codeset.score), (Subtag::Codeset, Comparison::Present(1.0), 2.0, 2.0));
+		let territory = explanation.candidates[1].subtags[0];
+		assert_eq!((territory.subtag, territory.comparison), (Subtag::Territory, Comparison::AvailableAbsent));
+
+		let explanation = Matcher::new().explain(["en US"], ["", "en@"]);
+		assert_eq!(explanation.malformed_available_locales, [0]);
+		assert_eq!(explanation.malformed_user_locales, [0, 1]);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,120 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Discovery of available locales from gettext message catalogs.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Returns the locales that have a gettext message catalog for the domain in the locale directory.
+///
+/// The locale directory is expected to have the standard gettext layout
+/// `<locale_dir>/<locale>/LC_MESSAGES/<domain>.mo`, e.g. `locale/pt_BR/LC_MESSAGES/myapp.mo`.
+/// Locales whose directory names are not valid Unicode are skipped.  
+/// The result is sorted, so it is ready to be passed to
+/// [`best_matching_locale`](super::best_matching_locale) as the available locales.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the locale directory cannot be read, e.g. if it does not exist.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_locale, gettext_locales, user_locales_from_env};
+///
+///
+/// let available_locales = gettext_locales("/usr/share/locale", "coreutils").unwrap_or_default();
+///
+/// let best_match = best_matching_locale(&available_locales, user_locales_from_env());
+/// ```
+pub fn gettext_locales(locale_dir: impl AsRef<Path>, domain: &str) -> Result<Vec<String>, Error> {
+	let mut locales = fs::read_dir(locale_dir)?
+		.filter_map(Result::ok)
+		.filter(|entry| catalog_path(&entry.path(), domain).is_file())
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.collect::<Vec<String>>();
+	locales.sort();
+	Ok(locales)
+}
+
+/// Finds the gettext message catalog for the domain that best matches the user locales.
+///
+/// The available locales are discovered with [`gettext_locales`] and matched with
+/// [`best_matching_locale`](super::best_matching_locale).  
+/// Returns the matched locale together with the path of its `.mo` file, or [`None`] if no
+/// catalog matches any of the user locales.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if the locale directory cannot be read, like [`gettext_locales`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_gettext_catalog, user_locales_from_env};
+///
+///
+/// if let Ok(Some((locale, path))) = best_matching_gettext_catalog("/usr/share/locale", "coreutils", user_locales_from_env()) {
+///     println!("Loading {} messages from {}", locale, path.display());
+/// }
+/// ```
+pub fn best_matching_gettext_catalog<T: AsRef<str>>(locale_dir: impl AsRef<Path>, domain: &str, user_locales: impl IntoIterator<Item = T>) -> Result<Option<(String, PathBuf)>, Error> {
+	let locale_dir = locale_dir.as_ref();
+	let best_match = super::best_matching_locale(gettext_locales(locale_dir, domain)?, user_locales);
+	Ok(best_match.map(|locale| {
+		let path = catalog_path(&locale_dir.join(&locale), domain);
+		(locale, path)
+	}))
+}
+
+/// Returns the path of the message catalog for the domain in the directory of a locale.
+fn catalog_path(locale_dir: &Path, domain: &str) -> PathBuf {
+	locale_dir.join("LC_MESSAGES").join(format!("{domain}.mo"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_gettext_catalogs() {
+		let dir = std::env::temp_dir().join(format!("locale-match-gettext-{}", std::process::id()));
+		for (locale, domain) in [("de", "app"), ("pt_BR", "app"), ("pt_PT", "other"), ("sr@latin", "app")] {
+			fs::create_dir_all(dir.join(locale).join("LC_MESSAGES")).unwrap();
+			fs::write(dir.join(locale).join("LC_MESSAGES").join(format!("{domain}.mo")), []).unwrap();
+		}
+		fs::create_dir_all(dir.join("fr").join("LC_MESSAGES")).unwrap();
+
+		let locales = gettext_locales(&dir, "app").unwrap();
+		let catalog = |user_locales: &[&str]| best_matching_gettext_catalog(&dir, "app", user_locales).unwrap();
+		let pt = catalog(&["pt_PT.UTF-8"]);
+		let de = catalog(&["fr_FR.UTF-8", "de_AT.UTF-8"]);
+		let fr = catalog(&["fr_FR.UTF-8"]);
+		let missing = gettext_locales(dir.join("missing"), "app");
+		let missing_catalog = best_matching_gettext_catalog(dir.join("missing"), "app", ["de"]);
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert_eq!(locales, ["de", "pt_BR", "sr@latin"]);
+		assert_eq!(pt, Some(("pt_BR".to_string(), dir.join("pt_BR").join("LC_MESSAGES").join("app.mo"))));
+		assert_eq!(de, Some(("de".to_string(), dir.join("de").join("LC_MESSAGES").join("app.mo"))));
+		assert_eq!(fr, None);
+		assert!(matches!(missing, Err(Error::Io(_))));
+		assert!(matches!(missing_catalog, Err(Error::Io(_))));
+	}
+}
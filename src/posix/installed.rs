@@ -0,0 +1,295 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Enumeration of the locales installed on the system.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Error;
+
+/// The locale archive of the GNU C Library, where `localedef` stores compiled locales by default.
+const LOCALE_ARCHIVE: &str = "/usr/lib/locale/locale-archive";
+
+/// The directories with compiled locales: the GNU C Library stores locales that are not in the
+/// archive in `/usr/lib/locale`, macOS and the BSDs store them in `/usr/share/locale`.
+const LOCALE_DIRS: &[&str] = &["/usr/lib/locale", "/usr/share/locale"];
+
+/// The magic number of the GNU C Library locale archive.
+const ARCHIVE_MAGIC: u32 = 0xde020109;
+
+/// Returns the locales installed on the system, similar to the output of `locale -a`.
+///
+/// The locales are collected from the locale archive of the GNU C Library
+/// (`/usr/lib/locale/locale-archive`) and from the locale directories (`/usr/lib/locale` and
+/// `/usr/share/locale`), where a subdirectory is considered a locale if it contains an `LC_CTYPE`
+/// file.  
+/// The `C` and `POSIX` locales are always included, since every POSIX system supports them.  
+/// The result is sorted and contains no duplicates, so it is ready to be passed to
+/// [`best_matching_locale`](super::best_matching_locale) as the available locales.
+///
+/// # Errors
+///
+/// Returns [`Error::Unsupported`] on platforms other than Unix, and [`Error::Io`] if the locale
+/// archive or a locale directory exists but cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_matching_locale, installed_locales, user_locales_from_env};
+///
+///
+/// let installed_locales = installed_locales().unwrap_or_default();
+///
+/// let best_match = best_matching_locale(&installed_locales, user_locales_from_env())
+///     .map_or("C", |l| l.as_str());
+/// ```
+pub fn installed_locales() -> Result<Vec<String>, Error> {
+	if !cfg!(unix) {
+		return Err(Error::Unsupported("enumerating the installed locales"));
+	}
+	installed_locales_in(Path::new(LOCALE_ARCHIVE), LOCALE_DIRS.iter().map(Path::new))
+}
+
+/// Finds the installed locale that best matches the preferred locales.
+///
+/// The preferred locales are matched against [`installed_locales`] with
+/// [`best_matching_locale`](super::best_matching_locale). Codesets are compared the way the GNU C
+/// Library normalizes them, so `en_US.UTF-8` matches the installed `en_US.utf8`.  
+/// The returned locale is spelled as it is installed, so it can be exported as `LANG` or `LC_ALL`
+/// for a spawned process.  
+/// Returns [`None`] if no installed locale matches any of the preferred locales.
+///
+/// # Errors
+///
+/// Returns an error if the installed locales cannot be enumerated, like [`installed_locales`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::{best_installed_locale, user_locales_from_env};
+///
+///
+/// let lang = best_installed_locale(user_locales_from_env()).ok().flatten().unwrap_or_else(|| "C".to_string());
+///
+/// let status = std::process::Command::new("date")
+///     .env("LANG", lang)
+///     .status();
+/// ```
+pub fn best_installed_locale<T: AsRef<str>>(preferred: impl IntoIterator<Item = T>) -> Result<Option<String>, Error> {
+	Ok(best_locale_among(installed_locales()?, preferred))
+}
+
+/// Finds the locale from `installed` that best matches the preferred locales, comparing
+/// normalized codesets.
+fn best_locale_among<T: AsRef<str>>(installed: Vec<String>, preferred: impl IntoIterator<Item = T>) -> Option<String> {
+	let normalized = installed.iter()
+		.map(|locale| normalize_codeset(locale))
+		.collect::<Vec<String>>();
+	let preferred = preferred.into_iter()
+		.map(|locale| normalize_codeset(locale.as_ref()))
+		.collect::<Vec<String>>();
+	super::best_matching_locale_with_confidence(&normalized, &preferred)
+		.and_then(|m| installed.into_iter().nth(m.available_index))
+}
+
+/// Normalizes the codeset of a locale the way the GNU C Library does: only letters and digits are
+/// kept, letters are lowercased, and a codeset of only digits is prefixed with `iso`
+/// (e.g. `UTF-8` becomes `utf8`, `8859-1` becomes `iso88591`).
+fn normalize_codeset(locale: &str) -> String {
+	let Some(dot) = locale.find('.') else {
+		return locale.to_string();
+	};
+	let (codeset, modifier) = match locale[dot + 1..].find('@') {
+		Some(at) => locale[dot + 1..].split_at(at),
+		None => (&locale[dot + 1..], ""),
+	};
+	let mut normalized = codeset.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.map(|c| c.to_ascii_lowercase())
+		.collect::<String>();
+	if normalized.chars().all(|c| c.is_ascii_digit()) {
+		normalized.insert_str(0, "iso");
+	}
+	format!("{}.{normalized}{modifier}", &locale[..dot])
+}
+
+/// Collects the locales from the given locale archive and locale directories, skipping the ones
+/// that do not exist.
+fn installed_locales_in<'a>(archive: &Path, dirs: impl IntoIterator<Item = &'a Path>) -> Result<Vec<String>, Error> {
+	let mut locales = vec!["C".to_string(), "POSIX".to_string()];
+	if let Some(data) = existing(fs::read(archive))? {
+		locales.extend(parse_locale_archive(&data));
+	}
+	for dir in dirs {
+		let Some(entries) = existing(fs::read_dir(dir))? else {
+			continue;
+		};
+		locales.extend(entries
+			.filter_map(Result::ok)
+			.filter(|entry| entry.path().join("LC_CTYPE").is_file())
+			.filter_map(|entry| entry.file_name().into_string().ok()));
+	}
+	locales.sort();
+	locales.dedup();
+	Ok(locales)
+}
+
+/// Turns the result of reading a file or a directory that does not exist into [`None`].
+fn existing<T>(result: io::Result<T>) -> Result<Option<T>, Error> {
+	match result {
+		Ok(value) => Ok(Some(value)),
+		Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(error) => Err(error.into()),
+	}
+}
+
+/// Reads the names of the locales stored in a GNU C Library locale archive.
+///
+/// The archive starts with a header of native-endian 32-bit fields: the magic number, the serial
+/// number, and the offset, the number of used entries and the size of the name hash table.
+/// Each entry of the table consists of the hash value, the offset of the name and the offset of
+/// the locale record; unused entries have zero offsets.  
+/// Returns an empty list if the data is not a valid archive.
+fn parse_locale_archive(data: &[u8]) -> Vec<String> {
+	let read_u32 = |offset: usize| data.get(offset..offset.checked_add(4)?)
+		.map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()));
+	let (Some(ARCHIVE_MAGIC), Some(namehash_offset), Some(namehash_size)) = (read_u32(0), read_u32(8), read_u32(16)) else {
+		return Vec::new();
+	};
+
+	let mut locales = Vec::new();
+	for i in 0..namehash_size as usize {
+		let entry = namehash_offset as usize + i * 12;
+		let (Some(name_offset), Some(locrec_offset)) = (read_u32(entry + 4), read_u32(entry + 8)) else {
+			break;
+		};
+		if name_offset == 0 || locrec_offset == 0 {
+			continue;
+		}
+		let Some(name) = data.get(name_offset as usize..)
+			.and_then(|rest| rest.split(|&b| b == 0).next())
+			.and_then(|name| std::str::from_utf8(name).ok())
+			.filter(|name| !name.is_empty())
+		else {
+			continue;
+		};
+		locales.push(name.to_string());
+	}
+	locales
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn archive(names: &[&str]) -> Vec<u8> {
+		let namehash_offset = 36u32;
+		let namehash_size = names.len() as u32 + 1;
+		let mut strings_offset = namehash_offset + namehash_size * 12;
+		let mut data = Vec::new();
+		for field in [ARCHIVE_MAGIC, 0, namehash_offset, names.len() as u32, namehash_size, 0, 0, 0, 0] {
+			data.extend(field.to_ne_bytes());
+		}
+		for name in names {
+			data.extend([0, strings_offset, 1].iter().flat_map(|field: &u32| field.to_ne_bytes()));
+			strings_offset += name.len() as u32 + 1;
+		}
+		data.extend([0u8; 12]);
+		for name in names {
+			data.extend(name.as_bytes());
+			data.push(0);
+		}
+		data
+	}
+
+	#[test]
+	fn test_parse_locale_archive() {
+		assert_eq!(parse_locale_archive(&archive(&["en_US.utf8", "ru_RU.utf8"])), ["en_US.utf8", "ru_RU.utf8"]);
+		assert_eq!(parse_locale_archive(&archive(&[])), Vec::<String>::new());
+
+		// Invalid archives
+		assert_eq!(parse_locale_archive(&[]), Vec::<String>::new());
+		assert_eq!(parse_locale_archive(&[0; 64]), Vec::<String>::new());
+		assert_eq!(parse_locale_archive(&archive(&["en_US.utf8"])[..40]), Vec::<String>::new());
+	}
+
+	#[test]
+	fn test_normalize_codeset() {
+		fn case(locale: &str, expected: &str) {
+			assert_eq!(normalize_codeset(locale), expected);
+		}
+
+		case("en_US.UTF-8", "en_US.utf8");
+		case("en_US.utf8", "en_US.utf8");
+		case("de_DE.ISO-8859-1", "de_DE.iso88591");
+		case("de_DE.8859-1", "de_DE.iso88591");
+		case("sr_RS.UTF-8@latin", "sr_RS.utf8@latin");
+
+		// No codeset
+		case("en_US", "en_US");
+		case("sr_RS@latin", "sr_RS@latin");
+		case("C", "C");
+	}
+
+	#[test]
+	fn test_best_locale_among() {
+		fn case(preferred: &[&str], expected: Option<&str>) {
+			let installed = ["C", "C.utf8", "POSIX", "de_DE.iso88591", "en_US.iso88591", "en_US.utf8", "ru_RU.utf8"]
+				.map(String::from)
+				.to_vec();
+			assert_eq!(best_locale_among(installed, preferred), expected.map(String::from));
+		}
+
+		case(&["en_US.UTF-8"], Some("en_US.utf8"));
+		case(&["en_US.ISO-8859-1"], Some("en_US.iso88591"));
+		case(&["ru_RU.UTF-8"], Some("ru_RU.utf8"));
+		case(&["en_GB.UTF-8"], Some("en_US.utf8"));
+		case(&["fr_FR.UTF-8", "de_DE.UTF-8"], Some("de_DE.iso88591"));
+		case(&["C.UTF-8"], Some("C.utf8"));
+
+		// No match
+		case(&["fr_FR.UTF-8"], None);
+		case(&[], None);
+	}
+
+	#[test]
+	fn test_installed_locales_in() {
+		let dir = std::env::temp_dir().join(format!("locale-match-installed-{}", std::process::id()));
+		fs::create_dir_all(dir.join("de_DE.utf8")).unwrap();
+		fs::write(dir.join("de_DE.utf8").join("LC_CTYPE"), []).unwrap();
+		fs::create_dir_all(dir.join("not-a-locale")).unwrap();
+		fs::write(dir.join("locale-archive"), archive(&["en_US.utf8", "de_DE.utf8"])).unwrap();
+
+		let locales = installed_locales_in(&dir.join("locale-archive"), [dir.as_path()]).unwrap();
+		// A locale archive that is a directory cannot be read
+		let unreadable = installed_locales_in(&dir, []);
+		fs::remove_dir_all(&dir).unwrap();
+		assert_eq!(locales, ["C", "POSIX", "de_DE.utf8", "en_US.utf8"]);
+		assert!(matches!(unreadable, Err(Error::Io(_))));
+
+		assert_eq!(installed_locales_in(Path::new("/nonexistent/locale-archive"), []).unwrap(), ["C", "POSIX"]);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_installed_locales() {
+		let locales = installed_locales().unwrap();
+		assert!(locales.iter().any(|l| l == "C"));
+		assert!(locales.iter().any(|l| l == "POSIX"));
+	}
+}
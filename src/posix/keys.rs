@@ -0,0 +1,93 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of the keys of maps, e.g. of translations by locale.
+
+use crate::engine;
+
+use super::Matcher;
+
+/// Finds the entry of a map whose key is the best matching locale, like
+/// [`best_matching_locale`](super::best_matching_locale), and returns the key with its value.
+///
+/// Any map with string-like keys can be passed by reference, e.g. a [`HashMap`](std::collections::HashMap)
+/// or a [`BTreeMap`](std::collections::BTreeMap), as well as any iterator over key-value pairs, so
+/// the keys don't need to be collected and the value doesn't need to be looked up again.  
+/// For maps with an unspecified iteration order, such as `HashMap`, the tie break by the order of
+/// the available locales picks an arbitrary one of the tied keys.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use locale_match::posix::best_matching_key;
+///
+///
+/// let translations = HashMap::from([("en_US.UTF-8", "Hello"), ("ru_RU.UTF-8", "Привет")]);
+///
+/// assert_eq!(best_matching_key(&translations, ["ru_BY.UTF-8"]), Some((&"ru_RU.UTF-8", &"Привет")));
+/// assert_eq!(best_matching_key(&translations, ["fr"]), None);
+/// ```
+pub fn best_matching_key<'a, K, V, T>(map: impl IntoIterator<Item = (&'a K, &'a V)>, user_locales: impl IntoIterator<Item = T>) -> Option<(&'a K, &'a V)>
+where
+	K: AsRef<str> + 'a,
+	V: 'a,
+	T: AsRef<str>
+{
+	Matcher::new().best_matching_key(map, user_locales)
+}
+
+impl Matcher {
+	/// Finds the entry of a map like [`best_matching_key`], using the configuration of the matcher.
+	pub fn best_matching_key<'a, K, V, T>(&self, map: impl IntoIterator<Item = (&'a K, &'a V)>, user_locales: impl IntoIterator<Item = T>) -> Option<(&'a K, &'a V)>
+	where
+		K: AsRef<str> + 'a,
+		V: 'a,
+		T: AsRef<str>
+	{
+		engine::best_matching_key(self, map, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::collections::{BTreeMap, HashMap};
+
+	#[test]
+	fn test_best_matching_key() {
+		// Maps with different key types
+		let map = BTreeMap::from([("en_US.UTF-8", 1), ("ru_RU.UTF-8", 2), ("de_DE.UTF-8", 3)]);
+		assert_eq!(best_matching_key(&map, ["ru_BY.UTF-8"]), Some((&"ru_RU.UTF-8", &2)));
+		assert_eq!(best_matching_key(&map, ["de"]), Some((&"de_DE.UTF-8", &3)));
+		assert_eq!(best_matching_key(&map, ["fr"]), None);
+
+		let map = HashMap::from([("en_US.UTF-8".to_string(), "one"), ("ru_RU.UTF-8".to_string(), "two")]);
+		assert_eq!(best_matching_key(&map, ["ru_BY.UTF-8"]), Some((&"ru_RU.UTF-8".to_string(), &"two")));
+
+		// Empty maps and iterators over pairs
+		assert_eq!(best_matching_key(&BTreeMap::<&str, i32>::new(), ["ru_BY.UTF-8"]), None);
+		let pairs = [("en_US.UTF-8", 'a'), ("ru_RU.UTF-8", 'b')];
+		assert_eq!(best_matching_key(pairs.iter().map(|(k, v)| (k, v)), ["ru_BY.UTF-8"]), Some((&"ru_RU.UTF-8", &'b')));
+
+		// Matcher configuration
+		let matcher = Matcher::new().with_tie_break(crate::matching::TieBreak::MostSpecific);
+		let map = BTreeMap::from([("en", 1), ("en_US.UTF-8", 2)]);
+		assert_eq!(matcher.best_matching_key(&map, ["en_GB"]), Some((&"en_US.UTF-8", &2)));
+	}
+}
\ No newline at end of file
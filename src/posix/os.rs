@@ -0,0 +1,140 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching of locales given as OS strings, e.g. the values of environment variables.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+use crate::matching::LocaleMatch;
+
+use super::Matcher;
+
+/// Finds the best matching locale from a list of available locales based on a list of user
+/// locales, like [`best_matching_locale`](super::best_matching_locale), for locales given as OS
+/// strings, e.g. [`OsString`](std::ffi::OsString) or [`Path`](std::path::Path).
+///
+/// Locales that are not valid Unicode are converted lossily with [`OsStr::to_string_lossy`],
+/// so the parts consisting of ASCII characters are still matched. For example, `de_DE.ISO-8859-1`
+/// with a broken modifier still matches `de_DE`.  
+/// The matched available locale is returned as it was passed in.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use locale_match::posix::best_matching_locale_os;
+///
+///
+/// let available_locales = ["en_US.UTF-8", "ru_RU.UTF-8"];
+/// let user_locales = ["LC_ALL", "LC_MESSAGES", "LANG"].into_iter().filter_map(env::var_os);
+///
+/// let best_match = best_matching_locale_os(available_locales, user_locales)
+///     .unwrap_or("en_US.UTF-8");
+/// ```
+pub fn best_matching_locale_os<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<OsStr>,
+	T2: AsRef<OsStr>
+{
+	Matcher::new().best_matching_locale_os(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_locale_os`], using the configuration of
+	/// the matcher.
+	pub fn best_matching_locale_os<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+	where
+		T1: AsRef<OsStr>,
+		T2: AsRef<OsStr>
+	{
+		self.best_matching_locale_os_with_confidence(available_locales, user_locales).map(|m| m.locale)
+	}
+
+	/// Finds the best matching locale like [`best_matching_locale_os`], using the configuration of
+	/// the matcher, and reports the quality of the match like
+	/// [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence).
+	pub fn best_matching_locale_os_with_confidence<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<LocaleMatch<T1>>
+	where
+		T1: AsRef<OsStr>,
+		T2: AsRef<OsStr>
+	{
+		let available_locales = available_locales.into_iter().map(Lossy::new);
+		let user_locales = user_locales.into_iter().map(Lossy::new);
+		self.best_matching_locale_with_confidence(available_locales, user_locales)
+			.map(|m| LocaleMatch { locale: m.locale.locale, available_index: m.available_index, user_index: m.user_index, confidence: m.confidence })
+	}
+}
+
+/// A locale given as an OS string, with its lossy conversion if it is not valid Unicode.
+struct Lossy<T> {
+	locale: T,
+	converted: Option<String>,
+}
+
+impl<T: AsRef<OsStr>> Lossy<T> {
+	fn new(locale: T) -> Self {
+		let converted = match locale.as_ref().to_string_lossy() {
+			Cow::Borrowed(_) => None,
+			Cow::Owned(converted) => Some(converted),
+		};
+		Self { locale, converted }
+	}
+}
+
+impl<T: AsRef<OsStr>> AsRef<str> for Lossy<T> {
+	fn as_ref(&self) -> &str {
+		self.converted.as_deref()
+			.or_else(|| self.locale.as_ref().to_str())
+			.unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::ffi::OsString;
+	use std::path::Path;
+
+	#[test]
+	fn test_best_matching_locale_os() {
+		fn case(available_locales: &[OsString], user_locales: &[OsString], expected: Option<usize>) {
+			let best_match = best_matching_locale_os(available_locales, user_locales);
+			assert_eq!(best_match, expected.map(|i| &available_locales[i]));
+		}
+
+		let os = |locales: &[&str]| locales.iter().map(OsString::from).collect::<Vec<OsString>>();
+
+		// Valid Unicode
+		case(&os(&["en_US.UTF-8", "ru_RU.UTF-8"]), &os(&["ru_BY", "en"]), Some(1));
+		case(&os(&["en_US.UTF-8", "ru_RU.UTF-8"]), &os(&["fr_FR"]), None);
+		case(&os(&[]), &os(&["fr_FR"]), None);
+
+		// Not valid Unicode
+		#[cfg(unix)]
+		{
+			use std::os::unix::ffi::OsStringExt;
+
+			let invalid = |locale: &[u8]| OsString::from_vec(locale.to_vec());
+			case(&[invalid(b"de_DE.ISO-8859-1@\xFF"), OsString::from("en_US")], &os(&["de_AT", "en"]), Some(0));
+			case(&os(&["de_DE", "de_AT"]), &[invalid(b"de_AT.\xFF"), OsString::from("en")], Some(1));
+		}
+
+		// Other types
+		assert_eq!(best_matching_locale_os([Path::new("en_US"), Path::new("ru_RU")], ["ru"]), Some(Path::new("ru_RU")));
+	}
+}
\ No newline at end of file
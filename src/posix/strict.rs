@@ -0,0 +1,88 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that rejects malformed locales instead of matching them by their parts.
+
+use crate::engine;
+use crate::matching::InvalidLocale;
+
+use super::{Matcher, ParseError};
+
+/// Finds the best matching locale like [`best_matching_locale`](super::best_matching_locale), but
+/// fails if any of the available or user locales is malformed, e.g. `en US`, instead of matching
+/// it by its parts.
+///
+/// This suits configuration-driven catalogs, where a malformed locale is a mistake to be fixed
+/// rather than an input to be tolerated.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::matching::LocaleKind;
+/// use locale_match::posix::try_best_matching_locale;
+///
+///
+/// assert_eq!(try_best_matching_locale(["en_US.UTF-8", "ru_BY.UTF-8"], ["ru_RU.UTF-8", "en"]), Ok(Some("ru_BY.UTF-8")));
+/// assert_eq!(try_best_matching_locale(["en_US.UTF-8", "ru_BY.UTF-8"], ["fr"]), Ok(None));
+///
+/// let error = try_best_matching_locale(["en_US.UTF-8", "ru BY"], ["ru_RU.UTF-8", "en"]).unwrap_err();
+/// assert_eq!((error.kind, error.index, error.input.as_str()), (LocaleKind::Available, 1, "ru BY"));
+/// ```
+pub fn try_best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Result<Option<T1>, InvalidLocale<ParseError>>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().try_best_matching_locale(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`try_best_matching_locale`], using the configuration of
+	/// the matcher.
+	pub fn try_best_matching_locale<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Result<Option<T1>, InvalidLocale<ParseError>>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::try_best_matching_locale(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::LocaleKind;
+
+	#[test]
+	fn test_try_best_matching_locale() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: Result<Option<&str>, (LocaleKind, usize, ParseError)>) {
+			let result = try_best_matching_locale(available_locales, user_locales)
+				.map(|best_match| best_match.copied())
+				.map_err(|error| (error.kind, error.index, error.source));
+			assert_eq!(result, expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Well-formed locales
+		case(&["en_US.UTF-8", "ru_RU.UTF-8"], &["ru", "en"], Ok(Some("ru_RU.UTF-8")));
+		case(&["en_US.UTF-8", "ru_RU.UTF-8"], &["fr"], Ok(None));
+		case(&[], &[], Ok(None));
+
+		// Malformed locales, the first one is reported
+		case(&["en_US", "ru RU", "de_"], &["ru"], Err((LocaleKind::Available, 1, ParseError::InvalidLanguage)));
+		case(&["en_US"], &["en", "en_US.UTF-8@"], Err((LocaleKind::User, 1, ParseError::InvalidModifier)));
+		case(&["en_"], &["en_"], Err((LocaleKind::Available, 0, ParseError::InvalidTerritory)));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,92 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that returns all of the available locales tied for the best match.
+
+use crate::engine;
+
+use super::Matcher;
+
+/// Finds all of the available locales that match the user locales equally well, i.e. those
+/// that [`best_matching_locale`](super::best_matching_locale) chooses among with its tie break.
+///
+/// The user locales are tried in the order of preference, and the available locales with the
+/// highest score for the first user locale that matches any of them are returned, in the order of
+/// the available locales. The result is empty if none of the locales match.  
+/// This lets the application apply its own tie break, e.g. by the completeness of the
+/// translations.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::posix::best_matching_locales_all_ties;
+///
+///
+/// let available_locales = ["en_US.UTF-8", "en_GB.UTF-8", "ru_RU.UTF-8"];
+///
+/// assert_eq!(best_matching_locales_all_ties(available_locales, ["en_CA.UTF-8"]), ["en_US.UTF-8", "en_GB.UTF-8"]);
+/// assert_eq!(best_matching_locales_all_ties(available_locales, ["en_GB"]), ["en_GB.UTF-8"]);
+/// assert!(best_matching_locales_all_ties(available_locales, ["fr"]).is_empty());
+/// ```
+pub fn best_matching_locales_all_ties<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locales_all_ties(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds all of the best matching locales like [`best_matching_locales_all_ties`], using the
+	/// configuration of the matcher.
+	///
+	/// The tie break of the matcher is not applied, but its scoring is.
+	pub fn best_matching_locales_all_ties<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Vec<T1>
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locales_all_ties(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_best_matching_locales_all_ties() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected: &[&str]) {
+			let ties = best_matching_locales_all_ties(available_locales, user_locales);
+			assert_eq!(ties.into_iter().copied().collect::<Vec<_>>(), expected, "{available_locales:?} and {user_locales:?}");
+		}
+
+		// Single best match
+		case(&["en_US", "en_GB", "ru_RU"], &["en_GB"], &["en_GB"]);
+		case(&["en_US", "ru_RU"], &["ru", "en"], &["ru_RU"]);
+
+		// Tied matches in the order of the available locales
+		case(&["en_US", "ru_RU", "en_GB", "en"], &["en_CA"], &["en_US", "en_GB", "en"]);
+		case(&["de_DE.UTF-8", "de_AT.UTF-8", "de_DE"], &["de_CH.UTF-8"], &["de_DE.UTF-8", "de_AT.UTF-8"]);
+
+		// The first matching user locale decides
+		case(&["de_DE", "de_AT", "en_US", "en_GB"], &["fr", "en", "de"], &["en_US", "en_GB"]);
+
+		// No match
+		case(&["en_US", "en_GB"], &["fr"], &[]);
+		case(&[], &["en"], &[]);
+	}
+}
\ No newline at end of file
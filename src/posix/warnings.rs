@@ -0,0 +1,89 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Matching that reports the malformed locales.
+
+use crate::engine;
+use crate::matching::{LocaleMatch, Warning};
+
+use super::Matcher;
+
+/// Finds the best matching locale like [`best_matching_locale_with_confidence`](super::best_matching_locale_with_confidence),
+/// and reports each malformed locale as a [`Warning`].
+///
+/// Malformed POSIX locales, e.g. `en US`, are not ignored, but still matched by their parts, so
+/// the result is the same as without the warnings.  
+/// All of the user locales are checked, including those after the one the match was found for.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::matching::LocaleKind;
+/// use locale_match::posix::best_matching_locale_with_warnings;
+///
+///
+/// let (best_match, warnings) = best_matching_locale_with_warnings(["en_US.UTF-8", "ru_RU UTF-8"], ["en", "de@"]);
+///
+/// assert_eq!(best_match.map(|m| m.locale), Some("en_US.UTF-8"));
+/// assert_eq!(warnings.iter().map(|w| (w.kind, w.input.as_str())).collect::<Vec<_>>(), [
+///     (LocaleKind::Available, "ru_RU UTF-8"),
+///     (LocaleKind::User, "de@"),
+/// ]);
+/// ```
+pub fn best_matching_locale_with_warnings<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> (Option<LocaleMatch<T1>>, Vec<Warning>)
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>
+{
+	Matcher::new().best_matching_locale_with_warnings(available_locales, user_locales)
+}
+
+impl Matcher {
+	/// Finds the best matching locale like [`best_matching_locale_with_warnings`], using the
+	/// configuration of the matcher.
+	pub fn best_matching_locale_with_warnings<T1, T2>(&self, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> (Option<LocaleMatch<T1>>, Vec<Warning>)
+	where
+		T1: AsRef<str>,
+		T2: AsRef<str>
+	{
+		engine::best_matching_locale_with_warnings(self, available_locales, user_locales)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matching::LocaleKind;
+
+	#[test]
+	fn test_best_matching_locale_with_warnings() {
+		fn case(available_locales: &[&str], user_locales: &[&str], expected_warnings: &[(LocaleKind, usize, &str)]) {
+			let (best_match, warnings) = best_matching_locale_with_warnings(available_locales, user_locales);
+			assert_eq!(best_match, super::super::best_matching_locale_with_confidence(available_locales, user_locales));
+			assert_eq!(warnings.iter().map(|w| (w.kind, w.index, w.input.as_str())).collect::<Vec<_>>(), expected_warnings);
+			assert!(warnings.iter().all(|w| !w.reason.is_empty()));
+		}
+
+		// Well-formed locales
+		case(&["en_US.UTF-8", "ru_RU.UTF-8"], &["ru", "en"], &[]);
+		case(&["sr_RS@latin", "C"], &["sr_RS.UTF-8@latin"], &[]);
+		case(&[], &[], &[]);
+
+		// Malformed locales are still matched
+		case(&["en US", "en_GB", ""], &["en_US"], &[(LocaleKind::Available, 0, "en US"), (LocaleKind::Available, 2, "")]);
+		case(&["en_US"], &["en_", "en", "en.UTF-8@"], &[(LocaleKind::User, 0, "en_"), (LocaleKind::User, 2, "en.UTF-8@")]);
+	}
+}
\ No newline at end of file
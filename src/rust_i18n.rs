@@ -0,0 +1,152 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for selecting the translations of [`rust-i18n`](https://docs.rs/rust-i18n) with the
+//! scoring of this library instead of exact locale matching.
+//!
+//! The current locale can be set to the best match once with [`set_best_matching_locale`], or every
+//! requested locale can be resolved to the best available one by wrapping the backend in a
+//! [`MatchingBackend`].
+
+use std::borrow::Cow;
+
+use ::rust_i18n::Backend;
+
+use crate::bcp47::Matcher;
+
+/// Sets the current locale of `rust-i18n` to the best match for the user locales among the available
+/// BCP 47 locales, usually the result of `rust_i18n::available_locales!()`.
+///
+/// See [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) for the details of the
+/// matching.  
+/// Returns the locale that was set, or [`None`] if none of the locales matches, in which case the
+/// current locale is left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::rust_i18n::set_best_matching_locale;
+///
+///
+/// let available_locales = ["en", "zh-CN", "ru"];
+///
+/// assert_eq!(set_best_matching_locale(available_locales, ["ru-RU", "en"]), Some("ru"));
+/// assert_eq!(&*rust_i18n::locale(), "ru");
+/// ```
+pub fn set_best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: AsRef<str>,
+	T2: AsRef<str>,
+{
+	let locale = crate::bcp47::best_matching_locale(available_locales, user_locales)?;
+	::rust_i18n::set_locale(locale.as_ref());
+	Some(locale)
+}
+
+/// A `rust-i18n` backend that resolves each requested locale to the best matching locale available
+/// in the wrapped backend, so e.g. `zh-Hans-CN` finds the translations of `zh-CN`.
+///
+/// The locales are resolved with a [`Matcher`] on every lookup, and a locale without any match finds
+/// no translations.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use locale_match::rust_i18n::MatchingBackend;
+/// use rust_i18n::{Backend, SimpleBackend};
+///
+///
+/// let mut backend = SimpleBackend::new();
+/// backend.add_translations("pt-BR".into(), HashMap::from([("hello".into(), "Olá".into())]));
+///
+/// let backend = MatchingBackend::new(backend);
+///
+/// assert_eq!(backend.translate("pt", "hello").as_deref(), Some("Olá"));
+/// ```
+#[derive(Debug)]
+pub struct MatchingBackend<B> {
+	backend: B,
+	matcher: Matcher,
+}
+
+impl<B: Backend> MatchingBackend<B> {
+	/// Wraps a backend, resolving the locales with the default [`Matcher`].
+	pub fn new(backend: B) -> Self {
+		Self { backend, matcher: Matcher::new() }
+	}
+
+	/// Sets the matcher used for resolving the locales.
+	pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+		self.matcher = matcher;
+		self
+	}
+
+	/// Returns the wrapped backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+
+	/// Returns the available locale of the wrapped backend that best matches the requested locale.
+	pub fn resolve_locale(&self, locale: &str) -> Option<Cow<'_, str>> {
+		self.matcher.best_matching_locale(self.backend.available_locales(), [locale])
+	}
+}
+
+impl<B: Backend> Backend for MatchingBackend<B> {
+	fn available_locales(&self) -> Vec<Cow<'_, str>> {
+		self.backend.available_locales()
+	}
+
+	fn translate(&self, locale: &str, key: &str) -> Option<Cow<'_, str>> {
+		self.backend.translate(&self.resolve_locale(locale)?, key)
+	}
+
+	fn messages_for_locale(&self, locale: &str) -> Option<Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+		self.backend.messages_for_locale(&self.resolve_locale(locale)?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::collections::HashMap;
+
+	use ::rust_i18n::SimpleBackend;
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_MatchingBackend() {
+		let mut backend = SimpleBackend::new();
+		backend.add_translations("en".into(), HashMap::from([("hello".into(), "Hello".into())]));
+		backend.add_translations("zh-CN".into(), HashMap::from([("hello".into(), "你好".into())]));
+		backend.add_translations("zh-TW".into(), HashMap::from([("hello".into(), "妳好".into())]));
+		let backend = MatchingBackend::new(backend);
+
+		assert_eq!(backend.translate("en-US", "hello").as_deref(), Some("Hello"));
+		assert_eq!(backend.translate("zh-CN", "hello").as_deref(), Some("你好"));
+		assert_eq!(backend.translate("zh-Hant-TW", "hello").as_deref(), Some("妳好"));
+		assert_eq!(backend.translate("zh-TW", "goodbye"), None);
+		assert_eq!(backend.translate("fr", "hello"), None);
+
+		assert_eq!(backend.resolve_locale("zh-HK").as_deref(), Some("zh-CN"));
+		assert_eq!(backend.messages_for_locale("en-GB").map(|messages| messages.len()), Some(1));
+		assert_eq!(backend.messages_for_locale("fr"), None);
+		assert_eq!(backend.available_locales(), ["en", "zh-CN", "zh-TW"]);
+	}
+}
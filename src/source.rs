@@ -0,0 +1,570 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Composable sources of user and available locales.
+//!
+//! A [`UserLocaleSource`] produces the user locales ordered by priority, e.g. from the environment
+//! variables, an `Accept-Language` header, the operating system or a fixed list, and the sources
+//! can be combined declaratively with [`or`](UserLocaleSource::or),
+//! [`chain`](UserLocaleSource::chain), [`filter_map`](UserLocaleSource::filter_map) and
+//! [`dedup`](UserLocaleSource::dedup).  
+//! An [`AvailableLocaleSource`] produces the available locales of a catalog, e.g. from a fixed
+//! list, translation files in a directory, embedded assets or a database callback, and the
+//! sources can be combined with [`merge`](AvailableLocaleSource::merge) and cached until refreshed
+//! with [`cached`](AvailableLocaleSource::cached).
+//!
+//! # Examples
+//!
+//! ```
+//! use locale_match::bcp47::best_matching_locale;
+//! use locale_match::source::{Header, Static, UserLocaleSource};
+//!
+//!
+//! // The header of the request, or English if the header names no locales
+//! let source = Header::new("de-AT, de;q=0.9, en;q=0.5")
+//!     .or(Static::new(["en"]))
+//!     .dedup();
+//!
+//! assert_eq!(source.user_locales(), ["de-AT", "de", "en"]);
+//! assert_eq!(best_matching_locale(["en-US", "de-DE"], source.user_locales()), Some("de-DE"));
+//! ```
+
+use std::sync::{PoisonError, RwLock};
+
+/// A source of user locales ordered by priority.
+///
+/// The trait is implemented for functions returning the locales, e.g.
+/// [`posix::user_locales_from_env`](crate::posix::user_locales_from_env), so any function can be
+/// used as a source.
+pub trait UserLocaleSource {
+	/// Returns the user locales ordered by priority, or an empty list if the source has none.
+	fn user_locales(&self) -> Vec<String>;
+
+	/// Returns a source of the locales of this source, or, if it has none, of the `other` source.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::source::{Static, UserLocaleSource};
+	///
+	///
+	/// assert_eq!(Static::new(["fr"]).or(Static::new(["en"])).user_locales(), ["fr"]);
+	/// assert_eq!(Static::<&str>::new([]).or(Static::new(["en"])).user_locales(), ["en"]);
+	/// ```
+	fn or<S: UserLocaleSource>(self, other: S) -> Or<Self, S>
+	where
+		Self: Sized
+	{
+		Or { first: self, second: other }
+	}
+
+	/// Returns a source of the locales of this source followed by the locales of the `other` source.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::source::{Static, UserLocaleSource};
+	///
+	///
+	/// assert_eq!(Static::new(["fr"]).chain(Static::new(["en"])).user_locales(), ["fr", "en"]);
+	/// ```
+	fn chain<S: UserLocaleSource>(self, other: S) -> Chain<Self, S>
+	where
+		Self: Sized
+	{
+		Chain { first: self, second: other }
+	}
+
+	/// Returns a source of the locales of this source converted with `f`, skipping the locales `f`
+	/// returns [`None`] for, e.g. [`posix::to_bcp47`](crate::posix::to_bcp47) for converting POSIX
+	/// locales to BCP 47.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::source::{Static, UserLocaleSource};
+	///
+	///
+	/// let source = Static::new(["en-US", "", "de"]).filter_map(|locale| (!locale.is_empty()).then(|| locale.to_uppercase()));
+	///
+	/// assert_eq!(source.user_locales(), ["EN-US", "DE"]);
+	/// ```
+	fn filter_map<F: Fn(&str) -> Option<String>>(self, f: F) -> FilterMap<Self, F>
+	where
+		Self: Sized
+	{
+		FilterMap { source: self, f }
+	}
+
+	/// Returns a source of the locales of this source without the repeated ones, which are compared
+	/// ignoring ASCII case. The first occurrence of each locale is kept.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use locale_match::source::{Static, UserLocaleSource};
+	///
+	///
+	/// assert_eq!(Static::new(["en-US", "de", "en-us", "de"]).dedup().user_locales(), ["en-US", "de"]);
+	/// ```
+	fn dedup(self) -> Dedup<Self>
+	where
+		Self: Sized
+	{
+		Dedup { source: self }
+	}
+}
+
+impl<F: Fn() -> Vec<String>> UserLocaleSource for F {
+	fn user_locales(&self) -> Vec<String> {
+		self()
+	}
+}
+
+/// A source of the available locales of a catalog.
+///
+/// The locales are produced anew on each call of [`available_locales`](Self::available_locales),
+/// unless the source is [`cached`](Self::cached).  
+/// The trait is implemented for functions returning the locales, e.g. a query of a database, so
+/// any function can be used as a source.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::source::{AvailableLocaleSource, Static};
+///
+///
+/// let catalog = Static::new(["en-US", "de-DE"])
+///     .merge(|| vec!["fr-FR".to_string()])
+///     .cached();
+///
+/// assert_eq!(catalog.available_locales(), ["en-US", "de-DE", "fr-FR"]);
+/// assert_eq!(best_matching_locale(catalog.available_locales(), ["fr-CA"]).as_deref(), Some("fr-FR"));
+/// ```
+pub trait AvailableLocaleSource {
+	/// Returns the available locales, or an empty list if the source has none.
+	fn available_locales(&self) -> Vec<String>;
+
+	/// Discards the locales kept by the source, if any, so they are produced anew on the next call of
+	/// [`available_locales`](Self::available_locales), e.g. after new translation files are
+	/// deployed.
+	///
+	/// Does nothing by default.
+	fn refresh(&self) {}
+
+	/// Returns a source of the locales of this source followed by the locales of the `other` source
+	/// that are not in this one, which are compared ignoring ASCII case.
+	fn merge<S: AvailableLocaleSource>(self, other: S) -> Merge<Self, S>
+	where
+		Self: Sized
+	{
+		Merge { first: self, second: other }
+	}
+
+	/// Returns a source that keeps the locales of this source after the first call of
+	/// [`available_locales`](Self::available_locales) until it is [refreshed](Self::refresh).
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use std::sync::atomic::{AtomicUsize, Ordering};
+	///
+	/// use locale_match::source::AvailableLocaleSource;
+	///
+	///
+	/// let queries = AtomicUsize::new(0);
+	/// let catalog = (|| {
+	///     queries.fetch_add(1, Ordering::Relaxed);
+	///     vec!["en-US".to_string()]
+	/// }).cached();
+	///
+	/// catalog.available_locales();
+	/// catalog.available_locales();
+	/// assert_eq!(queries.load(Ordering::Relaxed), 1);
+	///
+	/// catalog.refresh();
+	/// catalog.available_locales();
+	/// assert_eq!(queries.load(Ordering::Relaxed), 2);
+	/// ```
+	fn cached(self) -> Cached<Self>
+	where
+		Self: Sized
+	{
+		Cached { source: self, locales: RwLock::new(None) }
+	}
+}
+
+impl<F: Fn() -> Vec<String>> AvailableLocaleSource for F {
+	fn available_locales(&self) -> Vec<String> {
+		self()
+	}
+}
+
+/// A source of a fixed list of locales, e.g. from the settings of the application, as user or
+/// available locales.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Static<T: AsRef<str>> {
+	locales: Vec<T>,
+}
+
+impl<T: AsRef<str>> Static<T> {
+	/// Creates a source of the given locales.
+	pub fn new(locales: impl IntoIterator<Item = T>) -> Self {
+		Self { locales: locales.into_iter().collect() }
+	}
+}
+
+impl<T: AsRef<str>> UserLocaleSource for Static<T> {
+	fn user_locales(&self) -> Vec<String> {
+		self.locales.iter().map(|locale| locale.as_ref().to_string()).collect()
+	}
+}
+
+impl<T: AsRef<str>> AvailableLocaleSource for Static<T> {
+	fn available_locales(&self) -> Vec<String> {
+		self.user_locales()
+	}
+}
+
+/// A source of the locales of the translation files matching a pattern, e.g. `i18n/{locale}.ftl`,
+/// see [`files::available_locales_from_pattern`](crate::files::available_locales_from_pattern).
+///
+/// The directory is read on each call of [`available_locales`](AvailableLocaleSource::available_locales),
+/// unless the source is [`cached`](AvailableLocaleSource::cached). There are no locales if the
+/// directory cannot be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Files {
+	pattern: String,
+}
+
+impl Files {
+	/// Creates a source of the locales of the translation files matching the pattern.
+	pub fn new(pattern: impl Into<String>) -> Self {
+		Self { pattern: pattern.into() }
+	}
+}
+
+impl AvailableLocaleSource for Files {
+	fn available_locales(&self) -> Vec<String> {
+		crate::files::available_locales_from_pattern(&self.pattern).unwrap_or_default().into_iter().map(|file| file.locale).collect()
+	}
+}
+
+/// A source of the locales of embedded translation files, e.g. the paths of the assets of
+/// [`rust-embed`](https://docs.rs/rust-embed) or [`include_dir`](https://docs.rs/include_dir),
+/// matching a pattern like `i18n/{locale}.ftl`.
+///
+/// Paths use `/` as the separator. The locale may not contain separators and may not be empty.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::source::{AvailableLocaleSource, Embedded};
+///
+///
+/// let assets = ["i18n/en-US.ftl", "i18n/de.ftl", "i18n/README.md", "images/logo.png"];
+///
+/// assert_eq!(Embedded::new("i18n/{locale}.ftl", assets).available_locales(), ["en-US", "de"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embedded {
+	pattern: String,
+	paths: Vec<String>,
+}
+
+impl Embedded {
+	/// Creates a source of the locales of the embedded files at the paths matching the pattern.
+	pub fn new<T: AsRef<str>>(pattern: impl Into<String>, paths: impl IntoIterator<Item = T>) -> Self {
+		Self { pattern: pattern.into(), paths: paths.into_iter().map(|path| path.as_ref().to_string()).collect() }
+	}
+}
+
+impl AvailableLocaleSource for Embedded {
+	fn available_locales(&self) -> Vec<String> {
+		let Some((prefix, suffix)) = self.pattern.split_once("{locale}") else {
+			return Vec::new();
+		};
+		self.paths.iter()
+			.filter_map(|path| path.strip_prefix(prefix)?.strip_suffix(suffix))
+			.filter(|locale| !locale.is_empty() && !locale.contains('/'))
+			.map(str::to_string)
+			.collect()
+	}
+}
+
+/// A source of the POSIX locales from the environment variables, see
+/// [`posix::user_locales_from_env`](crate::posix::user_locales_from_env).
+///
+/// The locales are read on each call of [`user_locales`](UserLocaleSource::user_locales).
+#[cfg(feature = "posix")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Env;
+
+#[cfg(feature = "posix")]
+impl UserLocaleSource for Env {
+	fn user_locales(&self) -> Vec<String> {
+		crate::posix::user_locales_from_env()
+	}
+}
+
+/// A source of the BCP 47 locales of the operating system, see
+/// [`system::user_locales`](crate::system::user_locales).
+///
+/// The locales are detected on each call of [`user_locales`](UserLocaleSource::user_locales).
+#[cfg(all(feature = "bcp47", feature = "posix"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Platform;
+
+#[cfg(all(feature = "bcp47", feature = "posix"))]
+impl UserLocaleSource for Platform {
+	fn user_locales(&self) -> Vec<String> {
+		crate::system::user_locales()
+	}
+}
+
+/// A source of the locales accepted by the value of an `Accept-Language` header, ordered by
+/// descending quality value, see [`AcceptLanguage::preferred`](crate::bcp47::AcceptLanguage::preferred).
+#[cfg(feature = "bcp47")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+	value: String,
+}
+
+#[cfg(feature = "bcp47")]
+impl Header {
+	/// Creates a source of the locales accepted by the value of an `Accept-Language` header.
+	pub fn new(value: impl Into<String>) -> Self {
+		Self { value: value.into() }
+	}
+}
+
+#[cfg(feature = "bcp47")]
+impl UserLocaleSource for Header {
+	fn user_locales(&self) -> Vec<String> {
+		crate::bcp47::AcceptLanguage::parse(&self.value).preferred().into_iter().map(str::to_string).collect()
+	}
+}
+
+/// A source of the locales of one source, or of another source if the first one has none, see
+/// [`UserLocaleSource::or`].
+#[derive(Debug, Clone)]
+pub struct Or<S1, S2> {
+	first: S1,
+	second: S2,
+}
+
+impl<S1: UserLocaleSource, S2: UserLocaleSource> UserLocaleSource for Or<S1, S2> {
+	fn user_locales(&self) -> Vec<String> {
+		let locales = self.first.user_locales();
+		match locales.is_empty() {
+			true => self.second.user_locales(),
+			false => locales,
+		}
+	}
+}
+
+/// A source of the locales of two sources one after another, see [`UserLocaleSource::chain`].
+#[derive(Debug, Clone)]
+pub struct Chain<S1, S2> {
+	first: S1,
+	second: S2,
+}
+
+impl<S1: UserLocaleSource, S2: UserLocaleSource> UserLocaleSource for Chain<S1, S2> {
+	fn user_locales(&self) -> Vec<String> {
+		let mut locales = self.first.user_locales();
+		locales.extend(self.second.user_locales());
+		locales
+	}
+}
+
+/// A source of the converted locales of another source, see [`UserLocaleSource::filter_map`].
+#[derive(Debug, Clone)]
+pub struct FilterMap<S, F> {
+	source: S,
+	f: F,
+}
+
+impl<S: UserLocaleSource, F: Fn(&str) -> Option<String>> UserLocaleSource for FilterMap<S, F> {
+	fn user_locales(&self) -> Vec<String> {
+		self.source.user_locales().iter().filter_map(|locale| (self.f)(locale)).collect()
+	}
+}
+
+/// A source of the available locales of two sources merged, see [`AvailableLocaleSource::merge`].
+#[derive(Debug, Clone)]
+pub struct Merge<S1, S2> {
+	first: S1,
+	second: S2,
+}
+
+impl<S1: AvailableLocaleSource, S2: AvailableLocaleSource> AvailableLocaleSource for Merge<S1, S2> {
+	fn available_locales(&self) -> Vec<String> {
+		let mut locales = self.first.available_locales();
+		for locale in self.second.available_locales() {
+			if !locales.iter().any(|added| added.eq_ignore_ascii_case(&locale)) {
+				locales.push(locale);
+			}
+		}
+		locales
+	}
+
+	fn refresh(&self) {
+		self.first.refresh();
+		self.second.refresh();
+	}
+}
+
+/// A source keeping the available locales of another source until it is refreshed, see
+/// [`AvailableLocaleSource::cached`].
+#[derive(Debug)]
+pub struct Cached<S> {
+	source: S,
+	locales: RwLock<Option<Vec<String>>>,
+}
+
+impl<S: AvailableLocaleSource> AvailableLocaleSource for Cached<S> {
+	fn available_locales(&self) -> Vec<String> {
+		if let Some(locales) = self.locales.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+			return locales.clone();
+		}
+		let locales = self.source.available_locales();
+		*self.locales.write().unwrap_or_else(PoisonError::into_inner) = Some(locales.clone());
+		locales
+	}
+
+	fn refresh(&self) {
+		self.source.refresh();
+		*self.locales.write().unwrap_or_else(PoisonError::into_inner) = None;
+	}
+}
+
+/// A source of the locales of another source without the repeated ones, see
+/// [`UserLocaleSource::dedup`].
+#[derive(Debug, Clone)]
+pub struct Dedup<S> {
+	source: S,
+}
+
+impl<S: UserLocaleSource> UserLocaleSource for Dedup<S> {
+	fn user_locales(&self) -> Vec<String> {
+		let mut locales = Vec::<String>::new();
+		for locale in self.source.user_locales() {
+			if !locales.iter().any(|added| added.eq_ignore_ascii_case(&locale)) {
+				locales.push(locale);
+			}
+		}
+		locales
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_combinators() {
+		fn case(source: impl UserLocaleSource, expected: &[&str]) {
+			assert_eq!(source.user_locales(), expected);
+		}
+
+		let empty = || Static::<&str>::new([]);
+
+		// Static sources and functions
+		case(Static::new(["en-US", "de"]), &["en-US", "de"]);
+		case(Static::new(vec!["en-US".to_string()]), &["en-US"]);
+		case(empty(), &[]);
+		case(|| vec!["fr".to_string()], &["fr"]);
+
+		// Fallbacks
+		case(Static::new(["fr"]).or(Static::new(["en"])), &["fr"]);
+		case(empty().or(Static::new(["en"])), &["en"]);
+		case(empty().or(empty()).or(Static::new(["en"])), &["en"]);
+		case(empty().or(empty()), &[]);
+
+		// Concatenation
+		case(Static::new(["fr"]).chain(Static::new(["en"])), &["fr", "en"]);
+		case(empty().chain(Static::new(["en"])), &["en"]);
+
+		// Conversion
+		case(Static::new(["en", "", "de"]).filter_map(|l| (!l.is_empty()).then(|| l.to_string())), &["en", "de"]);
+
+		// Deduplication
+		case(Static::new(["en", "de", "EN", "en-US", "de"]).dedup(), &["en", "de", "en-US"]);
+		case(Static::new(["en"]).chain(Static::new(["fr", "en"])).dedup(), &["en", "fr"]);
+	}
+
+	#[test]
+	fn test_available_sources() {
+		fn case(source: impl AvailableLocaleSource, expected: &[&str]) {
+			assert_eq!(source.available_locales(), expected);
+		}
+
+		// Static sources and functions
+		case(Static::new(["en-US", "de"]), &["en-US", "de"]);
+		case(|| vec!["fr".to_string()], &["fr"]);
+
+		// Embedded files
+		let assets = ["i18n/en-US.ftl", "i18n/de.ftl", "i18n/.ftl", "i18n/x/fr.ftl", "po/ru/messages.po", "README.md"];
+		case(Embedded::new("i18n/{locale}.ftl", assets), &["en-US", "de"]);
+		case(Embedded::new("po/{locale}/messages.po", assets), &["ru"]);
+		case(Embedded::new("{locale}.md", assets), &["README"]);
+		case(Embedded::new("i18n/en-US.ftl", assets), &[]);
+
+		// Missing files
+		case(Files::new("missing/{locale}.ftl"), &[]);
+
+		// Merging
+		case(Static::new(["en", "de"]).merge(Static::new(["DE", "fr"])), &["en", "de", "fr"]);
+
+		// Caching and refreshing
+		let calls = std::sync::atomic::AtomicUsize::new(0);
+		let source = (|| vec![calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed).to_string()]).cached();
+		assert_eq!(source.available_locales(), ["0"]);
+		assert_eq!(source.available_locales(), ["0"]);
+		source.refresh();
+		assert_eq!(source.available_locales(), ["1"]);
+		let merged = Static::new(["en"]).merge(source);
+		merged.refresh();
+		assert_eq!(merged.available_locales(), ["en", "2"]);
+	}
+
+	#[test]
+	#[cfg(feature = "bcp47")]
+	#[allow(non_snake_case)]
+	fn test_Header() {
+		fn case(value: &str, expected: &[&str]) {
+			assert_eq!(Header::new(value).user_locales(), expected, "{value:?}");
+		}
+
+		case("de-AT, de;q=0.9, en;q=0.5", &["de-AT", "de", "en"]);
+		case("en;q=0.5, fr", &["fr", "en"]);
+		case("*, de;q=0", &[]);
+		case("", &[]);
+	}
+
+	#[test]
+	#[cfg(all(feature = "bcp47", feature = "posix"))]
+	#[allow(non_snake_case)]
+	fn test_Env() {
+		// The POSIX locales of the environment converted to BCP 47
+		let source = Env.filter_map(crate::posix::to_bcp47).dedup().or(Static::new(["en"]));
+		assert!(!source.user_locales().is_empty());
+		assert_eq!(Env.user_locales(), crate::posix::user_locales_from_env());
+		assert_eq!(Platform.user_locales(), crate::system::user_locales());
+	}
+}
\ No newline at end of file
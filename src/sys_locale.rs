@@ -0,0 +1,59 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching available locales against the user locales detected by the
+//! [`sys-locale`](https://docs.rs/sys-locale) crate.
+
+use crate::bcp47::best_matching_locale;
+
+/// Returns the user locales detected by [`sys_locale::get_locales`], which works on all major
+/// platforms.
+///
+/// The locales are in the BCP 47 format and ordered by priority, so the result is ready to be passed
+/// to [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) as the user locales.  
+/// Returns an empty list if the locales cannot be detected.
+pub fn user_locales() -> Vec<String> {
+	sys_locale::get_locales().collect()
+}
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on the user locales
+/// detected by [`sys_locale::get_locales`].
+///
+/// This is a shortcut for [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) with
+/// the locales returned by [`user_locales`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::sys_locale::best_matching_locale_for_system;
+///
+///
+/// let best_match = best_matching_locale_for_system(["en-US", "ru-RU", "de"])
+///     .unwrap_or("en-US");
+/// ```
+pub fn best_matching_locale_for_system<T: AsRef<str>>(available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+	best_matching_locale(available_locales, user_locales())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_locales() {
+		assert!(user_locales().iter().all(|l| !l.is_empty()));
+	}
+}
@@ -0,0 +1,84 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching available locales against the user locales of the operating system.
+
+use crate::bcp47::best_matching_locale;
+
+/// Returns the user locales of the operating system in the BCP 47 format, ordered by priority.
+///
+/// The locales are detected with the platform module if its feature is enabled:
+/// `windows` on Windows, `macos` on macOS, `ios` on iOS, `android` on Android and
+/// `web` on WebAssembly in a browser.  
+/// Otherwise, the locales are read from the environment variables with
+/// [`posix::user_locales_from_env`](crate::posix::user_locales_from_env) and converted to the
+/// BCP 47 format, e.g. `sr_RS.UTF-8@latin` to `sr-Latn-RS`; the `C` and `POSIX` locales are
+/// skipped.
+pub fn user_locales() -> Vec<String> {
+	#[cfg(all(feature = "windows", windows))]
+	return crate::windows::user_locales();
+	#[cfg(all(feature = "macos", target_os = "macos"))]
+	return crate::macos::user_locales();
+	#[cfg(all(feature = "ios", target_os = "ios"))]
+	return crate::ios::user_locales();
+	#[cfg(all(feature = "android", target_os = "android"))]
+	return crate::android::user_locales();
+	#[cfg(all(feature = "web", target_arch = "wasm32"))]
+	return crate::web::user_locales_from_navigator();
+	#[allow(unreachable_code)]
+	user_locales_from_env()
+}
+
+/// Reads the user locales from the environment variables and converts them to the BCP 47 format.
+fn user_locales_from_env() -> Vec<String> {
+	let mut locales = Vec::<String>::new();
+	for locale in crate::posix::user_locales_from_env().iter().filter_map(|l| crate::posix::to_bcp47(l)) {
+		if !locales.contains(&locale) {
+			locales.push(locale);
+		}
+	}
+	locales
+}
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on the user
+/// locales of the operating system.
+///
+/// This is a shortcut for [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale)
+/// with the locales returned by [`user_locales`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::system_best_match;
+///
+///
+/// let best_match = system_best_match(["en-US", "ru-RU", "de"])
+///     .unwrap_or("en-US");
+/// ```
+pub fn system_best_match<T: AsRef<str>>(available_locales: impl IntoIterator<Item = T>) -> Option<T> {
+	best_matching_locale(available_locales, user_locales())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_locales() {
+		let locales = user_locales();
+		assert!(locales.iter().all(|l| !l.is_empty() && !l.contains('_')));
+	}
+}
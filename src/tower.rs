@@ -0,0 +1,167 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module providing a [`tower`](https://docs.rs/tower) middleware for negotiating the locale of
+//! HTTP requests.
+//!
+//! The middleware works with any `http`-based stack built on `tower`, e.g. `hyper`, `axum` and
+//! `tonic`.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use ::http::Request;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::bcp47::{AcceptLanguage, Matcher};
+
+/// The locale negotiated by [`NegotiateLocale`], which is inserted into the extensions of each
+/// request.
+///
+/// It contains [`None`] if the request has no usable `Accept-Language` header or none of the
+/// languages matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegotiatedLocale<T>(pub Option<T>);
+
+/// A [`Layer`] that wraps services with [`NegotiateLocale`].
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::Matcher;
+/// use locale_match::tower::{NegotiateLocaleLayer, NegotiatedLocale};
+///
+///
+/// let layer = NegotiateLocaleLayer::new(Matcher::new(), ["en-US", "ru-RU"]);
+///
+/// // In a handler, e.g. with axum:
+/// // async fn handler(Extension(NegotiatedLocale(locale)): Extension<NegotiatedLocale<&'static str>>) { ... }
+/// ```
+#[derive(Debug)]
+pub struct NegotiateLocaleLayer<T> {
+	matcher: Matcher,
+	available_locales: Arc<[T]>,
+}
+
+impl<T> NegotiateLocaleLayer<T> {
+	/// Creates a layer that negotiates the locale of each request against the available BCP 47
+	/// locales with the given [`Matcher`].
+	pub fn new(matcher: Matcher, available_locales: impl IntoIterator<Item = T>) -> Self {
+		Self {
+			matcher,
+			available_locales: available_locales.into_iter().collect(),
+		}
+	}
+}
+
+impl<T> Clone for NegotiateLocaleLayer<T> {
+	fn clone(&self) -> Self {
+		Self { matcher: self.matcher.clone(), available_locales: self.available_locales.clone() }
+	}
+}
+
+impl<S, T> Layer<S> for NegotiateLocaleLayer<T> {
+	type Service = NegotiateLocale<S, T>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		NegotiateLocale { inner, matcher: self.matcher.clone(), available_locales: self.available_locales.clone() }
+	}
+}
+
+/// A [`Service`] that negotiates the locale of each request from its `Accept-Language` headers and
+/// inserts the result into the request extensions as [`NegotiatedLocale`] before calling the inner
+/// service.
+///
+/// The locale is negotiated as described in [`AcceptLanguage`]. The service never rejects a
+/// request.
+#[derive(Debug)]
+pub struct NegotiateLocale<S, T> {
+	inner: S,
+	matcher: Matcher,
+	available_locales: Arc<[T]>,
+}
+
+impl<S: Clone, T> Clone for NegotiateLocale<S, T> {
+	fn clone(&self) -> Self {
+		Self { inner: self.inner.clone(), matcher: self.matcher.clone(), available_locales: self.available_locales.clone() }
+	}
+}
+
+impl<S, T, B> Service<Request<B>> for NegotiateLocale<S, T>
+where
+	S: Service<Request<B>>,
+	T: AsRef<str> + Clone + Send + Sync + 'static,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = S::Future;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, mut request: Request<B>) -> Self::Future {
+		let locale = AcceptLanguage::parse(&crate::http::accept_language(request.headers()))
+			.best_matching_locale_with(&self.matcher, self.available_locales.iter())
+			.cloned();
+		request.extensions_mut().insert(NegotiatedLocale(locale));
+		self.inner.call(request)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::convert::Infallible;
+	use std::future::{ready, Ready};
+
+	use crate::http::complete;
+
+	struct Extract;
+
+	impl Service<Request<()>> for Extract {
+		type Response = Option<NegotiatedLocale<&'static str>>;
+		type Error = Infallible;
+		type Future = Ready<Result<Self::Response, Self::Error>>;
+
+		fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+			Poll::Ready(Ok(()))
+		}
+
+		fn call(&mut self, request: Request<()>) -> Self::Future {
+			ready(Ok(request.extensions().get::<NegotiatedLocale<&'static str>>().cloned()))
+		}
+	}
+
+	#[test]
+	#[allow(non_snake_case)]
+	fn test_NegotiateLocale() {
+		fn case(headers: &[&str], expected: Option<&str>) {
+			let mut service = NegotiateLocaleLayer::new(Matcher::new(), ["en-US", "de-DE", "ru-RU"]).layer(Extract);
+			let request = headers.iter().fold(Request::builder(), |request, value| request.header("accept-language", *value));
+			let response = complete(service.call(request.body(()).unwrap())).unwrap();
+			assert_eq!(response, Some(NegotiatedLocale(expected)));
+		}
+
+		case(&["de-AT, en;q=0.5"], Some("de-DE"));
+		case(&["fr", "en;q=0.5"], Some("en-US"));
+		case(&["en;q=0, ru"], Some("ru-RU"));
+		case(&["fr"], None);
+		case(&[], None);
+	}
+}
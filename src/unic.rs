@@ -0,0 +1,179 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for matching [`unic_langid`] language identifiers and converting them to and from
+//! [`LanguageTag`].
+
+use std::fmt::Display;
+
+use language_tags::LanguageTag;
+use unic_langid::{LanguageIdentifier, LanguageIdentifierError};
+
+use crate::bcp47::{Matcher, NegotiationStrategy, Serialized};
+
+/// Finds the best matching language identifier from a list of available ones based on a list of
+/// user ones, where the identifiers are [`LanguageIdentifier`]s or references to them, as used in
+/// the Fluent ecosystem.
+///
+/// The identifiers are matched exactly like in [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale),
+/// and the returned identifier is one of the available ones.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::unic::best_matching_locale;
+/// use unic_langid::LanguageIdentifier;
+///
+///
+/// let available_locales: Vec<LanguageIdentifier> = ["en-US", "ru-BY"].iter().map(|l| l.parse().unwrap()).collect();
+/// let user_locales: Vec<LanguageIdentifier> = ["ru-RU", "en"].iter().map(|l| l.parse().unwrap()).collect();
+///
+/// let best_match = best_matching_locale(&available_locales, &user_locales);
+///
+/// assert_eq!(best_match, Some(&available_locales[1]));
+/// ```
+pub fn best_matching_locale<T1, T2>(available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: Display,
+	T2: Display,
+{
+	best_matching_locale_with(&Matcher::new(), available_locales, user_locales)
+}
+
+/// Finds the best matching language identifier exactly like [`best_matching_locale`], but with the
+/// given [`Matcher`] instead of the default one.
+pub fn best_matching_locale_with<T1, T2>(matcher: &Matcher, available_locales: impl IntoIterator<Item = T1>, user_locales: impl IntoIterator<Item = T2>) -> Option<T1>
+where
+	T1: Display,
+	T2: Display,
+{
+	matcher.best_matching_displayed_locale(available_locales, user_locales)
+}
+
+/// Negotiates the available language identifiers for the requested ones with the given strategy,
+/// as a drop-in replacement for `fluent_langneg::negotiate_languages`.
+///
+/// See [`bcp47::negotiate_languages`](crate::bcp47::negotiate_languages) for the details.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::NegotiationStrategy;
+/// use locale_match::unic::negotiate_languages;
+/// use unic_langid::LanguageIdentifier;
+///
+///
+/// let requested: Vec<LanguageIdentifier> = ["de-AT", "fr"].iter().map(|l| l.parse().unwrap()).collect();
+/// let available: Vec<LanguageIdentifier> = ["en-US", "de-DE", "fr-FR"].iter().map(|l| l.parse().unwrap()).collect();
+///
+/// let supported = negotiate_languages(&requested, &available, Some(&available[0]), NegotiationStrategy::Filtering);
+///
+/// assert_eq!(supported, [&available[1], &available[2], &available[0]]);
+/// ```
+pub fn negotiate_languages<'a, R, A>(requested: &[R], available: &'a [A], default: Option<&'a A>, strategy: NegotiationStrategy) -> Vec<&'a A>
+where
+	R: Display,
+	A: Display,
+{
+	negotiate_languages_with(&Matcher::new(), requested, available, default, strategy)
+}
+
+/// Negotiates the available language identifiers exactly like [`negotiate_languages`], but with the
+/// given [`Matcher`] instead of the default one.
+pub fn negotiate_languages_with<'a, R, A>(matcher: &Matcher, requested: &[R], available: &'a [A], default: Option<&'a A>, strategy: NegotiationStrategy) -> Vec<&'a A>
+where
+	R: Display,
+	A: Display,
+{
+	let requested = requested.iter().map(ToString::to_string).collect::<Vec<String>>();
+	let available = available.iter().map(Serialized::new).collect::<Vec<Serialized<&A>>>();
+	let default = default.map(Serialized::new);
+	matcher.negotiate_languages(&requested, &available, default.as_ref(), strategy)
+		.into_iter()
+		.map(|Serialized(_, locale)| *locale)
+		.collect()
+}
+
+/// Converts a [`LanguageIdentifier`] to a [`LanguageTag`].
+///
+/// # Errors
+///
+/// Returns an error if the serialized identifier is not a well-formed BCP 47 language tag.
+pub fn to_language_tag(identifier: &LanguageIdentifier) -> Result<LanguageTag, language_tags::ParseError> {
+	LanguageTag::parse(&identifier.to_string())
+}
+
+/// Converts a [`LanguageTag`] to a [`LanguageIdentifier`].
+///
+/// Extensions and private use subtags of the tag are dropped, since language identifiers cannot
+/// represent them.
+///
+/// # Errors
+///
+/// Returns an error if the tag cannot be represented as a language identifier, e.g. if it has an
+/// extended language subtag or is grandfathered.
+pub fn to_language_identifier(tag: &LanguageTag) -> Result<LanguageIdentifier, LanguageIdentifierError> {
+	let subtags = [tag.primary_language()].into_iter()
+		.chain(tag.extended_language())
+		.chain(tag.script())
+		.chain(tag.region())
+		.chain(tag.variant_subtags());
+	LanguageIdentifier::from_bytes(subtags.collect::<Vec<&str>>().join("-").as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn langid(identifier: &str) -> LanguageIdentifier {
+		identifier.parse().unwrap()
+	}
+
+	#[test]
+	fn test_best_matching_locale() {
+		let available_locales = [langid("en-US"), langid("pt-BR"), langid("pt-PT")];
+		assert_eq!(best_matching_locale(&available_locales, [langid("pt-PT")]), Some(&langid("pt-PT")));
+		assert_eq!(best_matching_locale(&available_locales, [langid("pt")]), Some(&langid("pt-BR")));
+		assert_eq!(best_matching_locale(&available_locales, [langid("fr"), langid("en-GB")]), Some(&langid("en-US")));
+		assert_eq!(best_matching_locale(&available_locales, [langid("fr")]), None);
+
+		// Mixed types
+		assert_eq!(best_matching_locale(available_locales, ["en"]), Some(langid("en-US")));
+	}
+
+	#[test]
+	fn test_negotiate_languages() {
+		let requested = [langid("de-AT"), langid("fr")];
+		let available = [langid("en-US"), langid("de-DE"), langid("de"), langid("fr-FR")];
+		let default = langid("en-US");
+		assert_eq!(negotiate_languages(&requested, &available, Some(&default), NegotiationStrategy::Filtering), [&available[1], &available[2], &available[3], &default]);
+		assert_eq!(negotiate_languages(&requested, &available, None, NegotiationStrategy::Matching), [&available[1], &available[3]]);
+		assert_eq!(negotiate_languages(&requested, &available, None, NegotiationStrategy::Lookup), [&available[1]]);
+	}
+
+	#[test]
+	fn test_conversions() {
+		assert_eq!(to_language_tag(&langid("sr-Latn-RS")).unwrap().as_str(), "sr-Latn-RS");
+		assert_eq!(to_language_tag(&langid("de-CH-1996")).unwrap().as_str(), "de-CH-1996");
+
+		assert_eq!(to_language_identifier(&LanguageTag::parse("de-CH-1996-x-private").unwrap()), Ok(langid("de-CH-1996")));
+		assert_eq!(to_language_identifier(&LanguageTag::parse("zh-Hant-TW-u-ca-chinese").unwrap()), Ok(langid("zh-Hant-TW")));
+
+		// Not representable
+		assert!(to_language_identifier(&LanguageTag::parse("zh-cmn-Hans").unwrap()).is_err());
+		assert!(to_language_identifier(&LanguageTag::parse("i-klingon").unwrap()).is_err());
+	}
+}
@@ -0,0 +1,87 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module providing a [`warp`](https://docs.rs/warp) filter for negotiating the locale of a request.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use ::warp::http::HeaderMap;
+use ::warp::Filter;
+
+use crate::bcp47::{AcceptLanguage, Matcher};
+
+/// Creates a filter that extracts the locale negotiated from the `Accept-Language` headers of a
+/// request.
+///
+/// The filter matches the headers against the available BCP 47 locales with the given [`Matcher`],
+/// as described in [`AcceptLanguage`], and passes the matched locale to the downstream handlers.  
+/// It extracts [`None`] if the request has no usable `Accept-Language` header or none of the
+/// languages matches, and never rejects a request.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::Matcher;
+/// use locale_match::warp::negotiate_locale;
+/// use warp::Filter;
+///
+///
+/// let route = warp::path("hello")
+///     .and(negotiate_locale(Matcher::new(), ["en-US", "ru-RU"]))
+///     .map(|locale: Option<&str>| match locale.unwrap_or("en-US") {
+///         "ru-RU" => "Привет!",
+///         _ => "Hello!",
+///     });
+/// ```
+pub fn negotiate_locale<T>(matcher: Matcher, available_locales: impl IntoIterator<Item = T>) -> impl Filter<Extract = (Option<T>,), Error = Infallible> + Clone
+where
+	T: AsRef<str> + Clone + Send + Sync + 'static,
+{
+	let available_locales = available_locales.into_iter().collect::<Arc<[T]>>();
+	::warp::header::headers_cloned().map(move |headers: HeaderMap| {
+		AcceptLanguage::parse(&crate::http::accept_language(&headers))
+			.best_matching_locale_with(&matcher, available_locales.iter())
+			.cloned()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use ::warp::http::Request;
+	use tower_service::Service;
+
+	use crate::http::complete;
+
+	#[test]
+	fn test_negotiate_locale() {
+		fn case(headers: &[&str], expected: Option<&str>) {
+			let filter = negotiate_locale(Matcher::new(), ["en-US", "de-DE", "ru-RU"])
+				.map(|locale: Option<&str>| ::warp::reply::with_header(::warp::reply(), "content-language", locale.unwrap_or("")));
+			let request = headers.iter().fold(Request::builder(), |request, value| request.header("accept-language", *value));
+			let response = complete(::warp::service(filter).call(request.body(String::new()).unwrap())).unwrap();
+			assert_eq!(response.headers()["content-language"], expected.unwrap_or(""));
+		}
+
+		case(&["de-AT, en;q=0.5"], Some("de-DE"));
+		case(&["fr, en;q=0.5"], Some("en-US"));
+		case(&["en;q=0, ru"], Some("ru-RU"));
+		case(&["fr"], None);
+		case(&[], None);
+	}
+}
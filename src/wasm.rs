@@ -0,0 +1,81 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module exporting the matching functions to JavaScript with [`wasm-bindgen`](https://docs.rs/wasm-bindgen),
+//! so front-end applications can negotiate locales exactly like the Rust back-end.
+//!
+//! The functions take arrays of strings and return the best matching available locale, or
+//! `undefined` if none of them matches.
+//!
+//! The module can be built with `cargo rustc --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib`
+//! and turned into a JavaScript package with the `wasm-bindgen` CLI.
+//!
+//! # Examples
+//!
+//! ```js
+//! import { bestMatchingBcp47Locale } from "locale-match";
+//!
+//! const locale = bestMatchingBcp47Locale(["en-US", "ru-BY"], navigator.languages) ?? "en-US";
+//! ```
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Finds the best matching locale from a list of available BCP 47 locales based on a list of user
+/// BCP 47 locales.
+///
+/// Exported to JavaScript as `bestMatchingBcp47Locale`.  
+/// See [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) for the details of the
+/// matching.
+#[cfg(feature = "bcp47")]
+#[wasm_bindgen(js_name = bestMatchingBcp47Locale)]
+pub fn best_matching_bcp47_locale(available_locales: Vec<String>, user_locales: Vec<String>) -> Option<String> {
+	crate::bcp47::best_matching_locale(available_locales, user_locales)
+}
+
+/// Finds the best matching locale from a list of available POSIX locales based on a list of user
+/// POSIX locales.
+///
+/// Exported to JavaScript as `bestMatchingPosixLocale`.  
+/// See [`posix::best_matching_locale`](crate::posix::best_matching_locale) for the details of the
+/// matching.
+#[cfg(feature = "posix")]
+#[wasm_bindgen(js_name = bestMatchingPosixLocale)]
+pub fn best_matching_posix_locale(available_locales: Vec<String>, user_locales: Vec<String>) -> Option<String> {
+	crate::posix::best_matching_locale(available_locales, user_locales)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn strings(strings: &[&str]) -> Vec<String> {
+		strings.iter().map(|s| s.to_string()).collect()
+	}
+
+	#[cfg(feature = "bcp47")]
+	#[test]
+	fn test_best_matching_bcp47_locale() {
+		assert_eq!(best_matching_bcp47_locale(strings(&["en-US", "ru-BY"]), strings(&["ru-RU", "en"])).as_deref(), Some("ru-BY"));
+		assert_eq!(best_matching_bcp47_locale(strings(&["en-US", "ru-BY"]), strings(&["fr"])), None);
+	}
+
+	#[cfg(feature = "posix")]
+	#[test]
+	fn test_best_matching_posix_locale() {
+		assert_eq!(best_matching_posix_locale(strings(&["en_US.UTF-8", "ru_BY.UTF-8"]), strings(&["ru_RU", "en"])).as_deref(), Some("ru_BY.UTF-8"));
+		assert_eq!(best_matching_posix_locale(strings(&["en_US", "ru_BY"]), strings(&["fr"])), None);
+	}
+}
@@ -0,0 +1,54 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for detecting user locales in web browsers on WebAssembly targets.
+
+/// Returns the preferred languages of the user, as reported by the browser in
+/// [`navigator.languages`](https://developer.mozilla.org/en-US/docs/Web/API/Navigator/languages).
+///
+/// If `navigator.languages` is empty, [`navigator.language`](https://developer.mozilla.org/en-US/docs/Web/API/Navigator/language)
+/// is used instead.  
+/// The locales are in the BCP 47 format and ordered by priority, so the result is ready to be passed
+/// to [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) as the user locales.  
+/// Returns an empty list outside of a browser window, e.g. in a worker or in a non-browser runtime.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::web::user_locales_from_navigator;
+///
+///
+/// let available_locales = ["en-US", "ru-RU"];
+///
+/// let best_match = best_matching_locale(available_locales, user_locales_from_navigator())
+///     .unwrap_or("en-US");
+/// ```
+pub fn user_locales_from_navigator() -> Vec<String> {
+	let Some(window) = web_sys::window() else {
+		return Vec::new();
+	};
+	let navigator = window.navigator();
+	let languages = navigator.languages()
+		.iter()
+		.filter_map(|language| language.as_string())
+		.filter(|language| !language.is_empty())
+		.collect::<Vec<String>>();
+	match languages.is_empty() {
+		true => navigator.language().filter(|language| !language.is_empty()).into_iter().collect(),
+		false => languages,
+	}
+}
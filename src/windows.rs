@@ -0,0 +1,108 @@
+// locale-match is a small library for matching user's preferred locales to available locales.
+// Copyright (C) © 2024  Petr Alexandrovich Sabanov
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A module for detecting user locales on Windows.
+
+use std::ptr;
+
+use windows_sys::Win32::Globalization::{GetUserDefaultLocaleName, GetUserPreferredUILanguages, MUI_LANGUAGE_NAME};
+use windows_sys::Win32::System::SystemServices::LOCALE_NAME_MAX_LENGTH;
+
+/// Returns the user locales configured in Windows.
+///
+/// The result contains the user-preferred UI languages (see [`user_preferred_ui_languages`]),
+/// followed by the user default locale (see [`user_default_locale_name`]) if it is not already in
+/// the list.  
+/// The locales are in the BCP 47 format and ordered by priority, so the result is ready to be passed
+/// to [`bcp47::best_matching_locale`](crate::bcp47::best_matching_locale) as the user locales.
+///
+/// # Examples
+///
+/// ```
+/// use locale_match::bcp47::best_matching_locale;
+/// use locale_match::windows::user_locales;
+///
+///
+/// let available_locales = ["en-US", "ru-RU"];
+///
+/// let best_match = best_matching_locale(available_locales, user_locales())
+///     .unwrap_or("en-US");
+/// ```
+pub fn user_locales() -> Vec<String> {
+	let mut locales = user_preferred_ui_languages();
+	if let Some(locale) = user_default_locale_name() {
+		if !locales.contains(&locale) {
+			locales.push(locale);
+		}
+	}
+	locales
+}
+
+/// Returns the user-preferred UI languages, as returned by
+/// [`GetUserPreferredUILanguages`](https://learn.microsoft.com/en-us/windows/win32/api/winnls/nf-winnls-getuserpreferreduilanguages),
+/// ordered by priority.
+///
+/// Returns an empty list if the languages cannot be retrieved.
+pub fn user_preferred_ui_languages() -> Vec<String> {
+	let mut count = 0;
+	let mut len = 0;
+	// SAFETY: A null buffer with zero length is a request for the required buffer length.
+	if unsafe { GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, ptr::null_mut(), &mut len) } == 0 {
+		return Vec::new();
+	}
+	let mut buffer = vec![0u16; len as usize];
+	// SAFETY: The buffer has exactly the length reported by the previous call.
+	if unsafe { GetUserPreferredUILanguages(MUI_LANGUAGE_NAME, &mut count, buffer.as_mut_ptr(), &mut len) } == 0 {
+		return Vec::new();
+	}
+	// The buffer is a list of null-terminated strings terminated by an empty string
+	buffer[..(len as usize).min(buffer.len())]
+		.split(|&c| c == 0)
+		.filter(|l| !l.is_empty())
+		.map(String::from_utf16_lossy)
+		.collect()
+}
+
+/// Returns the user default locale name, as returned by
+/// [`GetUserDefaultLocaleName`](https://learn.microsoft.com/en-us/windows/win32/api/winnls/nf-winnls-getuserdefaultlocalename).
+///
+/// Returns [`None`] if the locale cannot be retrieved.
+pub fn user_default_locale_name() -> Option<String> {
+	let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+	// SAFETY: The length passed is the length of the buffer.
+	let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+	// The returned length includes the terminating null character
+	match len {
+		..=1 => None,
+		_ => Some(String::from_utf16_lossy(&buffer[..len as usize - 1])),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_user_locales() {
+		let locales = user_locales();
+		assert!(locales.iter().all(|l| !l.is_empty() && !l.contains('\0')));
+		for (i, locale) in locales.iter().enumerate() {
+			assert!(!locales[..i].contains(locale));
+		}
+		assert!(user_preferred_ui_languages().iter().all(|l| locales.contains(l)));
+		assert!(user_default_locale_name().is_none_or(|l| locales.contains(&l)));
+	}
+}